@@ -454,7 +454,7 @@ export function UsernameGenerator() {
                       </Label>
                       <Select
                         value={form.watch('service')?.type || ''}
-                        onValueChange={(value: 'AddyIo' | 'DuckDuckGo' | 'Firefox' | 'Fastmail' | 'ForwardEmail' | 'SimpleLogin') => {
+                        onValueChange={(value: 'AddyIo' | 'DuckDuckGo' | 'Firefox' | 'Fastmail' | 'ForwardEmail' | 'SimpleLogin' | 'IronVest') => {
                           const currentService = form.watch('service') || {};
                           form.setValue('service', { ...currentService, type: value });
                         }}
@@ -469,6 +469,7 @@ export function UsernameGenerator() {
                           <SelectItem value="Fastmail">Fastmail</SelectItem>
                           <SelectItem value="ForwardEmail">ForwardEmail</SelectItem>
                           <SelectItem value="SimpleLogin">SimpleLogin</SelectItem>
+                          <SelectItem value="IronVest">IronVest (Blur)</SelectItem>
                         </SelectContent>
                       </Select>
                     </div>
@@ -480,7 +481,7 @@ export function UsernameGenerator() {
                         </p>
                         
                         {/* API Token/Key field for most services */}
-                        {['AddyIo', 'Firefox', 'Fastmail', 'ForwardEmail', 'SimpleLogin'].includes(form.watch('service')?.type || '') && (
+                        {['AddyIo', 'Firefox', 'Fastmail', 'ForwardEmail', 'SimpleLogin', 'IronVest'].includes(form.watch('service')?.type || '') && (
                           <div className="space-y-2">
                             <Label className="text-sm font-medium text-foreground">
                               {form.watch('service')?.type === 'SimpleLogin' ? 'API Key' : 'API Token'}
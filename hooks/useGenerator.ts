@@ -83,7 +83,7 @@ export function useGenerator() {
         if (service.type === 'SimpleLogin' && !service.api_key) {
           throw new Error('SimpleLogin forwarding requires an API key. Please configure your SimpleLogin API key in the service settings.');
         }
-        if (['AddyIo', 'Fastmail', 'ForwardEmail'].includes(service.type) && !service.api_token) {
+        if (['AddyIo', 'Fastmail', 'ForwardEmail', 'IronVest'].includes(service.type) && !service.api_token) {
           throw new Error(`${service.type} forwarding requires an API token. Please configure your ${service.type} API token in the service settings.`);
         }
         if (['AddyIo', 'ForwardEmail'].includes(service.type) && !service.domain) {
@@ -12,61 +12,135 @@ interface TauriPasswordGeneratorRequest {
   numbers: boolean;
   special: boolean;
   length: number;
-  avoidAmbiguous: boolean;
+  /** Legacy flag, `true` is the same as setting every `ambiguousExclusions` group */
+  avoidAmbiguous?: boolean;
+  ambiguousExclusions?: { il1?: boolean; O0?: boolean; S5?: boolean; B8?: boolean; Z2?: boolean };
   minLowercase?: number;
   minUppercase?: number;
   minNumber?: number;
   minSpecial?: number;
+  /** Replaces the character set flags, the minimums are ignored */
+  customCharset?: string;
+  /** e.g. `Xxxx-####`, replaces every other option except the ambiguous exclusions */
+  pattern?: string;
+  maxConsecutiveSameChar?: number;
+  noConsecutiveSameClass?: boolean;
+  /** One of these characters always appears in the password */
+  requiredChars?: string[];
+  /** Characters that never appear in the password */
+  customExclusions?: string;
 }
 
+type PasswordPreset = 'NistMinimum' | 'HighSecurity' | 'MaxEntropy' | 'NoAmbiguous' | 'MobileEntry';
+
 interface TauriPassphraseRequest {
   num_words: number;
   word_separator: string;
-  capitalize: boolean;
+  /** Legacy flag, `true` is the same as `capitalization: 'FirstLetter'` */
+  capitalize?: boolean;
+  capitalization?: 'None' | 'FirstLetter' | 'TitleCase' | 'AllCaps' | 'RandomCase';
+  /** 0-based indices of the words to capitalize, overrides `capitalization` */
+  capitalize_positions?: number[];
+  capitalize_last_only?: boolean;
   include_number: boolean;
+  number_length?: 'OneDigit' | 'TwoDigits' | 'FourDigits';
+  number_position?: 'AppendToWord' | 'PrependToWord' | 'StandaloneWord';
+  diceware_compatible?: boolean;
+  /** Reorder the words so they are easier to say aloud */
+  phonetic_smoothing?: boolean;
+  /** Words never picked, case-insensitive, at most 1000 */
+  excluded_words?: string[];
+  /** Shortest word length, 3 by default */
+  word_min_length?: number;
+  /** Longest word length, unlimited by default */
+  word_max_length?: number;
 }
 
+type UuidVersion = 'V4' | 'V7';
+type UuidFormat = 'Hyphenated' | 'Simple' | 'Urn' | 'Braced';
+type UuidNamespace = 'Dns' | 'Url' | 'Oid' | 'X500';
+
+type UsernameFormat = 'Plain' | 'SnakeCase' | 'KebabCase' | 'CamelCase' | 'PascalCase';
+
 // Updated to match exact Rust enum structure after backend improvements
 type TauriUsernameRequest = 
   | {
       Word: {
         capitalize: boolean;
-        include_number: boolean;
+        /** Legacy flag, `true` is the same as `number_suffix: 'FourDigits'` */
+        include_number?: boolean;
+        number_suffix?: 'None' | 'FourDigits' | 'SixDigits' | 'EightDigits';
+        number_position?: 'Prefix' | 'Suffix';
         strength: 'Basic' | 'Standard' | 'Strong' | 'Maximum';
+        format?: UsernameFormat;
+      };
+    }
+  | {
+      CompoundWord: {
+        word_count: number;
+        strength: 'Basic' | 'Standard' | 'Strong' | 'Maximum';
+        format?: UsernameFormat;
       };
     }
   | {
       Subaddress: {
-        type: 'Random' | { WebsiteName: { website: string } };
+        type: 'Random' | { WebsiteName: { website: string } } | { Pronounceable: { syllables: number } };
         email: string;
       };
     }
   | {
       Catchall: {
-        type: 'Random' | { WebsiteName: { website: string } };
+        type: 'Random' | { WebsiteName: { website: string } } | { Pronounceable: { syllables: number } };
         domain: string;
+        original_domain?: string;
       };
     }
   | {
       Forwarded: {
         service: 
-          | { AddyIo: { api_token: string; domain: string; base_url: string } }
+          | { AddyIo: { api_token: string; domain: string; base_url: string; mailbox_id?: string } }
           | { DuckDuckGo: { token: string } }
           | { Firefox: { api_token: string } }
-          | { Fastmail: { api_token: string } }
+          | { Fastmail: { api_token: string; identity_id?: string } }
           | { ForwardEmail: { api_token: string; domain: string } }
-          | { SimpleLogin: { api_key: string; base_url: string } };
+          | { SimpleLogin: { api_key: string; base_url: string; note?: string; directory?: string } }
+          | { IronVest: { api_token: string } };
         website?: string;
       };
     };
 
+interface PassphraseResult {
+  schema_version?: number;
+  passphrase: string;
+  entropy_bits: number;
+  word_count: number;
+  wordlist_version: string;
+  wordlist_hash: string;
+}
+
+interface EntropyEstimate {
+  schema_version?: number;
+  entropy_bits: number;
+  equivalent_password_length: number;
+  crack_time_display: string;
+  wordlist_size: number;
+}
+
 interface PasswordStrength {
+  schema_version?: number;
   score: number;
   crack_times_display: string;
   feedback: string[];
+  /** Lowest rated word of a passphrase, only set by `calculatePassphraseStrength` */
+  weakest_word?: string | null;
+  weakest_word_score?: number | null;
+  /** zxcvbn's estimated guesses to crack the password, more precise than `score` */
+  guesses?: number;
+  guesses_log10?: number;
 }
 
 interface UsernameStrength {
+  schema_version?: number;
   score: number;
   security_level: string;
   feedback: string[];
@@ -74,11 +148,47 @@ interface UsernameStrength {
   uniqueness_score: number;
 }
 
+interface PasswordSimilarityResult {
+  schema_version?: number;
+  similarity_score: number;
+  is_too_similar: boolean;
+  changes: string[];
+}
+
+interface CredentialRecord {
+  name: string;
+  username?: string | null;
+  password?: string | null;
+  uris?: string[];
+  notes?: string | null;
+}
+
 interface IPResponse {
+  schema_version?: number;
   ip: string;
   masked_ip: string;
   country?: string;
   region?: string;
+  geo_lookup_available: boolean;
+}
+
+/**
+ * Structured error returned by commands such as `generate_username`.
+ * `code` is stable across versions, see `UsernameErrorCode` in the backend.
+ */
+export interface SerializableError {
+  code: number;
+  message: string;
+  details: string | null;
+}
+
+function isSerializableError(error: unknown): error is SerializableError {
+  return (
+    typeof error === 'object' &&
+    error !== null &&
+    typeof (error as SerializableError).code === 'number' &&
+    typeof (error as SerializableError).message === 'string'
+  );
 }
 
 export class TauriAPI {
@@ -107,6 +217,10 @@ export class TauriAPI {
     }
   }
 
+  static async getPasswordPreset(preset: PasswordPreset): Promise<TauriPasswordGeneratorRequest> {
+    return await invoke('get_password_preset', { preset });
+  }
+
   static async generatePassphrase(config: PassphraseConfig): Promise<string> {
     try {
       const tauriRequest: TauriPassphraseRequest = {
@@ -123,7 +237,46 @@ export class TauriAPI {
     }
   }
 
-  static async generateUsername(config: UsernameConfig): Promise<string> {
+  static async generatePassphraseWithStats(config: PassphraseConfig): Promise<PassphraseResult> {
+    const tauriRequest: TauriPassphraseRequest = {
+      num_words: config.wordCount,
+      word_separator: config.separator,
+      capitalize: config.capitalize,
+      include_number: config.includeNumbers,
+    };
+
+    return await invoke('generate_passphrase_with_stats', { request: tauriRequest });
+  }
+
+  /** Generate `count` passphrases, between 2 and 10, for the user to pick one from */
+  static async generatePassphraseChoices(config: PassphraseConfig, count: number): Promise<PassphraseResult[]> {
+    const tauriRequest: TauriPassphraseRequest = {
+      num_words: config.wordCount,
+      word_separator: config.separator,
+      capitalize: config.capitalize,
+      include_number: config.includeNumbers,
+    };
+
+    return await invoke('generate_passphrase_choices', { request: tauriRequest, count });
+  }
+
+  static async estimatePassphraseEntropy(config: PassphraseConfig): Promise<EntropyEstimate> {
+    const tauriRequest: TauriPassphraseRequest = {
+      num_words: config.wordCount,
+      word_separator: config.separator,
+      capitalize: config.capitalize,
+      include_number: config.includeNumbers,
+    };
+
+    return await invoke('estimate_passphrase_entropy', { request: tauriRequest });
+  }
+
+  /**
+   * Forwarded usernames fail with a `Timeout` error after `timeoutSeconds`,
+   * 30 by default and at most 120.
+   */
+  /** With `deduplicate`, usernames already generated this session are regenerated */
+  static async generateUsername(config: UsernameConfig, timeoutSeconds?: number, deduplicate?: boolean): Promise<string> {
     try {
       let tauriRequest: TauriUsernameRequest;
       
@@ -232,6 +385,14 @@ export class TauriAPI {
               };
               break;
               
+            case 'IronVest':
+              serviceConfig = {
+                IronVest: {
+                  api_token: config.service.api_token || '',
+                }
+              };
+              break;
+              
             default:
               throw new Error('Unsupported forwarding service');
           }
@@ -248,13 +409,36 @@ export class TauriAPI {
           throw new Error('Unsupported username type');
       }
       
-      return await invoke('generate_username', { request: tauriRequest });
+      return await invoke('generate_username', { request: tauriRequest, timeoutSeconds, deduplicate });
     } catch (error) {
       console.error('Failed to generate username:', error);
+      if (isSerializableError(error)) {
+        throw Object.assign(new Error(error.message), { code: error.code, details: error.details });
+      }
       throw error;
     }
   }
 
+  /** Forgets the usernames generated this session, so they may be generated again */
+  static async clearUsernameHistory(): Promise<void> {
+    await invoke('clear_username_history');
+  }
+
+  /** Number of different usernames generated this session */
+  static async getUsernameGenerationCount(): Promise<number> {
+    return await invoke('get_username_generation_count');
+  }
+
+  /** Lowercases and punycode-encodes the domain of an email address, the local part is kept as is */
+  static async normalizeEmail(email: string): Promise<string> {
+    return await invoke('normalize_email', { email });
+  }
+
+  /** Whether two email addresses are the same address, ignoring case and IDN encoding */
+  static async emailsEquivalent(a: string, b: string): Promise<boolean> {
+    return await invoke('emails_equivalent', { a, b });
+  }
+
   static async copyToClipboard(text: string): Promise<boolean> {
     try {
       await writeText(text);
@@ -284,6 +468,15 @@ export class TauriAPI {
     }
   }
 
+  static async generateUuid(version: UuidVersion = 'V4', format: UuidFormat = 'Hyphenated'): Promise<string> {
+    return await invoke('generate_uuid', { version, format });
+  }
+
+  /** Name-based (v5) UUID, always the same for a given namespace and name */
+  static async generateUuidNamespace(namespace: UuidNamespace, name: string): Promise<string> {
+    return await invoke('generate_uuid_namespace', { namespace, name });
+  }
+
   static async calculatePasswordStrength(password: string): Promise<PasswordStrength> {
     try {
       return await invoke('calculate_password_strength', { password });
@@ -298,6 +491,20 @@ export class TauriAPI {
     }
   }
 
+  /** zxcvbn strength of a passphrase, with its words (split on `wordSeparator`, a space by default) as context */
+  static async calculatePassphraseStrength(passphrase: string, wordSeparator?: string): Promise<PasswordStrength> {
+    return await invoke('calculate_passphrase_strength', { passphrase, wordSeparator });
+  }
+
+  /** Flags a new password that is too close to the one it replaces, above `maxSimilarity` (0.7 by default) */
+  static async comparePasswords(
+    newPassword: string,
+    oldPassword: string,
+    maxSimilarity?: number
+  ): Promise<PasswordSimilarityResult> {
+    return await invoke('compare_passwords', { newPassword, oldPassword, maxSimilarity });
+  }
+
   static async calculateUsernameStrength(username: string): Promise<UsernameStrength> {
     try {
       return await invoke('calculate_username_strength', { username });
@@ -314,6 +521,37 @@ export class TauriAPI {
     return fileName;
   }
 
+  /** Bitwarden's unencrypted JSON export format, for its import dialog */
+  static async exportAsBitwardenJson(credentials: CredentialRecord[]): Promise<string> {
+    return await invoke('export_as_bitwarden_json', { credentials });
+  }
+
+  /** Writes a Bitwarden JSON export to the save directory and returns its path */
+  static async saveBitwardenExport(credentials: CredentialRecord[]): Promise<string> {
+    return await invoke('save_bitwarden_export', { credentials });
+  }
+
+  /** KeePass 2.x XML, for KeePass' "KeePass XML (2.x)" import */
+  static async exportAsKeepassXml(credentials: CredentialRecord[], databaseName: string): Promise<string> {
+    return await invoke('export_as_keepass_xml', { credentials, databaseName });
+  }
+
+  /** Writes a KeePass XML export to the save directory and returns its path */
+  static async saveKeepassExport(credentials: CredentialRecord[], databaseName: string): Promise<string> {
+    return await invoke('save_keepass_export', { credentials, databaseName });
+  }
+
+  /** 1Password's 1PUX archive (a zip), for its "1Password Unencrypted Export" import */
+  static async exportAs1PasswordPux(credentials: CredentialRecord[]): Promise<Uint8Array> {
+    const bytes = await invoke<number[]>('export_as_1password_pux', { credentials });
+    return new Uint8Array(bytes);
+  }
+
+  /** Writes a 1PUX archive to the save directory and returns its path */
+  static async save1PasswordExport(credentials: CredentialRecord[]): Promise<string> {
+    return await invoke('save_1password_export', { credentials });
+  }
+
   /**
    * Masks an IP address for privacy protection.
    * Supports both IPv4 and IPv6 formats.
@@ -383,6 +621,11 @@ export class TauriAPI {
     }
   }
 
+  /** Forget the cached public IP so the next lookup goes to the network */
+  static async invalidateIpCache(): Promise<void> {
+    await invoke('invalidate_ip_cache');
+  }
+
   private static async detectLocalIP(): Promise<string | null> {
     try {
       // Create a dummy WebRTC connection to detect local IP
@@ -46,7 +46,7 @@ const catchallUsernameSchema = z.object({
 
 // Forwarded email service schema
 const forwardedServiceSchema = z.object({
-  type: z.enum(['AddyIo', 'DuckDuckGo', 'Firefox', 'Fastmail', 'ForwardEmail', 'SimpleLogin']),
+  type: z.enum(['AddyIo', 'DuckDuckGo', 'Firefox', 'Fastmail', 'ForwardEmail', 'SimpleLogin', 'IronVest']),
   api_token: z.string().optional(),
   domain: z.string().optional(),
   base_url: z.string().url('Invalid URL format').optional(),
@@ -0,0 +1,83 @@
+//! Benchmarks for parsing forwarder API payloads
+//!
+//! Run with `cargo bench --bench forwarders`. The payloads are serialized once up front, so only
+//! the JSON parsing is measured and no network access is needed.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use serde::de::DeserializeOwned;
+
+// The app is a binary crate, so the generators are compiled into the benchmark directly. Lints
+// are already reported for them on the app target.
+#[allow(dead_code, unused_imports, clippy::all)]
+#[path = "../src/generators/mod.rs"]
+mod generators;
+
+use generators::username_forwarders::addyio::{AddyMailbox, AliasInfo};
+use generators::username_forwarders::fastmail::FastmailIdentity;
+use generators::username_forwarders::firefox::FirefoxRelayQuota;
+use generators::username_forwarders::forwardemail::ForwardEmailAlias;
+use generators::username_forwarders::simplelogin::SimpleLoginDirectory;
+
+/// Number of entries in each mock list payload, about one page of results
+const PAYLOAD_ENTRIES: usize = 50;
+
+fn list_payload<T: serde::Serialize>(entry: impl Fn(usize) -> T) -> String {
+    serde_json::to_string(&(0..PAYLOAD_ENTRIES).map(entry).collect::<Vec<_>>()).unwrap()
+}
+
+fn bench_parse<T: DeserializeOwned>(c: &mut Criterion, name: &str, payload: &str) {
+    c.bench_function(name, |b| {
+        b.iter(|| serde_json::from_str::<T>(black_box(payload)).unwrap())
+    });
+}
+
+fn bench_forwarder_payloads(c: &mut Criterion) {
+    let aliases = list_payload(|i| AliasInfo {
+        id: format!("50c9e585-e7f5-41c4-9016-9014c15454{i:02}"),
+        email: format!("alias{i}@anonaddy.me"),
+        description: Some("Shopping".to_string()),
+        active: i % 2 == 0,
+        created_at: "2024-01-01 12:00:00".to_string(),
+    });
+    bench_parse::<Vec<AliasInfo>>(c, "addy.io aliases", &aliases);
+
+    let mailboxes = list_payload(|i| AddyMailbox {
+        id: format!("mailbox-{i}"),
+        email: format!("user{i}@example.com"),
+        default: i == 0,
+    });
+    bench_parse::<Vec<AddyMailbox>>(c, "addy.io mailboxes", &mailboxes);
+
+    let identities = list_payload(|i| FastmailIdentity {
+        id: format!("identity-{i}"),
+        email: format!("user{i}@fastmail.com"),
+        display_name: format!("User {i}"),
+    });
+    bench_parse::<Vec<FastmailIdentity>>(c, "Fastmail identities", &identities);
+
+    let forward_email_aliases = list_payload(|i| ForwardEmailAlias {
+        id: format!("alias-{i}"),
+        name: format!("alias{i}"),
+        address: format!("alias{i}@example.com"),
+        created_at: "2024-01-01T12:00:00.000Z".to_string(),
+        is_enabled: true,
+    });
+    bench_parse::<Vec<ForwardEmailAlias>>(c, "ForwardEmail aliases", &forward_email_aliases);
+
+    let directories = list_payload(|i| SimpleLoginDirectory {
+        name: format!("directory{i}"),
+        nb_alias: i as u32,
+        enabled: true,
+    });
+    bench_parse::<Vec<SimpleLoginDirectory>>(c, "SimpleLogin directories", &directories);
+
+    let quota = serde_json::to_string(&FirefoxRelayQuota {
+        aliases_remaining: Some(3),
+        has_premium: false,
+    })
+    .unwrap();
+    bench_parse::<FirefoxRelayQuota>(c, "Firefox Relay quota", &quota);
+}
+
+criterion_group!(benches, bench_forwarder_payloads);
+criterion_main!(benches);
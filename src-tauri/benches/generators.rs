@@ -0,0 +1,67 @@
+//! Benchmarks for the password, passphrase and username generators
+//!
+//! Run with `cargo bench --bench generators`. Every benchmark draws from a seeded `ChaCha8Rng`,
+//! so runs are comparable with each other.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+// The app is a binary crate, so the generators are compiled into the benchmark directly. Lints
+// are already reported for them on the app target.
+#[allow(dead_code, unused_imports, clippy::all)]
+#[path = "../src/generators/mod.rs"]
+mod generators;
+
+use generators::passphrase::{passphrase_from_request, PassphraseGeneratorRequest};
+use generators::password::{password_from_request, PasswordGeneratorRequest};
+use generators::username::{username_word, NumberPosition, NumberSuffix, UsernameFormat, UsernameStrength};
+
+fn bench_password(c: &mut Criterion) {
+    // The builder defaults to 16 characters from every class
+    let request = PasswordGeneratorRequest::builder().build().unwrap();
+    let mut rng = ChaCha8Rng::seed_from_u64(42);
+    c.bench_function("password 16 chars all classes", |b| {
+        b.iter(|| password_from_request(&mut rng, black_box(request.clone())).unwrap())
+    });
+}
+
+fn bench_passphrase(c: &mut Criterion) {
+    let request = PassphraseGeneratorRequest {
+        num_words: 5,
+        word_separator: "-".to_string(),
+        ..Default::default()
+    };
+    let mut rng = ChaCha8Rng::seed_from_u64(42);
+    c.bench_function("passphrase 5 words", |b| {
+        b.iter(|| passphrase_from_request(&mut rng, black_box(request.clone())).unwrap())
+    });
+}
+
+fn bench_username_word(c: &mut Criterion) {
+    let mut group = c.benchmark_group("username word");
+    for strength in [
+        UsernameStrength::Basic,
+        UsernameStrength::Standard,
+        UsernameStrength::Strong,
+        UsernameStrength::Maximum,
+    ] {
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        group.bench_function(format!("{:?}", strength), |b| {
+            b.iter(|| {
+                username_word(
+                    &mut rng,
+                    true,
+                    NumberSuffix::FourDigits,
+                    NumberPosition::Suffix,
+                    black_box(strength),
+                    UsernameFormat::Plain,
+                )
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_password, bench_passphrase, bench_username_word);
+criterion_main!(benches);
@@ -0,0 +1,74 @@
+//! Benchmarks for EFF word list membership checks
+//!
+//! Run with `cargo bench --bench wordlist_lookup`. The username strength check in `main.rs`
+//! looks up the whole username, the username without trailing digits and every two-word split
+//! of it, so each strategy below replays those lookups for a set of representative usernames.
+
+use std::collections::HashSet;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+#[allow(dead_code, unused_imports, clippy::all)]
+#[path = "../src/generators/wordlist.rs"]
+mod wordlist;
+
+use wordlist::{is_eff_word, EFF_LONG_WORD_LIST};
+
+/// Generated usernames and typical user picked ones, with and without trailing digits
+const USERNAMES: &[&str] = &[
+    "quietriver",
+    "unopened4821",
+    "abacus",
+    "zookeeper99",
+    "john_smith",
+    "xk9fq2lm",
+    "admin123",
+    "deflectedcarpool",
+    "gamer2024",
+    "motorcycleanywhere",
+];
+
+/// Calls per benchmark iteration, cycling through [`USERNAMES`]
+const CALLS: usize = 1000;
+
+/// Same lookups as `analyze_dictionary_usage`, with `contains` as the membership check
+fn lookups(username: &str, contains: impl Fn(&str) -> bool) -> bool {
+    let base_word = username.trim_end_matches(|c: char| c.is_ascii_digit());
+    if contains(username) || contains(base_word) {
+        return true;
+    }
+    if !(6..=20).contains(&base_word.len()) || !base_word.is_ascii() {
+        return false;
+    }
+    (3..=base_word.len() - 3).any(|i| {
+        let (first, second) = base_word.split_at(i);
+        contains(first) && contains(second)
+    })
+}
+
+fn run(contains: impl Fn(&str) -> bool + Copy) -> usize {
+    USERNAMES
+        .iter()
+        .cycle()
+        .take(CALLS)
+        .filter(|username| lookups(black_box(username), contains))
+        .count()
+}
+
+fn bench_wordlist_lookup(c: &mut Criterion) {
+    let set: HashSet<&str> = EFF_LONG_WORD_LIST.iter().copied().collect();
+
+    let mut group = c.benchmark_group("wordlist lookup");
+    group.bench_function("linear contains", |b| {
+        b.iter(|| run(|word| EFF_LONG_WORD_LIST.contains(&word)))
+    });
+    group.bench_function("binary search", |b| {
+        b.iter(|| run(|word| EFF_LONG_WORD_LIST.binary_search(&word).is_ok()))
+    });
+    group.bench_function("hash set", |b| b.iter(|| run(|word| set.contains(word))));
+    group.bench_function("is_eff_word", |b| b.iter(|| run(is_eff_word)));
+    group.finish();
+}
+
+criterion_group!(benches, bench_wordlist_lookup);
+criterion_main!(benches);
@@ -1,3 +1,52 @@
+use sha2::{Digest, Sha256};
+
 fn main() {
+  // Embed the GeoLite2 database only when it has been downloaded into `resources/`.
+  // See `src/geoip.rs` for details.
+  println!("cargo:rustc-check-cfg=cfg(geoip_db)");
+  println!("cargo:rerun-if-changed=../resources/GeoLite2-Country.mmdb");
+  if std::path::Path::new("../resources/GeoLite2-Country.mmdb").exists() {
+    println!("cargo:rustc-cfg=geoip_db");
+  }
+
+  // Record the compiler version for the `get_api_version` command
+  let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+  let rustc_version = std::process::Command::new(rustc)
+    .arg("--version")
+    .output()
+    .ok()
+    .and_then(|output| String::from_utf8(output.stdout).ok())
+    .map(|version| version.trim().to_string())
+    .unwrap_or_else(|| "unknown".to_string());
+  println!("cargo:rustc-env=SECUREGEN_RUSTC_VERSION={}", rustc_version);
+
+  // Hash the passphrase word list, one word per line, so generated passphrases can be traced
+  // back to the exact list they were drawn from. See `src/generators/wordlist.rs`.
+  println!("cargo:rerun-if-changed=src/generators/wordlist.rs");
+  let wordlist = std::fs::read_to_string("src/generators/wordlist.rs").expect("word list is readable");
+  let mut hasher = Sha256::new();
+  let mut previous: Option<&str> = None;
+  let entries = wordlist
+    .lines()
+    .map(str::trim)
+    .skip_while(|line| !line.starts_with("pub const EFF_LONG_WORD_LIST"))
+    .take_while(|line| *line != "];");
+  for line in entries {
+    if let Some(word) = line.strip_prefix('"').and_then(|line| line.strip_suffix("\",")) {
+      // Lookups into the list rely on it being sorted, fail the build rather than ship a list
+      // where they silently miss
+      if let Some(previous) = previous.filter(|previous| *previous >= word) {
+        panic!("EFF_LONG_WORD_LIST must be sorted without duplicates, found {previous:?} before {word:?}");
+      }
+      previous = Some(word);
+      hasher.update(word.as_bytes());
+      hasher.update(b"\n");
+    }
+  }
+  let hash: String = hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect();
+  let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR is set for build scripts");
+  std::fs::write(std::path::Path::new(&out_dir).join("wordlist_sha256.txt"), hash)
+    .expect("word list hash is writable");
+
   tauri_build::build()
 }
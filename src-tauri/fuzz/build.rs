@@ -0,0 +1,22 @@
+use sha2::{Digest, Sha256};
+
+// The generators module embeds the hash of its word list, computed the same way as the app's
+// build script in `../build.rs`
+fn main() {
+  println!("cargo:rerun-if-changed=../src/generators/wordlist.rs");
+  let wordlist = std::fs::read_to_string("../src/generators/wordlist.rs").expect("word list is readable");
+  let mut hasher = Sha256::new();
+  let entries = wordlist
+    .lines()
+    .map(str::trim)
+    .skip_while(|line| !line.starts_with("pub const EFF_LONG_WORD_LIST"))
+    .take_while(|line| *line != "];");
+  for word in entries.filter_map(|line| line.strip_prefix('"').and_then(|line| line.strip_suffix("\","))) {
+    hasher.update(word.as_bytes());
+    hasher.update(b"\n");
+  }
+  let hash: String = hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect();
+  let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR is set for build scripts");
+  std::fs::write(std::path::Path::new(&out_dir).join("wordlist_sha256.txt"), hash)
+    .expect("word list hash is writable");
+}
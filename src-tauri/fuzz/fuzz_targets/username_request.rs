@@ -0,0 +1,39 @@
+//! Fuzzes the JSON deserialization and validation of `UsernameGeneratorRequest`
+//!
+//! Run with `cargo +nightly fuzz run username_request` from `src-tauri/`. Any panic is reported as
+//! a crash, not only allocation failures.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// The app is a binary crate, so the generators are compiled into the fuzz target directly. Lints
+// are already reported for them on the app target.
+#[allow(dead_code, unused_imports, clippy::all)]
+#[path = "../../src/generators/mod.rs"]
+mod generators;
+
+use generators::username::{ForwarderServiceType, UsernameGeneratorRequest};
+use generators::username_forwarders::normalize_base_url;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(request) = serde_json::from_slice::<UsernameGeneratorRequest>(data) else {
+        return;
+    };
+    if request.validate().is_err() {
+        return;
+    }
+
+    // A base URL that passed validation is joined with the API path and parsed when the alias is
+    // created, which must fail with an error rather than panic
+    if let UsernameGeneratorRequest::Forwarded {
+        service: ForwarderServiceType::AddyIo { base_url, .. },
+        ..
+    } = &request
+    {
+        let base_url = normalize_base_url(base_url);
+        let _ = reqwest::Client::new()
+            .post(format!("{base_url}/api/v1/aliases"))
+            .build();
+    }
+});
@@ -0,0 +1,373 @@
+//! `securegen://` links that open the app with generator settings filled in, or with a credential
+//! to fill in.
+//!
+//! - `securegen://generate?type=password&length=20&upper=true&numbers=true`
+//! - `securegen://generate?type=passphrase&words=5&separator=-&capitalization=FirstLetter`
+//! - `securegen://generate?type=username&style=catchall&domain=example.com`
+//! - `securegen://fill?value=aHVudGVyMg` with the credential encoded as unpadded base64url
+//!
+//! Links can be opened by any website or application, so every value goes through
+//! [`sanitize_string_input`] and unknown or repeated parameters are rejected. Forwarded usernames
+//! need an API token and can't be set up by a link.
+
+use std::collections::HashMap;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::generators::passphrase::{estimate_passphrase_entropy, PassphraseGeneratorRequest};
+use crate::generators::password::PasswordGeneratorRequest;
+use crate::generators::username::UsernameGeneratorRequest;
+use crate::security::sanitize_string_input;
+
+pub const SCHEME: &str = "securegen";
+
+/// Longest credential accepted by a fill link, after base64 decoding
+const MAX_FILL_LENGTH: usize = 1024;
+/// Longest parameter value accepted, after percent decoding. Fits the padded base64 of the
+/// longest credential a fill link may carry, at up to 4 UTF-8 bytes per character.
+const MAX_PARAM_LENGTH: usize = 4 * ((MAX_FILL_LENGTH * 4 + 2) / 3);
+
+/// What a deep link asks the app to do
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeepLink {
+    /// Open a generator with these settings, sent to the frontend as a `deep-link-config` event
+    Generate(DeepLinkConfig),
+    /// Fill in a credential generated elsewhere, sent to the frontend as a `deep-link-fill` event
+    Fill(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", content = "request", rename_all = "lowercase")]
+pub enum DeepLinkConfig {
+    Password(PasswordGeneratorRequest),
+    Passphrase(PassphraseGeneratorRequest),
+    Username(UsernameGeneratorRequest),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ParamKind {
+    Bool,
+    Number,
+    Text,
+}
+
+/// Query parameter name, request field name and value type
+type Param = (&'static str, &'static str, ParamKind);
+
+const PASSWORD_PARAMS: &[Param] = &[
+    ("length", "length", ParamKind::Number),
+    ("lower", "lowercase", ParamKind::Bool),
+    ("upper", "uppercase", ParamKind::Bool),
+    ("numbers", "numbers", ParamKind::Bool),
+    ("special", "special", ParamKind::Bool),
+    ("min_lower", "minLowercase", ParamKind::Number),
+    ("min_upper", "minUppercase", ParamKind::Number),
+    ("min_numbers", "minNumber", ParamKind::Number),
+    ("min_special", "minSpecial", ParamKind::Number),
+];
+
+const PASSPHRASE_PARAMS: &[Param] = &[
+    ("words", "num_words", ParamKind::Number),
+    ("separator", "word_separator", ParamKind::Text),
+    ("capitalization", "capitalization", ParamKind::Text),
+    ("number", "include_number", ParamKind::Bool),
+];
+
+const USERNAME_PARAMS: &[Param] = &[
+    ("capitalize", "capitalize", ParamKind::Bool),
+    ("number_suffix", "number_suffix", ParamKind::Text),
+    ("strength", "strength", ParamKind::Text),
+    ("format", "format", ParamKind::Text),
+    ("words", "word_count", ParamKind::Number),
+    ("email", "email", ParamKind::Text),
+    ("domain", "domain", ParamKind::Text),
+];
+
+/// Parse a `securegen://` link
+pub fn parse(link: &str) -> Result<DeepLink, String> {
+    let url = reqwest::Url::parse(link).map_err(|e| format!("Invalid deep link: {}", e))?;
+    if url.scheme() != SCHEME {
+        return Err(format!("Deep links must use the {}:// scheme", SCHEME));
+    }
+
+    let mut params = HashMap::new();
+    for (name, value) in url.query_pairs() {
+        // Cutting the value short would change the credential in a fill link
+        if value.chars().count() > MAX_PARAM_LENGTH {
+            return Err(format!(
+                "Invalid deep link parameter '{}': must be at most {} characters",
+                name, MAX_PARAM_LENGTH
+            ));
+        }
+        let value = sanitize_string_input(&value, MAX_PARAM_LENGTH, false)
+            .map_err(|e| format!("Invalid deep link parameter '{}': {}", name, e))?;
+        if params.insert(name.to_string(), value).is_some() {
+            return Err(format!("Deep link parameter '{}' is repeated", name));
+        }
+    }
+
+    match url.host_str() {
+        Some("generate") => parse_generate(params).map(DeepLink::Generate),
+        Some("fill") => parse_fill(params).map(DeepLink::Fill),
+        _ => Err("Deep links must be securegen://generate or securegen://fill".to_string()),
+    }
+}
+
+fn parse_generate(mut params: HashMap<String, String>) -> Result<DeepLinkConfig, String> {
+    let generator = params
+        .remove("type")
+        .ok_or_else(|| "Deep link is missing the 'type' parameter".to_string())?;
+
+    match generator.as_str() {
+        "password" => {
+            let request: PasswordGeneratorRequest = apply_params(&PasswordGeneratorRequest::default(), PASSWORD_PARAMS, params)?;
+            request.validate().map_err(|e| e.to_string())?;
+            Ok(DeepLinkConfig::Password(request))
+        }
+        "passphrase" => {
+            let request: PassphraseGeneratorRequest =
+                apply_params(&PassphraseGeneratorRequest::default(), PASSPHRASE_PARAMS, params)?;
+            estimate_passphrase_entropy(request.clone()).map_err(|e| e.to_string())?;
+            Ok(DeepLinkConfig::Passphrase(request))
+        }
+        "username" => parse_username(params).map(DeepLinkConfig::Username),
+        other => Err(format!("Unknown generator type '{}'", other)),
+    }
+}
+
+fn parse_username(mut params: HashMap<String, String>) -> Result<UsernameGeneratorRequest, String> {
+    let style = params.remove("style").unwrap_or_else(|| "word".to_string());
+    // Subaddress and catchall usernames append the website name when one is given
+    let append_type = match params.remove("website") {
+        Some(website) => serde_json::json!({ "WebsiteName": { "website": website } }),
+        None => Value::String("Random".to_string()),
+    };
+
+    let (variant, defaults) = match style.as_str() {
+        "word" => ("Word", serde_json::json!({ "capitalize": false, "strength": "Standard" })),
+        "compound_word" => ("CompoundWord", serde_json::json!({ "word_count": 2, "strength": "Standard" })),
+        "subaddress" => ("Subaddress", serde_json::json!({ "type": append_type, "email": "" })),
+        "catchall" => ("Catchall", serde_json::json!({ "type": append_type, "domain": "" })),
+        "forwarded" => return Err("Forwarded usernames can't be set up by a deep link".to_string()),
+        other => return Err(format!("Unknown username style '{}'", other)),
+    };
+
+    let fields: Value = apply_params(&defaults, USERNAME_PARAMS, params)?;
+    let request: UsernameGeneratorRequest =
+        serde_json::from_value(serde_json::json!({ variant: fields })).map_err(|e| format!("Invalid deep link: {}", e))?;
+    request.validate().map_err(|e| e.to_string())?;
+    Ok(request)
+}
+
+/// Overwrite the fields of `defaults` with the matching query parameters
+fn apply_params<T: Serialize, R: serde::de::DeserializeOwned>(
+    defaults: &T,
+    known: &[Param],
+    params: HashMap<String, String>,
+) -> Result<R, String> {
+    let mut fields: Map<String, Value> = match serde_json::to_value(defaults) {
+        Ok(Value::Object(fields)) => fields,
+        _ => Map::new(),
+    };
+
+    for (name, value) in params {
+        let (_, field, kind) = known
+            .iter()
+            .find(|(param, ..)| *param == name)
+            .ok_or_else(|| format!("Unknown deep link parameter '{}'", name))?;
+        let value = match kind {
+            ParamKind::Bool => match value.as_str() {
+                "true" | "1" => Value::Bool(true),
+                "false" | "0" => Value::Bool(false),
+                _ => return Err(format!("Deep link parameter '{}' must be true or false", name)),
+            },
+            ParamKind::Number => value
+                .parse::<u8>()
+                .map(Value::from)
+                .map_err(|_| format!("Deep link parameter '{}' must be a number from 0 to 255", name))?,
+            ParamKind::Text => Value::String(value),
+        };
+        fields.insert(field.to_string(), value);
+    }
+
+    serde_json::from_value(Value::Object(fields)).map_err(|e| format!("Invalid deep link: {}", e))
+}
+
+fn parse_fill(mut params: HashMap<String, String>) -> Result<String, String> {
+    let encoded = params
+        .remove("value")
+        .ok_or_else(|| "Deep link is missing the 'value' parameter".to_string())?;
+    if let Some(name) = params.keys().next() {
+        return Err(format!("Unknown deep link parameter '{}'", name));
+    }
+
+    let bytes = URL_SAFE_NO_PAD
+        .decode(encoded.trim_end_matches('='))
+        .map_err(|_| "Deep link value must be base64url encoded".to_string())?;
+    let value = String::from_utf8(bytes).map_err(|_| "Deep link value must be UTF-8 text".to_string())?;
+    // Credentials may contain shell metacharacters, but not NUL bytes or line breaks
+    if value.contains(['\n', '\r']) {
+        return Err("Deep link value must be a single line".to_string());
+    }
+    if value.chars().count() > MAX_FILL_LENGTH {
+        return Err(format!("Deep link value must be at most {} characters", MAX_FILL_LENGTH));
+    }
+    sanitize_string_input(&value, MAX_FILL_LENGTH, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::passphrase::CapitalizationMode;
+    use crate::generators::username::{AppendType, NumberSuffix, NumberPosition, UsernameFormat, UsernameStrength};
+
+    #[test]
+    fn test_password_link() {
+        let link = parse("securegen://generate?type=password&length=20&upper=true&numbers=true&special=1").unwrap();
+        let expected = PasswordGeneratorRequest {
+            length: 20,
+            uppercase: true,
+            numbers: true,
+            special: true,
+            ..Default::default()
+        };
+        assert_eq!(link, DeepLink::Generate(DeepLinkConfig::Password(expected)));
+    }
+
+    #[test]
+    fn test_password_link_defaults() {
+        let link = parse("securegen://generate?type=password").unwrap();
+        assert_eq!(link, DeepLink::Generate(DeepLinkConfig::Password(PasswordGeneratorRequest::default())));
+    }
+
+    #[test]
+    fn test_passphrase_link() {
+        let link = parse("securegen://generate?type=passphrase&words=5&separator=-&capitalization=FirstLetter&number=true")
+            .unwrap();
+        let expected = PassphraseGeneratorRequest {
+            num_words: 5,
+            word_separator: "-".to_string(),
+            capitalization: CapitalizationMode::FirstLetter,
+            include_number: true,
+            ..Default::default()
+        };
+        assert_eq!(link, DeepLink::Generate(DeepLinkConfig::Passphrase(expected)));
+    }
+
+    #[test]
+    fn test_username_links() {
+        let link = parse("securegen://generate?type=username&capitalize=true&number_suffix=FourDigits").unwrap();
+        let expected = UsernameGeneratorRequest::Word {
+            capitalize: true,
+            number_suffix: NumberSuffix::FourDigits,
+            number_position: NumberPosition::default(),
+            strength: UsernameStrength::Standard,
+            format: UsernameFormat::default(),
+        };
+        assert_eq!(link, DeepLink::Generate(DeepLinkConfig::Username(expected)));
+
+        let link = parse("securegen://generate?type=username&style=compound_word&words=3&format=KebabCase").unwrap();
+        let expected = UsernameGeneratorRequest::CompoundWord {
+            word_count: 3,
+            strength: UsernameStrength::Standard,
+            format: UsernameFormat::KebabCase,
+        };
+        assert_eq!(link, DeepLink::Generate(DeepLinkConfig::Username(expected)));
+
+        let link = parse("securegen://generate?type=username&style=catchall&domain=example.com&website=github").unwrap();
+        let expected = UsernameGeneratorRequest::Catchall {
+            r#type: AppendType::WebsiteName {
+                website: "github".to_string(),
+            },
+            domain: "example.com".to_string(),
+            original_domain: None,
+        };
+        assert_eq!(link, DeepLink::Generate(DeepLinkConfig::Username(expected)));
+
+        let link = parse("securegen://generate?type=username&style=subaddress&email=user%40example.com").unwrap();
+        let expected = UsernameGeneratorRequest::Subaddress {
+            r#type: AppendType::Random,
+            email: "user@example.com".to_string(),
+        };
+        assert_eq!(link, DeepLink::Generate(DeepLinkConfig::Username(expected)));
+    }
+
+    #[test]
+    fn test_fill_link() {
+        let encoded = URL_SAFE_NO_PAD.encode("p@ss&w$rd~");
+        assert_eq!(
+            parse(&format!("securegen://fill?value={}", encoded)).unwrap(),
+            DeepLink::Fill("p@ss&w$rd~".to_string())
+        );
+        // Padding is accepted too
+        assert_eq!(parse("securegen://fill?value=aHVudGVyMg==").unwrap(), DeepLink::Fill("hunter2".to_string()));
+    }
+
+    #[test]
+    fn test_fill_link_longest_value() {
+        // 4 bytes per character, the longest encoding a credential can have
+        let longest = "🔑".repeat(MAX_FILL_LENGTH);
+        let link = format!("securegen://fill?value={}", URL_SAFE_NO_PAD.encode(&longest));
+        assert_eq!(parse(&link).unwrap(), DeepLink::Fill(longest));
+
+        let too_long = format!("securegen://fill?value={}", URL_SAFE_NO_PAD.encode("🔑".repeat(MAX_FILL_LENGTH + 1)));
+        assert!(parse(&too_long).is_err());
+
+        // Over-long parameters are rejected rather than cut short
+        let error = parse(&format!("securegen://fill?value={}", "a".repeat(MAX_PARAM_LENGTH + 1))).unwrap_err();
+        assert_eq!(
+            error,
+            format!("Invalid deep link parameter 'value': must be at most {} characters", MAX_PARAM_LENGTH)
+        );
+    }
+
+    #[test]
+    fn test_event_payload() {
+        let link = parse("securegen://generate?type=password&length=20").unwrap();
+        let DeepLink::Generate(config) = link else {
+            panic!("expected a generate link");
+        };
+        let payload = serde_json::to_value(config).unwrap();
+        assert_eq!(payload["type"], "password");
+        assert_eq!(payload["request"]["length"], 20);
+    }
+
+    #[test]
+    fn test_rejected_links() {
+        for link in [
+            "https://generate?type=password",
+            "securegen://delete?type=password",
+            "securegen://generate",
+            "securegen://generate?type=pin",
+            "securegen://generate?type=password&length=300",
+            "securegen://generate?type=password&length=4&upper=maybe",
+            "securegen://generate?type=password&length=20&length=8",
+            "securegen://generate?type=password&charset=abc",
+            "securegen://generate?type=password&lower=false&upper=false&numbers=false&special=false",
+            "securegen://generate?type=passphrase&words=1",
+            "securegen://generate?type=username&style=forwarded",
+            "securegen://generate?type=username&style=catchall",
+            "securegen://fill",
+            "securegen://fill?value=not*base64",
+            "securegen://fill?value=aGk&extra=1",
+        ] {
+            assert!(parse(link).is_err(), "{link} was accepted");
+        }
+    }
+
+    #[test]
+    fn test_rejects_injection() {
+        let error = parse("securegen://generate?type=username&style=catchall&domain=example.com%3B%20rm%20-rf%20%2F")
+            .unwrap_err();
+        assert_eq!(error, "Invalid deep link parameter 'domain': must not contain ';'");
+        assert!(parse("securegen://generate?type=passphrase&separator=%24(id)").is_err());
+        assert!(parse("securegen://generate?type=password%0Aspecial").is_err());
+        // NUL bytes are stripped rather than rejected
+        assert!(parse("securegen://generate?type=password&length=2%000").is_ok());
+        let fill = URL_SAFE_NO_PAD.encode("line\nbreak");
+        assert!(parse(&format!("securegen://fill?value={}", fill)).is_err());
+    }
+}
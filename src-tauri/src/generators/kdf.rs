@@ -0,0 +1,374 @@
+use crate::generators::to_hex;
+use argon2::{Algorithm, Argon2, Params, Version};
+use rand::RngCore;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum KdfError {
+    #[error("Password cannot be empty")]
+    EmptyPassword,
+    #[error("Salt must be at least {minimum} bytes")]
+    SaltTooShort { minimum: usize },
+    #[error("'output_length_bytes' must be between {minimum} and {maximum}")]
+    InvalidOutputLength { minimum: u8, maximum: u8 },
+    #[error("PBKDF2 iterations must be between {minimum} and {maximum}")]
+    InvalidPbkdf2Iterations { minimum: u32, maximum: u32 },
+    #[error("Argon2id parameters are below the OWASP minimum: {reason}")]
+    WeakArgon2Parameters { reason: String },
+    #[error("Argon2id parameters are too large: {reason}")]
+    ExcessiveArgon2Parameters { reason: String },
+    #[error("Key derivation failed: {0}")]
+    Argon2(argon2::Error),
+}
+
+/// Key derivation function and its cost parameters.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+pub enum KdfAlgorithm {
+    /// PBKDF2 with HMAC-SHA256
+    Pbkdf2Sha256 {
+        /// Number of iterations. OWASP recommends at least 600,000.
+        iterations: u32,
+    },
+    /// Argon2id (version 0x13)
+    Argon2id {
+        /// Memory cost in KiB
+        memory_kb: u32,
+        /// Number of passes over memory
+        iterations: u32,
+        /// Degree of parallelism
+        parallelism: u8,
+    },
+}
+
+/// The result of a key derivation.
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
+pub struct DerivedKey {
+    /// The derived key, hex encoded
+    pub key_hex: String,
+    /// The salt used for derivation, hex encoded. Store this alongside the key to re-derive it.
+    pub salt_hex: String,
+    /// Human readable algorithm name
+    pub algorithm: String,
+    /// The cost parameters used for derivation
+    pub parameters: serde_json::Value,
+}
+
+// OWASP Password Storage Cheat Sheet recommendations:
+// <https://cheatsheetseries.owasp.org/cheatsheets/Password_Storage_Cheat_Sheet.html>
+const PBKDF2_MIN_ITERATIONS: u32 = 600_000;
+const PBKDF2_MAX_ITERATIONS: u32 = 10_000_000;
+/// OWASP lists equivalent Argon2id configurations trading memory for passes, from
+/// m=47104 (46 MiB), t=1 down to m=7168 (7 MiB), t=5. All of them have a memory * passes
+/// product of at least 7168 * 5.
+const ARGON2_MIN_MEMORY_KB: u32 = 7 * 1024;
+const ARGON2_MIN_MEMORY_PASSES_PRODUCT: u64 = 7 * 1024 * 5;
+const ARGON2_MAX_MEMORY_KB: u32 = 1024 * 1024;
+const ARGON2_MAX_ITERATIONS: u32 = 100;
+const ARGON2_MAX_PARALLELISM: u8 = 16;
+
+const MIN_SALT_LENGTH: usize = 8;
+const GENERATED_SALT_LENGTH: usize = 16;
+const MIN_OUTPUT_LENGTH: u8 = 16;
+const MAX_OUTPUT_LENGTH: u8 = 64;
+
+impl KdfAlgorithm {
+    /// Validates the cost parameters against the OWASP minimum recommendations, and against
+    /// upper bounds that keep a single derivation from exhausting the machine.
+    fn validate(&self) -> Result<(), KdfError> {
+        match *self {
+            KdfAlgorithm::Pbkdf2Sha256 { iterations } => {
+                if !(PBKDF2_MIN_ITERATIONS..=PBKDF2_MAX_ITERATIONS).contains(&iterations) {
+                    return Err(KdfError::InvalidPbkdf2Iterations {
+                        minimum: PBKDF2_MIN_ITERATIONS,
+                        maximum: PBKDF2_MAX_ITERATIONS,
+                    });
+                }
+            }
+            KdfAlgorithm::Argon2id {
+                memory_kb,
+                iterations,
+                parallelism,
+            } => {
+                if memory_kb < ARGON2_MIN_MEMORY_KB {
+                    return Err(KdfError::WeakArgon2Parameters {
+                        reason: format!("memory must be at least {} KiB", ARGON2_MIN_MEMORY_KB),
+                    });
+                }
+                if iterations == 0
+                    || (memory_kb as u64) * (iterations as u64) < ARGON2_MIN_MEMORY_PASSES_PRODUCT
+                {
+                    return Err(KdfError::WeakArgon2Parameters {
+                        reason: format!(
+                            "memory_kb * iterations must be at least {}, e.g. 19456 KiB with 2 iterations",
+                            ARGON2_MIN_MEMORY_PASSES_PRODUCT
+                        ),
+                    });
+                }
+                if parallelism == 0 {
+                    return Err(KdfError::WeakArgon2Parameters {
+                        reason: "parallelism must be at least 1".to_string(),
+                    });
+                }
+                if memory_kb > ARGON2_MAX_MEMORY_KB {
+                    return Err(KdfError::ExcessiveArgon2Parameters {
+                        reason: format!("memory must be at most {} KiB", ARGON2_MAX_MEMORY_KB),
+                    });
+                }
+                if iterations > ARGON2_MAX_ITERATIONS {
+                    return Err(KdfError::ExcessiveArgon2Parameters {
+                        reason: format!("iterations must be at most {}", ARGON2_MAX_ITERATIONS),
+                    });
+                }
+                if parallelism > ARGON2_MAX_PARALLELISM {
+                    return Err(KdfError::ExcessiveArgon2Parameters {
+                        reason: format!("parallelism must be at most {}", ARGON2_MAX_PARALLELISM),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            KdfAlgorithm::Pbkdf2Sha256 { .. } => "PBKDF2-HMAC-SHA256",
+            KdfAlgorithm::Argon2id { .. } => "Argon2id",
+        }
+    }
+
+    fn parameters(&self) -> serde_json::Value {
+        match *self {
+            KdfAlgorithm::Pbkdf2Sha256 { iterations } => json!({ "iterations": iterations }),
+            KdfAlgorithm::Argon2id {
+                memory_kb,
+                iterations,
+                parallelism,
+            } => json!({
+                "memory_kb": memory_kb,
+                "iterations": iterations,
+                "parallelism": parallelism,
+            }),
+        }
+    }
+}
+
+/// Derives a key from `password`. When `salt` is `None`, a random 16 byte salt is generated.
+pub fn derive_key(
+    password: String,
+    salt: Option<String>,
+    algorithm: KdfAlgorithm,
+    output_length_bytes: u8,
+) -> Result<DerivedKey, KdfError> {
+    if password.is_empty() {
+        return Err(KdfError::EmptyPassword);
+    }
+    if !(MIN_OUTPUT_LENGTH..=MAX_OUTPUT_LENGTH).contains(&output_length_bytes) {
+        return Err(KdfError::InvalidOutputLength {
+            minimum: MIN_OUTPUT_LENGTH,
+            maximum: MAX_OUTPUT_LENGTH,
+        });
+    }
+    algorithm.validate()?;
+
+    let salt = match salt {
+        Some(salt) => salt.into_bytes(),
+        None => generate_salt(rand::thread_rng()),
+    };
+    if salt.len() < MIN_SALT_LENGTH {
+        return Err(KdfError::SaltTooShort {
+            minimum: MIN_SALT_LENGTH,
+        });
+    }
+
+    let key = derive_key_bytes(password.as_bytes(), &salt, &algorithm, output_length_bytes)?;
+
+    Ok(DerivedKey {
+        key_hex: to_hex(&key),
+        salt_hex: to_hex(&salt),
+        algorithm: algorithm.name().to_string(),
+        parameters: algorithm.parameters(),
+    })
+}
+
+fn generate_salt(mut rng: impl RngCore) -> Vec<u8> {
+    let mut salt = vec![0u8; GENERATED_SALT_LENGTH];
+    rng.fill_bytes(&mut salt);
+    salt
+}
+
+/// Runs the KDF without validating the parameters.
+fn derive_key_bytes(
+    password: &[u8],
+    salt: &[u8],
+    algorithm: &KdfAlgorithm,
+    output_length_bytes: u8,
+) -> Result<Vec<u8>, KdfError> {
+    let mut key = vec![0u8; output_length_bytes as usize];
+
+    match *algorithm {
+        KdfAlgorithm::Pbkdf2Sha256 { iterations } => {
+            pbkdf2::pbkdf2_hmac::<sha2::Sha256>(password, salt, iterations, &mut key);
+        }
+        KdfAlgorithm::Argon2id {
+            memory_kb,
+            iterations,
+            parallelism,
+        } => {
+            let params = Params::new(
+                memory_kb,
+                iterations,
+                parallelism as u32,
+                Some(output_length_bytes as usize),
+            )
+            .map_err(KdfError::Argon2)?;
+            Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+                .hash_password_into(password, salt, &mut key)
+                .map_err(KdfError::Argon2)?;
+        }
+    }
+
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn pbkdf2(password: &str, salt: &str, iterations: u32, length: u8) -> String {
+        let key = derive_key_bytes(
+            password.as_bytes(),
+            salt.as_bytes(),
+            &KdfAlgorithm::Pbkdf2Sha256 { iterations },
+            length,
+        )
+        .unwrap();
+        to_hex(&key)
+    }
+
+    #[test]
+    fn test_pbkdf2_sha256_vectors() {
+        // RFC 6070 inputs, with the published PBKDF2-HMAC-SHA256 outputs
+        assert_eq!(
+            pbkdf2("password", "salt", 1, 32),
+            "120fb6cffcf8b32c43e7225256c4f837a86548c92ccc35480805987cb70be17b"
+        );
+        assert_eq!(
+            pbkdf2("password", "salt", 2, 32),
+            "ae4d0c95af6b46d32d0adff928f06dd02a303f8ef3c251dfd6e2d85a95474c43"
+        );
+        assert_eq!(
+            pbkdf2("password", "salt", 4096, 32),
+            "c5e478d59288c841aa530db6845c4c8d962893a001ce4e11a4963873aa98134a"
+        );
+        assert_eq!(
+            pbkdf2(
+                "passwordPASSWORDpassword",
+                "saltSALTsaltSALTsaltSALTsaltSALTsalt",
+                4096,
+                40
+            ),
+            "348c89dbcbd32b2f32d814b8116e84cf2b17347ebc1800181c4e2a1fb8dd53e1c635518c7dac47e9"
+        );
+        assert_eq!(pbkdf2("pass\0word", "sa\0lt", 4096, 16), "89b69d0516f829893c696226650a8687");
+    }
+
+    #[test]
+    fn test_argon2id_deterministic() {
+        let algorithm = KdfAlgorithm::Argon2id {
+            memory_kb: 64,
+            iterations: 1,
+            parallelism: 1,
+        };
+        let first = derive_key_bytes(b"password", b"somesalt", &algorithm, 32).unwrap();
+        let second = derive_key_bytes(b"password", b"somesalt", &algorithm, 32).unwrap();
+        let other_salt = derive_key_bytes(b"password", b"othersalt", &algorithm, 32).unwrap();
+
+        assert_eq!(first.len(), 32);
+        assert_eq!(first, second);
+        assert_ne!(first, other_salt);
+    }
+
+    #[test]
+    fn test_owasp_validation() {
+        assert!(KdfAlgorithm::Pbkdf2Sha256 { iterations: 600_000 }.validate().is_ok());
+        assert!(KdfAlgorithm::Pbkdf2Sha256 { iterations: 100_000 }.validate().is_err());
+
+        let argon2 = |memory_kb, iterations, parallelism| KdfAlgorithm::Argon2id {
+            memory_kb,
+            iterations,
+            parallelism,
+        };
+        // Each of the OWASP recommended configurations is accepted
+        assert!(argon2(47104, 1, 1).validate().is_ok());
+        assert!(argon2(19456, 2, 1).validate().is_ok());
+        assert!(argon2(12288, 3, 1).validate().is_ok());
+        assert!(argon2(9216, 4, 1).validate().is_ok());
+        assert!(argon2(7168, 5, 1).validate().is_ok());
+
+        assert!(matches!(
+            argon2(19456, 1, 1).validate(),
+            Err(KdfError::WeakArgon2Parameters { .. })
+        ));
+        assert!(matches!(
+            argon2(4096, 10, 1).validate(),
+            Err(KdfError::WeakArgon2Parameters { .. })
+        ));
+        assert!(matches!(
+            argon2(19456, 2, 0).validate(),
+            Err(KdfError::WeakArgon2Parameters { .. })
+        ));
+        assert!(matches!(
+            argon2(4 * 1024 * 1024, 2, 1).validate(),
+            Err(KdfError::ExcessiveArgon2Parameters { .. })
+        ));
+    }
+
+    #[test]
+    fn test_derive_key_rejects_invalid_input() {
+        let algorithm = KdfAlgorithm::Pbkdf2Sha256 { iterations: 600_000 };
+
+        assert!(matches!(
+            derive_key("".into(), None, algorithm.clone(), 32),
+            Err(KdfError::EmptyPassword)
+        ));
+        assert!(matches!(
+            derive_key("password".into(), None, algorithm.clone(), 8),
+            Err(KdfError::InvalidOutputLength { .. })
+        ));
+        assert!(matches!(
+            derive_key("password".into(), Some("salt".into()), algorithm, 32),
+            Err(KdfError::SaltTooShort { .. })
+        ));
+    }
+
+    #[test]
+    fn test_generate_salt() {
+        let mut rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
+        let first = generate_salt(&mut rng);
+        let second = generate_salt(&mut rng);
+
+        assert_eq!(first.len(), GENERATED_SALT_LENGTH);
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_serde_compatibility() {
+        let json = r#"{"Argon2id":{"memory_kb":19456,"iterations":2,"parallelism":1}}"#;
+        let algorithm: KdfAlgorithm = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            algorithm,
+            KdfAlgorithm::Argon2id {
+                memory_kb: 19456,
+                iterations: 2,
+                parallelism: 1
+            }
+        );
+        assert_eq!(algorithm.parameters()["memory_kb"], 19456);
+        assert_eq!(algorithm.name(), "Argon2id");
+    }
+}
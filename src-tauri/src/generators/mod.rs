@@ -1,11 +1,149 @@
 pub mod password;
 pub mod passphrase;
+pub mod kdf;
 pub mod username;
 pub mod username_forwarders;
+pub mod uuid;
 pub mod wordlist;
 
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use passphrase::PassphraseError;
+use password::PasswordError;
+use username::UsernameError;
+
+/// Error returned by the generator entry points, so callers that invoke several generators can
+/// handle failures through a single type
+#[derive(Debug, Error)]
+pub enum GeneratorError {
+    #[error(transparent)]
+    Password(#[from] PasswordError),
+    #[error(transparent)]
+    Passphrase(#[from] PassphraseError),
+    #[error(transparent)]
+    Username(#[from] UsernameError),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+}
+
+/// Code used for errors that don't have a more specific code
+pub const GENERIC_ERROR_CODE: u16 = 1000;
+
+/// Error shape returned to the frontend, so it can branch on a stable `code` instead of
+/// matching on message text
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SerializableError {
+    pub code: u16,
+    pub message: String,
+    /// Underlying cause, when there is more to say than `message`
+    pub details: Option<String>,
+}
+
+impl From<GeneratorError> for SerializableError {
+    fn from(error: GeneratorError) -> Self {
+        match error {
+            GeneratorError::Username(error) => error.into(),
+            other => SerializableError {
+                code: GENERIC_ERROR_CODE,
+                message: other.to_string(),
+                details: None,
+            },
+        }
+    }
+}
+
+/// Generate a random number with the given number of digits, including leading zeros
+pub(crate) fn random_number(mut rng: impl Rng, digits: u8) -> String {
+    let num = rng.gen_range(0..10u64.pow(digits.into()));
+    format!("{num:0>width$}", width = digits as usize)
+}
+
+/// Lowercase hex encoding of `bytes`, two digits per byte
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
 #[cfg(test)]
 pub mod username_integration_tests;
 
 #[cfg(test)]
-pub mod username_forwarders_unit_tests; 
\ No newline at end of file
+pub mod username_forwarders_unit_tests; 
+#[cfg(test)]
+mod schema_tests;
+#[cfg(test)]
+mod passphrase_proptest;
+#[cfg(test)]
+mod snapshot_tests;
+#[cfg(all(test, not(target_env = "msvc")))]
+mod memory_audit;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_generator_errors() {
+        let error: GeneratorError = PasswordError::InvalidLength.into();
+        assert!(matches!(error, GeneratorError::Password(PasswordError::InvalidLength)));
+
+        let error: GeneratorError = PassphraseError::InvalidNumWords { minimum: 3, maximum: 20 }.into();
+        assert!(matches!(error, GeneratorError::Passphrase(PassphraseError::InvalidNumWords { .. })));
+
+        let error: GeneratorError = UsernameError::EmptyDomain.into();
+        assert!(matches!(error, GeneratorError::Username(UsernameError::EmptyDomain)));
+
+        let error: GeneratorError = std::io::Error::new(std::io::ErrorKind::NotFound, "missing").into();
+        assert!(matches!(error, GeneratorError::Io(_)));
+    }
+
+    #[test]
+    fn test_display_matches_source_error() {
+        // The frontend shows these messages, so wrapping must not change them
+        assert_eq!(
+            GeneratorError::from(PasswordError::NoCharacterSetEnabled).to_string(),
+            "No character set enabled"
+        );
+        assert_eq!(
+            GeneratorError::from(PassphraseError::InvalidNumWords { minimum: 3, maximum: 20 }).to_string(),
+            "'num_words' must be between 3 and 20"
+        );
+        assert_eq!(
+            GeneratorError::from(UsernameError::InvalidApiKey).to_string(),
+            "Invalid API Key"
+        );
+        assert_eq!(
+            GeneratorError::InvalidInput("count must be positive".to_string()).to_string(),
+            "Invalid input: count must be positive"
+        );
+    }
+
+    #[test]
+    fn test_to_hex() {
+        assert_eq!(to_hex(&[]), "");
+        assert_eq!(to_hex(&[0x00, 0x0f, 0xab, 0xff]), "000fabff");
+    }
+
+    #[test]
+    fn test_generators_return_generator_error() {
+        let request = password::PasswordGeneratorRequest {
+            length: 2,
+            ..Default::default()
+        };
+        assert!(matches!(
+            password::generate_password(request),
+            Err(GeneratorError::Password(PasswordError::InvalidLength))
+        ));
+
+        let request = passphrase::PassphraseGeneratorRequest {
+            num_words: 1,
+            ..Default::default()
+        };
+        assert!(matches!(
+            passphrase::generate_passphrase(request),
+            Err(GeneratorError::Passphrase(_))
+        ));
+    }
+}
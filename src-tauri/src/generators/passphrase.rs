@@ -1,28 +1,220 @@
-use crate::generators::wordlist::EFF_LONG_WORD_LIST;
+use crate::generators::wordlist::{EFF_LONG_WORD_LIST, WORDLIST_SHA256, WORDLIST_VERSION};
+use crate::generators::{random_number, GeneratorError};
 use rand::{seq::SliceRandom, Rng, RngCore};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::LazyLock;
 use thiserror::Error;
 
+/// Shown in place of the dice roll for words that are not on the EFF list
+const UNKNOWN_ROLL: &str = "?????";
+
+/// Five digit dice roll for every entry of the EFF list. The list is sorted by roll, so the roll
+/// is the word's index written in base 6 with digits 1 to 6.
+static DICE_ROLL_CODES: LazyLock<Vec<String>> = LazyLock::new(|| {
+    (0..EFF_LONG_WORD_LIST.len())
+        .map(|mut index| {
+            let mut digits = [b'1'; 5];
+            for digit in digits.iter_mut().rev() {
+                *digit += (index % 6) as u8;
+                index /= 6;
+            }
+            String::from_utf8(digits.to_vec()).expect("digits are ascii")
+        })
+        .collect()
+});
+
+/// Reverse lookup from an EFF word to its dice roll
+static DICE_ROLLS: LazyLock<HashMap<&'static str, &'static str>> = LazyLock::new(|| {
+    EFF_LONG_WORD_LIST
+        .iter()
+        .zip(DICE_ROLL_CODES.iter())
+        .map(|(word, roll)| (*word, roll.as_str()))
+        .collect()
+});
+
 #[derive(Debug, Error)]
 pub enum PassphraseError {
     #[error("'num_words' must be between {minimum} and {maximum}")]
     InvalidNumWords { minimum: u8, maximum: u8 },
+    #[error("'word_separator' must not contain NUL bytes or zero-width characters")]
+    InvalidSeparator,
+    #[error("'count' must be between {minimum} and {maximum}")]
+    ChoiceCountOutOfRange { minimum: u8, maximum: u8 },
+    #[error("'excluded_words' must not have more than {maximum} entries")]
+    TooManyExcludedWords { maximum: usize },
+    #[error(
+        "Only {remaining} words are left after 'excluded_words', at least {} are needed",
+        MINIMUM_WORD_LIST_SIZE
+    )]
+    InsufficientWordsAfterExclusion { remaining: usize },
+    #[error("'word_min_length' must not be greater than 'word_max_length'")]
+    InvalidWordLengthRange,
+    #[error(
+        "Only {remaining} words are between 'word_min_length' and 'word_max_length' letters long, at least {} are needed",
+        MINIMUM_WORD_LIST_SIZE
+    )]
+    InsufficientWordsForConstraint { remaining: usize },
+}
+
+/// Zero-width characters that would make a separator invisible when the passphrase is displayed
+const ZERO_WIDTH_CHARS: &[char] = &['\u{200B}', '\u{200C}', '\u{2060}', '\u{FEFF}'];
+
+/// Zero-width joiner, allowed only inside emoji sequences such as `🏳️‍🌈`
+const ZERO_WIDTH_JOINER: char = '\u{200D}';
+
+/// Small words kept lowercase by [`CapitalizationMode::TitleCase`] unless they start the passphrase
+const TITLE_CASE_STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "as", "at", "but", "by", "for", "from", "in", "into", "nor", "of", "off", "on",
+    "onto", "or", "over", "so", "the", "to", "up", "via", "with", "yet",
+];
+
+/// How the words of a passphrase are capitalized
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "PascalCase")]
+pub enum CapitalizationMode {
+    /// Words are left lowercase
+    #[default]
+    None,
+    /// The first letter of every word is uppercased
+    FirstLetter,
+    /// Like `FirstLetter`, but small words such as "and" or "the" stay lowercase unless they
+    /// start the passphrase
+    TitleCase,
+    /// Every letter is uppercased
+    AllCaps,
+    /// Every letter is randomly uppercased or lowercased
+    RandomCase,
+}
+
+/// Accepts either a [`CapitalizationMode`] or the legacy `capitalize` boolean, where `true` maps
+/// to [`CapitalizationMode::FirstLetter`]
+fn deserialize_capitalization<'de, D>(deserializer: D) -> Result<CapitalizationMode, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        Legacy(bool),
+        Mode(CapitalizationMode),
+    }
+
+    Ok(match Repr::deserialize(deserializer)? {
+        Repr::Legacy(true) => CapitalizationMode::FirstLetter,
+        Repr::Legacy(false) => CapitalizationMode::None,
+        Repr::Mode(mode) => mode,
+    })
+}
+
+/// Number of digits added by `include_number`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "PascalCase")]
+pub enum NumberLength {
+    #[default]
+    OneDigit,
+    TwoDigits,
+    FourDigits,
+}
+
+impl NumberLength {
+    fn digits(self) -> u8 {
+        match self {
+            NumberLength::OneDigit => 1,
+            NumberLength::TwoDigits => 2,
+            NumberLength::FourDigits => 4,
+        }
+    }
+}
+
+/// Where the number added by `include_number` is placed
+// The variant names are part of the serialized API
+#[allow(clippy::enum_variant_names)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "PascalCase")]
+pub enum NumberPosition {
+    /// After one of the words, e.g. `river7`
+    #[default]
+    AppendToWord,
+    /// Before one of the words, e.g. `7river`
+    PrependToWord,
+    /// As a separate element between the words, adding one to the word count
+    StandaloneWord,
+}
+
+/// Common emoji separators, for passphrases typed on mobile keyboards
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+pub enum EmojiSeparator {
+    Key,
+    Wave,
+    Lock,
+}
+
+impl EmojiSeparator {
+    /// Every preset, in the order they are offered to the user
+    pub const ALL: [EmojiSeparator; 3] = [EmojiSeparator::Key, EmojiSeparator::Wave, EmojiSeparator::Lock];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EmojiSeparator::Key => "🔑",
+            EmojiSeparator::Wave => "🌊",
+            EmojiSeparator::Lock => "🔒",
+        }
+    }
 }
 
 /// Passphrase generator request options.
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "snake_case", deny_unknown_fields)]
 pub struct PassphraseGeneratorRequest {
     /// Number of words in the generated passphrase.
     /// This value must be between 3 and 20.
     pub num_words: u8,
     /// Character separator between words in the generated passphrase. The value cannot be empty.
+    /// NUL bytes and zero-width characters are rejected, since they can't be seen when displayed.
     pub word_separator: String,
-    /// When set to true, capitalize the first letter of each word in the generated passphrase.
-    pub capitalize: bool,
+    /// How the words of the generated passphrase are capitalized. Also accepts the legacy
+    /// `capitalize` boolean, where `true` capitalizes the first letter of each word.
+    #[serde(default, alias = "capitalize", deserialize_with = "deserialize_capitalization")]
+    pub capitalization: CapitalizationMode,
+    /// 0-based indices of the words to capitalize the first letter of. When set, this overrides
+    /// `capitalization`. Indices past the last word are ignored.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capitalize_positions: Option<Vec<u8>>,
+    /// Shorthand for `capitalize_positions` holding only the last word
+    #[serde(default)]
+    pub capitalize_last_only: bool,
     /// When set to true, include a number at the end of one of the words in the generated
     /// passphrase.
     pub include_number: bool,
+    /// Number of digits in the number added by `include_number`
+    #[serde(default)]
+    pub number_length: NumberLength,
+    /// Where the number added by `include_number` is placed
+    #[serde(default)]
+    pub number_position: NumberPosition,
+    /// When set to true, output one `{dice_roll}: {word}` line per word instead of joining the
+    /// words with `word_separator`, so the passphrase can be checked against the Diceware list.
+    #[serde(default)]
+    pub diceware_compatible: bool,
+    /// When set to true, the drawn words are reordered so they are easier to say aloud, see
+    /// [`phonetic_score`]. The order no longer adds entropy, only the choice of words does.
+    #[serde(default)]
+    pub phonetic_smoothing: bool,
+    /// Words that are never picked, such as banned terms. Compared case-insensitively, at most
+    /// 1000 entries, and at least 100 words of the list must remain.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub excluded_words: Option<Vec<String>>,
+    /// Shortest word length picked, 3 (the shortest EFF words) when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub word_min_length: Option<u8>,
+    /// Longest word length picked, no limit when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub word_max_length: Option<u8>,
 }
 
 impl Default for PassphraseGeneratorRequest {
@@ -30,8 +222,17 @@ impl Default for PassphraseGeneratorRequest {
         Self {
             num_words: 3,
             word_separator: " ".to_string(),
-            capitalize: false,
+            capitalization: CapitalizationMode::None,
+            capitalize_positions: None,
+            capitalize_last_only: false,
             include_number: false,
+            number_length: NumberLength::OneDigit,
+            number_position: NumberPosition::AppendToWord,
+            diceware_compatible: false,
+            phonetic_smoothing: false,
+            excluded_words: None,
+            word_min_length: None,
+            word_max_length: None,
         }
     }
 }
@@ -39,18 +240,88 @@ impl Default for PassphraseGeneratorRequest {
 const MINIMUM_PASSPHRASE_NUM_WORDS: u8 = 3;
 const MAXIMUM_PASSPHRASE_NUM_WORDS: u8 = 20;
 
+/// Shortest words on the EFF list
+const DEFAULT_WORD_MIN_LENGTH: u8 = 3;
+const MAXIMUM_EXCLUDED_WORDS: usize = 1000;
+/// Fewest words that may remain of the word list after the length constraints and
+/// `excluded_words` are applied
+const MINIMUM_WORD_LIST_SIZE: usize = 100;
+
+const MINIMUM_PASSPHRASE_CHOICES: u8 = 2;
+const MAXIMUM_PASSPHRASE_CHOICES: u8 = 10;
+
 /// Represents a set of valid options to generate a passphrase with.
 /// To get an instance of it, use
 /// [`PassphraseGeneratorRequest::validate_options`](PassphraseGeneratorRequest::validate_options)
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 struct ValidPassphraseGeneratorOptions {
     pub(super) num_words: u8,
     pub(super) word_separator: String,
-    pub(super) capitalize: bool,
+    pub(super) capitalization: CapitalizationMode,
+    pub(super) capitalize_positions: Option<Vec<u8>>,
     pub(super) include_number: bool,
+    pub(super) number_length: NumberLength,
+    pub(super) number_position: NumberPosition,
+    pub(super) diceware_compatible: bool,
+    pub(super) phonetic_smoothing: bool,
+    /// Words to pick from, the EFF list within the length constraints and without `excluded_words`
+    pub(super) word_list: Cow<'static, [&'static str]>,
 }
 
 impl PassphraseGeneratorRequest {
+    /// Returns the `word_separator` value for an emoji preset
+    pub fn word_separator_preset(preset: EmojiSeparator) -> String {
+        preset.as_str().to_string()
+    }
+
+    /// The EFF list narrowed down to words of `word_min_length` to `word_max_length` letters and
+    /// without `excluded_words`. The list is only copied when something is filtered out.
+    fn word_list(&self) -> Result<Cow<'static, [&'static str]>, PassphraseError> {
+        let min_length = usize::from(self.word_min_length.unwrap_or(DEFAULT_WORD_MIN_LENGTH));
+        let max_length = self.word_max_length.map_or(usize::MAX, usize::from);
+        if min_length > max_length {
+            return Err(PassphraseError::InvalidWordLengthRange);
+        }
+        let excluded = self.excluded_words.as_deref().unwrap_or_default();
+        if excluded.len() > MAXIMUM_EXCLUDED_WORDS {
+            return Err(PassphraseError::TooManyExcludedWords {
+                maximum: MAXIMUM_EXCLUDED_WORDS,
+            });
+        }
+
+        let mut word_list = Cow::Borrowed(EFF_LONG_WORD_LIST);
+        let length_range = min_length..=max_length;
+        if word_list.iter().any(|word| !length_range.contains(&word.len())) {
+            let constrained: Vec<&'static str> = EFF_LONG_WORD_LIST
+                .iter()
+                .copied()
+                .filter(|word| length_range.contains(&word.len()))
+                .collect();
+            if constrained.len() < MINIMUM_WORD_LIST_SIZE {
+                return Err(PassphraseError::InsufficientWordsForConstraint {
+                    remaining: constrained.len(),
+                });
+            }
+            word_list = Cow::Owned(constrained);
+        }
+
+        if !excluded.is_empty() {
+            let excluded: HashSet<String> = excluded.iter().map(|word| word.trim().to_lowercase()).collect();
+            let remaining: Vec<&'static str> = word_list
+                .iter()
+                .copied()
+                .filter(|word| !excluded.contains(*word))
+                .collect();
+            if remaining.len() < MINIMUM_WORD_LIST_SIZE {
+                return Err(PassphraseError::InsufficientWordsAfterExclusion {
+                    remaining: remaining.len(),
+                });
+            }
+            word_list = Cow::Owned(remaining);
+        }
+        Ok(word_list)
+    }
+
     /// Validates the request and returns an immutable struct with valid options to use with the
     /// passphrase generator.
     fn validate_options(self) -> Result<ValidPassphraseGeneratorOptions, PassphraseError> {
@@ -62,36 +333,310 @@ impl PassphraseGeneratorRequest {
             });
         }
 
+        if !is_valid_separator(&self.word_separator) {
+            return Err(PassphraseError::InvalidSeparator);
+        }
+
+        let word_list = self.word_list()?;
+
+        let capitalize_positions = match (self.capitalize_positions, self.capitalize_last_only) {
+            (Some(positions), last_only) => {
+                if last_only {
+                    tracing::warn!("'capitalize_positions' is set, ignoring 'capitalize_last_only'");
+                }
+                Some(positions)
+            }
+            (None, true) => Some(vec![self.num_words - 1]),
+            (None, false) => None,
+        };
+        // Positions replace the capitalization mode, so it must not count towards the entropy either
+        let capitalization = if capitalize_positions.is_some() {
+            if self.capitalization != CapitalizationMode::None {
+                tracing::warn!(
+                    capitalization = ?self.capitalization,
+                    "Capitalizing only the requested word positions, ignoring 'capitalization'"
+                );
+            }
+            CapitalizationMode::None
+        } else {
+            self.capitalization
+        };
+
         Ok(ValidPassphraseGeneratorOptions {
             num_words: self.num_words,
             word_separator: self.word_separator,
-            capitalize: self.capitalize,
+            capitalization,
+            capitalize_positions,
             include_number: self.include_number,
+            number_length: self.number_length,
+            number_position: self.number_position,
+            diceware_compatible: self.diceware_compatible,
+            phonetic_smoothing: self.phonetic_smoothing,
+            word_list,
         })
     }
 }
 
+/// Short summary of the request, e.g. `Passphrase(5 words, "-" separator, capitalized, with number)`
+impl fmt::Display for PassphraseGeneratorRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = vec![
+            format!("{} words", self.num_words),
+            format!("{:?} separator", self.word_separator),
+        ];
+        let capitalization = if self.capitalize_positions.is_some() {
+            Some("selected words capitalized")
+        } else if self.capitalize_last_only {
+            Some("last word capitalized")
+        } else {
+            match self.capitalization {
+                CapitalizationMode::None => None,
+                CapitalizationMode::FirstLetter => Some("capitalized"),
+                CapitalizationMode::TitleCase => Some("title case"),
+                CapitalizationMode::AllCaps => Some("all caps"),
+                CapitalizationMode::RandomCase => Some("random case"),
+            }
+        };
+        parts.extend(capitalization.map(str::to_string));
+        if self.include_number {
+            parts.push("with number".to_string());
+        }
+        if self.phonetic_smoothing {
+            parts.push("phonetically smoothed".to_string());
+        }
+        match (self.word_min_length, self.word_max_length) {
+            (Some(min), Some(max)) => parts.push(format!("{}-{} letter words", min, max)),
+            (Some(min), None) => parts.push(format!("words of {}+ letters", min)),
+            (None, Some(max)) => parts.push(format!("words of up to {} letters", max)),
+            (None, None) => {}
+        }
+        if let Some(excluded) = self.excluded_words.as_ref().filter(|excluded| !excluded.is_empty()) {
+            parts.push(format!("{} words excluded", excluded.len()));
+        }
+        if self.diceware_compatible {
+            parts.push("Diceware output".to_string());
+        }
+
+        write!(f, "Passphrase({})", parts.join(", "))
+    }
+}
+
+/// Passphrase together with an estimate of its strength
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct PassphraseResult {
+    pub passphrase: String,
+    /// Entropy of the generator settings in bits, not of this particular passphrase
+    pub entropy_bits: f64,
+    /// Number of words taken from the word list
+    pub word_count: u8,
+    /// Version of the word list the words were taken from
+    pub wordlist_version: String,
+    /// SHA-256 of the word list the words were taken from
+    pub wordlist_hash: String,
+}
+
+/// Guesses per second assumed by [`crack_time_display`], a fast offline attack on a weak hash
+const GUESSES_PER_SECOND: f64 = 1e10;
+
+/// Size of a typical password alphabet: lowercase, uppercase, digits and 10 common symbols
+const PASSWORD_ALPHABET_SIZE: f64 = 72.0;
+
+/// Strength of the passphrases a request would produce, computed without generating one
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct EntropyEstimate {
+    pub entropy_bits: f64,
+    /// Length of a random password over a 72 character alphabet with the same entropy
+    pub equivalent_password_length: f64,
+    pub crack_time_display: String,
+    pub wordlist_size: usize,
+}
+
 /// Implementation of the random passphrase generator.
-pub fn generate_passphrase(request: PassphraseGeneratorRequest) -> Result<String, PassphraseError> {
+///
+/// # Examples
+///
+/// Run as `tests::test_generate_passphrase_examples`.
+///
+/// ```ignore
+/// use crate::generators::passphrase::{generate_passphrase, PassphraseGeneratorRequest};
+///
+/// let request = PassphraseGeneratorRequest {
+///     num_words: 5,
+///     word_separator: " ".to_string(),
+///     ..Default::default()
+/// };
+/// let passphrase = generate_passphrase(request).unwrap();
+/// assert!(!passphrase.is_empty());
+/// assert_eq!(passphrase.split(' ').count(), 5);
+///
+/// // Below the 3 word minimum
+/// let request = PassphraseGeneratorRequest { num_words: 2, ..Default::default() };
+/// assert!(generate_passphrase(request).is_err());
+/// ```
+pub fn generate_passphrase(request: PassphraseGeneratorRequest) -> Result<String, GeneratorError> {
+    Ok(passphrase_from_request(rand::thread_rng(), request)?)
+}
+
+/// Same as [`generate_passphrase`], drawing from `rng` instead of the thread RNG
+pub(crate) fn passphrase_from_request(
+    rng: impl RngCore,
+    request: PassphraseGeneratorRequest,
+) -> Result<String, PassphraseError> {
+    let options = request.validate_options()?;
+    Ok(passphrase_with_rng(rng, options))
+}
+
+/// Same as [`generate_passphrase`], but also returns the entropy of the passphrase
+pub fn generate_passphrase_with_stats(
+    request: PassphraseGeneratorRequest,
+) -> Result<PassphraseResult, GeneratorError> {
+    let options = request.validate_options()?;
+    Ok(passphrase_result_with_rng(rand::thread_rng(), options))
+}
+
+/// Generate `count` passphrases with the same settings for the user to choose from
+pub fn generate_passphrase_choices(
+    request: PassphraseGeneratorRequest,
+    count: u8,
+) -> Result<Vec<PassphraseResult>, GeneratorError> {
+    if !(MINIMUM_PASSPHRASE_CHOICES..=MAXIMUM_PASSPHRASE_CHOICES).contains(&count) {
+        return Err(PassphraseError::ChoiceCountOutOfRange {
+            minimum: MINIMUM_PASSPHRASE_CHOICES,
+            maximum: MAXIMUM_PASSPHRASE_CHOICES,
+        }
+        .into());
+    }
     let options = request.validate_options()?;
-    Ok(passphrase_with_rng(rand::thread_rng(), options))
+    let mut rng = rand::thread_rng();
+    Ok((0..count)
+        .map(|_| passphrase_result_with_rng(&mut rng, options.clone()))
+        .collect())
+}
+
+/// Estimate the entropy of the passphrases `request` would produce, without generating one
+pub fn estimate_passphrase_entropy(request: PassphraseGeneratorRequest) -> Result<EntropyEstimate, GeneratorError> {
+    let options = request.validate_options()?;
+    let entropy_bits = passphrase_entropy(&options);
+    Ok(EntropyEstimate {
+        entropy_bits,
+        equivalent_password_length: entropy_bits / PASSWORD_ALPHABET_SIZE.log2(),
+        crack_time_display: crack_time_display(entropy_bits),
+        wordlist_size: options.word_list.len(),
+    })
+}
+
+fn passphrase_result_with_rng(rng: impl RngCore, options: ValidPassphraseGeneratorOptions) -> PassphraseResult {
+    let word_count = options.num_words;
+    let entropy_bits = passphrase_entropy(&options);
+    PassphraseResult {
+        passphrase: passphrase_with_rng(rng, options),
+        entropy_bits,
+        word_count,
+        wordlist_version: WORDLIST_VERSION.to_string(),
+        wordlist_hash: WORDLIST_SHA256.to_string(),
+    }
+}
+
+/// Estimate the entropy in bits of a passphrase generated with `options`. Each word adds
+/// `log2(list size)`, [`CapitalizationMode::RandomCase`] adds half a bit per letter of an average
+/// word, and `include_number` adds the number's digits plus the choice of where it was placed.
+/// `phonetic_smoothing` fixes the order of the words, which removes up to `log2(num_words!)` bits.
+fn passphrase_entropy(options: &ValidPassphraseGeneratorOptions) -> f64 {
+    let list_size = options.word_list.len() as f64;
+    let mut entropy = list_size.log2() * f64::from(options.num_words);
+    if options.phonetic_smoothing {
+        entropy -= (2..=options.num_words).map(|n| f64::from(n).log2()).sum::<f64>();
+    }
+
+    if options.capitalization == CapitalizationMode::RandomCase {
+        let letters: usize = options.word_list.iter().map(|word| word.len()).sum();
+        let average_word_length = letters as f64 / list_size;
+        entropy += 0.5 * average_word_length * f64::from(options.num_words);
+    }
+
+    if options.include_number {
+        entropy += f64::from(options.number_length.digits()) * 10f64.log2();
+        let positions = match options.number_position {
+            NumberPosition::AppendToWord | NumberPosition::PrependToWord => options.num_words,
+            NumberPosition::StandaloneWord => options.num_words + 1,
+        };
+        entropy += f64::from(positions).log2();
+    }
+
+    entropy
+}
+
+/// Describe how long an attacker making [`GUESSES_PER_SECOND`] guesses would need to search the
+/// whole space of `entropy_bits`
+fn crack_time_display(entropy_bits: f64) -> String {
+    const MINUTE: f64 = 60.0;
+    const HOUR: f64 = 60.0 * MINUTE;
+    const DAY: f64 = 24.0 * HOUR;
+    const YEAR: f64 = 365.25 * DAY;
+
+    let seconds = entropy_bits.exp2() / GUESSES_PER_SECOND;
+    let (amount, unit) = if seconds < 1.0 {
+        return "Less than a second".to_string();
+    } else if seconds < MINUTE {
+        (seconds, "second")
+    } else if seconds < HOUR {
+        (seconds / MINUTE, "minute")
+    } else if seconds < DAY {
+        (seconds / HOUR, "hour")
+    } else if seconds < YEAR {
+        (seconds / DAY, "day")
+    } else if seconds < 100.0 * YEAR {
+        (seconds / YEAR, "year")
+    } else {
+        return "Centuries".to_string();
+    };
+
+    let amount = amount.round();
+    let plural = if amount == 1.0 { "" } else { "s" };
+    format!("About {} {}{}", amount, unit, plural)
 }
 
 fn passphrase_with_rng(mut rng: impl RngCore, options: ValidPassphraseGeneratorOptions) -> String {
-    let mut passphrase_words = gen_words(&mut rng, options.num_words);
+    let mut passphrase_words = gen_words(&mut rng, &options.word_list, options.num_words);
+    if options.phonetic_smoothing {
+        smooth_phonetically(&mut passphrase_words);
+    }
+    // Capitalize before adding the number, so a prepended number doesn't hide the first letter
+    capitalize_words(
+        &mut rng,
+        &mut passphrase_words,
+        options.capitalization,
+        options.capitalize_positions.as_deref(),
+    );
     if options.include_number {
-        include_number_in_words(&mut rng, &mut passphrase_words);
+        include_number_in_words(
+            &mut rng,
+            &mut passphrase_words,
+            options.number_length,
+            options.number_position,
+        );
     }
-    if options.capitalize {
-        capitalize_words(&mut passphrase_words);
+    if options.diceware_compatible {
+        return passphrase_words
+            .iter()
+            .map(|word| format!("{}: {}", dice_roll(word), word))
+            .collect::<Vec<_>>()
+            .join("\n");
     }
     passphrase_words.join(&options.word_separator)
 }
 
-fn gen_words(mut rng: impl RngCore, num_words: u8) -> Vec<String> {
+/// Look up the dice roll of a passphrase word. Case and any digits added by `include_number` are
+/// ignored, words not on the EFF list give [`UNKNOWN_ROLL`].
+fn dice_roll(word: &str) -> &'static str {
+    let word = word.trim_matches(|c: char| c.is_ascii_digit()).to_lowercase();
+    DICE_ROLLS.get(word.as_str()).copied().unwrap_or(UNKNOWN_ROLL)
+}
+
+fn gen_words(mut rng: impl RngCore, word_list: &[&str], num_words: u8) -> Vec<String> {
     (0..num_words)
         .map(|_| {
-            EFF_LONG_WORD_LIST
+            word_list
                 .choose(&mut rng)
                 .expect("slice is not empty")
                 .to_string()
@@ -99,15 +644,165 @@ fn gen_words(mut rng: impl RngCore, num_words: u8) -> Vec<String> {
         .collect()
 }
 
-fn include_number_in_words(mut rng: impl RngCore, words: &mut [String]) {
-    let number_idx = rng.gen_range(0..words.len());
-    words[number_idx].push_str(&rng.gen_range(0..=9).to_string());
+fn is_vowel(c: char) -> bool {
+    matches!(c.to_ascii_lowercase(), 'a' | 'e' | 'i' | 'o' | 'u')
 }
 
-fn capitalize_words(words: &mut [String]) {
-    words
-        .iter_mut()
-        .for_each(|w| *w = capitalize_first_letter(w));
+/// The consonants a word starts with, lowercased, e.g. `str` for `stream`
+fn leading_consonants(word: &str) -> String {
+    word.chars()
+        .skip_while(|c| !c.is_alphabetic())
+        .take_while(|c| c.is_alphabetic() && !is_vowel(*c))
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// How easily `word_b` is said after `word_a`, from -1.0 to 1.5. Across the word boundary a
+/// change between vowel and consonant scores 1.0, two vowels 0.5 and two consonants nothing.
+/// First letters of different kinds add 0.5, and starting with the same consonant cluster
+/// costs 1.0.
+fn phonetic_score(word_a: &str, word_b: &str) -> f64 {
+    let first_letter = |word: &str| word.chars().find(|c| c.is_alphabetic());
+    let (Some(a_first), Some(a_last), Some(b_first)) = (
+        first_letter(word_a),
+        word_a.chars().rev().find(|c| c.is_alphabetic()),
+        first_letter(word_b),
+    ) else {
+        return 0.0;
+    };
+
+    let mut score = match (is_vowel(a_last), is_vowel(b_first)) {
+        (true, false) | (false, true) => 1.0,
+        (true, true) => 0.5,
+        (false, false) => 0.0,
+    };
+    if is_vowel(a_first) != is_vowel(b_first) {
+        score += 0.5;
+    }
+    let cluster = leading_consonants(word_a);
+    if !cluster.is_empty() && cluster == leading_consonants(word_b) {
+        score -= 1.0;
+    }
+    score
+}
+
+/// Reorder `words` to maximize the [`phonetic_score`] of neighbouring words. Starting from each
+/// word in turn, the best scoring word is appended until none are left, and the chain with the
+/// highest total wins. The words are sorted first, so the result only depends on which words
+/// were drawn and not on the order they were drawn in.
+fn smooth_phonetically(words: &mut Vec<String>) {
+    words.sort();
+    let mut best: Option<(f64, Vec<usize>)> = None;
+    for start in 0..words.len() {
+        let mut remaining: Vec<usize> = (0..words.len()).filter(|&i| i != start).collect();
+        let mut chain = vec![start];
+        let mut total = 0.0;
+        while !remaining.is_empty() {
+            let last = &words[chain[chain.len() - 1]];
+            let mut next = 0;
+            let mut next_score = f64::NEG_INFINITY;
+            for (position, &candidate) in remaining.iter().enumerate() {
+                let score = phonetic_score(last, &words[candidate]);
+                if score > next_score {
+                    next = position;
+                    next_score = score;
+                }
+            }
+            total += next_score;
+            chain.push(remaining.remove(next));
+        }
+        if best.as_ref().map_or(true, |(best_total, _)| total > *best_total) {
+            best = Some((total, chain));
+        }
+    }
+
+    if let Some((_, chain)) = best {
+        let sorted = std::mem::take(words);
+        *words = chain.into_iter().map(|i| sorted[i].clone()).collect();
+    }
+}
+
+/// Rejects separators containing NUL bytes or zero-width characters. A zero-width joiner is only
+/// accepted between two visible characters, so emoji sequences built with it still work.
+fn is_valid_separator(separator: &str) -> bool {
+    let chars: Vec<char> = separator.chars().collect();
+    chars.iter().enumerate().all(|(i, &c)| {
+        if c == '\0' || ZERO_WIDTH_CHARS.contains(&c) {
+            return false;
+        }
+        if c == ZERO_WIDTH_JOINER {
+            let joins = |other: Option<&char>| other.map_or(false, |&o| o != ZERO_WIDTH_JOINER);
+            return i > 0 && joins(chars.get(i - 1)) && joins(chars.get(i + 1));
+        }
+        true
+    })
+}
+
+fn include_number_in_words(
+    mut rng: impl RngCore,
+    words: &mut Vec<String>,
+    length: NumberLength,
+    position: NumberPosition,
+) {
+    let number = random_number(&mut rng, length.digits());
+    match position {
+        NumberPosition::AppendToWord => {
+            let number_idx = rng.gen_range(0..words.len());
+            words[number_idx].push_str(&number);
+        }
+        NumberPosition::PrependToWord => {
+            let number_idx = rng.gen_range(0..words.len());
+            words[number_idx].insert_str(0, &number);
+        }
+        NumberPosition::StandaloneWord => {
+            let number_idx = rng.gen_range(0..=words.len());
+            words.insert(number_idx, number);
+        }
+    }
+}
+
+/// Capitalize `words` according to `mode`, or only the first letter of the words at `positions`
+/// when given. Positions past the last word are ignored.
+fn capitalize_words(
+    mut rng: impl RngCore,
+    words: &mut [String],
+    mode: CapitalizationMode,
+    positions: Option<&[u8]>,
+) {
+    if let Some(positions) = positions {
+        for &position in positions {
+            if let Some(word) = words.get_mut(usize::from(position)) {
+                *word = capitalize_first_letter(word);
+            }
+        }
+        return;
+    }
+
+    match mode {
+        CapitalizationMode::None => {}
+        CapitalizationMode::FirstLetter => words
+            .iter_mut()
+            .for_each(|w| *w = capitalize_first_letter(w)),
+        CapitalizationMode::TitleCase => words.iter_mut().enumerate().for_each(|(i, w)| {
+            let bare_word = w.trim_matches(|c: char| c.is_ascii_digit());
+            if i == 0 || !TITLE_CASE_STOP_WORDS.contains(&bare_word) {
+                *w = capitalize_first_letter(w);
+            }
+        }),
+        CapitalizationMode::AllCaps => words.iter_mut().for_each(|w| *w = w.to_uppercase()),
+        CapitalizationMode::RandomCase => words.iter_mut().for_each(|w| {
+            *w = w
+                .chars()
+                .map(|c| {
+                    if rng.gen_bool(0.5) {
+                        c.to_uppercase().collect::<String>()
+                    } else {
+                        c.to_lowercase().collect::<String>()
+                    }
+                })
+                .collect();
+        }),
+    }
 }
 
 fn capitalize_first_letter(s: &str) -> String {
@@ -126,10 +821,26 @@ mod tests {
     use super::*;
     use rand::SeedableRng;
 
+    #[test]
+    fn test_generate_passphrase_examples() {
+        let request = PassphraseGeneratorRequest {
+            num_words: 5,
+            word_separator: " ".to_string(),
+            ..Default::default()
+        };
+        let passphrase = generate_passphrase(request).unwrap();
+        assert!(!passphrase.is_empty());
+        assert_eq!(passphrase.split(' ').count(), 5);
+
+        // Below the 3 word minimum
+        let request = PassphraseGeneratorRequest { num_words: 2, ..Default::default() };
+        assert!(generate_passphrase(request).is_err());
+    }
+
     #[test]
     fn test_gen_words() {
         let mut rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
-        let words = gen_words(&mut rng, 4);
+        let words = gen_words(&mut rng, EFF_LONG_WORD_LIST, 4);
         assert_eq!(words.len(), 4);
         // All words should be from the EFF wordlist
         for word in &words {
@@ -138,7 +849,7 @@ mod tests {
         
         // Test with deterministic seed for specific output
         let mut rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
-        let words = gen_words(&mut rng, 2);
+        let words = gen_words(&mut rng, EFF_LONG_WORD_LIST, 2);
         assert_eq!(words.len(), 2);
         // First two words should be consistent with the seed
         assert!(!words[0].is_empty());
@@ -159,8 +870,9 @@ mod tests {
 
     #[test]
     fn test_capitalize_words() {
+        let mut rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
         let mut words = vec!["hello".into(), "world".into()];
-        capitalize_words(&mut words);
+        capitalize_words(&mut rng, &mut words, CapitalizationMode::FirstLetter, None);
         assert_eq!(words, &["Hello", "World"]);
     }
 
@@ -169,7 +881,7 @@ mod tests {
         let mut rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
 
         let mut words = vec!["hello".into(), "world".into()];
-        include_number_in_words(&mut rng, &mut words);
+        include_number_in_words(&mut rng, &mut words, NumberLength::OneDigit, NumberPosition::AppendToWord);
         
         // One of the words should have a number appended
         let has_number = words.iter().any(|word| word.chars().any(|c| c.is_ascii_digit()));
@@ -178,7 +890,7 @@ mod tests {
         // Test deterministic behavior
         let mut rng = rand_chacha::ChaCha8Rng::from_seed([42u8; 32]);
         let mut words = vec!["test".into(), "word".into()];
-        include_number_in_words(&mut rng, &mut words);
+        include_number_in_words(&mut rng, &mut words, NumberLength::OneDigit, NumberPosition::AppendToWord);
         
         // Should have exactly one number in the entire passphrase
         let digit_count = words.iter()
@@ -188,6 +900,127 @@ mod tests {
         assert_eq!(digit_count, 1);
     }
 
+    #[test]
+    fn test_capitalize_positions() {
+        let request = PassphraseGeneratorRequest {
+            num_words: 4,
+            capitalize_positions: Some(vec![0, 2]),
+            ..Default::default()
+        };
+        let passphrase = generate_passphrase(request).unwrap();
+        let words: Vec<&str> = passphrase.split(' ').collect();
+        assert_eq!(words.len(), 4);
+        for (i, word) in words.iter().enumerate() {
+            let first_letter = word.chars().next().unwrap();
+            assert_eq!(first_letter.is_uppercase(), i == 0 || i == 2, "{}", passphrase);
+        }
+    }
+
+    #[test]
+    fn test_capitalize_positions_overrides_capitalization() {
+        let mut rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
+        let options = PassphraseGeneratorRequest {
+            num_words: 3,
+            capitalization: CapitalizationMode::AllCaps,
+            capitalize_positions: Some(vec![1, 3, 200]),
+            ..Default::default()
+        }
+        .validate_options()
+        .unwrap();
+        assert_eq!(options.capitalization, CapitalizationMode::None);
+
+        // Out of range positions are ignored
+        let passphrase = passphrase_with_rng(&mut rng, options);
+        let words: Vec<&str> = passphrase.split(' ').collect();
+        assert_eq!(words[0], words[0].to_lowercase());
+        assert!(words[1].starts_with(|c: char| c.is_uppercase()));
+        assert_eq!(words[1][1..], words[1][1..].to_lowercase());
+        assert_eq!(words[2], words[2].to_lowercase());
+    }
+
+    #[test]
+    fn test_capitalize_last_only() {
+        let request = PassphraseGeneratorRequest {
+            num_words: 5,
+            capitalize_last_only: true,
+            ..Default::default()
+        };
+        let options = request.clone().validate_options().unwrap();
+        assert_eq!(options.capitalize_positions, Some(vec![4]));
+
+        let passphrase = generate_passphrase(request).unwrap();
+        let words: Vec<&str> = passphrase.split(' ').collect();
+        for word in &words[..4] {
+            assert_eq!(*word, word.to_lowercase());
+        }
+        assert!(words[4].starts_with(|c: char| c.is_uppercase()));
+    }
+
+    #[test]
+    fn test_number_length_and_position() {
+        let lengths = [NumberLength::OneDigit, NumberLength::TwoDigits, NumberLength::FourDigits];
+        let positions = [
+            NumberPosition::AppendToWord,
+            NumberPosition::PrependToWord,
+            NumberPosition::StandaloneWord,
+        ];
+        for length in lengths {
+            for position in positions {
+                let mut rng = rand_chacha::ChaCha8Rng::from_seed([7u8; 32]);
+                let mut words: Vec<String> = vec!["alpha".into(), "bravo".into(), "charlie".into()];
+                include_number_in_words(&mut rng, &mut words, length, position);
+
+                let digits = length.digits() as usize;
+                let numbered: Vec<&String> = words.iter().filter(|w| w.chars().any(|c| c.is_ascii_digit())).collect();
+                assert_eq!(numbered.len(), 1, "{:?} {:?}: {:?}", length, position, words);
+                let numbered = numbered[0];
+
+                match position {
+                    NumberPosition::AppendToWord => {
+                        assert_eq!(words.len(), 3);
+                        let (word, number) = numbered.split_at(numbered.len() - digits);
+                        assert!(word.chars().all(|c| c.is_ascii_alphabetic()));
+                        assert!(number.chars().all(|c| c.is_ascii_digit()));
+                    }
+                    NumberPosition::PrependToWord => {
+                        assert_eq!(words.len(), 3);
+                        let (number, word) = numbered.split_at(digits);
+                        assert!(number.chars().all(|c| c.is_ascii_digit()));
+                        assert!(word.chars().all(|c| c.is_ascii_alphabetic()));
+                    }
+                    NumberPosition::StandaloneWord => {
+                        assert_eq!(words.len(), 4);
+                        assert_eq!(numbered.len(), digits);
+                        assert!(numbered.chars().all(|c| c.is_ascii_digit()));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_prepended_number_keeps_capitalization() {
+        let mut rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
+        let options = PassphraseGeneratorRequest {
+            num_words: 3,
+            capitalization: CapitalizationMode::FirstLetter,
+            capitalize_positions: None,
+            capitalize_last_only: false,
+            include_number: true,
+            number_length: NumberLength::TwoDigits,
+            number_position: NumberPosition::PrependToWord,
+            ..Default::default()
+        }
+        .validate_options()
+        .unwrap();
+
+        let passphrase = passphrase_with_rng(&mut rng, options);
+        for word in passphrase.split(' ') {
+            let first_letter = word.chars().find(|c| c.is_alphabetic()).unwrap();
+            assert!(first_letter.is_uppercase(), "{}", passphrase);
+        }
+    }
+
     #[test]
     fn test_separator_unicode() {
         let mut rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
@@ -196,8 +1029,17 @@ mod tests {
             num_words: 3,
             // Test with unicode separator
             word_separator: "🔒".into(),
-            capitalize: false,
+            capitalization: CapitalizationMode::None,
+            capitalize_positions: None,
+            capitalize_last_only: false,
             include_number: false,
+            number_length: NumberLength::OneDigit,
+            number_position: NumberPosition::AppendToWord,
+            diceware_compatible: false,
+            phonetic_smoothing: false,
+            excluded_words: None,
+            word_min_length: None,
+            word_max_length: None,
         }
         .validate_options()
         .unwrap();
@@ -206,13 +1048,79 @@ mod tests {
         assert_eq!(result.matches('🔒').count(), 2); // Should have 2 separators for 3 words
     }
 
+    #[test]
+    fn test_emoji_separator_presets() {
+        for preset in EmojiSeparator::ALL {
+            let separator = PassphraseGeneratorRequest::word_separator_preset(preset);
+            let separator_char = separator.chars().next().unwrap();
+            // Each preset is a single multi-byte character
+            assert_eq!(separator.chars().count(), 1);
+            assert!(separator.len() > 1);
+
+            let mut rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
+            let options = PassphraseGeneratorRequest {
+                num_words: 5,
+                word_separator: separator.clone(),
+                ..Default::default()
+            }
+            .validate_options()
+            .unwrap();
+
+            let passphrase = passphrase_with_rng(&mut rng, options);
+            let words: Vec<&str> = passphrase.split(separator_char).collect();
+            assert_eq!(words.len(), 5, "{}", passphrase);
+            assert!(words.iter().all(|w| !w.is_empty() && w.is_ascii()));
+        }
+    }
+
+    #[test]
+    fn test_invalid_separators() {
+        for separator in ["\0", "-\0-", "\u{200B}", "a\u{200C}b", "\u{2060}", "\u{FEFF}", "\u{200D}", "🔑\u{200D}"] {
+            let request = PassphraseGeneratorRequest {
+                word_separator: separator.to_string(),
+                ..Default::default()
+            };
+            assert!(
+                matches!(request.validate_options(), Err(PassphraseError::InvalidSeparator)),
+                "{:?}",
+                separator
+            );
+        }
+    }
+
+    #[test]
+    fn test_emoji_zwj_sequence_separator() {
+        // Rainbow flag, built from a flag, a variation selector, a zero-width joiner and a rainbow
+        let separator = "🏳\u{FE0F}\u{200D}🌈";
+        let options = PassphraseGeneratorRequest {
+            num_words: 4,
+            word_separator: separator.to_string(),
+            ..Default::default()
+        }
+        .validate_options()
+        .unwrap();
+
+        let mut rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
+        let passphrase = passphrase_with_rng(&mut rng, options);
+        assert_eq!(passphrase.split(separator).count(), 4);
+    }
+
     #[test]
     fn test_passphrase_generation() {
         let request = PassphraseGeneratorRequest {
             num_words: 4,
             word_separator: "-".to_string(),
-            capitalize: true,
+            capitalization: CapitalizationMode::FirstLetter,
+            capitalize_positions: None,
+            capitalize_last_only: false,
             include_number: true,
+            number_length: NumberLength::OneDigit,
+            number_position: NumberPosition::AppendToWord,
+            diceware_compatible: false,
+            phonetic_smoothing: false,
+            excluded_words: None,
+            word_min_length: None,
+            word_max_length: None,
         };
 
         let passphrase = generate_passphrase(request).unwrap();
@@ -244,8 +1152,14 @@ mod tests {
         let options = ValidPassphraseGeneratorOptions {
             num_words: 4,
             word_separator: "-".to_string(),
-            capitalize: true,
+            capitalization: CapitalizationMode::FirstLetter,
+            capitalize_positions: None,
             include_number: true,
+            number_length: NumberLength::OneDigit,
+            number_position: NumberPosition::AppendToWord,
+            diceware_compatible: false,
+            phonetic_smoothing: false,
+            word_list: Cow::Borrowed(EFF_LONG_WORD_LIST),
         };
         
         let result1 = passphrase_with_rng(&mut rng1, options.clone());
@@ -254,14 +1168,161 @@ mod tests {
         assert_eq!(result1, result2);
     }
 
+    #[test]
+    fn test_entropy_grows_with_word_count() {
+        let mut previous = 0.0;
+        for num_words in MINIMUM_PASSPHRASE_NUM_WORDS..=MAXIMUM_PASSPHRASE_NUM_WORDS {
+            let mut rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
+            let options = PassphraseGeneratorRequest {
+                num_words,
+                ..Default::default()
+            }
+            .validate_options()
+            .unwrap();
+
+            let result = passphrase_result_with_rng(&mut rng, options);
+            assert_eq!(result.word_count, num_words);
+            assert_eq!(result.wordlist_version, WORDLIST_VERSION);
+            assert_eq!(result.wordlist_hash, WORDLIST_SHA256);
+            assert_eq!(result.passphrase.split(' ').count(), num_words as usize);
+            assert!(result.entropy_bits > previous, "{} words: {}", num_words, result.entropy_bits);
+            previous = result.entropy_bits;
+        }
+    }
+
+    #[test]
+    fn test_entropy_adjustments() {
+        let entropy = |request: PassphraseGeneratorRequest| {
+            let mut rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
+            passphrase_result_with_rng(&mut rng, request.validate_options().unwrap()).entropy_bits
+        };
+        let base = PassphraseGeneratorRequest {
+            num_words: 5,
+            ..Default::default()
+        };
+
+        // 5 words from the 7776 word list is the classic 64.6 bit Diceware passphrase
+        let plain = entropy(base.clone());
+        assert!((plain - 7776f64.log2() * 5.0).abs() < 1e-9);
+
+        // Predictable capitalization doesn't add anything
+        let title_case = entropy(PassphraseGeneratorRequest {
+            capitalization: CapitalizationMode::TitleCase,
+            ..base.clone()
+        });
+        assert!((title_case - plain).abs() < 1e-9);
+
+        let random_case = entropy(PassphraseGeneratorRequest {
+            capitalization: CapitalizationMode::RandomCase,
+            ..base.clone()
+        });
+        assert!(random_case > plain);
+
+        let one_digit = entropy(PassphraseGeneratorRequest {
+            include_number: true,
+            ..base.clone()
+        });
+        let four_digits = entropy(PassphraseGeneratorRequest {
+            include_number: true,
+            number_length: NumberLength::FourDigits,
+            ..base
+        });
+        assert!(one_digit > plain);
+        assert!(four_digits > one_digit);
+    }
+
+    #[test]
+    fn test_estimate_matches_generated_entropy() {
+        let requests = [
+            PassphraseGeneratorRequest::default(),
+            PassphraseGeneratorRequest {
+                num_words: 7,
+                capitalization: CapitalizationMode::RandomCase,
+                ..Default::default()
+            },
+            PassphraseGeneratorRequest {
+                num_words: 4,
+                include_number: true,
+                number_length: NumberLength::FourDigits,
+                number_position: NumberPosition::StandaloneWord,
+                ..Default::default()
+            },
+        ];
+
+        for request in requests {
+            let estimate = estimate_passphrase_entropy(request.clone()).unwrap();
+            let result = generate_passphrase_with_stats(request).unwrap();
+            assert!((estimate.entropy_bits - result.entropy_bits).abs() < 1e-9);
+            assert_eq!(estimate.wordlist_size, EFF_LONG_WORD_LIST.len());
+            assert!((estimate.equivalent_password_length * 72f64.log2() - estimate.entropy_bits).abs() < 1e-9);
+        }
+
+        assert!(matches!(
+            estimate_passphrase_entropy(PassphraseGeneratorRequest {
+                num_words: 1,
+                ..Default::default()
+            }),
+            Err(GeneratorError::Passphrase(PassphraseError::InvalidNumWords { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_generate_passphrase_choices() {
+        let request = PassphraseGeneratorRequest {
+            num_words: 5,
+            word_separator: "-".to_string(),
+            ..Default::default()
+        };
+        for count in [MINIMUM_PASSPHRASE_CHOICES, 4, MAXIMUM_PASSPHRASE_CHOICES] {
+            let choices = generate_passphrase_choices(request.clone(), count).unwrap();
+            assert_eq!(choices.len(), usize::from(count));
+            for choice in &choices {
+                assert_eq!(choice.word_count, 5);
+                let words: Vec<&str> = choice.passphrase.split('-').collect();
+                assert_eq!(words.len(), 5);
+                assert!(words.iter().all(|word| EFF_LONG_WORD_LIST.contains(word)));
+                assert!(choice.entropy_bits > 0.0);
+            }
+        }
+
+        for count in [0, 1, MAXIMUM_PASSPHRASE_CHOICES + 1] {
+            assert!(matches!(
+                generate_passphrase_choices(request.clone(), count),
+                Err(GeneratorError::Passphrase(PassphraseError::ChoiceCountOutOfRange { .. }))
+            ));
+        }
+        assert!(matches!(
+            generate_passphrase_choices(PassphraseGeneratorRequest { num_words: 2, ..Default::default() }, 3),
+            Err(GeneratorError::Passphrase(PassphraseError::InvalidNumWords { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_crack_time_display() {
+        assert_eq!(crack_time_display(20.0), "Less than a second");
+        assert_eq!(crack_time_display((120.0 * GUESSES_PER_SECOND).log2()), "About 2 minutes");
+        assert_eq!(crack_time_display(51.7), "About 4 days");
+        assert_eq!(crack_time_display(64.6), "About 89 years");
+        assert_eq!(crack_time_display(77.5), "Centuries");
+    }
+
     #[test]
     fn test_validation() {
         // Valid request
         let valid_request = PassphraseGeneratorRequest {
             num_words: 5,
             word_separator: " ".to_string(),
-            capitalize: false,
+            capitalization: CapitalizationMode::None,
+            capitalize_positions: None,
+            capitalize_last_only: false,
             include_number: false,
+            number_length: NumberLength::OneDigit,
+            number_position: NumberPosition::AppendToWord,
+            diceware_compatible: false,
+            phonetic_smoothing: false,
+            excluded_words: None,
+            word_min_length: None,
+            word_max_length: None,
         };
         assert!(valid_request.validate_options().is_ok());
 
@@ -269,8 +1330,17 @@ mod tests {
         let min_request = PassphraseGeneratorRequest {
             num_words: MINIMUM_PASSPHRASE_NUM_WORDS,
             word_separator: " ".to_string(),
-            capitalize: false,
+            capitalization: CapitalizationMode::None,
+            capitalize_positions: None,
+            capitalize_last_only: false,
             include_number: false,
+            number_length: NumberLength::OneDigit,
+            number_position: NumberPosition::AppendToWord,
+            diceware_compatible: false,
+            phonetic_smoothing: false,
+            excluded_words: None,
+            word_min_length: None,
+            word_max_length: None,
         };
         assert!(min_request.validate_options().is_ok());
 
@@ -278,8 +1348,17 @@ mod tests {
         let max_request = PassphraseGeneratorRequest {
             num_words: MAXIMUM_PASSPHRASE_NUM_WORDS,
             word_separator: " ".to_string(),
-            capitalize: false,
+            capitalization: CapitalizationMode::None,
+            capitalize_positions: None,
+            capitalize_last_only: false,
             include_number: false,
+            number_length: NumberLength::OneDigit,
+            number_position: NumberPosition::AppendToWord,
+            diceware_compatible: false,
+            phonetic_smoothing: false,
+            excluded_words: None,
+            word_min_length: None,
+            word_max_length: None,
         };
         assert!(max_request.validate_options().is_ok());
 
@@ -287,8 +1366,17 @@ mod tests {
         let invalid_request = PassphraseGeneratorRequest {
             num_words: 2,
             word_separator: " ".to_string(),
-            capitalize: false,
+            capitalization: CapitalizationMode::None,
+            capitalize_positions: None,
+            capitalize_last_only: false,
             include_number: false,
+            number_length: NumberLength::OneDigit,
+            number_position: NumberPosition::AppendToWord,
+            diceware_compatible: false,
+            phonetic_smoothing: false,
+            excluded_words: None,
+            word_min_length: None,
+            word_max_length: None,
         };
         assert!(invalid_request.validate_options().is_err());
 
@@ -296,8 +1384,17 @@ mod tests {
         let invalid_request = PassphraseGeneratorRequest {
             num_words: 25,
             word_separator: " ".to_string(),
-            capitalize: false,
+            capitalization: CapitalizationMode::None,
+            capitalize_positions: None,
+            capitalize_last_only: false,
             include_number: false,
+            number_length: NumberLength::OneDigit,
+            number_position: NumberPosition::AppendToWord,
+            diceware_compatible: false,
+            phonetic_smoothing: false,
+            excluded_words: None,
+            word_min_length: None,
+            word_max_length: None,
         };
         assert!(invalid_request.validate_options().is_err());
     }
@@ -308,8 +1405,17 @@ mod tests {
         let request = PassphraseGeneratorRequest {
             num_words: 5,
             word_separator: "-".to_string(),
-            capitalize: true,
+            capitalization: CapitalizationMode::FirstLetter,
+            capitalize_positions: None,
+            capitalize_last_only: false,
             include_number: false,
+            number_length: NumberLength::OneDigit,
+            number_position: NumberPosition::AppendToWord,
+            diceware_compatible: false,
+            phonetic_smoothing: false,
+            excluded_words: None,
+            word_min_length: None,
+            word_max_length: None,
         };
 
         let json = serde_json::to_string(&request).unwrap();
@@ -317,7 +1423,7 @@ mod tests {
         
         assert_eq!(request.num_words, deserialized.num_words);
         assert_eq!(request.word_separator, deserialized.word_separator);
-        assert_eq!(request.capitalize, deserialized.capitalize);
+        assert_eq!(request.capitalization, deserialized.capitalization);
         assert_eq!(request.include_number, deserialized.include_number);
         
         // Verify snake_case serialization
@@ -326,13 +1432,107 @@ mod tests {
         assert!(json.contains("include_number"));
     }
 
+    #[test]
+    fn test_dice_rolls_match_eff_list() {
+        // Published codes from <https://www.eff.org/files/2016/07/18/eff_large_wordlist.txt>
+        assert_eq!(dice_roll("abacus"), "11111");
+        assert_eq!(dice_roll("abdomen"), "11112");
+        assert_eq!(dice_roll("ablaze"), "11121");
+        assert_eq!(dice_roll("yoyo"), "66631");
+        assert_eq!(dice_roll("zoom"), "66666");
+
+        // Capitalization and appended numbers don't affect the lookup
+        assert_eq!(dice_roll("Abacus7"), "11111");
+        assert_eq!(dice_roll("notaword"), UNKNOWN_ROLL);
+        assert_eq!(DICE_ROLLS.len(), EFF_LONG_WORD_LIST.len());
+    }
+
+    #[test]
+    fn test_diceware_compatible_output() {
+        let mut rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
+        let options = PassphraseGeneratorRequest {
+            num_words: 5,
+            capitalization: CapitalizationMode::FirstLetter,
+            capitalize_positions: None,
+            capitalize_last_only: false,
+            include_number: true,
+            number_length: NumberLength::OneDigit,
+            number_position: NumberPosition::AppendToWord,
+            diceware_compatible: true,
+            ..Default::default()
+        }
+        .validate_options()
+        .unwrap();
+
+        let result = passphrase_with_rng(&mut rng, options);
+        let lines: Vec<&str> = result.lines().collect();
+        assert_eq!(lines.len(), 5);
+        for line in lines {
+            let (roll, word) = line.split_once(": ").unwrap();
+            assert_eq!(roll.len(), 5);
+            assert!(roll.chars().all(|c| ('1'..='6').contains(&c)), "{}", line);
+            assert_eq!(dice_roll(word), roll);
+        }
+    }
+
+    #[test]
+    fn test_capitalization_modes() {
+        let words = || -> Vec<String> { ["the", "quick", "and", "abacus7", "of"].iter().map(|w| w.to_string()).collect() };
+        let capitalize = |seed: u8, mode| {
+            let mut rng = rand_chacha::ChaCha8Rng::from_seed([seed; 32]);
+            let mut words = words();
+            capitalize_words(&mut rng, &mut words, mode, None);
+            words.join(" ")
+        };
+
+        assert_eq!(capitalize(0, CapitalizationMode::None), "the quick and abacus7 of");
+        assert_eq!(capitalize(0, CapitalizationMode::FirstLetter), "The Quick And Abacus7 Of");
+        assert_eq!(capitalize(0, CapitalizationMode::TitleCase), "The Quick and Abacus7 of");
+        assert_eq!(capitalize(0, CapitalizationMode::AllCaps), "THE QUICK AND ABACUS7 OF");
+
+        let random = capitalize(0, CapitalizationMode::RandomCase);
+        assert_eq!(random.to_lowercase(), "the quick and abacus7 of");
+        assert!(random.chars().any(|c| c.is_ascii_uppercase()));
+        assert!(random.chars().any(|c| c.is_ascii_lowercase()));
+        // Deterministic for a given seed
+        assert_eq!(random, capitalize(0, CapitalizationMode::RandomCase));
+        assert_ne!(random, capitalize(1, CapitalizationMode::RandomCase));
+    }
+
+    #[test]
+    fn test_legacy_capitalize_field() {
+        let parse = |json: &str| serde_json::from_str::<PassphraseGeneratorRequest>(json).unwrap().capitalization;
+
+        assert_eq!(
+            parse(r#"{"num_words":3,"word_separator":" ","capitalize":true,"include_number":false}"#),
+            CapitalizationMode::FirstLetter
+        );
+        assert_eq!(
+            parse(r#"{"num_words":3,"word_separator":" ","capitalize":false,"include_number":false}"#),
+            CapitalizationMode::None
+        );
+        assert_eq!(
+            parse(r#"{"num_words":3,"word_separator":" ","capitalization":"TitleCase","include_number":false}"#),
+            CapitalizationMode::TitleCase
+        );
+    }
+
     #[test]
     fn test_empty_separator() {
         let request = PassphraseGeneratorRequest {
             num_words: 3,
             word_separator: "".to_string(), // Empty separator
-            capitalize: false,
+            capitalization: CapitalizationMode::None,
+            capitalize_positions: None,
+            capitalize_last_only: false,
             include_number: false,
+            number_length: NumberLength::OneDigit,
+            number_position: NumberPosition::AppendToWord,
+            diceware_compatible: false,
+            phonetic_smoothing: false,
+            excluded_words: None,
+            word_min_length: None,
+            word_max_length: None,
         };
 
         let passphrase = generate_passphrase(request).unwrap();
@@ -341,4 +1541,250 @@ mod tests {
         assert!(!passphrase.is_empty());
         assert!(!passphrase.contains(' ')); // No spaces since separator is empty
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_passphrase_request_display() {
+        let request = PassphraseGeneratorRequest {
+            num_words: 5,
+            word_separator: "-".to_string(),
+            capitalization: CapitalizationMode::FirstLetter,
+            include_number: true,
+            ..Default::default()
+        };
+        assert_eq!(request.to_string(), r#"Passphrase(5 words, "-" separator, capitalized, with number)"#);
+
+        let request = PassphraseGeneratorRequest {
+            word_separator: "🔑".to_string(),
+            capitalize_last_only: true,
+            diceware_compatible: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            request.to_string(),
+            r#"Passphrase(3 words, "🔑" separator, last word capitalized, Diceware output)"#
+        );
+    }
+
+    #[test]
+    fn test_phonetic_score() {
+        // Consonant into vowel, different first letters
+        assert_eq!(phonetic_score("tulip", "apple"), 1.5);
+        // Vowel into consonant, same kind of first letter
+        assert_eq!(phonetic_score("banjo", "tulip"), 1.0);
+        // Two vowels meet
+        assert_eq!(phonetic_score("tuba", "acorn"), 1.0);
+        // Two consonants meet
+        assert_eq!(phonetic_score("tulip", "parrot"), 0.0);
+        // Same starting cluster
+        assert_eq!(phonetic_score("stream", "strong"), -1.0);
+        assert_eq!(phonetic_score("", "apple"), 0.0);
+        assert_eq!(phonetic_score("a-b", "C"), 0.5);
+    }
+
+    #[test]
+    fn test_smooth_phonetically() {
+        let drawn: Vec<String> = ["street", "stripe", "train", "eagle", "oven"]
+            .iter()
+            .map(|word| word.to_string())
+            .collect();
+        let mut words = drawn.clone();
+        smooth_phonetically(&mut words);
+
+        assert_ne!(words, drawn, "the order should change");
+        let mut sorted = words.clone();
+        sorted.sort();
+        let mut expected = drawn.clone();
+        expected.sort();
+        assert_eq!(sorted, expected, "no word may be lost or duplicated");
+        // The two "str" words are no longer neighbours
+        for pair in words.windows(2) {
+            assert!(!(pair[0].starts_with("str") && pair[1].starts_with("str")), "{:?}", words);
+        }
+        let total = |words: &[String]| -> f64 { words.windows(2).map(|pair| phonetic_score(&pair[0], &pair[1])).sum() };
+        assert!(total(&words) > total(&drawn));
+
+        // The result doesn't depend on the order the words were drawn in
+        let mut reversed: Vec<String> = drawn.into_iter().rev().collect();
+        smooth_phonetically(&mut reversed);
+        assert_eq!(reversed, words);
+    }
+
+    #[test]
+    fn test_phonetic_smoothing_keeps_words() {
+        let options = |phonetic_smoothing| {
+            PassphraseGeneratorRequest {
+                num_words: 8,
+                word_separator: "-".to_string(),
+                phonetic_smoothing,
+                ..Default::default()
+            }
+            .validate_options()
+            .unwrap()
+        };
+
+        let mut reordered = 0;
+        for seed in 0..20u8 {
+            let rng = || rand_chacha::ChaCha8Rng::from_seed([seed; 32]);
+            let plain = passphrase_with_rng(rng(), options(false));
+            let smoothed = passphrase_with_rng(rng(), options(true));
+            let mut plain_words: Vec<&str> = plain.split('-').collect();
+            let mut smoothed_words: Vec<&str> = smoothed.split('-').collect();
+            if plain_words != smoothed_words {
+                reordered += 1;
+            }
+            plain_words.sort_unstable();
+            smoothed_words.sort_unstable();
+            assert_eq!(plain_words, smoothed_words);
+        }
+        assert!(reordered > 0);
+
+        let full = passphrase_entropy(&options(false));
+        let smoothed = passphrase_entropy(&options(true));
+        assert!((full - smoothed - 40320f64.log2()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_excluded_words() {
+        // Mixed case and padding, exclusions are compared lowercased and trimmed
+        let excluded: Vec<String> = EFF_LONG_WORD_LIST[..MAXIMUM_EXCLUDED_WORDS]
+            .iter()
+            .enumerate()
+            .map(|(i, word)| if i % 2 == 0 { word.to_uppercase() } else { format!(" {} ", word) })
+            .collect();
+        let request = PassphraseGeneratorRequest {
+            num_words: 3,
+            word_separator: "-".to_string(),
+            excluded_words: Some(excluded),
+            ..Default::default()
+        };
+        let options = request.clone().validate_options().unwrap();
+        assert_eq!(options.word_list.len(), EFF_LONG_WORD_LIST.len() - MAXIMUM_EXCLUDED_WORDS);
+
+        let banned: HashSet<&str> = EFF_LONG_WORD_LIST[..MAXIMUM_EXCLUDED_WORDS].iter().copied().collect();
+        let mut rng = rand_chacha::ChaCha8Rng::from_seed([3u8; 32]);
+        for _ in 0..10_000 {
+            let passphrase = passphrase_with_rng(&mut rng, options.clone());
+            for word in passphrase.split('-') {
+                assert!(!banned.contains(word), "{:?} was excluded", word);
+            }
+        }
+
+        let estimate = estimate_passphrase_entropy(request).unwrap();
+        assert_eq!(estimate.wordlist_size, EFF_LONG_WORD_LIST.len() - MAXIMUM_EXCLUDED_WORDS);
+        let full = estimate_passphrase_entropy(PassphraseGeneratorRequest::default()).unwrap();
+        assert!(estimate.entropy_bits < full.entropy_bits);
+
+        // An empty list excludes nothing
+        let options = PassphraseGeneratorRequest {
+            excluded_words: Some(Vec::new()),
+            ..Default::default()
+        }
+        .validate_options()
+        .unwrap();
+        assert_eq!(options.word_list.len(), EFF_LONG_WORD_LIST.len());
+    }
+
+    #[test]
+    fn test_too_many_excluded_words() {
+        let request = PassphraseGeneratorRequest {
+            excluded_words: Some(vec!["abacus".to_string(); MAXIMUM_EXCLUDED_WORDS + 1]),
+            ..Default::default()
+        };
+        assert!(matches!(
+            request.validate_options(),
+            Err(PassphraseError::TooManyExcludedWords { maximum: MAXIMUM_EXCLUDED_WORDS })
+        ));
+        assert_eq!(
+            PassphraseError::InsufficientWordsAfterExclusion { remaining: 42 }.to_string(),
+            "Only 42 words are left after 'excluded_words', at least 100 are needed"
+        );
+    }
+
+    #[test]
+    fn test_word_length_constraints() {
+        let constrained = |word_min_length, word_max_length| PassphraseGeneratorRequest {
+            num_words: 5,
+            word_separator: " ".to_string(),
+            word_min_length,
+            word_max_length,
+            ..Default::default()
+        };
+
+        for (min, max) in [(Some(3), Some(4)), (Some(9), None), (None, Some(5)), (Some(6), Some(6))] {
+            let min_length = usize::from(min.unwrap_or(DEFAULT_WORD_MIN_LENGTH));
+            let max_length = max.map_or(usize::MAX, usize::from);
+            let expected = EFF_LONG_WORD_LIST
+                .iter()
+                .filter(|word| (min_length..=max_length).contains(&word.len()))
+                .count();
+
+            let options = constrained(min, max).validate_options().unwrap();
+            assert_eq!(options.word_list.len(), expected);
+            let estimate = estimate_passphrase_entropy(constrained(min, max)).unwrap();
+            assert_eq!(estimate.wordlist_size, expected);
+            assert!((estimate.entropy_bits - 5.0 * (expected as f64).log2()).abs() < 1e-9);
+
+            let mut rng = rand_chacha::ChaCha8Rng::from_seed([5u8; 32]);
+            for _ in 0..1000 {
+                let passphrase = passphrase_with_rng(&mut rng, options.clone());
+                for word in passphrase.split(' ') {
+                    assert!((min_length..=max_length).contains(&word.len()), "{:?} for {:?}..{:?}", word, min, max);
+                }
+            }
+        }
+
+        // The EFF list only has words of 3 to 9 letters, so these don't filter anything
+        let options = constrained(Some(1), Some(20)).validate_options().unwrap();
+        assert!(matches!(options.word_list, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_word_length_constraint_errors() {
+        let request = |word_min_length, word_max_length| PassphraseGeneratorRequest {
+            word_min_length,
+            word_max_length,
+            ..Default::default()
+        };
+        assert!(matches!(
+            request(Some(6), Some(5)).validate_options(),
+            Err(PassphraseError::InvalidWordLengthRange)
+        ));
+        // Only 82 words have 3 letters
+        assert!(matches!(
+            request(None, Some(3)).validate_options(),
+            Err(PassphraseError::InsufficientWordsForConstraint { remaining: 82 })
+        ));
+        assert!(matches!(
+            request(Some(10), None).validate_options(),
+            Err(PassphraseError::InsufficientWordsForConstraint { remaining: 0 })
+        ));
+
+        // Exclusions are applied to the constrained list
+        let short_words: Vec<String> = EFF_LONG_WORD_LIST
+            .iter()
+            .filter(|word| word.len() <= 4)
+            .map(|word| word.to_string())
+            .collect();
+        let excluded = short_words[..short_words.len() - 99].to_vec();
+        let request = PassphraseGeneratorRequest {
+            excluded_words: Some(excluded),
+            ..request(None, Some(4))
+        };
+        assert!(matches!(
+            request.validate_options(),
+            Err(PassphraseError::InsufficientWordsAfterExclusion { remaining: 99 })
+        ));
+    }
+
+    #[test]
+    fn test_request_equality() {
+        let request = PassphraseGeneratorRequest {
+            num_words: 5,
+            include_number: true,
+            ..Default::default()
+        };
+        assert_eq!(request, request.clone());
+        assert_ne!(request, PassphraseGeneratorRequest::default());
+        assert!(request.clone().validate_options().unwrap() == request.validate_options().unwrap());
+    }
+}
@@ -0,0 +1,108 @@
+//! Property based tests for the passphrase generator
+//!
+//! Separators are drawn from arbitrary Unicode, minus the characters that can appear in a
+//! generated word (ASCII letters, digits and the hyphen of e.g. "t-shirt"), so the output can be
+//! split back into its words.
+
+use proptest::prelude::*;
+
+use crate::generators::passphrase::{
+    generate_passphrase, CapitalizationMode, NumberLength, NumberPosition, PassphraseGeneratorRequest,
+};
+use crate::generators::wordlist::is_eff_word;
+
+/// Separators that can't be confused with word characters. NUL and zero-width characters are
+/// left out, since the generator rejects them.
+const SEPARATOR_REGEX: &str = "[^\\x00\\x{200B}-\\x{200D}\\x{2060}\\x{FEFF}a-zA-Z0-9-]{1,5}";
+
+fn request_strategy() -> impl Strategy<Value = PassphraseGeneratorRequest> {
+    (3u8..=20, SEPARATOR_REGEX, any::<bool>(), any::<bool>()).prop_map(
+        |(num_words, word_separator, capitalize, include_number)| PassphraseGeneratorRequest {
+            num_words,
+            word_separator,
+            capitalization: if capitalize {
+                CapitalizationMode::FirstLetter
+            } else {
+                CapitalizationMode::None
+            },
+            include_number,
+            ..Default::default()
+        },
+    )
+}
+
+/// Every valid request, including the options `request_strategy` leaves at their defaults
+fn any_request_strategy() -> impl Strategy<Value = PassphraseGeneratorRequest> {
+    let capitalization = prop_oneof![
+        Just(CapitalizationMode::None),
+        Just(CapitalizationMode::FirstLetter),
+        Just(CapitalizationMode::TitleCase),
+        Just(CapitalizationMode::AllCaps),
+        Just(CapitalizationMode::RandomCase),
+    ];
+    let number_length = prop_oneof![
+        Just(NumberLength::OneDigit),
+        Just(NumberLength::TwoDigits),
+        Just(NumberLength::FourDigits),
+    ];
+    let number_position = prop_oneof![
+        Just(NumberPosition::AppendToWord),
+        Just(NumberPosition::PrependToWord),
+        Just(NumberPosition::StandaloneWord),
+    ];
+    (
+        request_strategy(),
+        capitalization,
+        proptest::option::of(proptest::collection::vec(any::<u8>(), 0..5)),
+        any::<bool>(),
+        number_length,
+        number_position,
+        any::<bool>(),
+        any::<bool>(),
+    )
+        .prop_map(
+            |(
+                request,
+                capitalization,
+                capitalize_positions,
+                capitalize_last_only,
+                number_length,
+                number_position,
+                diceware_compatible,
+                phonetic_smoothing,
+            )| PassphraseGeneratorRequest {
+                capitalization,
+                capitalize_positions,
+                capitalize_last_only,
+                number_length,
+                number_position,
+                diceware_compatible,
+                phonetic_smoothing,
+                ..request
+            },
+        )
+}
+
+proptest! {
+    #[test]
+    fn test_passphrase_words_and_separators(request in request_strategy()) {
+        let passphrase = generate_passphrase(request.clone()).unwrap();
+        prop_assert!(!passphrase.is_empty());
+
+        let words: Vec<&str> = passphrase.split(request.word_separator.as_str()).collect();
+        prop_assert_eq!(words.len(), request.num_words as usize);
+        for word in words {
+            let word = word.trim_end_matches(|c: char| c.is_ascii_digit()).to_lowercase();
+            prop_assert!(is_eff_word(&word), "{:?} is not an EFF word", word);
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10_000))]
+
+    #[test]
+    fn test_passphrase_no_panic(request in any_request_strategy()) {
+        prop_assert!(generate_passphrase(request).is_ok());
+    }
+}
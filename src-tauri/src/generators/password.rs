@@ -1,20 +1,102 @@
 use std::collections::BTreeSet;
+use std::fmt;
 
 use rand::{distributions::Distribution, seq::SliceRandom, RngCore};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::generators::GeneratorError;
+
 #[derive(Debug, Error)]
 pub enum PasswordError {
     #[error("No character set enabled")]
     NoCharacterSetEnabled,
     #[error("Invalid password length")]
     InvalidLength,
+    #[error("Custom character set must contain at least 2 distinct characters")]
+    InsufficientCharset,
+    #[error("Invalid pattern: {0}")]
+    InvalidPattern(String),
+    #[error("Cannot generate a password within the consecutive character limit")]
+    CannotSatisfyConsecutiveConstraint,
+    #[error("Required character '{0}' is not in the enabled character sets")]
+    RequiredCharNotInCharset(char),
+    #[error("Every character of an enabled character set is excluded")]
+    AllCharsExcluded,
+}
+
+/// Groups of look-alike characters to leave out of generated passwords. Each flag removes every
+/// character of its group, e.g. `o0` removes both `O` and `0`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, JsonSchema)]
+#[serde(default)]
+pub struct AmbiguousExclusions {
+    /// I, l and 1
+    pub il1: bool,
+    /// O and 0
+    #[serde(rename = "O0")]
+    pub o0: bool,
+    /// S and 5
+    #[serde(rename = "S5")]
+    pub s5: bool,
+    /// B and 8
+    #[serde(rename = "B8")]
+    pub b8: bool,
+    /// Z and 2
+    #[serde(rename = "Z2")]
+    pub z2: bool,
+}
+
+impl AmbiguousExclusions {
+    /// Every group excluded, what the legacy `avoid_ambiguous` flag maps to
+    pub fn all() -> Self {
+        Self {
+            il1: true,
+            o0: true,
+            s5: true,
+            b8: true,
+            z2: true,
+        }
+    }
+
+    /// The characters of every excluded group
+    fn excluded_chars(&self) -> Vec<char> {
+        [
+            (self.il1, IL1_CHARS),
+            (self.o0, O0_CHARS),
+            (self.s5, S5_CHARS),
+            (self.b8, B8_CHARS),
+            (self.z2, Z2_CHARS),
+        ]
+        .iter()
+        .filter(|(excluded, _)| *excluded)
+        .flat_map(|(_, chars)| chars.iter().copied())
+        .collect()
+    }
+}
+
+/// Accepts either [`AmbiguousExclusions`] or the legacy `avoid_ambiguous` boolean, where `true`
+/// maps to [`AmbiguousExclusions::all`]
+fn deserialize_ambiguous_exclusions<'de, D>(deserializer: D) -> Result<AmbiguousExclusions, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        Legacy(bool),
+        Exclusions(AmbiguousExclusions),
+    }
+
+    Ok(match Repr::deserialize(deserializer)? {
+        Repr::Legacy(true) => AmbiguousExclusions::all(),
+        Repr::Legacy(false) => AmbiguousExclusions::default(),
+        Repr::Exclusions(exclusions) => exclusions,
+    })
 }
 
 /// Password generator request options.
-#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct PasswordGeneratorRequest {
     /// Include lowercase characters (a-z).
@@ -30,9 +112,10 @@ pub struct PasswordGeneratorRequest {
     /// Note that the password length must be greater than the sum of all the minimums.
     pub length: u8,
 
-    /// When set to true, the generated password will not contain ambiguous characters.
-    /// The ambiguous characters are: I, O, l, 0, 1
-    pub avoid_ambiguous: bool,
+    /// Groups of look-alike characters to leave out of the generated password. Also accepts the
+    /// legacy `avoidAmbiguous` boolean, where `true` leaves out every group.
+    #[serde(default, alias = "avoidAmbiguous", deserialize_with = "deserialize_ambiguous_exclusions")]
+    pub ambiguous_exclusions: AmbiguousExclusions,
 
     /// The minimum number of lowercase characters in the generated password.
     /// This value is ignored if lowercase is false. The sum of all minimums must not exceed the password length.
@@ -46,10 +129,62 @@ pub struct PasswordGeneratorRequest {
     /// The minimum number of special characters in the generated password.
     /// This value is ignored if special is false. The sum of all minimums must not exceed the password length.
     pub min_special: Option<u8>,
+
+    /// Characters to generate the password from. When set and not empty, this replaces the
+    /// lowercase, uppercase, numbers and special character sets, and the minimums are ignored.
+    /// Duplicate characters are only counted once.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_charset: Option<String>,
+
+    /// Pattern the password must follow, e.g. `Xxxx-####`. When set, this replaces all other
+    /// options except `ambiguous_exclusions`. The placeholders are `X` (uppercase), `x` (lowercase),
+    /// `#` (digit), `!` (special character) and `a` (letter or digit). A backslash makes the next
+    /// placeholder literal, e.g. `\#`, and any other character is used as is.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+
+    /// The maximum number of times the same character may appear in a row, e.g. `1` rules out
+    /// `aa`. Passwords over the limit are regenerated, up to 100 times.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_consecutive_same_char: Option<u8>,
+
+    /// When set to true, two characters of the same class (lowercase, uppercase, number, other)
+    /// are never next to each other. If the characters can't be arranged that way, e.g. when only
+    /// one class is enabled, they are left in random order.
+    #[serde(default)]
+    pub no_consecutive_same_class: bool,
+
+    /// Characters of which one, picked at random, always appears in the generated password, e.g.
+    /// `['@', '#']` for policies requiring one of them. Each one must be in an enabled character
+    /// set, or in `custom_charset` when that is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub required_chars: Option<Vec<char>>,
+
+    /// Characters that never appear in the generated password, on top of `ambiguous_exclusions`.
+    /// Also applies to `custom_charset` and `pattern` placeholders.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub custom_exclusions: String,
 }
 
 const DEFAULT_PASSWORD_LENGTH: u8 = 16;
 
+/// Common password policies, to fill in a [`PasswordGeneratorRequest`] with
+/// [`PasswordGeneratorRequest::preset`]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub enum PasswordPreset {
+    /// 8 lowercase letters and numbers, the shortest length NIST SP 800-63B allows
+    NistMinimum,
+    /// 24 characters from every class, at least 2 of each
+    HighSecurity,
+    /// 64 characters from every class
+    MaxEntropy,
+    /// 16 characters from every class, without look-alike characters
+    NoAmbiguous,
+    /// 16 letters and numbers without look-alike characters, easy to type on a phone keyboard
+    MobileEntry,
+}
+
 impl Default for PasswordGeneratorRequest {
     fn default() -> Self {
         Self {
@@ -58,20 +193,35 @@ impl Default for PasswordGeneratorRequest {
             numbers: true,
             special: false,
             length: DEFAULT_PASSWORD_LENGTH,
-            avoid_ambiguous: false,
+            ambiguous_exclusions: AmbiguousExclusions::default(),
             min_lowercase: None,
             min_uppercase: None,
             min_number: None,
             min_special: None,
+            custom_charset: None,
+            pattern: None,
+            max_consecutive_same_char: None,
+            no_consecutive_same_class: false,
+            required_chars: None,
+            custom_exclusions: String::new(),
         }
     }
 }
 
-const UPPER_CHARS_AMBIGUOUS: &[char] = &['I', 'O'];
-const LOWER_CHARS_AMBIGUOUS: &[char] = &['l'];
-const NUMBER_CHARS_AMBIGUOUS: &[char] = &['0', '1'];
+const IL1_CHARS: &[char] = &['I', 'l', '1'];
+const O0_CHARS: &[char] = &['O', '0'];
+const S5_CHARS: &[char] = &['S', '5'];
+const B8_CHARS: &[char] = &['B', '8'];
+const Z2_CHARS: &[char] = &['Z', '2'];
 const SPECIAL_CHARS: &[char] = &['!', '@', '#', '$', '%', '^', '&', '*'];
 
+/// Number of times a password is regenerated to satisfy `max_consecutive_same_char`
+const MAXIMUM_CONSECUTIVE_RETRIES: usize = 100;
+
+const MAXIMUM_PATTERN_LENGTH: usize = 128;
+const PATTERN_PLACEHOLDERS: &[char] = &['X', 'x', '#', '!', 'a'];
+const PATTERN_ESCAPE: char = '\\';
+
 /// A set of characters used to generate a password. This set is backed by a BTreeSet
 /// to have consistent ordering between runs. This is not important during normal execution,
 /// but it's necessary for the tests to be repeatable.
@@ -95,6 +245,12 @@ impl CharSet {
     }
 
     /// Excludes the given characters from the set. Any missing items will be ignored
+    pub fn exclude<'a>(self, other: impl IntoIterator<Item = &'a char>) -> Self {
+        self.exclude_if(true, other)
+    }
+
+    /// Excludes the given characters from the set if the predicate is true. Any missing items will
+    /// be ignored
     pub fn exclude_if<'a>(
         self,
         predicate: bool,
@@ -117,6 +273,196 @@ impl<'a> IntoIterator for &'a CharSet {
     }
 }
 
+/// Character classes kept apart by `no_consecutive_same_class`
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Lower,
+    Upper,
+    Number,
+    Other,
+}
+
+impl CharClass {
+    fn of(c: char) -> Self {
+        if c.is_lowercase() {
+            CharClass::Lower
+        } else if c.is_uppercase() {
+            CharClass::Upper
+        } else if c.is_numeric() {
+            CharClass::Number
+        } else {
+            CharClass::Other
+        }
+    }
+}
+
+/// Whether characters with the given number of each class can be ordered so that no two of the
+/// same class are adjacent, when the character placed before them has class `previous`
+fn can_separate_classes(counts: &[usize; 4], previous: Option<CharClass>) -> bool {
+    let total: usize = counts.iter().sum();
+    counts.iter().enumerate().all(|(class, &count)| {
+        // A class can take at most every other position, and can't take the first one if it
+        // matches the previous character
+        let limit = if previous.map(|p| p as usize) == Some(class) {
+            total / 2
+        } else {
+            (total + 1) / 2
+        };
+        count <= limit
+    })
+}
+
+fn class_counts(chars: &[char]) -> [usize; 4] {
+    let mut counts = [0usize; 4];
+    for c in chars {
+        counts[CharClass::of(*c) as usize] += 1;
+    }
+    counts
+}
+
+/// Redraws characters from the start of `buf` (the first `redrawable`, which were sampled from
+/// `set`) while one class has too many characters to be kept apart, e.g. 9 uppercase letters in
+/// 16 characters. Each redraw moves a character to the least used class `set` offers, so the
+/// per class minimums at the end of `buf` are never touched.
+fn balance_classes(mut rng: impl RngCore, buf: &mut [char], redrawable: usize, set: &CharSet) {
+    for _ in 0..redrawable {
+        let counts = class_counts(buf);
+        if can_separate_classes(&counts, None) {
+            return;
+        }
+        let crowded = (0..counts.len()).max_by_key(|&class| counts[class]).expect("classes exist");
+
+        let positions: Vec<usize> = (0..redrawable)
+            .filter(|&i| CharClass::of(buf[i]) as usize == crowded)
+            .collect();
+        let Some(least_used) = set
+            .into_iter()
+            .map(|c| CharClass::of(c) as usize)
+            .filter(|&class| class != crowded)
+            .min_by_key(|&class| counts[class])
+        else {
+            return;
+        };
+        let replacements: Vec<char> = set
+            .into_iter()
+            .filter(|&c| CharClass::of(c) as usize == least_used)
+            .collect();
+
+        match (positions.choose(&mut rng), replacements.choose(&mut rng)) {
+            (Some(&i), Some(&c)) => buf[i] = c,
+            _ => return,
+        }
+    }
+}
+
+/// Reorders `buf` so no two characters of the same class are adjacent. Each position is filled by
+/// swapping in a random later character that keeps the rest arrangeable, like a Fisher-Yates
+/// shuffle restricted to valid choices. If no such order exists, `buf` is left unchanged.
+fn separate_classes(mut rng: impl RngCore, buf: &mut [char]) {
+    let mut counts = class_counts(buf);
+    if !can_separate_classes(&counts, None) {
+        return;
+    }
+
+    let mut previous = None;
+    for i in 0..buf.len() {
+        let candidates: Vec<usize> = (i..buf.len())
+            .filter(|&j| {
+                let class = CharClass::of(buf[j]);
+                if Some(class) == previous {
+                    return false;
+                }
+                counts[class as usize] -= 1;
+                let valid = can_separate_classes(&counts, Some(class));
+                counts[class as usize] += 1;
+                valid
+            })
+            .collect();
+        let j = *candidates
+            .choose(&mut rng)
+            .expect("an arrangement exists, so some character fits");
+        buf.swap(i, j);
+
+        let class = CharClass::of(buf[i]);
+        counts[class as usize] -= 1;
+        previous = Some(class);
+    }
+}
+
+/// Warning for `no_consecutive_same_class` when every character of `set` has the same class
+fn single_class_warning(no_consecutive_same_class: bool, set: &CharSet) -> Option<String> {
+    let mut classes = set.into_iter().map(CharClass::of);
+    let first = classes.next()?;
+    if no_consecutive_same_class && classes.all(|class| class == first) {
+        Some("Characters of the same class can't be kept apart with a single character class".to_string())
+    } else {
+        None
+    }
+}
+
+/// A position in a password pattern
+enum PatternPart {
+    Literal(char),
+    Random(CharSet),
+}
+
+/// Parses a password pattern into its positions. See [`PasswordGeneratorRequest::pattern`] for the
+/// pattern language.
+fn parse_pattern(pattern: &str, excluded: &[char]) -> Result<Vec<PatternPart>, PasswordError> {
+    if pattern.chars().count() > MAXIMUM_PATTERN_LENGTH {
+        return Err(PasswordError::InvalidPattern(format!(
+            "must not be longer than {} characters",
+            MAXIMUM_PATTERN_LENGTH
+        )));
+    }
+
+    let upper = || CharSet::default().include('A'..='Z').exclude(excluded);
+    let lower = || CharSet::default().include('a'..='z').exclude(excluded);
+    let number = || CharSet::default().include('0'..='9').exclude(excluded);
+
+    let mut parts = Vec::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        let part = match c {
+            'X' => PatternPart::Random(upper()),
+            'x' => PatternPart::Random(lower()),
+            '#' => PatternPart::Random(number()),
+            '!' => PatternPart::Random(CharSet::default().include(SPECIAL_CHARS.iter().copied()).exclude(excluded)),
+            'a' => PatternPart::Random(upper().include(&lower()).include(&number())),
+            PATTERN_ESCAPE => match chars.next() {
+                Some(escaped) if escaped == PATTERN_ESCAPE || PATTERN_PLACEHOLDERS.contains(&escaped) => {
+                    PatternPart::Literal(escaped)
+                }
+                Some(escaped) => {
+                    return Err(PasswordError::InvalidPattern(format!(
+                        "unrecognized escape sequence '{}{}'",
+                        PATTERN_ESCAPE, escaped
+                    )))
+                }
+                None => {
+                    return Err(PasswordError::InvalidPattern(format!(
+                        "pattern ends with an unfinished escape '{}'",
+                        PATTERN_ESCAPE
+                    )))
+                }
+            },
+            literal => PatternPart::Literal(literal),
+        };
+        parts.push(part);
+    }
+
+    if !parts.iter().any(|part| matches!(part, PatternPart::Random(_))) {
+        return Err(PasswordError::InvalidPattern(
+            "must contain at least one placeholder".to_string(),
+        ));
+    }
+    if parts.iter().any(|part| matches!(part, PatternPart::Random(set) if set.0.is_empty())) {
+        return Err(PasswordError::AllCharsExcluded);
+    }
+
+    Ok(parts)
+}
+
 impl Distribution<char> for CharSet {
     fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> char {
         let idx = rng.gen_range(0..self.0.len());
@@ -133,16 +479,92 @@ struct PasswordGeneratorOptions {
     pub(super) number: (CharSet, usize),
     pub(super) special: (CharSet, usize),
     pub(super) all: (CharSet, usize),
+    pub(super) required: (CharSet, usize),
 
     pub(super) length: usize,
+
+    /// When set, the password is generated position by position from this pattern instead of
+    /// from the character sets above
+    pub(super) pattern: Option<Vec<PatternPart>>,
+
+    pub(super) max_consecutive_same_char: Option<usize>,
+
+    pub(super) no_consecutive_same_class: bool,
+
+    /// Request options that were ignored, to show to the user alongside the password
+    pub(super) warnings: Vec<String>,
 }
 
 impl PasswordGeneratorRequest {
+    /// Returns the request options for a common password policy
+    pub fn preset(preset: PasswordPreset) -> PasswordGeneratorRequest {
+        let builder = PasswordGeneratorRequest::builder();
+        let builder = match preset {
+            PasswordPreset::NistMinimum => builder.uppercase(false).special(false).length(8),
+            PasswordPreset::HighSecurity => builder
+                .length(24)
+                .min_lowercase(2)
+                .min_uppercase(2)
+                .min_number(2)
+                .min_special(2),
+            PasswordPreset::MaxEntropy => builder.length(64),
+            PasswordPreset::NoAmbiguous => builder.ambiguous_exclusions(AmbiguousExclusions::all()),
+            PasswordPreset::MobileEntry => builder
+                .special(false)
+                .ambiguous_exclusions(AmbiguousExclusions::all()),
+        };
+        builder.build().expect("presets are valid requests")
+    }
+
+    /// Returns a builder starting from 16 characters of every class and no minimums
+    pub fn builder() -> PasswordGeneratorRequestBuilder {
+        PasswordGeneratorRequestBuilder::default()
+    }
+
+    /// Checks that the request can be used to generate a password, without generating one
+    pub fn validate(&self) -> Result<(), PasswordError> {
+        self.clone().validate_options().map(|_| ())
+    }
+
+    /// Every character to leave out of the password, from both `ambiguous_exclusions` and
+    /// `custom_exclusions`
+    fn excluded_chars(&self) -> Vec<char> {
+        let mut excluded = self.ambiguous_exclusions.excluded_chars();
+        excluded.extend(self.custom_exclusions.chars());
+        excluded
+    }
+
+    /// Returns the set to pick the required character from, and how many to pick. Every required
+    /// character must be in `enabled`.
+    fn required_options(&self, enabled: &CharSet) -> Result<(CharSet, usize), PasswordError> {
+        let required_chars = match self.required_chars.as_deref() {
+            Some(chars) if !chars.is_empty() => chars,
+            _ => return Ok((CharSet::default(), 0)),
+        };
+        if let Some(&missing) = required_chars.iter().find(|c| !enabled.0.contains(c)) {
+            return Err(PasswordError::RequiredCharNotInCharset(missing));
+        }
+        Ok((CharSet::default().include(required_chars.iter().copied()), 1))
+    }
+
     /// Validates the request and returns an immutable struct with valid options to use with the
     /// password generator.
     fn validate_options(self) -> Result<PasswordGeneratorOptions, PasswordError> {
         // TODO: Add password generator policy checks
 
+        // Every character is a run of at least one
+        if self.max_consecutive_same_char == Some(0) {
+            return Err(PasswordError::CannotSatisfyConsecutiveConstraint);
+        }
+
+        if let Some(pattern) = self.pattern.as_deref() {
+            return self.validate_pattern_options(pattern);
+        }
+
+        if let Some(custom_charset) = self.custom_charset.as_deref().filter(|c| !c.is_empty()) {
+            return self.validate_custom_charset_options(custom_charset);
+        }
+
         // We always have to have at least one character set enabled
         if !self.lowercase && !self.uppercase && !self.numbers && !self.special {
             return Err(PasswordError::NoCharacterSetEnabled);
@@ -174,33 +596,48 @@ impl PasswordGeneratorRequest {
             return Err(PasswordError::InvalidLength);
         }
 
+        let excluded = self.excluded_chars();
+
         let lower = (
             CharSet::default()
                 .include_if(self.lowercase, 'a'..='z')
-                .exclude_if(self.avoid_ambiguous, LOWER_CHARS_AMBIGUOUS),
+                .exclude(&excluded),
             min_lowercase,
         );
 
         let upper = (
             CharSet::default()
                 .include_if(self.uppercase, 'A'..='Z')
-                .exclude_if(self.avoid_ambiguous, UPPER_CHARS_AMBIGUOUS),
+                .exclude(&excluded),
             min_uppercase,
         );
 
         let number = (
             CharSet::default()
                 .include_if(self.numbers, '0'..='9')
-                .exclude_if(self.avoid_ambiguous, NUMBER_CHARS_AMBIGUOUS),
+                .exclude(&excluded),
             min_number,
         );
 
         let special = (
-            CharSet::default().include_if(self.special, SPECIAL_CHARS.iter().copied()),
+            CharSet::default()
+                .include_if(self.special, SPECIAL_CHARS.iter().copied())
+                .exclude(&excluded),
             min_special,
         );
 
-        let all = (
+        // An enabled set with nothing left can't provide its minimum
+        let sets = [
+            (self.lowercase, &lower.0),
+            (self.uppercase, &upper.0),
+            (self.numbers, &number.0),
+            (self.special, &special.0),
+        ];
+        if sets.iter().any(|(enabled, set)| *enabled && set.0.is_empty()) {
+            return Err(PasswordError::AllCharsExcluded);
+        }
+
+        let mut all = (
             CharSet::default()
                 .include(&lower.0)
                 .include(&upper.0)
@@ -209,24 +646,287 @@ impl PasswordGeneratorRequest {
             length - minimum_length,
         );
 
+        // The required character takes one of the positions otherwise drawn from `all`
+        let required = self.required_options(&all.0)?;
+        if required.1 > all.1 {
+            return Err(PasswordError::InvalidLength);
+        }
+        all.1 -= required.1;
+
+        let warnings = single_class_warning(self.no_consecutive_same_class, &all.0)
+            .into_iter()
+            .collect();
+
         Ok(PasswordGeneratorOptions {
             lower,
             upper,
             number,
             special,
             all,
+            required,
+            length,
+            pattern: None,
+            max_consecutive_same_char: self.max_consecutive_same_char.map(usize::from),
+            no_consecutive_same_class: self.no_consecutive_same_class,
+            warnings,
+        })
+    }
+
+    /// Validates a request with a custom character set. The password is drawn only from that set,
+    /// so the per set minimums can't apply.
+    fn validate_custom_charset_options(&self, custom_charset: &str) -> Result<PasswordGeneratorOptions, PasswordError> {
+        let charset = CharSet::default()
+            .include(custom_charset.chars())
+            .exclude(&self.excluded_chars());
+        if charset.0.is_empty() {
+            return Err(PasswordError::AllCharsExcluded);
+        }
+        if charset.0.len() < 2 {
+            return Err(PasswordError::InsufficientCharset);
+        }
+
+        if self.length < 4 {
+            return Err(PasswordError::InvalidLength);
+        }
+
+        let mut warnings = Vec::new();
+        let minimums = [self.min_lowercase, self.min_uppercase, self.min_number, self.min_special];
+        if minimums.iter().any(Option::is_some) {
+            warnings.push("Minimum character counts are ignored when a custom character set is used".to_string());
+        }
+        warnings.extend(single_class_warning(self.no_consecutive_same_class, &charset));
+
+        let length = self.length as usize;
+        let required = self.required_options(&charset)?;
+        Ok(PasswordGeneratorOptions {
+            lower: (CharSet::default(), 0),
+            upper: (CharSet::default(), 0),
+            number: (CharSet::default(), 0),
+            special: (CharSet::default(), 0),
+            all: (charset, length - required.1),
+            required,
             length,
+            pattern: None,
+            max_consecutive_same_char: self.max_consecutive_same_char.map(usize::from),
+            no_consecutive_same_class: self.no_consecutive_same_class,
+            warnings,
         })
     }
+
+    /// Validates a request with a pattern. Every position of the password comes from the pattern,
+    /// so the length, character sets and minimums are ignored.
+    fn validate_pattern_options(&self, pattern: &str) -> Result<PasswordGeneratorOptions, PasswordError> {
+        let parts = parse_pattern(pattern, &self.excluded_chars())?;
+
+        let mut warnings = Vec::new();
+        if self.custom_charset.as_deref().map_or(false, |c| !c.is_empty()) {
+            warnings.push("The custom character set is ignored when a pattern is used".to_string());
+        }
+        let minimums = [self.min_lowercase, self.min_uppercase, self.min_number, self.min_special];
+        if minimums.iter().any(Option::is_some) {
+            warnings.push("Minimum character counts are ignored when a pattern is used".to_string());
+        }
+        if self.no_consecutive_same_class {
+            warnings.push("Characters of the same class can't be kept apart when a pattern is used".to_string());
+        }
+        if self.required_chars.as_deref().map_or(false, |chars| !chars.is_empty()) {
+            warnings.push("Required characters are ignored when a pattern is used".to_string());
+        }
+
+        Ok(PasswordGeneratorOptions {
+            lower: (CharSet::default(), 0),
+            upper: (CharSet::default(), 0),
+            number: (CharSet::default(), 0),
+            special: (CharSet::default(), 0),
+            all: (CharSet::default(), 0),
+            required: (CharSet::default(), 0),
+            length: parts.len(),
+            pattern: Some(parts),
+            max_consecutive_same_char: self.max_consecutive_same_char.map(usize::from),
+            no_consecutive_same_class: false,
+            warnings,
+        })
+    }
+}
+
+/// Short summary of the request, e.g. `Password(16 chars, upper+lower+digits, no ambiguous)`
+impl fmt::Display for PasswordGeneratorRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(pattern) = self.pattern.as_deref() {
+            return write!(f, "Password(pattern {:?})", pattern);
+        }
+
+        let mut parts = vec![format!("{} chars", self.length)];
+        match self.custom_charset.as_deref().filter(|c| !c.is_empty()) {
+            Some(charset) => {
+                let distinct: BTreeSet<char> = charset.chars().collect();
+                parts.push(format!("custom charset of {} chars", distinct.len()));
+            }
+            None => {
+                let classes: Vec<&str> = [
+                    (self.uppercase, "upper"),
+                    (self.lowercase, "lower"),
+                    (self.numbers, "digits"),
+                    (self.special, "special"),
+                ]
+                .iter()
+                .filter(|(enabled, _)| *enabled)
+                .map(|(_, name)| *name)
+                .collect();
+                parts.push(if classes.is_empty() {
+                    "no character sets".to_string()
+                } else {
+                    classes.join("+")
+                });
+            }
+        }
+        if self.ambiguous_exclusions == AmbiguousExclusions::all() {
+            parts.push("no ambiguous".to_string());
+        } else if self.ambiguous_exclusions != AmbiguousExclusions::default() {
+            parts.push("some ambiguous excluded".to_string());
+        }
+        if !self.custom_exclusions.is_empty() {
+            parts.push("custom exclusions".to_string());
+        }
+
+        write!(f, "Password({})", parts.join(", "))
+    }
+}
+
+/// Builds a [`PasswordGeneratorRequest`] without naming every field. To get an instance of it,
+/// use [`PasswordGeneratorRequest::builder`].
+pub struct PasswordGeneratorRequestBuilder {
+    request: PasswordGeneratorRequest,
+}
+
+impl Default for PasswordGeneratorRequestBuilder {
+    fn default() -> Self {
+        Self {
+            request: PasswordGeneratorRequest {
+                lowercase: true,
+                uppercase: true,
+                numbers: true,
+                special: true,
+                length: DEFAULT_PASSWORD_LENGTH,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl PasswordGeneratorRequestBuilder {
+    pub fn lowercase(mut self, lowercase: bool) -> Self {
+        self.request.lowercase = lowercase;
+        self
+    }
+
+    pub fn uppercase(mut self, uppercase: bool) -> Self {
+        self.request.uppercase = uppercase;
+        self
+    }
+
+    pub fn numbers(mut self, numbers: bool) -> Self {
+        self.request.numbers = numbers;
+        self
+    }
+
+    pub fn special(mut self, special: bool) -> Self {
+        self.request.special = special;
+        self
+    }
+
+    pub fn length(mut self, length: u8) -> Self {
+        self.request.length = length;
+        self
+    }
+
+    pub fn ambiguous_exclusions(mut self, ambiguous_exclusions: AmbiguousExclusions) -> Self {
+        self.request.ambiguous_exclusions = ambiguous_exclusions;
+        self
+    }
+
+    pub fn min_lowercase(mut self, min_lowercase: u8) -> Self {
+        self.request.min_lowercase = Some(min_lowercase);
+        self
+    }
+
+    pub fn min_uppercase(mut self, min_uppercase: u8) -> Self {
+        self.request.min_uppercase = Some(min_uppercase);
+        self
+    }
+
+    pub fn min_number(mut self, min_number: u8) -> Self {
+        self.request.min_number = Some(min_number);
+        self
+    }
+
+    pub fn min_special(mut self, min_special: u8) -> Self {
+        self.request.min_special = Some(min_special);
+        self
+    }
+
+    pub fn custom_exclusions(mut self, custom_exclusions: impl Into<String>) -> Self {
+        self.request.custom_exclusions = custom_exclusions.into();
+        self
+    }
+
+    /// Returns the request, or the reason it can't be used to generate a password
+    pub fn build(self) -> Result<PasswordGeneratorRequest, PasswordError> {
+        self.request.validate()?;
+        Ok(self.request)
+    }
 }
 
 /// Implementation of the random password generator.
 pub(crate) fn password(input: PasswordGeneratorRequest) -> Result<String, PasswordError> {
+    password_from_request(rand::thread_rng(), input)
+}
+
+/// Same as [`password`], drawing from `rng` instead of the thread RNG
+pub(crate) fn password_from_request(rng: impl RngCore, input: PasswordGeneratorRequest) -> Result<String, PasswordError> {
     let options = input.validate_options()?;
-    Ok(password_with_rng(rand::thread_rng(), options))
+    password_with_rng(rng, options)
+}
+
+fn password_with_rng(mut rng: impl RngCore, options: PasswordGeneratorOptions) -> Result<String, PasswordError> {
+    let Some(max_consecutive) = options.max_consecutive_same_char else {
+        return Ok(sample_password(&mut rng, &options));
+    };
+
+    // Rejection sampling keeps every allowed password equally likely
+    for _ in 0..=MAXIMUM_CONSECUTIVE_RETRIES {
+        let password = sample_password(&mut rng, &options);
+        if longest_run(&password) <= max_consecutive {
+            return Ok(password);
+        }
+    }
+    Err(PasswordError::CannotSatisfyConsecutiveConstraint)
 }
 
-fn password_with_rng(mut rng: impl RngCore, options: PasswordGeneratorOptions) -> String {
+/// Length of the longest run of the same character in `password`
+fn longest_run(password: &str) -> usize {
+    let mut longest = 0;
+    let mut current = 0;
+    let mut previous = None;
+    for c in password.chars() {
+        current = if previous == Some(c) { current + 1 } else { 1 };
+        longest = longest.max(current);
+        previous = Some(c);
+    }
+    longest
+}
+
+fn sample_password(mut rng: impl RngCore, options: &PasswordGeneratorOptions) -> String {
+    if let Some(pattern) = &options.pattern {
+        return pattern
+            .iter()
+            .map(|part| match part {
+                PatternPart::Literal(c) => *c,
+                PatternPart::Random(set) => set.sample(&mut rng),
+            })
+            .collect();
+    }
+
     let mut buf: Vec<char> = Vec::with_capacity(options.length);
 
     let opts = [
@@ -235,19 +935,80 @@ fn password_with_rng(mut rng: impl RngCore, options: PasswordGeneratorOptions) -
         &options.lower,
         &options.number,
         &options.special,
+        &options.required,
     ];
     for (set, qty) in opts {
         buf.extend(set.sample_iter(&mut rng).take(*qty));
     }
+    if options.no_consecutive_same_class {
+        balance_classes(&mut rng, &mut buf, options.all.1, &options.all.0);
+    }
 
     buf.shuffle(&mut rng);
+    if options.no_consecutive_same_class {
+        separate_classes(&mut rng, &mut buf);
+    }
 
     buf.iter().collect()
 }
 
+/// Generated password together with warnings about request options that were ignored
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+pub struct PasswordResult {
+    pub password: String,
+    pub warnings: Vec<String>,
+}
+
 /// Public API for generating passwords.
-pub fn generate_password(input: PasswordGeneratorRequest) -> Result<String, PasswordError> {
-    password(input)
+///
+/// # Examples
+///
+/// Run as `tests::test_generate_password_examples`.
+///
+/// ```ignore
+/// use crate::generators::password::{generate_password, PasswordGeneratorRequest};
+///
+/// let request = PasswordGeneratorRequest::builder().length(20).build().unwrap();
+/// let password = generate_password(request).unwrap();
+/// assert_eq!(password.chars().count(), 20);
+///
+/// // Fewer characters than the enabled sets need
+/// let request = PasswordGeneratorRequest { length: 2, ..Default::default() };
+/// assert!(generate_password(request).is_err());
+/// ```
+pub fn generate_password(input: PasswordGeneratorRequest) -> Result<String, GeneratorError> {
+    Ok(password(input)?)
+}
+
+/// Same as [`generate_password`], but also returns warnings about ignored request options
+pub fn generate_password_with_warnings(input: PasswordGeneratorRequest) -> Result<PasswordResult, GeneratorError> {
+    let mut options = input.validate_options().map_err(GeneratorError::from)?;
+    let warnings = std::mem::take(&mut options.warnings);
+    Ok(PasswordResult {
+        password: password_with_rng(rand::thread_rng(), options)?,
+        warnings,
+    })
+}
+
+/// Estimate the entropy in bits of the passwords `request` would produce: `log2` of the
+/// character set size for every character, or of the placeholder's set for every pattern
+/// position. Minimums and run limits remove a few candidates and aren't accounted for.
+pub fn estimate_password_entropy(request: PasswordGeneratorRequest) -> Result<f64, GeneratorError> {
+    let options = request.validate_options()?;
+    let entropy = match &options.pattern {
+        Some(parts) => parts
+            .iter()
+            .map(|part| match part {
+                PatternPart::Literal(_) => 0.0,
+                PatternPart::Random(set) => (set.0.len() as f64).log2(),
+            })
+            .sum(),
+        None => {
+            let (all, _) = &options.all;
+            (all.0.len() as f64).log2() * options.length as f64
+        }
+    };
+    Ok(entropy)
 }
 
 #[cfg(test)]
@@ -266,8 +1027,28 @@ mod test {
         chars.into_iter().collect()
     }
 
+    // The characters `avoid_ambiguous` removed before the exclusions were split into groups
+    const LEGACY_AMBIGUOUS_EXCLUSIONS: AmbiguousExclusions = AmbiguousExclusions {
+        il1: true,
+        o0: true,
+        s5: false,
+        b8: false,
+        z2: false,
+    };
 
 
+
+    #[test]
+    fn test_generate_password_examples() {
+        let request = PasswordGeneratorRequest::builder().length(20).build().unwrap();
+        let password = generate_password(request).unwrap();
+        assert_eq!(password.chars().count(), 20);
+
+        // Fewer characters than the enabled sets need
+        let request = PasswordGeneratorRequest { length: 2, ..Default::default() };
+        assert!(generate_password(request).is_err());
+    }
+
     #[test]
     fn test_password_gen_all_charsets_enabled() {
         let mut rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
@@ -277,7 +1058,7 @@ mod test {
             uppercase: true,
             numbers: true,
             special: true,
-            avoid_ambiguous: false,
+            ambiguous_exclusions: AmbiguousExclusions::default(),
             ..Default::default()
         }
         .validate_options()
@@ -288,7 +1069,7 @@ mod test {
         assert_eq!(to_set(&options.number.0), to_set('0'..='9'));
         assert_eq!(to_set(&options.special.0), ref_to_set(SPECIAL_CHARS));
 
-        let pass = password_with_rng(&mut rng, options);
+        let pass = password_with_rng(&mut rng, options).unwrap();
         assert_eq!(pass, "Z!^B5r%hUa23dFM@");
     }
 
@@ -301,7 +1082,7 @@ mod test {
             uppercase: true,
             numbers: false,
             special: false,
-            avoid_ambiguous: false,
+            ambiguous_exclusions: AmbiguousExclusions::default(),
             ..Default::default()
         }
         .validate_options()
@@ -312,7 +1093,7 @@ mod test {
         assert_eq!(to_set(&options.number.0), to_set([]));
         assert_eq!(to_set(&options.special.0), to_set([]));
 
-        let pass = password_with_rng(&mut rng, options);
+        let pass = password_with_rng(&mut rng, options).unwrap();
         assert_eq!(pass, "NQiFrGufQMiNUAmj");
     }
 
@@ -325,22 +1106,22 @@ mod test {
             uppercase: false,
             numbers: true,
             special: false,
-            avoid_ambiguous: true,
+            ambiguous_exclusions: LEGACY_AMBIGUOUS_EXCLUSIONS,
             ..Default::default()
         }
         .validate_options()
         .unwrap();
 
         assert!(to_set(&options.lower.0).is_subset(&to_set('a'..='z')));
-        assert!(to_set(&options.lower.0).is_disjoint(&ref_to_set(LOWER_CHARS_AMBIGUOUS)));
+        assert!(!to_set(&options.lower.0).contains(&'l'));
 
         assert!(to_set(&options.number.0).is_subset(&to_set('0'..='9')));
-        assert!(to_set(&options.number.0).is_disjoint(&ref_to_set(NUMBER_CHARS_AMBIGUOUS)));
+        assert!(to_set(&options.number.0).is_disjoint(&ref_to_set(&['0', '1'])));
 
         assert_eq!(to_set(&options.upper.0), to_set([]));
         assert_eq!(to_set(&options.special.0), to_set([]));
 
-        let pass = password_with_rng(&mut rng, options);
+        let pass = password_with_rng(&mut rng, options).unwrap();
         assert_eq!(pass, "mnjabfz5ct272prf");
     }
 
@@ -353,21 +1134,21 @@ mod test {
             uppercase: true,
             numbers: false,
             special: true,
-            avoid_ambiguous: true,
+            ambiguous_exclusions: LEGACY_AMBIGUOUS_EXCLUSIONS,
             ..Default::default()
         }
         .validate_options()
         .unwrap();
 
         assert!(to_set(&options.upper.0).is_subset(&to_set('A'..='Z')));
-        assert!(to_set(&options.upper.0).is_disjoint(&ref_to_set(UPPER_CHARS_AMBIGUOUS)));
+        assert!(to_set(&options.upper.0).is_disjoint(&ref_to_set(&['I', 'O'])));
 
         assert_eq!(to_set(&options.special.0), ref_to_set(SPECIAL_CHARS));
 
         assert_eq!(to_set(&options.lower.0), to_set([]));
         assert_eq!(to_set(&options.number.0), to_set([]));
 
-        let pass = password_with_rng(&mut rng, options);
+        let pass = password_with_rng(&mut rng, options).unwrap();
         assert_eq!(pass, "B*GBQANS%UZPQD!K");
     }
 
@@ -380,12 +1161,18 @@ mod test {
             uppercase: true,
             numbers: true,
             special: true,
-            avoid_ambiguous: false,
+            ambiguous_exclusions: AmbiguousExclusions::default(),
             length: 24,
             min_lowercase: Some(5),
             min_uppercase: Some(5),
             min_number: Some(5),
             min_special: Some(5),
+            custom_charset: None,
+            pattern: None,
+            max_consecutive_same_char: None,
+            no_consecutive_same_class: false,
+            required_chars: None,
+            custom_exclusions: String::new(),
         }
         .validate_options()
         .unwrap();
@@ -400,7 +1187,627 @@ mod test {
         assert_eq!(options.number.1, 5);
         assert_eq!(options.special.1, 5);
 
-        let pass = password_with_rng(&mut rng, options);
+        let pass = password_with_rng(&mut rng, options).unwrap();
         assert_eq!(pass, "236q5!a#R%PG5rI%k1!*@uRt");
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_password_gen_custom_charset_hex() {
+        let mut rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
+
+        let options = PasswordGeneratorRequest {
+            // The flags are overridden by the custom character set
+            lowercase: false,
+            uppercase: true,
+            special: true,
+            length: 32,
+            custom_charset: Some("0123456789abcdef".to_string()),
+            ..Default::default()
+        }
+        .validate_options()
+        .unwrap();
+
+        assert_eq!(to_set(&options.all.0), to_set(('0'..='9').chain('a'..='f')));
+        assert_eq!(options.all.1, 32);
+        assert!(options.warnings.is_empty());
+
+        let pass = password_with_rng(&mut rng, options).unwrap();
+        assert_eq!(pass.len(), 32);
+        assert!(pass.chars().all(|c| c.is_ascii_hexdigit() && !c.is_ascii_uppercase()));
+    }
+
+    #[test]
+    fn test_password_gen_custom_charset_deduplicated() {
+        let options = PasswordGeneratorRequest {
+            custom_charset: Some("aeiouaeiouAAA".to_string()),
+            ..Default::default()
+        }
+        .validate_options()
+        .unwrap();
+        assert_eq!(to_set(&options.all.0), to_set("aeiouA".chars()));
+
+        // Repeating a single character doesn't make a usable character set
+        let result = PasswordGeneratorRequest {
+            custom_charset: Some("xxxx".to_string()),
+            ..Default::default()
+        }
+        .validate_options();
+        assert!(matches!(result, Err(PasswordError::InsufficientCharset)));
+
+        // An empty custom character set falls back to the flags
+        let options = PasswordGeneratorRequest {
+            custom_charset: Some(String::new()),
+            ..Default::default()
+        }
+        .validate_options()
+        .unwrap();
+        assert_eq!(to_set(&options.lower.0), to_set('a'..='z'));
+    }
+
+    #[test]
+    fn test_password_gen_custom_charset_exclusions() {
+        let options = PasswordGeneratorRequest {
+            custom_charset: Some("0123456789abcdefOlI".to_string()),
+            ambiguous_exclusions: AmbiguousExclusions::all(),
+            custom_exclusions: "f".to_string(),
+            ..Default::default()
+        }
+        .validate_options()
+        .unwrap();
+
+        // Ambiguous exclusions apply to the custom character set like custom exclusions do
+        let excluded = AmbiguousExclusions::all().excluded_chars();
+        let chars = to_set(&options.all.0);
+        assert!(chars.iter().all(|c| !excluded.contains(c) && *c != 'f'));
+        assert!(chars.contains(&'a'));
+    }
+
+    #[test]
+    fn test_password_gen_custom_charset_ignores_minimums() {
+        let result = generate_password_with_warnings(PasswordGeneratorRequest {
+            length: 8,
+            min_number: Some(10),
+            custom_charset: Some("ab".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert_eq!(result.password.len(), 8);
+        assert!(result.password.chars().all(|c| c == 'a' || c == 'b'));
+        assert_eq!(result.warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_password_gen_pattern() {
+        let mut rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
+
+        let options = PasswordGeneratorRequest {
+            pattern: Some("Xxxx-####".to_string()),
+            ..Default::default()
+        }
+        .validate_options()
+        .unwrap();
+        assert_eq!(options.length, 9);
+
+        let pass: Vec<char> = password_with_rng(&mut rng, options).unwrap().chars().collect();
+        assert_eq!(pass.len(), 9);
+        assert!(pass[0].is_ascii_uppercase());
+        assert!(pass[1..4].iter().all(char::is_ascii_lowercase));
+        assert_eq!(pass[4], '-');
+        assert!(pass[5..].iter().all(char::is_ascii_digit));
+    }
+
+    #[test]
+    fn test_password_gen_pattern_all_placeholders() {
+        let mut rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
+
+        let options = PasswordGeneratorRequest {
+            pattern: Some("Xx#!a".repeat(20)),
+            ambiguous_exclusions: AmbiguousExclusions::all(),
+            ..Default::default()
+        }
+        .validate_options()
+        .unwrap();
+
+        let pass: Vec<char> = password_with_rng(&mut rng, options).unwrap().chars().collect();
+        assert_eq!(pass.len(), 100);
+        let alphanumeric = to_set(('a'..='z').chain('A'..='Z').chain('0'..='9'));
+        for chunk in pass.chunks(5) {
+            assert!(chunk[0].is_ascii_uppercase());
+            assert!(chunk[1].is_ascii_lowercase());
+            assert!(chunk[2].is_ascii_digit());
+            assert!(SPECIAL_CHARS.contains(&chunk[3]));
+            assert!(alphanumeric.contains(&chunk[4]));
+        }
+        let ambiguous = to_set(AmbiguousExclusions::all().excluded_chars());
+        assert!(pass.iter().all(|c| !ambiguous.contains(c)));
+    }
+
+    #[test]
+    fn test_password_gen_pattern_escapes() {
+        let mut rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
+
+        // Escaped placeholders next to real ones, and an escaped backslash
+        let options = PasswordGeneratorRequest {
+            pattern: Some(r"\##\X\\x!".to_string()),
+            ..Default::default()
+        }
+        .validate_options()
+        .unwrap();
+
+        let pass: Vec<char> = password_with_rng(&mut rng, options).unwrap().chars().collect();
+        assert_eq!(pass.len(), 6);
+        assert_eq!(pass[0], '#');
+        assert!(pass[1].is_ascii_digit());
+        assert_eq!(pass[2], 'X');
+        assert_eq!(pass[3], '\\');
+        assert!(pass[4].is_ascii_lowercase());
+        assert!(SPECIAL_CHARS.contains(&pass[5]));
+    }
+
+    #[test]
+    fn test_password_gen_invalid_patterns() {
+        // Unknown escape, unfinished escape, and patterns without a placeholder
+        let patterns = [r"Xx\q", r"Xx\", "----", r"\#\X", ""];
+        for pattern in patterns {
+            let result = PasswordGeneratorRequest {
+                pattern: Some(pattern.to_string()),
+                ..Default::default()
+            }
+            .validate_options();
+            assert!(matches!(result, Err(PasswordError::InvalidPattern(_))), "{:?}", pattern);
+        }
+
+        let too_long = PasswordGeneratorRequest {
+            pattern: Some("x".repeat(MAXIMUM_PATTERN_LENGTH + 1)),
+            ..Default::default()
+        }
+        .validate_options();
+        assert!(matches!(too_long, Err(PasswordError::InvalidPattern(_))));
+
+        let longest = PasswordGeneratorRequest {
+            pattern: Some("x".repeat(MAXIMUM_PATTERN_LENGTH)),
+            ..Default::default()
+        }
+        .validate_options();
+        assert!(longest.is_ok());
+    }
+
+    #[test]
+    fn test_password_gen_max_consecutive_same_char() {
+        let mut rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
+
+        for _ in 0..50 {
+            // A small character set makes runs likely without the limit
+            let options = PasswordGeneratorRequest {
+                length: 24,
+                custom_charset: Some("abcd".to_string()),
+                max_consecutive_same_char: Some(2),
+                ..Default::default()
+            }
+            .validate_options()
+            .unwrap();
+
+            let pass = password_with_rng(&mut rng, options).unwrap();
+            assert!(longest_run(&pass) <= 2, "{}", pass);
+        }
+
+        for _ in 0..50 {
+            let options = PasswordGeneratorRequest {
+                length: 32,
+                max_consecutive_same_char: Some(1),
+                ..Default::default()
+            }
+            .validate_options()
+            .unwrap();
+
+            let pass: Vec<char> = password_with_rng(&mut rng, options).unwrap().chars().collect();
+            assert!(pass.windows(2).all(|pair| pair[0] != pair[1]), "{:?}", pass);
+        }
+    }
+
+    #[test]
+    fn test_password_gen_max_consecutive_same_char_unsatisfiable() {
+        let result = PasswordGeneratorRequest {
+            max_consecutive_same_char: Some(0),
+            ..Default::default()
+        }
+        .validate_options();
+        assert!(matches!(result, Err(PasswordError::CannotSatisfyConsecutiveConstraint)));
+
+        // The pattern's literal run can never satisfy the limit
+        let mut rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
+        let options = PasswordGeneratorRequest {
+            pattern: Some("x---#".to_string()),
+            max_consecutive_same_char: Some(2),
+            ..Default::default()
+        }
+        .validate_options()
+        .unwrap();
+        assert!(matches!(
+            password_with_rng(&mut rng, options),
+            Err(PasswordError::CannotSatisfyConsecutiveConstraint)
+        ));
+    }
+
+    #[test]
+    fn test_longest_run() {
+        assert_eq!(longest_run(""), 0);
+        assert_eq!(longest_run("abc"), 1);
+        assert_eq!(longest_run("aaabbbb123"), 4);
+        assert_eq!(longest_run("aAaA"), 1);
+    }
+
+    #[test]
+    fn test_password_gen_no_consecutive_same_class() {
+        let mut rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
+
+        for length in [4, 16, 64] {
+            for _ in 0..1000 {
+                let options = PasswordGeneratorRequest {
+                    special: true,
+                    length,
+                    no_consecutive_same_class: true,
+                    ..Default::default()
+                }
+                .validate_options()
+                .unwrap();
+
+                let pass: Vec<char> = password_with_rng(&mut rng, options).unwrap().chars().collect();
+                assert_eq!(pass.len(), length as usize);
+                assert!(
+                    pass.windows(2).all(|pair| CharClass::of(pair[0]) != CharClass::of(pair[1])),
+                    "{:?}",
+                    pass
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_password_gen_no_consecutive_same_class_keeps_minimums() {
+        let mut rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
+
+        // 8 uppercase characters fit in 16 positions only by alternating
+        let options = PasswordGeneratorRequest {
+            length: 16,
+            min_uppercase: Some(8),
+            no_consecutive_same_class: true,
+            ..Default::default()
+        }
+        .validate_options()
+        .unwrap();
+
+        let pass: Vec<char> = password_with_rng(&mut rng, options).unwrap().chars().collect();
+        assert_eq!(pass.iter().filter(|c| c.is_ascii_uppercase()).count(), 8);
+        assert!(pass.windows(2).all(|pair| CharClass::of(pair[0]) != CharClass::of(pair[1])));
+    }
+
+    #[test]
+    fn test_password_gen_no_consecutive_same_class_impossible() {
+        let mut rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
+
+        let options = PasswordGeneratorRequest {
+            lowercase: true,
+            uppercase: false,
+            numbers: false,
+            no_consecutive_same_class: true,
+            ..Default::default()
+        }
+        .validate_options()
+        .unwrap();
+        assert_eq!(options.warnings.len(), 1);
+
+        // Falls back to the shuffled order instead of failing
+        let pass = password_with_rng(&mut rng, options).unwrap();
+        assert_eq!(pass.len(), DEFAULT_PASSWORD_LENGTH as usize);
+        assert!(pass.chars().all(|c| c.is_ascii_lowercase()));
+    }
+
+    #[test]
+    fn test_password_gen_required_chars() {
+        let mut rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
+
+        for _ in 0..1000 {
+            let options = PasswordGeneratorRequest {
+                special: true,
+                min_special: Some(1),
+                length: 8,
+                required_chars: Some(vec!['@', '#']),
+                ..Default::default()
+            }
+            .validate_options()
+            .unwrap();
+            // One of each enabled set and the required character leave 3 positions
+            assert_eq!(options.all.1, 3);
+
+            let pass = password_with_rng(&mut rng, options).unwrap();
+            assert_eq!(pass.len(), 8);
+            assert!(pass.contains('@') || pass.contains('#'), "{}", pass);
+        }
+    }
+
+    #[test]
+    fn test_password_gen_required_chars_custom_charset() {
+        let mut rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
+
+        for _ in 0..1000 {
+            let options = PasswordGeneratorRequest {
+                custom_charset: Some("0123456789abcdef".to_string()),
+                required_chars: Some(vec!['f']),
+                ..Default::default()
+            }
+            .validate_options()
+            .unwrap();
+
+            let pass = password_with_rng(&mut rng, options).unwrap();
+            assert_eq!(pass.len(), DEFAULT_PASSWORD_LENGTH as usize);
+            assert!(pass.contains('f'), "{}", pass);
+        }
+    }
+
+    #[test]
+    fn test_password_gen_required_chars_not_in_charset() {
+        // Special characters are disabled by default
+        let result = PasswordGeneratorRequest {
+            required_chars: Some(vec!['a', '@']),
+            ..Default::default()
+        }
+        .validate_options();
+        assert!(matches!(result, Err(PasswordError::RequiredCharNotInCharset('@'))));
+
+        // Ambiguous characters are removed from the enabled sets
+        let result = PasswordGeneratorRequest {
+            ambiguous_exclusions: AmbiguousExclusions::all(),
+            required_chars: Some(vec!['l']),
+            ..Default::default()
+        }
+        .validate_options();
+        assert!(matches!(result, Err(PasswordError::RequiredCharNotInCharset('l'))));
+
+        let result = PasswordGeneratorRequest {
+            custom_charset: Some("abc".to_string()),
+            required_chars: Some(vec!['d']),
+            ..Default::default()
+        }
+        .validate_options();
+        assert!(matches!(result, Err(PasswordError::RequiredCharNotInCharset('d'))));
+
+        // No room left after the minimums
+        let result = PasswordGeneratorRequest {
+            length: 4,
+            special: true,
+            required_chars: Some(vec!['@']),
+            ..Default::default()
+        }
+        .validate_options();
+        assert!(matches!(result, Err(PasswordError::InvalidLength)));
+    }
+
+    #[test]
+    fn test_password_gen_ambiguous_exclusion_groups() {
+        let groups: [(AmbiguousExclusions, &[char]); 5] = [
+            (AmbiguousExclusions { il1: true, ..Default::default() }, IL1_CHARS),
+            (AmbiguousExclusions { o0: true, ..Default::default() }, O0_CHARS),
+            (AmbiguousExclusions { s5: true, ..Default::default() }, S5_CHARS),
+            (AmbiguousExclusions { b8: true, ..Default::default() }, B8_CHARS),
+            (AmbiguousExclusions { z2: true, ..Default::default() }, Z2_CHARS),
+        ];
+        let all_look_alikes = to_set(AmbiguousExclusions::all().excluded_chars());
+
+        for (exclusions, chars) in groups {
+            let options = PasswordGeneratorRequest {
+                special: true,
+                ambiguous_exclusions: exclusions,
+                ..Default::default()
+            }
+            .validate_options()
+            .unwrap();
+
+            // Only the characters of the flagged group are removed
+            let removed = &all_look_alikes - &to_set(&options.all.0);
+            assert_eq!(removed, ref_to_set(chars));
+        }
+    }
+
+    #[test]
+    fn test_ambiguous_exclusions_deserialization() {
+        let parse = |json: &str| serde_json::from_str::<PasswordGeneratorRequest>(json).unwrap().ambiguous_exclusions;
+        let base = r#""lowercase":true,"uppercase":true,"numbers":true,"special":false,"length":16"#;
+
+        assert_eq!(parse(&format!(r#"{{{},"avoidAmbiguous":true}}"#, base)), AmbiguousExclusions::all());
+        assert_eq!(parse(&format!(r#"{{{},"avoidAmbiguous":false}}"#, base)), AmbiguousExclusions::default());
+        assert_eq!(parse(&format!("{{{}}}", base)), AmbiguousExclusions::default());
+        assert_eq!(
+            parse(&format!(r#"{{{},"ambiguousExclusions":{{"O0":true}}}}"#, base)),
+            AmbiguousExclusions {
+                o0: true,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_password_gen_custom_exclusions() {
+        let mut rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
+        let excluded = to_set("aeiouAEIOU02468!@".chars());
+
+        for _ in 0..1000 {
+            let options = PasswordGeneratorRequest {
+                special: true,
+                custom_exclusions: "aeiouAEIOU02468!@".to_string(),
+                ..Default::default()
+            }
+            .validate_options()
+            .unwrap();
+
+            let pass = password_with_rng(&mut rng, options).unwrap();
+            assert!(pass.chars().all(|c| !excluded.contains(&c)), "{}", pass);
+        }
+    }
+
+    #[test]
+    fn test_password_gen_custom_exclusions_whole_set() {
+        // Every digit excluded while numbers are enabled
+        let result = PasswordGeneratorRequest {
+            custom_exclusions: "0123456789".to_string(),
+            ..Default::default()
+        }
+        .validate_options();
+        assert!(matches!(result, Err(PasswordError::AllCharsExcluded)));
+
+        // Fine once numbers are disabled
+        let options = PasswordGeneratorRequest {
+            numbers: false,
+            custom_exclusions: "0123456789".to_string(),
+            ..Default::default()
+        }
+        .validate_options();
+        assert!(options.is_ok());
+
+        let result = PasswordGeneratorRequest {
+            custom_charset: Some("abc".to_string()),
+            custom_exclusions: "cba".to_string(),
+            ..Default::default()
+        }
+        .validate_options();
+        assert!(matches!(result, Err(PasswordError::AllCharsExcluded)));
+
+        let result = PasswordGeneratorRequest {
+            pattern: Some("x-!".to_string()),
+            custom_exclusions: "!@#$%^&*".to_string(),
+            ..Default::default()
+        }
+        .validate_options();
+        assert!(matches!(result, Err(PasswordError::AllCharsExcluded)));
+    }
+
+    #[test]
+    fn test_password_presets() {
+        let presets = [
+            PasswordPreset::NistMinimum,
+            PasswordPreset::HighSecurity,
+            PasswordPreset::MaxEntropy,
+            PasswordPreset::NoAmbiguous,
+            PasswordPreset::MobileEntry,
+        ];
+        for preset in presets {
+            let options = PasswordGeneratorRequest::preset(preset).validate_options();
+            assert!(options.is_ok(), "{:?}", preset);
+        }
+
+        let nist = PasswordGeneratorRequest::preset(PasswordPreset::NistMinimum);
+        assert_eq!(nist.length, 8);
+        assert!(nist.lowercase && nist.numbers && !nist.uppercase && !nist.special);
+
+        let max_entropy = PasswordGeneratorRequest::preset(PasswordPreset::MaxEntropy);
+        assert_eq!(max_entropy.length, 64);
+        assert!(max_entropy.lowercase && max_entropy.uppercase && max_entropy.numbers && max_entropy.special);
+
+        let mobile = PasswordGeneratorRequest::preset(PasswordPreset::MobileEntry);
+        assert_eq!(mobile.length, 16);
+        assert!(!mobile.special);
+        assert_eq!(mobile.ambiguous_exclusions, AmbiguousExclusions::all());
+    }
+
+    #[test]
+    fn test_password_request_builder() {
+        let built = PasswordGeneratorRequest::builder()
+            .length(20)
+            .uppercase(true)
+            .lowercase(true)
+            .numbers(true)
+            .special(false)
+            .min_number(3)
+            .custom_exclusions("xyz")
+            .build()
+            .unwrap();
+
+        let direct = PasswordGeneratorRequest {
+            lowercase: true,
+            uppercase: true,
+            numbers: true,
+            special: false,
+            length: 20,
+            min_number: Some(3),
+            custom_exclusions: "xyz".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(built, direct);
+
+        // Every class is enabled by default
+        let default = PasswordGeneratorRequest::builder().build().unwrap();
+        assert!(default.lowercase && default.uppercase && default.numbers && default.special);
+        assert_eq!(default.length, DEFAULT_PASSWORD_LENGTH);
+        assert_eq!(default.min_lowercase, None);
+    }
+
+    #[test]
+    fn test_password_request_builder_invalid() {
+        let result = PasswordGeneratorRequest::builder().length(3).build();
+        assert!(matches!(result, Err(PasswordError::InvalidLength)));
+
+        let result = PasswordGeneratorRequest::builder()
+            .lowercase(false)
+            .uppercase(false)
+            .numbers(false)
+            .special(false)
+            .build();
+        assert!(matches!(result, Err(PasswordError::NoCharacterSetEnabled)));
+
+        let result = PasswordGeneratorRequest::builder()
+            .lowercase(false)
+            .uppercase(false)
+            .special(false)
+            .custom_exclusions("0123456789")
+            .build();
+        assert!(matches!(result, Err(PasswordError::AllCharsExcluded)));
+    }
+
+    #[test]
+    fn test_estimate_password_entropy() {
+        let all_classes = PasswordGeneratorRequest::builder().length(12).build().unwrap();
+        let expected = 70f64.log2() * 12.0;
+        assert!((estimate_password_entropy(all_classes).unwrap() - expected).abs() < 1e-9);
+
+        let hex = PasswordGeneratorRequest {
+            length: 32,
+            custom_charset: Some("0123456789abcdef".to_string()),
+            ..Default::default()
+        };
+        assert!((estimate_password_entropy(hex).unwrap() - 128.0).abs() < 1e-9);
+
+        // Literal pattern positions add nothing
+        let pattern = PasswordGeneratorRequest {
+            pattern: Some("####-####".to_string()),
+            ..Default::default()
+        };
+        assert!((estimate_password_entropy(pattern).unwrap() - 10f64.log2() * 8.0).abs() < 1e-9);
+
+        let invalid = PasswordGeneratorRequest { length: 2, ..Default::default() };
+        assert!(estimate_password_entropy(invalid).is_err());
+    }
+
+    #[test]
+    fn test_password_request_display() {
+        let request = PasswordGeneratorRequest {
+            special: true,
+            ambiguous_exclusions: AmbiguousExclusions::all(),
+            ..Default::default()
+        };
+        assert_eq!(request.to_string(), "Password(16 chars, upper+lower+digits+special, no ambiguous)");
+
+        let request = PasswordGeneratorRequest {
+            length: 32,
+            custom_charset: Some("0123456789abcdefabc".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(request.to_string(), "Password(32 chars, custom charset of 16 chars)");
+
+        let request = PasswordGeneratorRequest {
+            pattern: Some("Xxxx-####".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(request.to_string(), "Password(pattern \"Xxxx-####\")");
+    }
+}
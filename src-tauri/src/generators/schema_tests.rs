@@ -0,0 +1,205 @@
+//! JSON roundtrip tests for the generator requests
+//!
+//! The frontend builds these requests in TypeScript, so every variant must survive a
+//! serialize/deserialize cycle unchanged. A failure here usually means the Rust and TypeScript
+//! schemas have drifted apart.
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::json;
+
+use crate::generators::passphrase::{CapitalizationMode, NumberLength, NumberPosition, PassphraseGeneratorRequest};
+use crate::generators::password::{AmbiguousExclusions, PasswordGeneratorRequest, PasswordPreset};
+use crate::generators::username::{
+    AppendType, ForwarderServiceType, NumberPosition as UsernameNumberPosition, NumberSuffix, UsernameFormat,
+    UsernameGeneratorRequest, UsernameStrength,
+};
+
+fn assert_roundtrip<T>(value: T)
+where
+    T: Serialize + DeserializeOwned + PartialEq + std::fmt::Debug,
+{
+    let json = serde_json::to_string(&value).unwrap();
+    let parsed: T = serde_json::from_str(&json).unwrap_or_else(|e| panic!("{json} did not parse: {e}"));
+    assert_eq!(parsed, value, "{json}");
+}
+
+fn forwarder_services() -> Vec<ForwarderServiceType> {
+    vec![
+        ForwarderServiceType::AddyIo {
+            api_token: "addy-token".to_string(),
+            domain: "anonaddy.me".to_string(),
+            base_url: "https://app.addy.io".to_string(),
+            mailbox_id: Some("mailbox-1".to_string()),
+        },
+        ForwarderServiceType::DuckDuckGo {
+            token: "duck-token".to_string(),
+        },
+        ForwarderServiceType::Firefox {
+            api_token: "relay-token".to_string(),
+        },
+        ForwarderServiceType::Fastmail {
+            api_token: "fastmail-token".to_string(),
+            identity_id: None,
+        },
+        ForwarderServiceType::ForwardEmail {
+            api_token: "forward-token".to_string(),
+            domain: "example.com".to_string(),
+        },
+        ForwarderServiceType::SimpleLogin {
+            api_key: "simplelogin-key".to_string(),
+            base_url: "https://app.simplelogin.io".to_string(),
+            note: Some("Shopping".to_string()),
+            directory: None,
+        },
+        ForwarderServiceType::IronVest {
+            api_token: "ironvest-token".to_string(),
+        },
+    ]
+}
+
+#[test]
+fn test_username_request_roundtrip() {
+    let mut requests = vec![
+        UsernameGeneratorRequest::Word {
+            capitalize: true,
+            number_suffix: NumberSuffix::SixDigits,
+            number_position: UsernameNumberPosition::Prefix,
+            strength: UsernameStrength::Strong,
+            format: UsernameFormat::KebabCase,
+        },
+        UsernameGeneratorRequest::CompoundWord {
+            word_count: 3,
+            strength: UsernameStrength::Basic,
+            format: UsernameFormat::PascalCase,
+        },
+        UsernameGeneratorRequest::Subaddress {
+            r#type: AppendType::WebsiteName {
+                website: "example.com".to_string(),
+            },
+            email: "someone@example.com".to_string(),
+        },
+        UsernameGeneratorRequest::Catchall {
+            r#type: AppendType::Pronounceable { syllables: 4 },
+            domain: "xn--mnchen-3ya.de".to_string(),
+            original_domain: Some("münchen.de".to_string()),
+        },
+        UsernameGeneratorRequest::Catchall {
+            r#type: AppendType::Random,
+            domain: "example.com".to_string(),
+            original_domain: None,
+        },
+    ];
+    requests.extend(forwarder_services().into_iter().map(|service| UsernameGeneratorRequest::Forwarded {
+        service,
+        website: Some("example.com".to_string()),
+    }));
+
+    for request in requests {
+        assert_roundtrip(request);
+    }
+}
+
+#[test]
+fn test_forwarder_service_roundtrip_keeps_token() {
+    // PartialEq ignores the token value, so check the serialized form as well
+    for service in forwarder_services() {
+        let json = serde_json::to_value(&service).unwrap();
+        let parsed: ForwarderServiceType = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), json);
+    }
+}
+
+#[test]
+fn test_passphrase_request_roundtrip() {
+    let requests = [
+        PassphraseGeneratorRequest::default(),
+        PassphraseGeneratorRequest {
+            num_words: 6,
+            word_separator: "🔑".to_string(),
+            capitalization: CapitalizationMode::TitleCase,
+            include_number: true,
+            number_length: NumberLength::FourDigits,
+            number_position: NumberPosition::StandaloneWord,
+            ..Default::default()
+        },
+        PassphraseGeneratorRequest {
+            capitalize_positions: Some(vec![0, 2]),
+            diceware_compatible: true,
+            phonetic_smoothing: true,
+            excluded_words: Some(vec!["abacus".to_string()]),
+            ..Default::default()
+        },
+    ];
+    for request in requests {
+        assert_roundtrip(request);
+    }
+}
+
+#[test]
+fn test_password_request_roundtrip() {
+    let mut requests = vec![
+        PasswordGeneratorRequest::default(),
+        PasswordGeneratorRequest {
+            ambiguous_exclusions: AmbiguousExclusions {
+                b8: true,
+                ..Default::default()
+            },
+            custom_exclusions: "{}".to_string(),
+            required_chars: Some(vec!['#']),
+            max_consecutive_same_char: Some(2),
+            no_consecutive_same_class: true,
+            ..Default::default()
+        },
+        PasswordGeneratorRequest {
+            custom_charset: Some("0123456789abcdef".to_string()),
+            ..Default::default()
+        },
+        PasswordGeneratorRequest {
+            pattern: Some("Xxxx-####".to_string()),
+            ..Default::default()
+        },
+    ];
+    requests.extend(
+        [
+            PasswordPreset::NistMinimum,
+            PasswordPreset::HighSecurity,
+            PasswordPreset::MaxEntropy,
+            PasswordPreset::NoAmbiguous,
+            PasswordPreset::MobileEntry,
+        ]
+        .into_iter()
+        .map(PasswordGeneratorRequest::preset),
+    );
+
+    for request in requests {
+        assert_roundtrip(request);
+    }
+}
+
+#[test]
+fn test_unknown_fields() {
+    let mut passphrase = serde_json::to_value(PassphraseGeneratorRequest::default()).unwrap();
+    passphrase["unknown_field"] = json!(true);
+    assert!(serde_json::from_value::<PassphraseGeneratorRequest>(passphrase).is_err());
+
+    let mut password = serde_json::to_value(PasswordGeneratorRequest::default()).unwrap();
+    password["unknownField"] = json!(true);
+    assert!(serde_json::from_value::<PasswordGeneratorRequest>(password).is_err());
+
+    let username = json!({
+        "Subaddress": {
+            "type": "Random",
+            "email": "someone@example.com",
+            "unknown_field": true,
+        }
+    });
+    assert!(serde_json::from_value::<UsernameGeneratorRequest>(username).is_ok());
+
+    let service = json!({
+        "DuckDuckGo": {
+            "token": "duck-token",
+            "unknown_field": true,
+        }
+    });
+    assert!(serde_json::from_value::<ForwarderServiceType>(service).is_ok());
+}
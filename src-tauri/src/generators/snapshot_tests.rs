@@ -0,0 +1,95 @@
+//! Snapshot tests for generator output with a fixed seed
+//!
+//! A changed snapshot means the word list, the formatting or the way the generators draw from the
+//! RNG has changed. When that is intended, review and accept the new output with
+//! `cargo insta review`. CI should run with `INSTA_UPDATE=no` so unexpected diffs fail.
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::generators::passphrase::{passphrase_from_request, CapitalizationMode, PassphraseGeneratorRequest};
+use crate::generators::password::{password_from_request, AmbiguousExclusions, PasswordGeneratorRequest};
+use crate::generators::username::{username_word, NumberPosition, NumberSuffix, UsernameFormat, UsernameStrength};
+
+fn rng() -> ChaCha8Rng {
+    ChaCha8Rng::from_seed([42u8; 32])
+}
+
+/// Snapshot files are named after `name` only, without the module path
+fn assert_snapshot(name: String, value: String) {
+    let mut settings = insta::Settings::clone_current();
+    settings.set_prepend_module_to_snapshot(false);
+    settings.bind(|| insta::assert_snapshot!(name, value));
+}
+
+#[test]
+fn test_username_word_snapshots() {
+    for (name, strength) in [
+        ("basic", UsernameStrength::Basic),
+        ("standard", UsernameStrength::Standard),
+        ("strong", UsernameStrength::Strong),
+        ("maximum", UsernameStrength::Maximum),
+    ] {
+        let username = username_word(
+            rng(),
+            true,
+            NumberSuffix::FourDigits,
+            NumberPosition::Suffix,
+            strength,
+            UsernameFormat::Plain,
+        );
+        assert_snapshot(format!("username_word_{name}"), username);
+    }
+}
+
+#[test]
+fn test_passphrase_snapshots() {
+    for num_words in 3..=7 {
+        let request = PassphraseGeneratorRequest {
+            num_words,
+            word_separator: "-".to_string(),
+            capitalization: CapitalizationMode::FirstLetter,
+            include_number: true,
+            ..Default::default()
+        };
+        let passphrase = passphrase_from_request(rng(), request).unwrap();
+        assert_snapshot(format!("passphrase_{num_words}_words"), passphrase);
+    }
+}
+
+#[test]
+fn test_password_snapshots() {
+    let requests = [
+        ("all_classes", PasswordGeneratorRequest::builder().build().unwrap()),
+        (
+            "lowercase_only",
+            PasswordGeneratorRequest {
+                uppercase: false,
+                numbers: false,
+                length: 12,
+                ..Default::default()
+            },
+        ),
+        (
+            "no_ambiguous_min_counts",
+            PasswordGeneratorRequest::builder()
+                .length(24)
+                .min_number(3)
+                .min_special(3)
+                .ambiguous_exclusions(AmbiguousExclusions::all())
+                .build()
+                .unwrap(),
+        ),
+        (
+            "pattern",
+            PasswordGeneratorRequest {
+                pattern: Some("Xxxx-####-!!".to_string()),
+                ..Default::default()
+            },
+        ),
+    ];
+    for (name, request) in requests {
+        let password = password_from_request(rng(), request).unwrap();
+        assert_snapshot(format!("password_{name}"), password);
+    }
+}
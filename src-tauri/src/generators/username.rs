@@ -1,8 +1,10 @@
 use crate::generators::wordlist::EFF_LONG_WORD_LIST;
 use crate::generators::username_forwarders;
+use crate::generators::{random_number, GeneratorError, SerializableError};
 use rand::{distributions::Distribution, seq::SliceRandom, Rng};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
 use std::sync::LazyLock;
 use thiserror::Error;
 
@@ -45,7 +47,13 @@ pub enum UsernameError {
     #[error("Rate limit exceeded - please wait before making more requests")]
     RateLimitExceeded,
     #[error("Network error occurred while making HTTP request")]
-    Http(#[from] reqwest::Error),
+    Http(reqwest::Error),
+    #[error("The request to the forwarding service timed out")]
+    Timeout,
+    #[error("The forwarding service is unavailable - please try again later")]
+    ServiceUnavailable,
+    #[error("Could not resolve the forwarding service's address - check the server URL and your connection")]
+    DnsFailure,
     #[error("Unknown error")]
     Unknown,
     #[error("Received error message from server: [{status}] {message}")]
@@ -58,6 +66,156 @@ pub enum UsernameError {
     EmptyWebsiteName,
     #[error("API configuration is incomplete for {service}")]
     IncompleteApiConfig { service: String },
+    #[error("Generation cancelled")]
+    Cancelled,
+    #[error("Invalid domain: {domain}")]
+    InvalidDomain { domain: String },
+    #[error("The server URL for {service} must use https://")]
+    InsecureBaseUrl { service: String },
+    #[error("Alias not found")]
+    AliasNotFound,
+    #[error("This operation is not supported by {service}")]
+    UnsupportedOperation { service: String },
+    #[error("Identity {identity_id} does not belong to this account")]
+    InvalidIdentity { identity_id: String },
+    #[error("Syllable count must be between 2 and 6, got {syllables}")]
+    InvalidSyllableCount { syllables: u8 },
+    #[error("Word count must be between 2 and 4, got {word_count}")]
+    InvalidWordCount { word_count: u8 },
+    #[error("Permission denied - the account is not allowed to perform this operation")]
+    PermissionDenied,
+    #[error("Every username generated in {attempts} attempts was already generated this session")]
+    DuplicateUsername { attempts: u8 },
+    #[error("Invalid website name: {reason}")]
+    InvalidWebsiteName { reason: String },
+    #[error("{service} returned an empty alias")]
+    EmptyResponse { service: String },
+    /// The app's own limit on username generation, where [`UsernameError::RateLimitExceeded`]
+    /// comes from a forwarding service
+    #[error("{message}")]
+    LocalRateLimitExceeded { message: String },
+}
+
+/// Stable numeric codes for [`UsernameError`] variants, sent to the frontend in
+/// [`SerializableError`]. Codes must never be changed or reused once released.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u16)]
+pub enum UsernameErrorCode {
+    InvalidApiKey = 1001,
+    RateLimitExceeded = 1002,
+    DomainRejected = 1003,
+    Http = 1004,
+    Unknown = 1005,
+    ResponseContent = 1006,
+    InvalidEmail = 1007,
+    EmptyDomain = 1008,
+    EmptyWebsiteName = 1009,
+    IncompleteApiConfig = 1010,
+    Timeout = 1011,
+    ServiceUnavailable = 1012,
+    DnsFailure = 1013,
+    Cancelled = 1014,
+    InvalidDomain = 1015,
+    InsecureBaseUrl = 1016,
+    AliasNotFound = 1017,
+    UnsupportedOperation = 1018,
+    InvalidIdentity = 1019,
+    InvalidSyllableCount = 1020,
+    InvalidWordCount = 1021,
+    PermissionDenied = 1022,
+    DuplicateUsername = 1023,
+    InvalidWebsiteName = 1024,
+    EmptyResponse = 1025,
+    LocalRateLimitExceeded = 1026,
+}
+
+impl UsernameError {
+    pub fn code(&self) -> UsernameErrorCode {
+        match self {
+            UsernameError::InvalidApiKey => UsernameErrorCode::InvalidApiKey,
+            UsernameError::RateLimitExceeded => UsernameErrorCode::RateLimitExceeded,
+            UsernameError::DomainRejected => UsernameErrorCode::DomainRejected,
+            UsernameError::Http(_) => UsernameErrorCode::Http,
+            UsernameError::Unknown => UsernameErrorCode::Unknown,
+            UsernameError::ResponseContent { .. } => UsernameErrorCode::ResponseContent,
+            UsernameError::InvalidEmail { .. } => UsernameErrorCode::InvalidEmail,
+            UsernameError::EmptyDomain => UsernameErrorCode::EmptyDomain,
+            UsernameError::EmptyWebsiteName => UsernameErrorCode::EmptyWebsiteName,
+            UsernameError::IncompleteApiConfig { .. } => UsernameErrorCode::IncompleteApiConfig,
+            UsernameError::Timeout => UsernameErrorCode::Timeout,
+            UsernameError::ServiceUnavailable => UsernameErrorCode::ServiceUnavailable,
+            UsernameError::DnsFailure => UsernameErrorCode::DnsFailure,
+            UsernameError::Cancelled => UsernameErrorCode::Cancelled,
+            UsernameError::InvalidDomain { .. } => UsernameErrorCode::InvalidDomain,
+            UsernameError::InsecureBaseUrl { .. } => UsernameErrorCode::InsecureBaseUrl,
+            UsernameError::AliasNotFound => UsernameErrorCode::AliasNotFound,
+            UsernameError::UnsupportedOperation { .. } => UsernameErrorCode::UnsupportedOperation,
+            UsernameError::InvalidIdentity { .. } => UsernameErrorCode::InvalidIdentity,
+            UsernameError::InvalidSyllableCount { .. } => UsernameErrorCode::InvalidSyllableCount,
+            UsernameError::InvalidWordCount { .. } => UsernameErrorCode::InvalidWordCount,
+            UsernameError::PermissionDenied => UsernameErrorCode::PermissionDenied,
+            UsernameError::DuplicateUsername { .. } => UsernameErrorCode::DuplicateUsername,
+            UsernameError::InvalidWebsiteName { .. } => UsernameErrorCode::InvalidWebsiteName,
+            UsernameError::EmptyResponse { .. } => UsernameErrorCode::EmptyResponse,
+            UsernameError::LocalRateLimitExceeded { .. } => UsernameErrorCode::LocalRateLimitExceeded,
+        }
+    }
+
+    /// Returns `true` for transient failures where retrying the same request may succeed,
+    /// including a success response that came back without an alias.
+    /// Authentication, validation and rate limit errors are never retried.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            UsernameError::Timeout | UsernameError::ServiceUnavailable | UsernameError::EmptyResponse { .. }
+        )
+    }
+}
+
+impl From<UsernameError> for SerializableError {
+    fn from(error: UsernameError) -> Self {
+        // The generic HTTP message hides the cause, so pass the reqwest error along as details
+        let details = match &error {
+            UsernameError::Http(http) => Some(http.to_string()),
+            _ => None,
+        };
+        SerializableError {
+            code: error.code() as u16,
+            message: error.to_string(),
+            details,
+        }
+    }
+}
+
+impl From<reqwest::Error> for UsernameError {
+    /// Classify transport errors so timeouts, DNS failures and unreachable services can be told
+    /// apart from other HTTP errors
+    fn from(error: reqwest::Error) -> Self {
+        if error.is_timeout() {
+            return UsernameError::Timeout;
+        }
+        if error.is_connect() {
+            if is_dns_error(&error) {
+                return UsernameError::DnsFailure;
+            }
+            return UsernameError::ServiceUnavailable;
+        }
+        UsernameError::Http(error)
+    }
+}
+
+/// reqwest doesn't expose DNS failures as a separate kind, so look for the resolver's
+/// error message in the source chain
+fn is_dns_error(error: &reqwest::Error) -> bool {
+    let mut source: Option<&dyn std::error::Error> = Some(error);
+    while let Some(err) = source {
+        let message = err.to_string();
+        if message.contains("dns error") || message.contains("failed to lookup address") {
+            return true;
+        }
+        source = err.source();
+    }
+    false
 }
 
 /// Username strength levels for word-based generation
@@ -95,8 +253,9 @@ impl UsernameStrength {
         }
     }
 
-    /// Get words that match this strength level from the precomputed cache
-    fn filter_words(&self) -> &'static Vec<&'static str> {
+    /// Get words that match this strength level from the precomputed cache. Nothing is allocated
+    /// after the cache is built, see `memory_audit.rs`.
+    pub(crate) fn filter_words(&self) -> &'static [&'static str] {
         FILTERED_WORD_CACHE
             .get(self)
             .expect("All UsernameStrength variants should be precomputed in cache")
@@ -104,15 +263,129 @@ impl UsernameStrength {
 }
 
 /// Append type for subaddress and catchall username generation
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "PascalCase")]
 pub enum AppendType {
     /// Generates a random string of 8 lowercase characters as part of your username
     Random,
     /// Uses the website name as part of your username
     WebsiteName { website: String },
+    /// Generates a pronounceable string of consonant-vowel syllables, e.g. `mifako`
+    Pronounceable { syllables: u8 },
+}
+
+/// Longest website name kept in a generated address
+const MAX_WEBSITE_NAME_LENGTH: usize = 64;
+
+/// Characters that would let a website name break out of the local part of an address, or
+/// inject headers if the address ends up in an email
+const FORBIDDEN_WEBSITE_NAME_CHARS: [char; 5] = ['@', '\r', '\n', ':', ';'];
+
+impl AppendType {
+    /// Reduces website names to lowercase letters, digits, `-`, `.` and `_`, cut to 64
+    /// characters, so they can be used in the local part of an address as is
+    fn normalize(self) -> Self {
+        match self {
+            AppendType::WebsiteName { website } => AppendType::WebsiteName {
+                website: sanitize_website_name(&website),
+            },
+            other => other,
+        }
+    }
+}
+
+fn sanitize_website_name(website: &str) -> String {
+    website
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '.' | '_'))
+        .take(MAX_WEBSITE_NAME_LENGTH)
+        .collect()
+}
+
+/// Casing and separator style for word based usernames
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "PascalCase")]
+pub enum UsernameFormat {
+    /// Words joined as they are, e.g. `quietriver`
+    #[default]
+    Plain,
+    /// `quiet_river`
+    SnakeCase,
+    /// `quiet-river`
+    KebabCase,
+    /// `quietRiver`
+    CamelCase,
+    /// `QuietRiver`
+    PascalCase,
+}
+
+/// Length of the number appended to word usernames
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "PascalCase")]
+pub enum NumberSuffix {
+    #[default]
+    None,
+    FourDigits,
+    SixDigits,
+    EightDigits,
+}
+
+impl NumberSuffix {
+    /// Number of digits to append, `None` when no number is added
+    fn digits(self) -> Option<u8> {
+        match self {
+            NumberSuffix::None => None,
+            NumberSuffix::FourDigits => Some(4),
+            NumberSuffix::SixDigits => Some(6),
+            NumberSuffix::EightDigits => Some(8),
+        }
+    }
+}
+
+/// Where the number is placed in word usernames
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "PascalCase")]
+pub enum NumberPosition {
+    /// `1234quiet`
+    Prefix,
+    /// `quiet1234`
+    #[default]
+    Suffix,
+}
+
+/// Accepts either a [`NumberSuffix`] or the legacy `include_number` boolean, where `true` maps to
+/// four digits
+fn deserialize_number_suffix<'de, D>(deserializer: D) -> Result<NumberSuffix, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        Legacy(bool),
+        Suffix(NumberSuffix),
+    }
+
+    Ok(match Repr::deserialize(deserializer)? {
+        Repr::Legacy(true) => NumberSuffix::FourDigits,
+        Repr::Legacy(false) => NumberSuffix::None,
+        Repr::Suffix(suffix) => suffix,
+    })
 }
 
+/// Allowed word count for [`UsernameGeneratorRequest::CompoundWord`]
+const MIN_COMPOUND_WORDS: u8 = 2;
+const MAX_COMPOUND_WORDS: u8 = 4;
+
+/// Allowed syllable count for [`AppendType::Pronounceable`]
+const MIN_SYLLABLES: u8 = 2;
+const MAX_SYLLABLES: u8 = 6;
+/// Letters used to build pronounceable syllables. Letters that are easily misheard or mistyped
+/// (c, q, x, y) are left out.
+const SYLLABLE_CONSONANTS: &[u8] = b"bdfghjklmnprstvwz";
+const SYLLABLE_VOWELS: &[u8] = b"aeiou";
+
 /// Configures the email forwarding service to use.
 /// For instructions on how to configure each service, see the documentation:
 /// <https://bitwarden.com/help/generator/#username-types>
@@ -124,6 +397,9 @@ pub enum ForwarderServiceType {
         api_token: String,
         domain: String,
         base_url: String,
+        /// Mailbox the alias forwards to, the account default is used when unset
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        mailbox_id: Option<String>,
     },
     DuckDuckGo {
         token: String,
@@ -133,6 +409,9 @@ pub enum ForwarderServiceType {
     },
     Fastmail {
         api_token: String,
+        /// Identity that owns the masked email, the account default is used when unset
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        identity_id: Option<String>,
     },
     ForwardEmail {
         api_token: String,
@@ -141,21 +420,51 @@ pub enum ForwarderServiceType {
     SimpleLogin {
         api_key: String,
         base_url: String,
+        /// Note attached to the alias, "Generated by SecureGen" when unset
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        note: Option<String>,
+        /// Directory the alias is created in, premium accounts only
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        directory: Option<String>,
+    },
+    /// Previously known as "Abine Blur"
+    IronVest {
+        api_token: String,
     },
 }
 
 /// Username generator request options
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "PascalCase")]
 pub enum UsernameGeneratorRequest {
     /// Generates a single word username from the EFF word list
     Word {
-        /// Capitalize the first letter of the word
+        /// Capitalize the first letter of the word. Only applies to the `Plain` format, the
+        /// other formats define their own casing.
         capitalize: bool,
-        /// Include a 4 digit number at the end of the word
-        include_number: bool,
+        /// Number appended to the word. Also accepts the legacy `include_number` boolean, where
+        /// `true` appends four digits.
+        #[serde(default, alias = "include_number", deserialize_with = "deserialize_number_suffix")]
+        number_suffix: NumberSuffix,
+        /// Whether the number goes before or after the word. `capitalize` always applies to the
+        /// first letter of the word, so a prefixed number gives e.g. `1234Quiet`.
+        #[serde(default)]
+        number_position: NumberPosition,
         /// Strength level for word selection
         strength: UsernameStrength,
+        /// Casing of the word
+        #[serde(default)]
+        format: UsernameFormat,
+    },
+    /// Generates a username from several EFF words, joined according to `format`
+    CompoundWord {
+        /// Number of words to combine, between 2 and 4
+        word_count: u8,
+        /// Strength level for word selection
+        strength: UsernameStrength,
+        /// How the words are cased and joined
+        #[serde(default)]
+        format: UsernameFormat,
     },
     /// Generates an email using your provider's subaddressing capabilities.
     /// Note that not all providers support this functionality.
@@ -172,8 +481,12 @@ pub enum UsernameGeneratorRequest {
         /// The type of username to use with the catchall email domain
         #[serde(rename = "type")]
         r#type: AppendType,
-        /// The domain to use for the catchall email address
+        /// The domain to use for the catchall email address. Internationalized domains are
+        /// converted to their ASCII compatible (punycode) form before generating.
         domain: String,
+        /// The Unicode form of `domain` for display, only set for internationalized domains
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        original_domain: Option<String>,
     },
     /// Generates an email using a forwarding service
     Forwarded {
@@ -188,21 +501,28 @@ pub enum UsernameGeneratorRequest {
 
 impl UsernameGeneratorRequest {
     /// Validates the request parameters and returns any validation errors
-    fn validate(&self) -> Result<(), UsernameError> {
+    pub(crate) fn validate(&self) -> Result<(), UsernameError> {
         match self {
             UsernameGeneratorRequest::Word { .. } => {
                 // Word generation doesn't need validation
                 Ok(())
             }
+            UsernameGeneratorRequest::CompoundWord { word_count, .. } => {
+                if !(MIN_COMPOUND_WORDS..=MAX_COMPOUND_WORDS).contains(word_count) {
+                    return Err(UsernameError::InvalidWordCount { word_count: *word_count });
+                }
+                Ok(())
+            }
             UsernameGeneratorRequest::Subaddress { r#type, email } => {
-                validate_email(email)?;
+                validate_email(email, true)?;
                 validate_append_type(r#type)?;
                 Ok(())
             }
-            UsernameGeneratorRequest::Catchall { r#type, domain } => {
+            UsernameGeneratorRequest::Catchall { r#type, domain, .. } => {
                 if domain.trim().is_empty() {
                     return Err(UsernameError::EmptyDomain);
                 }
+                normalize_idn_domain(domain)?;
                 validate_append_type(r#type)?;
                 Ok(())
             }
@@ -212,18 +532,264 @@ impl UsernameGeneratorRequest {
             }
         }
     }
+
+    /// Normalizes user entered values: internationalized catchall domains are converted to their
+    /// ASCII compatible form, keeping the Unicode form in `original_domain`, website names are
+    /// sanitized for use in an address, and trailing slashes are stripped from forwarder base URLs.
+    fn normalize(self) -> Result<Self, UsernameError> {
+        match self {
+            UsernameGeneratorRequest::Subaddress { r#type, email } => Ok(UsernameGeneratorRequest::Subaddress {
+                r#type: r#type.normalize(),
+                email,
+            }),
+            UsernameGeneratorRequest::Catchall { r#type, domain, .. } => {
+                let (domain, original_domain) = normalize_idn_domain(&domain)?;
+                Ok(UsernameGeneratorRequest::Catchall {
+                    r#type: r#type.normalize(),
+                    domain,
+                    original_domain,
+                })
+            }
+            UsernameGeneratorRequest::Forwarded { service, website } => Ok(UsernameGeneratorRequest::Forwarded {
+                service: service.normalize(),
+                website,
+            }),
+            other => Ok(other),
+        }
+    }
+
+    /// Returns a copy of the request with forwarder API tokens replaced by a placeholder,
+    /// so it can be stored or exported without leaking credentials
+    pub fn redact_secrets(&self) -> Self {
+        match self {
+            UsernameGeneratorRequest::Forwarded { service, website } => UsernameGeneratorRequest::Forwarded {
+                service: service.redact_secrets(),
+                website: website.clone(),
+            },
+            other => other.clone(),
+        }
+    }
+}
+
+/// Short summary of the request, e.g. `Username(forwarded via SimpleLogin)`. Email addresses are
+/// reduced to their domain and forwarder credentials are left out entirely.
+impl fmt::Display for UsernameGeneratorRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn describe_append_type(r#type: &AppendType) -> String {
+            match r#type {
+                AppendType::Random => "random".to_string(),
+                AppendType::WebsiteName { .. } => "website name".to_string(),
+                AppendType::Pronounceable { syllables } => format!("{} syllables", syllables),
+            }
+        }
+
+        match self {
+            UsernameGeneratorRequest::Word {
+                number_suffix, strength, ..
+            } => match number_suffix.digits() {
+                None => write!(f, "Username(word, {:?} strength)", strength),
+                Some(digits) => write!(f, "Username(word, {:?} strength, {} digit number)", strength, digits),
+            },
+            UsernameGeneratorRequest::CompoundWord {
+                word_count, format, ..
+            } => write!(f, "Username({} words, {:?})", word_count, format),
+            UsernameGeneratorRequest::Subaddress { r#type, email } => {
+                let domain = email.rsplit_once('@').map_or("", |(_, domain)| domain);
+                write!(f, "Username(subaddress at {}, {})", domain, describe_append_type(r#type))
+            }
+            UsernameGeneratorRequest::Catchall {
+                r#type,
+                domain,
+                original_domain,
+            } => write!(
+                f,
+                "Username(catchall at {}, {})",
+                original_domain.as_deref().unwrap_or(domain),
+                describe_append_type(r#type)
+            ),
+            UsernameGeneratorRequest::Forwarded { service, website } => match website {
+                Some(website) => write!(f, "Username(forwarded via {}, for {})", service.name(), website),
+                None => write!(f, "Username(forwarded via {})", service.name()),
+            },
+        }
+    }
+}
+
+/// Placeholder written in place of redacted API tokens
+const REDACTED: &str = "[redacted]";
+
+impl ForwarderServiceType {
+    /// Strips trailing slashes from the base URL of self-hostable services
+    fn normalize(self) -> Self {
+        match self {
+            ForwarderServiceType::AddyIo { api_token, domain, base_url, mailbox_id } => ForwarderServiceType::AddyIo {
+                api_token,
+                domain,
+                base_url: username_forwarders::normalize_base_url(&base_url),
+                mailbox_id,
+            },
+            ForwarderServiceType::SimpleLogin { api_key, base_url, note, directory } => ForwarderServiceType::SimpleLogin {
+                api_key,
+                base_url: username_forwarders::normalize_base_url(&base_url),
+                note,
+                directory,
+            },
+            other => other,
+        }
+    }
+
+    /// Returns a copy of the service configuration with its API token replaced by a placeholder
+    pub fn redact_secrets(&self) -> Self {
+        let redacted = || REDACTED.to_string();
+        match self {
+            ForwarderServiceType::AddyIo { domain, base_url, mailbox_id, .. } => ForwarderServiceType::AddyIo {
+                api_token: redacted(),
+                domain: domain.clone(),
+                base_url: base_url.clone(),
+                mailbox_id: mailbox_id.clone(),
+            },
+            ForwarderServiceType::DuckDuckGo { .. } => ForwarderServiceType::DuckDuckGo { token: redacted() },
+            ForwarderServiceType::Firefox { .. } => ForwarderServiceType::Firefox { api_token: redacted() },
+            ForwarderServiceType::Fastmail { identity_id, .. } => ForwarderServiceType::Fastmail {
+                api_token: redacted(),
+                identity_id: identity_id.clone(),
+            },
+            ForwarderServiceType::ForwardEmail { domain, .. } => ForwarderServiceType::ForwardEmail {
+                api_token: redacted(),
+                domain: domain.clone(),
+            },
+            ForwarderServiceType::SimpleLogin { base_url, note, directory, .. } => ForwarderServiceType::SimpleLogin {
+                api_key: redacted(),
+                base_url: base_url.clone(),
+                note: note.clone(),
+                directory: directory.clone(),
+            },
+            ForwarderServiceType::IronVest { .. } => ForwarderServiceType::IronVest { api_token: redacted() },
+        }
+    }
 }
 
-/// Validates email format
-fn validate_email(email: &str) -> Result<(), UsernameError> {
+/// Validates email format. The basic check only looks for an `@` and a minimum length,
+/// `strict` additionally applies the checks in [`is_valid_email_strict`].
+fn validate_email(email: &str, strict: bool) -> Result<(), UsernameError> {
+    let invalid = || UsernameError::InvalidEmail {
+        email: email.to_string(),
+    };
     if email.trim().is_empty() || !email.contains('@') || email.len() < 5 {
-        return Err(UsernameError::InvalidEmail {
-            email: email.to_string(),
-        });
+        return Err(invalid());
+    }
+    if strict && !is_valid_email_strict(email) {
+        return Err(invalid());
     }
     Ok(())
 }
 
+const MAX_LOCAL_PART_LENGTH: usize = 64;
+const MAX_DOMAIN_LENGTH: usize = 253;
+const MAX_DOMAIN_LABEL_LENGTH: usize = 63;
+
+/// Characters allowed in an unquoted local part besides ASCII alphanumerics and `.` (RFC 5322 atext)
+const LOCAL_PART_SPECIALS: &str = "!#$%&'*+-/=?^_`{|}~";
+
+/// Checks an address the way mail servers do, without supporting quoted local parts,
+/// comments or IP address literals:
+///
+/// - exactly one `@`
+/// - a local part of at most 64 characters, using only letters, digits, `.` and
+///   RFC 5322 specials, without leading, trailing or consecutive dots
+/// - a domain of at most 253 characters with at least one dot, where every label is
+///   1-63 letters, digits or hyphens and doesn't start or end with a hyphen
+/// - a top level domain that isn't purely numeric
+fn is_valid_email_strict(email: &str) -> bool {
+    let Some((local, domain)) = email.split_once('@') else {
+        return false;
+    };
+    if domain.contains('@') {
+        return false;
+    }
+
+    let local_valid = !local.is_empty()
+        && local.len() <= MAX_LOCAL_PART_LENGTH
+        && local.split('.').all(|atom| !atom.is_empty())
+        && local
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || LOCAL_PART_SPECIALS.contains(c));
+    if !local_valid {
+        return false;
+    }
+
+    if domain.len() > MAX_DOMAIN_LENGTH || !domain.contains('.') {
+        return false;
+    }
+    let labels_valid = domain.split('.').all(|label| {
+        !label.is_empty()
+            && label.len() <= MAX_DOMAIN_LABEL_LENGTH
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    });
+    let tld_valid = domain
+        .rsplit('.')
+        .next()
+        .map_or(false, |tld| !tld.chars().all(|c| c.is_ascii_digit()));
+
+    labels_valid && tld_valid
+}
+
+/// Converts `domain` to the form used in generated addresses. Plain ASCII domains are returned
+/// as entered. Unicode and punycode (`xn--`) domains are returned in their ASCII compatible
+/// form along with the Unicode form for display.
+fn normalize_idn_domain(domain: &str) -> Result<(String, Option<String>), UsernameError> {
+    let domain = domain.trim();
+    let ascii = idna::domain_to_ascii(domain).map_err(|_| UsernameError::InvalidDomain {
+        domain: domain.to_string(),
+    })?;
+
+    let is_idn = !domain.is_ascii() || ascii.split('.').any(|label| label.starts_with("xn--"));
+    if !is_idn {
+        return Ok((domain.to_string(), None));
+    }
+
+    let (unicode, result) = idna::domain_to_unicode(&ascii);
+    if result.is_err() {
+        return Err(UsernameError::InvalidDomain {
+            domain: domain.to_string(),
+        });
+    }
+    Ok((ascii, Some(unicode)))
+}
+
+/// Normalizes an email address for comparison. The domain is lowercased, converted to its ASCII
+/// compatible form and stripped of trailing dots. The local part, including any `+` subaddress,
+/// is kept as entered since some providers treat it as case-sensitive.
+pub fn normalize_email(email: &str) -> Result<String, UsernameError> {
+    let email = email.trim();
+    let invalid = || UsernameError::InvalidEmail {
+        email: email.to_string(),
+    };
+    let (local, domain) = email.rsplit_once('@').ok_or_else(invalid)?;
+    let domain = domain.trim_end_matches('.');
+    if local.is_empty() || domain.is_empty() {
+        return Err(invalid());
+    }
+    let ascii = idna::domain_to_ascii(domain).map_err(|_| UsernameError::InvalidDomain {
+        domain: domain.to_string(),
+    })?;
+    Ok(format!("{}@{}", local, ascii))
+}
+
+/// Whether `a` and `b` are the same address once normalized with [`normalize_email`]. Local
+/// parts are compared case-insensitively, which is how nearly every provider delivers mail.
+/// Different `+` subaddresses are different addresses.
+pub fn emails_equivalent(a: &str, b: &str) -> Result<bool, UsernameError> {
+    let (a, b) = (normalize_email(a)?, normalize_email(b)?);
+    let split = |email: &str| -> (String, String) {
+        let (local, domain) = email.rsplit_once('@').expect("normalized addresses contain an @");
+        (local.to_lowercase(), domain.to_string())
+    };
+    Ok(split(&a) == split(&b))
+}
+
 /// Validates append type
 fn validate_append_type(append_type: &AppendType) -> Result<(), UsernameError> {
     match append_type {
@@ -232,6 +798,22 @@ fn validate_append_type(append_type: &AppendType) -> Result<(), UsernameError> {
             if website.trim().is_empty() {
                 return Err(UsernameError::EmptyWebsiteName);
             }
+            if let Some(c) = website.chars().find(|c| FORBIDDEN_WEBSITE_NAME_CHARS.contains(c)) {
+                return Err(UsernameError::InvalidWebsiteName {
+                    reason: format!("must not contain {:?}", c),
+                });
+            }
+            if sanitize_website_name(website).is_empty() {
+                return Err(UsernameError::InvalidWebsiteName {
+                    reason: "must contain a letter, digit, '-', '.' or '_'".to_string(),
+                });
+            }
+            Ok(())
+        }
+        AppendType::Pronounceable { syllables } => {
+            if !(MIN_SYLLABLES..=MAX_SYLLABLES).contains(syllables) {
+                return Err(UsernameError::InvalidSyllableCount { syllables: *syllables });
+            }
             Ok(())
         }
     }
@@ -240,12 +822,13 @@ fn validate_append_type(append_type: &AppendType) -> Result<(), UsernameError> {
 /// Validates forwarder service configuration
 fn validate_forwarder_service(service: &ForwarderServiceType) -> Result<(), UsernameError> {
     match service {
-        ForwarderServiceType::AddyIo { api_token, domain, .. } => {
-            if api_token.trim().is_empty() || domain.trim().is_empty() {
+        ForwarderServiceType::AddyIo { api_token, domain, base_url, .. } => {
+            if api_token.trim().is_empty() || domain.trim().is_empty() || base_url.trim().is_empty() {
                 return Err(UsernameError::IncompleteApiConfig {
                     service: "AddyIo".to_string(),
                 });
             }
+            validate_base_url(base_url, "AddyIo")?;
         }
         ForwarderServiceType::DuckDuckGo { token } => {
             if token.trim().is_empty() {
@@ -261,8 +844,8 @@ fn validate_forwarder_service(service: &ForwarderServiceType) -> Result<(), User
                 });
             }
         }
-        ForwarderServiceType::Fastmail { api_token } => {
-            if api_token.trim().is_empty() {
+        ForwarderServiceType::Fastmail { api_token, identity_id } => {
+            if api_token.trim().is_empty() || identity_id.as_deref().map_or(false, |id| id.trim().is_empty()) {
                 return Err(UsernameError::IncompleteApiConfig {
                     service: "Fastmail".to_string(),
                 });
@@ -275,37 +858,112 @@ fn validate_forwarder_service(service: &ForwarderServiceType) -> Result<(), User
                 });
             }
         }
-        ForwarderServiceType::SimpleLogin { api_key, .. } => {
-            if api_key.trim().is_empty() {
+        ForwarderServiceType::SimpleLogin { api_key, base_url, .. } => {
+            if api_key.trim().is_empty() || base_url.trim().is_empty() {
                 return Err(UsernameError::IncompleteApiConfig {
                     service: "SimpleLogin".to_string(),
                 });
             }
+            validate_base_url(base_url, "SimpleLogin")?;
+        }
+        ForwarderServiceType::IronVest { api_token } => {
+            if api_token.trim().is_empty() {
+                return Err(UsernameError::IncompleteApiConfig {
+                    service: "IronVest".to_string(),
+                });
+            }
         }
     }
     Ok(())
 }
 
+/// Requires self-hosted forwarder URLs to use HTTPS, so API tokens are never sent in plaintext.
+/// Plain HTTP is only accepted for loopback hosts, where traffic never leaves the machine.
+pub(crate) fn validate_base_url(base_url: &str, service: &str) -> Result<(), UsernameError> {
+    let insecure = || UsernameError::InsecureBaseUrl {
+        service: service.to_string(),
+    };
+    let base_url = base_url.trim();
+
+    if base_url.len() >= 8 && base_url[..8].eq_ignore_ascii_case("https://") {
+        return Ok(());
+    }
+
+    let url = reqwest::Url::parse(base_url).map_err(|_| insecure())?;
+    let is_loopback = match url.host_str() {
+        Some(host) => {
+            host.eq_ignore_ascii_case("localhost")
+                || host
+                    .trim_start_matches('[')
+                    .trim_end_matches(']')
+                    .parse::<std::net::IpAddr>()
+                    .map(|ip| ip.is_loopback())
+                    .unwrap_or(false)
+        }
+        None => false,
+    };
+    if url.scheme() == "http" && is_loopback {
+        Ok(())
+    } else {
+        Err(insecure())
+    }
+}
+
 /// Implementation of the username generator.
 ///
 /// Note: The HTTP client is passed in as a required parameter for convenience,
 /// as some username generators require making API calls.
+///
+/// # Examples
+///
+/// Run as `tests::test_generate_username_examples`.
+///
+/// ```ignore
+/// use crate::generators::username::{generate_username, AppendType, UsernameGeneratorRequest};
+///
+/// # tokio_test::block_on(async {
+/// let http = reqwest::Client::new();
+/// let request = UsernameGeneratorRequest::Catchall {
+///     r#type: AppendType::Random,
+///     domain: "example.com".to_string(),
+///     original_domain: None,
+/// };
+/// let username = generate_username(request, &http).await.unwrap();
+/// assert!(username.ends_with("@example.com"));
+///
+/// let request = UsernameGeneratorRequest::Catchall {
+///     r#type: AppendType::Random,
+///     domain: String::new(),
+///     original_domain: None,
+/// };
+/// assert!(generate_username(request, &http).await.is_err());
+/// # });
+/// ```
+#[tracing::instrument(skip_all)]
 pub async fn generate_username(
     input: UsernameGeneratorRequest,
     http: &reqwest::Client,
-) -> Result<String, UsernameError> {
+) -> Result<String, GeneratorError> {
+    Ok(username(input, http).await?)
+}
+
+async fn username(input: UsernameGeneratorRequest, http: &reqwest::Client) -> Result<String, UsernameError> {
     // Validate input first
     input.validate()?;
+    let input = input.normalize()?;
     
     use rand::thread_rng;
     match input {
-        UsernameGeneratorRequest::Word { capitalize, include_number, strength } => {
-            Ok(username_word(&mut thread_rng(), capitalize, include_number, strength))
+        UsernameGeneratorRequest::Word { capitalize, number_suffix, number_position, strength, format } => {
+            Ok(username_word(&mut thread_rng(), capitalize, number_suffix, number_position, strength, format))
+        }
+        UsernameGeneratorRequest::CompoundWord { word_count, strength, format } => {
+            Ok(username_compound_word(&mut thread_rng(), word_count, strength, format))
         }
         UsernameGeneratorRequest::Subaddress { r#type, email } => {
             Ok(username_subaddress(&mut thread_rng(), r#type, email))
         }
-        UsernameGeneratorRequest::Catchall { r#type, domain } => {
+        UsernameGeneratorRequest::Catchall { r#type, domain, .. } => {
             Ok(username_catchall(&mut thread_rng(), r#type, domain))
         }
         UsernameGeneratorRequest::Forwarded { service, website } => {
@@ -317,14 +975,15 @@ pub async fn generate_username(
 impl ForwarderServiceType {
     /// Generate a username using the specified email forwarding service
     /// This requires an HTTP client to be passed in, as the service will need to make API calls
+    #[tracing::instrument(skip_all, fields(service = self.name()))]
     pub async fn generate(
         self,
         http: &reqwest::Client,
         website: Option<String>,
     ) -> Result<String, UsernameError> {
         match self {
-            ForwarderServiceType::AddyIo { api_token, domain, base_url } => {
-                username_forwarders::addyio::generate(http, api_token, domain, base_url, website).await
+            ForwarderServiceType::AddyIo { api_token, domain, base_url, mailbox_id } => {
+                username_forwarders::addyio::generate(http, api_token, domain, base_url, mailbox_id, website).await
             }
             ForwarderServiceType::DuckDuckGo { token } => {
                 username_forwarders::duckduckgo::generate(http, token).await
@@ -332,51 +991,184 @@ impl ForwarderServiceType {
             ForwarderServiceType::Firefox { api_token } => {
                 username_forwarders::firefox::generate(http, api_token, website).await
             }
-            ForwarderServiceType::Fastmail { api_token } => {
-                username_forwarders::fastmail::generate(http, api_token, website).await
+            ForwarderServiceType::Fastmail { api_token, identity_id } => {
+                username_forwarders::fastmail::generate(http, api_token, identity_id, website).await
             }
             ForwarderServiceType::ForwardEmail { api_token, domain } => {
                 username_forwarders::forwardemail::generate(http, api_token, domain, website).await
             }
-            ForwarderServiceType::SimpleLogin { api_key, base_url } => {
-                username_forwarders::simplelogin::generate(http, api_key, base_url, website).await
+            ForwarderServiceType::SimpleLogin { api_key, base_url, note, directory } => {
+                username_forwarders::simplelogin::generate(http, api_key, base_url, note, directory, website).await
+            }
+            ForwarderServiceType::IronVest { api_token } => {
+                username_forwarders::ironvest::generate(http, api_token).await
             }
         }
     }
+
+    /// Deactivate an existing alias so it stops forwarding mail. Only supported by services
+    /// whose API allows disabling aliases.
+    pub async fn deactivate_alias(self, http: &reqwest::Client, alias_id: String) -> Result<(), UsernameError> {
+        validate_forwarder_service(&self)?;
+        match self {
+            ForwarderServiceType::AddyIo { api_token, base_url, .. } => {
+                username_forwarders::addyio::deactivate_alias(http, api_token, base_url, alias_id).await
+            }
+            ForwarderServiceType::SimpleLogin { api_key, base_url, .. } => {
+                username_forwarders::simplelogin::deactivate_alias(http, api_key, base_url, alias_id).await
+            }
+            other => Err(UsernameError::UnsupportedOperation {
+                service: other.name().to_string(),
+            }),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            ForwarderServiceType::AddyIo { .. } => "AddyIo",
+            ForwarderServiceType::DuckDuckGo { .. } => "DuckDuckGo",
+            ForwarderServiceType::Firefox { .. } => "Firefox",
+            ForwarderServiceType::Fastmail { .. } => "Fastmail",
+            ForwarderServiceType::ForwardEmail { .. } => "ForwardEmail",
+            ForwarderServiceType::SimpleLogin { .. } => "SimpleLogin",
+            ForwarderServiceType::IronVest { .. } => "IronVest",
+        }
+    }
+}
+
+/// Compares the service configuration, with API tokens only compared for emptiness. Comparing
+/// secret values would leak their contents through timing, and two requests that only differ in
+/// the token describe the same alias setup.
+impl PartialEq for ForwarderServiceType {
+    fn eq(&self, other: &Self) -> bool {
+        use ForwarderServiceType::*;
+        match (self, other) {
+            (
+                AddyIo { api_token, domain, base_url, mailbox_id },
+                AddyIo {
+                    api_token: other_token,
+                    domain: other_domain,
+                    base_url: other_base_url,
+                    mailbox_id: other_mailbox_id,
+                },
+            ) => {
+                api_token.is_empty() == other_token.is_empty()
+                    && domain == other_domain
+                    && base_url == other_base_url
+                    && mailbox_id == other_mailbox_id
+            }
+            (DuckDuckGo { token }, DuckDuckGo { token: other_token }) => token.is_empty() == other_token.is_empty(),
+            (Firefox { api_token }, Firefox { api_token: other_token }) => {
+                api_token.is_empty() == other_token.is_empty()
+            }
+            (
+                Fastmail { api_token, identity_id },
+                Fastmail {
+                    api_token: other_token,
+                    identity_id: other_identity_id,
+                },
+            ) => api_token.is_empty() == other_token.is_empty() && identity_id == other_identity_id,
+            (
+                ForwardEmail { api_token, domain },
+                ForwardEmail {
+                    api_token: other_token,
+                    domain: other_domain,
+                },
+            ) => api_token.is_empty() == other_token.is_empty() && domain == other_domain,
+            (
+                SimpleLogin { api_key, base_url, note, directory },
+                SimpleLogin {
+                    api_key: other_key,
+                    base_url: other_base_url,
+                    note: other_note,
+                    directory: other_directory,
+                },
+            ) => {
+                api_key.is_empty() == other_key.is_empty()
+                    && base_url == other_base_url
+                    && note == other_note
+                    && directory == other_directory
+            }
+            (IronVest { api_token }, IronVest { api_token: other_token }) => {
+                api_token.is_empty() == other_token.is_empty()
+            }
+            _ => false,
+        }
+    }
 }
 
 /// Generate a word-based username
-fn username_word(mut rng: impl Rng, capitalize: bool, include_number: bool, strength: UsernameStrength) -> String {
-    let filtered_words = strength.filter_words();
-    
-    // Fallback to full list if no words match the criteria (shouldn't happen with current strength levels)
-    let word_list = if filtered_words.is_empty() {
-        EFF_LONG_WORD_LIST
-    } else {
-        filtered_words.as_slice()
-    };
-    
-    let word = word_list
-        .choose(&mut rng)
-        .expect("word list is not empty");
+pub(crate) fn username_word(
+    mut rng: impl Rng,
+    capitalize: bool,
+    number_suffix: NumberSuffix,
+    number_position: NumberPosition,
+    strength: UsernameStrength,
+    format: UsernameFormat,
+) -> String {
+    let word = choose_word(&mut rng, &strength);
 
-    let mut word = if capitalize {
+    let word = if capitalize && format == UsernameFormat::Plain {
         capitalize_first_letter(word)
     } else {
-        word.to_string()
+        apply_format(&[word], format)
     };
 
-    if include_number {
-        word.push_str(&random_number(&mut rng));
+    match number_suffix.digits() {
+        Some(digits) => {
+            let number = random_number(&mut rng, digits);
+            match number_position {
+                NumberPosition::Prefix => number + &word,
+                NumberPosition::Suffix => word + &number,
+            }
+        }
+        None => word,
     }
+}
+
+/// Generate a username from `word_count` words joined according to `format`
+pub(crate) fn username_compound_word(mut rng: impl Rng, word_count: u8, strength: UsernameStrength, format: UsernameFormat) -> String {
+    let words: Vec<&str> = (0..word_count).map(|_| choose_word(&mut rng, &strength)).collect();
+    apply_format(&words, format)
+}
+
+/// Pick a random word matching the strength level
+fn choose_word(mut rng: impl Rng, strength: &UsernameStrength) -> &'static str {
+    let filtered_words = strength.filter_words();
+
+    // Fallback to full list if no words match the criteria (shouldn't happen with current strength levels)
+    let word_list = if filtered_words.is_empty() {
+        EFF_LONG_WORD_LIST
+    } else {
+        filtered_words
+    };
 
-    word
+    word_list.choose(&mut rng).expect("word list is not empty")
 }
 
-/// Generate a random 4 digit number, including leading zeros
-fn random_number(mut rng: impl Rng) -> String {
-    let num = rng.gen_range(0..=9999);
-    format!("{num:0>4}")
+/// Case and join words according to `format`
+fn apply_format(words: &[&str], format: UsernameFormat) -> String {
+    match format {
+        UsernameFormat::Plain => words.concat(),
+        UsernameFormat::SnakeCase => words.iter().map(|w| w.to_lowercase()).collect::<Vec<_>>().join("_"),
+        UsernameFormat::KebabCase => words.iter().map(|w| w.to_lowercase()).collect::<Vec<_>>().join("-"),
+        UsernameFormat::CamelCase => words
+            .iter()
+            .enumerate()
+            .map(|(i, w)| {
+                let w = w.to_lowercase();
+                if i == 0 {
+                    w
+                } else {
+                    capitalize_first_letter(&w)
+                }
+            })
+            .collect(),
+        UsernameFormat::PascalCase => words
+            .iter()
+            .map(|w| capitalize_first_letter(&w.to_lowercase()))
+            .collect(),
+    }
 }
 
 /// Generate a username using a plus addressed email address
@@ -389,6 +1181,7 @@ fn username_subaddress(mut rng: impl Rng, r#type: AppendType, email: String) ->
     let email_middle = match r#type {
         AppendType::Random => random_lowercase_string(&mut rng, 8),
         AppendType::WebsiteName { website } => website,
+        AppendType::Pronounceable { syllables } => pronounceable_string(&mut rng, syllables),
     };
 
     format!("{}+{}@{}", email_begin, email_middle, email_end)
@@ -402,6 +1195,7 @@ fn username_catchall(mut rng: impl Rng, r#type: AppendType, domain: String) -> S
     let email_start = match r#type {
         AppendType::Random => random_lowercase_string(&mut rng, 8),
         AppendType::WebsiteName { website } => website,
+        AppendType::Pronounceable { syllables } => pronounceable_string(&mut rng, syllables),
     };
 
     format!("{}@{}", email_start, domain)
@@ -418,6 +1212,16 @@ fn random_lowercase_string(mut rng: impl Rng, length: usize) -> String {
         .collect()
 }
 
+/// Generate a lowercase string of consonant-vowel syllables
+fn pronounceable_string(mut rng: impl Rng, syllables: u8) -> String {
+    let consonants = rand::distributions::Slice::new(SYLLABLE_CONSONANTS).expect("Non-empty slice");
+    let vowels = rand::distributions::Slice::new(SYLLABLE_VOWELS).expect("Non-empty slice");
+
+    (0..syllables)
+        .flat_map(|_| [*rng.sample(consonants) as char, *rng.sample(vowels) as char])
+        .collect()
+}
+
 /// Capitalize the first letter of a string
 fn capitalize_first_letter(s: &str) -> String {
     let mut chars = s.chars();
@@ -432,43 +1236,304 @@ mod tests {
     use super::*;
     use rand::SeedableRng;
 
+    #[tokio::test]
+    async fn test_reqwest_error_classification() {
+        use wiremock::{matchers::method, Mock, MockServer, ResponseTemplate};
+
+        // Timeouts
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_delay(std::time::Duration::from_secs(2)))
+            .mount(&server)
+            .await;
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_millis(100))
+            .build()
+            .unwrap();
+        let error = client.get(server.uri()).send().await.unwrap_err();
+        assert!(matches!(UsernameError::from(error), UsernameError::Timeout));
+
+        // Nothing listening on the port
+        let closed_port = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap().port()
+        };
+        let error = reqwest::get(format!("http://127.0.0.1:{}", closed_port)).await.unwrap_err();
+        assert!(matches!(UsernameError::from(error), UsernameError::ServiceUnavailable));
+
+        // `.invalid` is reserved and never resolves
+        let error = reqwest::get("http://forwarder.invalid").await.unwrap_err();
+        assert!(matches!(UsernameError::from(error), UsernameError::DnsFailure));
+
+        // Other failures remain HTTP errors
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&server)
+            .await;
+        let error = reqwest::get(server.uri()).await.unwrap().error_for_status().unwrap_err();
+        assert!(matches!(UsernameError::from(error), UsernameError::Http(_)));
+    }
+
+    #[test]
+    fn test_error_codes_are_stable() {
+        // These codes are part of the frontend contract, never change an existing entry
+        let expected = [
+            (UsernameError::InvalidApiKey, 1001),
+            (UsernameError::RateLimitExceeded, 1002),
+            (UsernameError::DomainRejected, 1003),
+            (UsernameError::Unknown, 1005),
+            (
+                UsernameError::ResponseContent {
+                    status: reqwest::StatusCode::BAD_REQUEST,
+                    message: "bad".to_string(),
+                },
+                1006,
+            ),
+            (UsernameError::InvalidEmail { email: "x".to_string() }, 1007),
+            (UsernameError::EmptyDomain, 1008),
+            (UsernameError::EmptyWebsiteName, 1009),
+            (UsernameError::IncompleteApiConfig { service: "AddyIo".to_string() }, 1010),
+            (UsernameError::Timeout, 1011),
+            (UsernameError::ServiceUnavailable, 1012),
+            (UsernameError::DnsFailure, 1013),
+            (UsernameError::Cancelled, 1014),
+            (UsernameError::InvalidDomain { domain: "x".to_string() }, 1015),
+            (UsernameError::InsecureBaseUrl { service: "AddyIo".to_string() }, 1016),
+            (UsernameError::AliasNotFound, 1017),
+            (UsernameError::UnsupportedOperation { service: "Firefox".to_string() }, 1018),
+            (UsernameError::InvalidIdentity { identity_id: "x".to_string() }, 1019),
+            (UsernameError::InvalidSyllableCount { syllables: 9 }, 1020),
+            (UsernameError::InvalidWordCount { word_count: 9 }, 1021),
+            (UsernameError::PermissionDenied, 1022),
+            (UsernameError::DuplicateUsername { attempts: 11 }, 1023),
+            (UsernameError::InvalidWebsiteName { reason: "x".to_string() }, 1024),
+            (UsernameError::EmptyResponse { service: "AddyIo".to_string() }, 1025),
+            (UsernameError::LocalRateLimitExceeded { message: "x".to_string() }, 1026),
+        ];
+        for (error, code) in expected {
+            assert_eq!(error.code() as u16, code, "Code changed for {:?}", error);
+        }
+        assert_eq!(UsernameErrorCode::Http as u16, 1004);
+    }
+
+    #[tokio::test]
+    async fn test_serializable_error_from_username_error() {
+        let error = SerializableError::from(UsernameError::RateLimitExceeded);
+        assert_eq!(error.code, 1002);
+        assert_eq!(error.message, "Rate limit exceeded - please wait before making more requests");
+        assert_eq!(error.details, None);
+
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!(json, serde_json::json!({
+            "code": 1002,
+            "message": "Rate limit exceeded - please wait before making more requests",
+            "details": null
+        }));
+
+        // HTTP errors carry the underlying cause as details
+        let http = reqwest::get("http://forwarder.invalid").await.unwrap_err();
+        let error = SerializableError::from(UsernameError::Http(http));
+        assert_eq!(error.code, 1004);
+        assert!(error.details.is_some());
+
+        // Generator errors wrapping a username error keep its code
+        let error = SerializableError::from(GeneratorError::from(UsernameError::InvalidApiKey));
+        assert_eq!(error.code, 1001);
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        assert!(UsernameError::Timeout.is_retryable());
+        assert!(UsernameError::ServiceUnavailable.is_retryable());
+        assert!(UsernameError::EmptyResponse { service: "IronVest".to_string() }.is_retryable());
+
+        assert!(!UsernameError::InvalidApiKey.is_retryable());
+        assert!(!UsernameError::DomainRejected.is_retryable());
+        assert!(!UsernameError::RateLimitExceeded.is_retryable());
+        assert!(!UsernameError::DnsFailure.is_retryable());
+        assert!(!UsernameError::EmptyDomain.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn test_with_retries() {
+        use std::sync::atomic::{AtomicU8, Ordering};
+
+        let attempts = AtomicU8::new(0);
+        let result: Result<(), _> = username_forwarders::with_retries(|| {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            async { Err(UsernameError::ServiceUnavailable) }
+        })
+        .await;
+        assert!(matches!(result, Err(UsernameError::ServiceUnavailable)));
+        assert_eq!(attempts.load(Ordering::SeqCst), username_forwarders::MAX_REQUEST_ATTEMPTS);
+
+        // Only transient failures are retried
+        let attempts = AtomicU8::new(0);
+        let result: Result<(), _> = username_forwarders::with_retries(|| {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            async { Err(UsernameError::InvalidApiKey) }
+        })
+        .await;
+        assert!(matches!(result, Err(UsernameError::InvalidApiKey)));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+
+        let attempts = AtomicU8::new(0);
+        let result = username_forwarders::with_retries(|| {
+            let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+            async move {
+                match attempt {
+                    0 => Err(UsernameError::Timeout),
+                    1 => Err(UsernameError::EmptyResponse { service: "AddyIo".to_string() }),
+                    _ => Ok("alias@example.com"),
+                }
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), "alias@example.com");
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_redact_secrets() {
+        let request = UsernameGeneratorRequest::Forwarded {
+            service: ForwarderServiceType::AddyIo {
+                api_token: "secret-token".to_string(),
+                domain: "example.com".to_string(),
+                base_url: "https://app.addy.io".to_string(),
+                mailbox_id: None,
+            },
+            website: Some("github.com".to_string()),
+        };
+
+        let json = serde_json::to_string(&request.redact_secrets()).unwrap();
+        assert!(!json.contains("secret-token"));
+        assert!(json.contains(REDACTED));
+        assert!(json.contains("example.com"));
+        assert!(json.contains("github.com"));
+
+        // Requests without secrets are unchanged
+        let word = UsernameGeneratorRequest::Word {
+            capitalize: true,
+            number_suffix: NumberSuffix::None,
+            number_position: NumberPosition::Suffix,
+            strength: UsernameStrength::Standard,
+            format: UsernameFormat::Plain,
+        };
+        assert_eq!(
+            serde_json::to_string(&word.redact_secrets()).unwrap(),
+            serde_json::to_string(&word).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_generate_username_examples() {
+        let http = reqwest::Client::new();
+        let request = UsernameGeneratorRequest::Catchall {
+            r#type: AppendType::Random,
+            domain: "example.com".to_string(),
+            original_domain: None,
+        };
+        let username = generate_username(request, &http).await.unwrap();
+        assert!(username.ends_with("@example.com"));
+
+        let request = UsernameGeneratorRequest::Catchall {
+            r#type: AppendType::Random,
+            domain: String::new(),
+            original_domain: None,
+        };
+        assert!(generate_username(request, &http).await.is_err());
+    }
+
     #[test]
     fn test_username_word() {
         let mut rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
         
         // Test basic word generation
-        let username = username_word(&mut rng, false, false, UsernameStrength::Standard);
+        let username = username_word(&mut rng, false, NumberSuffix::None, NumberPosition::Suffix, UsernameStrength::Standard, UsernameFormat::Plain);
         assert!(!username.is_empty());
         assert!(username.chars().all(|c| c.is_ascii_lowercase()));
         
         // Test capitalized word
-        let username_cap = username_word(&mut rng, true, false, UsernameStrength::Standard);
+        let username_cap = username_word(&mut rng, true, NumberSuffix::None, NumberPosition::Suffix, UsernameStrength::Standard, UsernameFormat::Plain);
         assert!(username_cap.chars().next().unwrap().is_ascii_uppercase());
         
         // Test with numbers
-        let username_num = username_word(&mut rng, false, true, UsernameStrength::Standard);
+        let username_num = username_word(&mut rng, false, NumberSuffix::FourDigits, NumberPosition::Suffix, UsernameStrength::Standard, UsernameFormat::Plain);
         assert!(username_num.len() > 4); // Should have word + 4 digit number
         assert!(username_num.chars().rev().take(4).all(|c| c.is_ascii_digit()));
     }
 
+    #[test]
+    fn test_apply_format() {
+        let single = ["quiet"];
+        let compound = ["quiet", "River", "stone"];
+        let cases = [
+            (UsernameFormat::Plain, "quiet", "quietRiverstone"),
+            (UsernameFormat::SnakeCase, "quiet", "quiet_river_stone"),
+            (UsernameFormat::KebabCase, "quiet", "quiet-river-stone"),
+            (UsernameFormat::CamelCase, "quiet", "quietRiverStone"),
+            (UsernameFormat::PascalCase, "Quiet", "QuietRiverStone"),
+        ];
+        for (format, expected_single, expected_compound) in cases {
+            assert_eq!(apply_format(&single, format), expected_single, "{:?}", format);
+            assert_eq!(apply_format(&compound, format), expected_compound, "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn test_username_word_format() {
+        let mut rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
+
+        // Formats other than Plain define their own casing and ignore `capitalize`
+        let pascal = username_word(&mut rng, false, NumberSuffix::None, NumberPosition::Suffix, UsernameStrength::Standard, UsernameFormat::PascalCase);
+        assert!(pascal.chars().next().unwrap().is_ascii_uppercase());
+        let camel = username_word(&mut rng, true, NumberSuffix::None, NumberPosition::Suffix, UsernameStrength::Standard, UsernameFormat::CamelCase);
+        assert!(camel.chars().all(|c| c.is_ascii_lowercase()));
+    }
+
+    #[test]
+    fn test_username_compound_word() {
+        let mut rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
+
+        let snake = username_compound_word(&mut rng, 3, UsernameStrength::Standard, UsernameFormat::SnakeCase);
+        assert_eq!(snake.split('_').count(), 3);
+        assert!(snake.split('_').all(|w| w.len() >= 5 && w.len() <= 6));
+
+        let kebab = username_compound_word(&mut rng, 2, UsernameStrength::Basic, UsernameFormat::KebabCase);
+        assert_eq!(kebab.split('-').count(), 2);
+
+        let pascal = username_compound_word(&mut rng, 2, UsernameStrength::Standard, UsernameFormat::PascalCase);
+        assert_eq!(pascal.chars().filter(|c| c.is_ascii_uppercase()).count(), 2);
+
+        for word_count in [0, 1, 5] {
+            let request = UsernameGeneratorRequest::CompoundWord {
+                word_count,
+                strength: UsernameStrength::Standard,
+                format: UsernameFormat::Plain,
+            };
+            assert!(matches!(request.validate(), Err(UsernameError::InvalidWordCount { .. })));
+        }
+    }
+
     #[test]
     fn test_username_strength_levels() {
         let mut rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
         
         // Test basic strength (3-4 characters)
-        let basic_username = username_word(&mut rng, false, false, UsernameStrength::Basic);
+        let basic_username = username_word(&mut rng, false, NumberSuffix::None, NumberPosition::Suffix, UsernameStrength::Basic, UsernameFormat::Plain);
         assert!(basic_username.len() >= 3 && basic_username.len() <= 4);
         
         // Test standard strength (5-6 characters)
-        let standard_username = username_word(&mut rng, false, false, UsernameStrength::Standard);
+        let standard_username = username_word(&mut rng, false, NumberSuffix::None, NumberPosition::Suffix, UsernameStrength::Standard, UsernameFormat::Plain);
         assert!(standard_username.len() >= 5 && standard_username.len() <= 6);
         
         // Test strong strength (7-8 characters)
-        let strong_username = username_word(&mut rng, false, false, UsernameStrength::Strong);
+        let strong_username = username_word(&mut rng, false, NumberSuffix::None, NumberPosition::Suffix, UsernameStrength::Strong, UsernameFormat::Plain);
         assert!(strong_username.len() >= 7 && strong_username.len() <= 8);
         
         // Test maximum strength (9+ characters)
-        let max_username = username_word(&mut rng, false, false, UsernameStrength::Maximum);
+        let max_username = username_word(&mut rng, false, NumberSuffix::None, NumberPosition::Suffix, UsernameStrength::Maximum, UsernameFormat::Plain);
         assert!(max_username.len() >= 9);
     }
 
@@ -493,6 +1558,156 @@ mod tests {
         // before this function is called, so we don't test invalid inputs here
     }
 
+    fn assert_strict(valid: bool, emails: &[&str]) {
+        for email in emails {
+            assert_eq!(
+                validate_email(email, true).is_ok(),
+                valid,
+                "Expected '{}' to be {}",
+                email,
+                if valid { "valid" } else { "invalid" }
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_email_strict_accepts_common_addresses() {
+        assert_strict(
+            true,
+            &[
+                "test@example.com",
+                "first.last@example.com",
+                "user+tag@example.co.uk",
+                "user_name@sub.domain.example.org",
+                "x@example.io",
+                "1234@example.com",
+                "user@my-domain.com",
+                "user@xn--bcher-kva.example",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_validate_email_strict_accepts_rfc_specials_in_local_part() {
+        assert_strict(
+            true,
+            &[
+                "o'brien@example.com",
+                "user!def@example.com",
+                "a#b$c%d&e@example.com",
+                "user=x?y^z@example.com",
+                "{user}|`~@example.com",
+                "first-last/dept@example.com",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_validate_email_strict_rejects_bad_domains() {
+        assert_strict(
+            false,
+            &[
+                "a@b",
+                "test@.com",
+                "test@example.",
+                "test@example..com",
+                "test@localhost",
+                "test@-example.com",
+                "test@example-.com",
+                "test@exa_mple.com",
+                "test@exam ple.com",
+                "test@192.168.1.1",
+                "test@[192.168.1.1]",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_validate_email_strict_rejects_bad_local_parts() {
+        assert_strict(
+            false,
+            &[
+                "@example.com",
+                ".test@example.com",
+                "test.@example.com",
+                "te..st@example.com",
+                "te st@example.com",
+                "\"quoted\"@example.com",
+                "a(comment)@example.com",
+                "a,b@example.com",
+                "a:b@example.com",
+                "a;b@example.com",
+                "<a>@example.com",
+                "a\\b@example.com",
+                "a[b]@example.com",
+                "tést@example.com",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_validate_email_strict_rejects_multiple_at_signs() {
+        assert_strict(false, &["a@b@example.com", "test@@example.com", "test@example.com@"]);
+    }
+
+    #[test]
+    fn test_validate_email_strict_length_limits() {
+        let local_64 = "a".repeat(64);
+        let local_65 = "a".repeat(65);
+        let label_63 = "b".repeat(63);
+        let label_64 = "b".repeat(64);
+        // Three 61 character labels, one 63 character label, the dots and ".com" make 253
+        let domain_253 = format!("{}.{}.com", vec!["c".repeat(61); 3].join("."), "c".repeat(63));
+        let domain_254 = format!("d{}", domain_253);
+        assert_eq!(domain_253.len(), 253);
+
+        assert_strict(
+            true,
+            &[
+                &format!("{}@example.com", local_64),
+                &format!("user@{}.com", label_63),
+                &format!("user@{}", domain_253),
+            ],
+        );
+        assert_strict(
+            false,
+            &[
+                &format!("{}@example.com", local_65),
+                &format!("user@{}.com", label_64),
+                &format!("user@{}", domain_254),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_validate_email_basic_mode_is_unchanged() {
+        // Without strict checks, only the presence of '@' and the minimum length matter
+        assert!(validate_email("a@b.c", false).is_ok());
+        assert!(validate_email("test@.com", false).is_ok());
+        assert!(validate_email("a@bcd", false).is_ok());
+        assert!(validate_email("a@b", false).is_err());
+        assert!(validate_email("abcdef", false).is_err());
+        assert!(validate_email("   ", false).is_err());
+    }
+
+    #[test]
+    fn test_subaddress_request_uses_strict_validation() {
+        let request = |email: &str| UsernameGeneratorRequest::Subaddress {
+            r#type: AppendType::Random,
+            email: email.to_string(),
+        };
+
+        assert!(request("test@example.com").validate().is_ok());
+        assert!(matches!(
+            request("test@.com").validate(),
+            Err(UsernameError::InvalidEmail { .. })
+        ));
+        assert!(matches!(
+            request("user@localhost").validate(),
+            Err(UsernameError::InvalidEmail { .. })
+        ));
+    }
+
     #[test]
     fn test_username_catchall() {
         let mut rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
@@ -514,18 +1729,381 @@ mod tests {
         // before this function is called, so we don't test invalid inputs here
     }
 
+    #[test]
+    fn test_username_catchall_pronounceable() {
+        for seed in 0..16u8 {
+            let mut rng = rand_chacha::ChaCha8Rng::from_seed([seed; 32]);
+            for syllables in MIN_SYLLABLES..=MAX_SYLLABLES {
+                let username = username_catchall(
+                    &mut rng,
+                    AppendType::Pronounceable { syllables },
+                    "domain.com".to_string(),
+                );
+                let (local, domain) = username.split_once('@').unwrap();
+                assert_eq!(domain, "domain.com");
+                assert_eq!(local.len(), syllables as usize * 2);
+                assert!(local.bytes().all(|b| b.is_ascii_lowercase()), "{}", local);
+
+                let bytes = local.as_bytes();
+                for pair in bytes.chunks(2) {
+                    assert!(SYLLABLE_CONSONANTS.contains(&pair[0]));
+                    assert!(SYLLABLE_VOWELS.contains(&pair[1]));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_website_name_injection_rejected() {
+        for website in [
+            "evil.com\r\nBcc: attacker@evil.com",
+            "evil.com\nBcc",
+            "attacker@evil.com",
+            "evil.com:25",
+            "a;b",
+        ] {
+            assert!(
+                matches!(
+                    validate_append_type(&AppendType::WebsiteName { website: website.to_string() }),
+                    Err(UsernameError::InvalidWebsiteName { .. })
+                ),
+                "{:?} should be rejected",
+                website
+            );
+        }
+        // Nothing usable left after sanitizing
+        assert!(matches!(
+            validate_append_type(&AppendType::WebsiteName { website: "!!! ???".to_string() }),
+            Err(UsernameError::InvalidWebsiteName { .. })
+        ));
+        assert!(matches!(
+            validate_append_type(&AppendType::WebsiteName { website: "  ".to_string() }),
+            Err(UsernameError::EmptyWebsiteName)
+        ));
+    }
+
+    #[test]
+    fn test_website_name_sanitized() {
+        assert_eq!(sanitize_website_name("github"), "github");
+        assert_eq!(sanitize_website_name("GitHub.com"), "github.com");
+        assert_eq!(sanitize_website_name("my_site-2.example"), "my_site-2.example");
+        assert_eq!(sanitize_website_name("Hacker News!"), "hackernews");
+        assert_eq!(sanitize_website_name("a+b%c\"d'e<f>"), "abcdef");
+        assert_eq!(sanitize_website_name("café"), "caf");
+        assert_eq!(sanitize_website_name(&"x".repeat(100)).len(), MAX_WEBSITE_NAME_LENGTH);
+
+        for website in ["github", "GitHub.com", "my_site-2.example", "Hacker News!"] {
+            assert!(validate_append_type(&AppendType::WebsiteName { website: website.to_string() }).is_ok());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subaddress_uses_sanitized_website_name() {
+        let client = reqwest::Client::new();
+        let request = |website: &str| UsernameGeneratorRequest::Subaddress {
+            r#type: AppendType::WebsiteName {
+                website: website.to_string(),
+            },
+            email: "user@example.com".to_string(),
+        };
+        assert_eq!(
+            generate_username(request("Git Hub"), &client).await.unwrap(),
+            "user+github@example.com"
+        );
+        assert!(generate_username(request("evil.com\r\nBcc: attacker@evil.com"), &client)
+            .await
+            .is_err());
+
+        let catchall = UsernameGeneratorRequest::Catchall {
+            r#type: AppendType::WebsiteName {
+                website: "Example Shop".to_string(),
+            },
+            domain: "example.com".to_string(),
+            original_domain: None,
+        };
+        assert_eq!(generate_username(catchall, &client).await.unwrap(), "exampleshop@example.com");
+    }
+
+    #[test]
+    fn test_validate_pronounceable_syllables() {
+        for syllables in [0, 1, 7, 255] {
+            assert!(matches!(
+                validate_append_type(&AppendType::Pronounceable { syllables }),
+                Err(UsernameError::InvalidSyllableCount { .. })
+            ));
+        }
+        for syllables in MIN_SYLLABLES..=MAX_SYLLABLES {
+            assert!(validate_append_type(&AppendType::Pronounceable { syllables }).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_normalize_idn_domain_ascii_unchanged() {
+        for domain in ["example.com", "Example.COM", "my-domain.co.uk"] {
+            assert_eq!(normalize_idn_domain(domain).unwrap(), (domain.to_string(), None));
+        }
+    }
+
+    #[test]
+    fn test_normalize_idn_domain_unicode() {
+        let (ascii, original) = normalize_idn_domain("münchen.de").unwrap();
+        assert_eq!(ascii, "xn--mnchen-3ya.de");
+        assert_eq!(original.as_deref(), Some("münchen.de"));
+
+        let (ascii, original) = normalize_idn_domain("例え.jp").unwrap();
+        assert!(ascii.starts_with("xn--"));
+        assert_eq!(original.as_deref(), Some("例え.jp"));
+    }
+
+    #[test]
+    fn test_normalize_idn_domain_ace_input() {
+        let (ascii, original) = normalize_idn_domain("xn--mnchen-3ya.de").unwrap();
+        assert_eq!(ascii, "xn--mnchen-3ya.de");
+        assert_eq!(original.as_deref(), Some("münchen.de"));
+    }
+
+    #[test]
+    fn test_normalize_idn_domain_invalid() {
+        assert!(matches!(
+            normalize_idn_domain("xn--a.de"),
+            Err(UsernameError::InvalidDomain { .. })
+        ));
+    }
+
+    #[test]
+    fn test_normalize_email() {
+        assert_eq!(normalize_email("ME+github@EXAMPLE.COM").unwrap(), "ME+github@example.com");
+        assert_eq!(normalize_email("  me+github@example.com.. ").unwrap(), "me+github@example.com");
+        assert_eq!(normalize_email("user@münchen.de").unwrap(), "user@xn--mnchen-3ya.de");
+        assert_eq!(normalize_email("user@MÜNCHEN.DE").unwrap(), "user@xn--mnchen-3ya.de");
+        assert_eq!(normalize_email("user@xn--mnchen-3ya.de").unwrap(), "user@xn--mnchen-3ya.de");
+
+        for email in ["", "user", "@example.com", "user@", "user@."] {
+            assert!(matches!(normalize_email(email), Err(UsernameError::InvalidEmail { .. })), "{:?}", email);
+        }
+        assert!(matches!(normalize_email("user@xn--a.de"), Err(UsernameError::InvalidDomain { .. })));
+    }
+
+    #[test]
+    fn test_emails_equivalent() {
+        let equivalent = |a, b| emails_equivalent(a, b).unwrap();
+        assert!(equivalent("ME+github@EXAMPLE.COM", "me+github@example.com"));
+        assert!(equivalent("Me+GitHub@Example.com.", "me+github@example.com"));
+        assert!(equivalent("user+shop@münchen.de", "USER+shop@xn--mnchen-3ya.de"));
+        assert!(!equivalent("me+github@example.com", "me+gitlab@example.com"));
+        assert!(!equivalent("me+github@example.com", "me@example.com"));
+        assert!(!equivalent("me+github@example.com", "me+github@example.org"));
+        assert!(emails_equivalent("me@example.com", "not an email").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_catchall_idn_uses_ace_form() {
+        let request = UsernameGeneratorRequest::Catchall {
+            r#type: AppendType::WebsiteName {
+                website: "shop".to_string(),
+            },
+            domain: "münchen.de".to_string(),
+            original_domain: None,
+        };
+
+        let normalized = request.normalize().unwrap();
+        let json = serde_json::to_value(&normalized).unwrap();
+        assert_eq!(json["Catchall"]["domain"], "xn--mnchen-3ya.de");
+        assert_eq!(json["Catchall"]["original_domain"], "münchen.de");
+
+        let username = generate_username(normalized, &reqwest::Client::new()).await.unwrap();
+        assert_eq!(username, "shop@xn--mnchen-3ya.de");
+    }
+
+    fn addy_service(base_url: &str) -> ForwarderServiceType {
+        ForwarderServiceType::AddyIo {
+            api_token: "token".to_string(),
+            domain: "example.com".to_string(),
+            base_url: base_url.to_string(),
+            mailbox_id: None,
+        }
+    }
+
+    fn simplelogin_service(base_url: &str) -> ForwarderServiceType {
+        ForwarderServiceType::SimpleLogin {
+            api_key: "key".to_string(),
+            base_url: base_url.to_string(),
+            note: None,
+            directory: None,
+        }
+    }
+
+    #[test]
+    fn test_forwarder_base_url_requires_https() {
+        for base_url in ["http://app.addy.io", "HTTP://app.addy.io", "ftp://app.addy.io", "app.addy.io"] {
+            for service in [addy_service(base_url), simplelogin_service(base_url)] {
+                assert!(
+                    matches!(validate_forwarder_service(&service), Err(UsernameError::InsecureBaseUrl { .. })),
+                    "{} should be rejected",
+                    base_url
+                );
+            }
+        }
+
+        for base_url in ["https://app.addy.io", "HTTPS://app.addy.io/", "http://127.0.0.1:8080", "http://localhost:3000"] {
+            for service in [addy_service(base_url), simplelogin_service(base_url)] {
+                assert!(validate_forwarder_service(&service).is_ok(), "{} should be accepted", base_url);
+            }
+        }
+
+        assert!(matches!(
+            validate_forwarder_service(&addy_service("  ")),
+            Err(UsernameError::IncompleteApiConfig { .. })
+        ));
+    }
+
+    #[test]
+    fn test_validate_ironvest_token() {
+        let service = |api_token: &str| ForwarderServiceType::IronVest {
+            api_token: api_token.to_string(),
+        };
+
+        assert!(validate_forwarder_service(&service("token")).is_ok());
+        for api_token in ["", "   "] {
+            assert!(matches!(
+                validate_forwarder_service(&service(api_token)),
+                Err(UsernameError::IncompleteApiConfig { service }) if service == "IronVest"
+            ));
+        }
+        assert!(!serde_json::to_string(&service("token").redact_secrets()).unwrap().contains("\"token\""));
+    }
+
+    #[tokio::test]
+    async fn test_deactivate_alias_unsupported_service() {
+        let service = ForwarderServiceType::Firefox {
+            api_token: "token".to_string(),
+        };
+        let result = service.deactivate_alias(&reqwest::Client::new(), "1".to_string()).await;
+        assert!(matches!(
+            result,
+            Err(UsernameError::UnsupportedOperation { ref service }) if service == "Firefox"
+        ));
+    }
+
+    #[test]
+    fn test_forwarder_base_url_trailing_slashes_stripped() {
+        let request = UsernameGeneratorRequest::Forwarded {
+            service: addy_service("https://app.addy.io//"),
+            website: None,
+        };
+        match request.normalize().unwrap() {
+            UsernameGeneratorRequest::Forwarded {
+                service: ForwarderServiceType::AddyIo { base_url, .. },
+                ..
+            } => assert_eq!(base_url, "https://app.addy.io"),
+            _ => panic!("Expected AddyIo forwarder"),
+        }
+
+        let request = UsernameGeneratorRequest::Forwarded {
+            service: simplelogin_service("https://app.simplelogin.io/ "),
+            website: None,
+        };
+        match request.normalize().unwrap() {
+            UsernameGeneratorRequest::Forwarded {
+                service: ForwarderServiceType::SimpleLogin { base_url, .. },
+                ..
+            } => assert_eq!(base_url, "https://app.simplelogin.io"),
+            _ => panic!("Expected SimpleLogin forwarder"),
+        }
+    }
+
+    #[test]
+    fn test_random_number_lengths() {
+        let mut rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
+
+        for digits in [6, 8] {
+            for _ in 0..100 {
+                let num = random_number(&mut rng, digits);
+                assert_eq!(num.len(), digits as usize);
+                assert!(num.chars().all(|c| c.is_ascii_digit()));
+            }
+        }
+
+        let six = username_word(&mut rng, false, NumberSuffix::SixDigits, NumberPosition::Suffix, UsernameStrength::Standard, UsernameFormat::Plain);
+        assert!(six.chars().rev().take(6).all(|c| c.is_ascii_digit()));
+        assert!(!six.chars().rev().nth(6).unwrap().is_ascii_digit());
+
+        let eight = username_word(&mut rng, false, NumberSuffix::EightDigits, NumberPosition::Suffix, UsernameStrength::Standard, UsernameFormat::Plain);
+        assert!(eight.chars().rev().take(8).all(|c| c.is_ascii_digit()));
+        assert!(!eight.chars().rev().nth(8).unwrap().is_ascii_digit());
+    }
+
+    #[test]
+    fn test_number_position() {
+        let mut rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
+
+        for capitalize in [false, true] {
+            let prefixed = username_word(
+                &mut rng,
+                capitalize,
+                NumberSuffix::FourDigits,
+                NumberPosition::Prefix,
+                UsernameStrength::Standard,
+                UsernameFormat::Plain,
+            );
+            assert!(prefixed[..4].chars().all(|c| c.is_ascii_digit()), "{}", prefixed);
+            let first_letter = prefixed[4..].chars().next().unwrap();
+            assert!(first_letter.is_ascii_alphabetic());
+            assert_eq!(first_letter.is_ascii_uppercase(), capitalize, "{}", prefixed);
+            assert!(prefixed[5..].chars().all(|c| c.is_ascii_lowercase()));
+
+            let suffixed = username_word(
+                &mut rng,
+                capitalize,
+                NumberSuffix::FourDigits,
+                NumberPosition::Suffix,
+                UsernameStrength::Standard,
+                UsernameFormat::Plain,
+            );
+            let (word, number) = suffixed.split_at(suffixed.len() - 4);
+            assert!(number.chars().all(|c| c.is_ascii_digit()), "{}", suffixed);
+            assert_eq!(word.chars().next().unwrap().is_ascii_uppercase(), capitalize, "{}", suffixed);
+            assert!(word[1..].chars().all(|c| c.is_ascii_lowercase()));
+        }
+    }
+
+    #[test]
+    fn test_number_suffix_deserialization() {
+        let parse = |json: &str| match serde_json::from_str::<UsernameGeneratorRequest>(json).unwrap() {
+            UsernameGeneratorRequest::Word { number_suffix, .. } => number_suffix,
+            _ => panic!("Expected Word variant"),
+        };
+
+        assert_eq!(
+            parse(r#"{"Word":{"capitalize":false,"include_number":true,"strength":"Standard"}}"#),
+            NumberSuffix::FourDigits
+        );
+        assert_eq!(
+            parse(r#"{"Word":{"capitalize":false,"include_number":false,"strength":"Standard"}}"#),
+            NumberSuffix::None
+        );
+        assert_eq!(
+            parse(r#"{"Word":{"capitalize":false,"number_suffix":"EightDigits","strength":"Standard"}}"#),
+            NumberSuffix::EightDigits
+        );
+        assert_eq!(
+            parse(r#"{"Word":{"capitalize":false,"strength":"Standard"}}"#),
+            NumberSuffix::None
+        );
+    }
+
     #[test]
     fn test_random_number() {
         let mut rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
         
-        let num = random_number(&mut rng);
+        let num = random_number(&mut rng, 4);
         assert_eq!(num.len(), 4);
         assert!(num.chars().all(|c| c.is_ascii_digit()));
         
         // Test that it can generate leading zeros
         let mut _found_leading_zero = false;
         for _ in 0..100 {
-            let num = random_number(&mut rng);
+            let num = random_number(&mut rng, 4);
             if num.starts_with('0') {
                 _found_leading_zero = true;
                 break;
@@ -561,9 +2139,11 @@ mod tests {
         let word_json = r#"{"Word":{"capitalize":true,"include_number":false,"strength":"Standard"}}"#;
         let word_request: UsernameGeneratorRequest = serde_json::from_str(word_json).unwrap();
         match word_request {
-            UsernameGeneratorRequest::Word { capitalize, include_number, strength } => {
+            UsernameGeneratorRequest::Word { capitalize, number_suffix, number_position, strength, format } => {
+                assert_eq!(number_position, NumberPosition::Suffix);
+                assert_eq!(format, UsernameFormat::Plain);
                 assert_eq!(capitalize, true);
-                assert_eq!(include_number, false);
+                assert_eq!(number_suffix, NumberSuffix::None);
                 assert!(matches!(strength, UsernameStrength::Standard));
             }
             _ => panic!("Expected Word variant"),
@@ -600,13 +2180,105 @@ mod tests {
         let catchall_random_json = r#"{"Catchall":{"type":"Random","domain":"example.com"}}"#;
         let catchall_request: UsernameGeneratorRequest = serde_json::from_str(catchall_random_json).unwrap();
         match catchall_request {
-            UsernameGeneratorRequest::Catchall { r#type, domain } => {
+            UsernameGeneratorRequest::Catchall { r#type, domain, original_domain } => {
                 assert!(matches!(r#type, AppendType::Random));
                 assert_eq!(domain, "example.com");
+                assert_eq!(original_domain, None);
             }
             _ => panic!("Expected Catchall variant"),
         }
     }
 
 
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_username_request_display() {
+        let requests = [
+            UsernameGeneratorRequest::Word {
+                capitalize: false,
+                number_suffix: NumberSuffix::FourDigits,
+                number_position: NumberPosition::default(),
+                strength: UsernameStrength::Standard,
+                format: UsernameFormat::default(),
+            },
+            UsernameGeneratorRequest::CompoundWord {
+                word_count: 3,
+                strength: UsernameStrength::Standard,
+                format: UsernameFormat::default(),
+            },
+            UsernameGeneratorRequest::Subaddress {
+                r#type: AppendType::Random,
+                email: "someone@example.com".to_string(),
+            },
+            UsernameGeneratorRequest::Catchall {
+                r#type: AppendType::Pronounceable { syllables: 3 },
+                domain: "example.com".to_string(),
+                original_domain: None,
+            },
+        ];
+        for request in requests {
+            assert!(request.to_string().starts_with("Username("), "{}", request);
+        }
+
+        let request = UsernameGeneratorRequest::Subaddress {
+            r#type: AppendType::Random,
+            email: "someone@example.com".to_string(),
+        };
+        assert!(!request.to_string().contains("someone"));
+
+        let request = UsernameGeneratorRequest::Forwarded {
+            service: ForwarderServiceType::Firefox {
+                api_token: "secret-token-1234".to_string(),
+            },
+            website: Some("example.com".to_string()),
+        };
+        let summary = request.to_string();
+        assert_eq!(summary, "Username(forwarded via Firefox, for example.com)");
+        assert!(!summary.contains("secret"));
+        assert!(!summary.contains("1234"));
+    }
+
+    #[test]
+    fn test_request_equality() {
+        let request = UsernameGeneratorRequest::Subaddress {
+            r#type: AppendType::WebsiteName {
+                website: "example.com".to_string(),
+            },
+            email: "someone@example.com".to_string(),
+        };
+        assert_eq!(request, request.clone());
+        assert_ne!(
+            request,
+            UsernameGeneratorRequest::Catchall {
+                r#type: AppendType::WebsiteName {
+                    website: "example.com".to_string(),
+                },
+                domain: "example.com".to_string(),
+                original_domain: None,
+            }
+        );
+        assert_ne!(AppendType::Random, AppendType::Pronounceable { syllables: 3 });
+    }
+
+    #[test]
+    fn test_forwarder_service_equality_ignores_token_value() {
+        let firefox = |api_token: &str| ForwarderServiceType::Firefox {
+            api_token: api_token.to_string(),
+        };
+        assert_eq!(firefox("token-a"), firefox("token-b"));
+        assert_ne!(firefox("token-a"), firefox(""));
+        assert_ne!(
+            firefox("token-a"),
+            ForwarderServiceType::DuckDuckGo {
+                token: "token-a".to_string(),
+            }
+        );
+
+        let forward_email = |domain: &str| ForwarderServiceType::ForwardEmail {
+            api_token: "token".to_string(),
+            domain: domain.to_string(),
+        };
+        assert_eq!(forward_email("example.com"), forward_email("example.com"));
+        assert_ne!(forward_email("example.com"), forward_email("example.org"));
+    }
+}
@@ -1,23 +1,56 @@
 use reqwest::{header::CONTENT_TYPE, StatusCode};
 
+use super::HttpClientTrait;
 use crate::generators::username::UsernameError;
 
+/// Domains addy.io shares between all users, including the per-user subdomains of them. They are
+/// not listed with the account's own domains.
+const SHARED_DOMAINS: &[&str] = &["anonaddy.me", "anonaddy.com", "addymail.com"];
+
 pub async fn generate(
-    http: &reqwest::Client,
+    http: &impl HttpClientTrait,
     api_token: String,
     domain: String,
     base_url: String,
+    mailbox_id: Option<String>,
     website: Option<String>,
 ) -> Result<String, UsernameError> {
     let description = super::format_description(&website);
+    let base_url = super::normalize_base_url(&base_url);
+
+    // The domain lookup is best effort, if it fails the create request reports the real error
+    if !is_shared_domain(&domain) {
+        if let Ok(domains) = list_domains_internal(http, &api_token, &base_url).await {
+            if !domains.iter().any(|d| d.domain.eq_ignore_ascii_case(domain.trim())) {
+                tracing::warn!(%domain, "Domain is not set up on the addy.io account");
+                return Err(UsernameError::DomainRejected);
+            }
+        }
+    }
+
+    super::with_retries(|| {
+        create_alias(http, &api_token, &domain, &base_url, mailbox_id.as_deref(), &description)
+    })
+    .await
+}
 
+async fn create_alias(
+    http: &impl HttpClientTrait,
+    api_token: &str,
+    domain: &str,
+    base_url: &str,
+    mailbox_id: Option<&str>,
+    description: &str,
+) -> Result<String, UsernameError> {
     #[derive(serde::Serialize)]
-    struct Request {
-        domain: String,
-        description: String,
+    struct Request<'a> {
+        domain: &'a str,
+        description: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        mailbox_id: Option<&'a str>,
     }
 
-    let response = http
+    let request = http
         .post(format!("{base_url}/api/v1/aliases"))
         .header(CONTENT_TYPE, "application/json")
         .bearer_auth(api_token)
@@ -25,9 +58,12 @@ pub async fn generate(
         .json(&Request {
             domain,
             description,
+            mailbox_id,
         })
-        .send()
-        .await?;
+        .build()?;
+    let response = http.execute(request).await?;
+
+    super::check_service_available(response.status())?;
 
     // Handle specific HTTP status codes with meaningful error messages
     match response.status() {
@@ -48,7 +84,7 @@ pub async fn generate(
 
     #[derive(serde::Deserialize)]
     struct ResponseData {
-        email: String,
+        email: Option<String>,
     }
     #[derive(serde::Deserialize)]
     struct Response {
@@ -56,7 +92,215 @@ pub async fn generate(
     }
     let response: Response = response.json().await?;
 
-    Ok(response.data.email)
+    super::require_alias("AddyIo", response.data.email)
+}
+
+/// An existing alias on the user's addy.io account
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AliasInfo {
+    pub id: String,
+    pub email: String,
+    pub description: Option<String>,
+    pub active: bool,
+    pub created_at: String,
+}
+
+/// A custom domain on the user's addy.io account
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AddyDomain {
+    pub domain: String,
+    pub active: bool,
+    pub aliases_count: u32,
+}
+
+fn is_shared_domain(domain: &str) -> bool {
+    let domain = domain.trim().to_ascii_lowercase();
+    SHARED_DOMAINS
+        .iter()
+        .any(|shared| domain == *shared || domain.ends_with(&format!(".{shared}")))
+}
+
+/// Fetch the custom domains of the account. The shared addy.io domains are not included.
+pub async fn list_domains(
+    http: &reqwest::Client,
+    api_token: String,
+    base_url: String,
+) -> Result<Vec<AddyDomain>, UsernameError> {
+    let base_url = super::normalize_base_url(&base_url);
+    list_domains_internal(http, &api_token, &base_url).await
+}
+
+async fn list_domains_internal(
+    http: &impl HttpClientTrait,
+    api_token: &str,
+    base_url: &str,
+) -> Result<Vec<AddyDomain>, UsernameError> {
+    let request = http
+        .get(format!("{base_url}/api/v1/domains"))
+        .bearer_auth(api_token)
+        .header("X-Requested-With", "XMLHttpRequest")
+        .build()?;
+    let response = http.execute(request).await?;
+
+    super::check_service_available(response.status())?;
+
+    match response.status() {
+        StatusCode::UNAUTHORIZED => {
+            return Err(UsernameError::InvalidApiKey);
+        }
+        StatusCode::TOO_MANY_REQUESTS => {
+            return Err(UsernameError::RateLimitExceeded);
+        }
+        _ => {}
+    }
+
+    let response = response.error_for_status()?;
+
+    #[derive(serde::Deserialize)]
+    struct Response {
+        data: Vec<AddyDomain>,
+    }
+    let response: Response = response.json().await?;
+
+    Ok(response.data)
+}
+
+/// A mailbox aliases on the user's addy.io account can forward to
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AddyMailbox {
+    pub id: String,
+    pub email: String,
+    pub default: bool,
+}
+
+/// Fetch one page of the aliases on the account
+pub async fn list_aliases(
+    http: &reqwest::Client,
+    api_token: String,
+    base_url: String,
+    page: u8,
+) -> Result<Vec<AliasInfo>, UsernameError> {
+    let base_url = super::normalize_base_url(&base_url);
+
+    let response = http
+        .get(format!("{base_url}/api/v1/aliases"))
+        .query(&[("page", page)])
+        .bearer_auth(api_token)
+        .header("X-Requested-With", "XMLHttpRequest")
+        .send()
+        .await?;
+
+    super::check_service_available(response.status())?;
+
+    match response.status() {
+        StatusCode::UNAUTHORIZED => {
+            return Err(UsernameError::InvalidApiKey);
+        }
+        StatusCode::TOO_MANY_REQUESTS => {
+            return Err(UsernameError::RateLimitExceeded);
+        }
+        _ => {}
+    }
+
+    let response = response.error_for_status()?;
+
+    #[derive(serde::Deserialize)]
+    struct Response {
+        data: Vec<AliasInfo>,
+    }
+    let response: Response = response.json().await?;
+
+    Ok(response.data)
+}
+
+/// Deactivate an alias so it stops forwarding mail, without deleting it
+pub async fn deactivate_alias(
+    http: &reqwest::Client,
+    api_token: String,
+    base_url: String,
+    alias_id: String,
+) -> Result<(), UsernameError> {
+    let base_url = super::normalize_base_url(&base_url);
+
+    let response = http
+        .patch(format!("{base_url}/api/v1/aliases/{}", urlencoding::encode(&alias_id)))
+        .header(CONTENT_TYPE, "application/json")
+        .bearer_auth(api_token)
+        .header("X-Requested-With", "XMLHttpRequest")
+        .json(&serde_json::json!({ "active": false }))
+        .send()
+        .await?;
+
+    super::check_service_available(response.status())?;
+
+    match response.status() {
+        StatusCode::UNAUTHORIZED => {
+            return Err(UsernameError::InvalidApiKey);
+        }
+        StatusCode::NOT_FOUND => {
+            return Err(UsernameError::AliasNotFound);
+        }
+        StatusCode::TOO_MANY_REQUESTS => {
+            return Err(UsernameError::RateLimitExceeded);
+        }
+        _ => {}
+    }
+
+    response.error_for_status()?;
+    Ok(())
+}
+
+/// Fetch the mailboxes of the account, the default mailbox is flagged with `default`
+pub async fn list_mailboxes(
+    http: &reqwest::Client,
+    api_token: String,
+    base_url: String,
+) -> Result<Vec<AddyMailbox>, UsernameError> {
+    let base_url = super::normalize_base_url(&base_url);
+
+    let response = http
+        .get(format!("{base_url}/api/v1/recipients"))
+        .bearer_auth(api_token)
+        .header("X-Requested-With", "XMLHttpRequest")
+        .send()
+        .await?;
+
+    super::check_service_available(response.status())?;
+
+    match response.status() {
+        StatusCode::UNAUTHORIZED => {
+            return Err(UsernameError::InvalidApiKey);
+        }
+        StatusCode::TOO_MANY_REQUESTS => {
+            return Err(UsernameError::RateLimitExceeded);
+        }
+        _ => {}
+    }
+
+    let response = response.error_for_status()?;
+
+    #[derive(serde::Deserialize)]
+    struct Recipient {
+        id: String,
+        email: String,
+        #[serde(default)]
+        default: bool,
+    }
+    #[derive(serde::Deserialize)]
+    struct Response {
+        data: Vec<Recipient>,
+    }
+    let response: Response = response.json().await?;
+
+    Ok(response
+        .data
+        .into_iter()
+        .map(|recipient| AddyMailbox {
+            id: recipient.id,
+            email: recipient.email,
+            default: recipient.default,
+        })
+        .collect())
 }
 
 #[cfg(test)]
@@ -131,6 +375,7 @@ mod tests {
             "MY_TOKEN".into(),
             "myemail.com".into(),
             format!("http://{}", server.address()),
+            None,
             Some("example.com".into()),
         )
         .await
@@ -141,6 +386,7 @@ mod tests {
             "MY_FAKE_TOKEN".into(),
             "myemail.com".into(),
             format!("http://{}", server.address()),
+            None,
             Some("example.com".into()),
         )
         .await
@@ -156,6 +402,7 @@ mod tests {
             "MY_TOKEN".into(),
             "gmail.com".into(),
             format!("http://{}", server.address()),
+            None,
             Some("example.com".into()),
         )
         .await
@@ -169,4 +416,487 @@ mod tests {
         server.verify().await;
         assert_eq!(address, "50c9e585-e7f5-41c4-9016-9014c15454bc@myemail.com");
     }
-} 
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_mailbox_id_in_request_body() {
+        use wiremock::{matchers, Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+
+        // body_json matches exactly, so the request without a mailbox must not send the field
+        server
+            .register(
+                Mock::given(matchers::path("/api/v1/aliases"))
+                    .and(matchers::method("POST"))
+                    .and(matchers::body_json(json!({
+                        "domain": "myemail.com",
+                        "description": "Generated by Bitwarden",
+                        "mailbox_id": "46eebc50-f7f8-46d7-beb9-c37f04c29a84"
+                    })))
+                    .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                        "data": { "email": "with-mailbox@myemail.com" }
+                    })))
+                    .expect(1),
+            )
+            .await;
+        server
+            .register(
+                Mock::given(matchers::path("/api/v1/aliases"))
+                    .and(matchers::method("POST"))
+                    .and(matchers::body_json(json!({
+                        "domain": "myemail.com",
+                        "description": "Generated by Bitwarden"
+                    })))
+                    .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                        "data": { "email": "default-mailbox@myemail.com" }
+                    })))
+                    .expect(1),
+            )
+            .await;
+
+        let address = super::generate(
+            &reqwest::Client::new(),
+            "MY_TOKEN".into(),
+            "myemail.com".into(),
+            server.uri(),
+            Some("46eebc50-f7f8-46d7-beb9-c37f04c29a84".into()),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(address, "with-mailbox@myemail.com");
+
+        let address = super::generate(
+            &reqwest::Client::new(),
+            "MY_TOKEN".into(),
+            "myemail.com".into(),
+            server.uri(),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(address, "default-mailbox@myemail.com");
+
+        server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn test_list_mailboxes() {
+        use wiremock::{matchers, Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+
+        server
+            .register(
+                Mock::given(matchers::path("/api/v1/recipients"))
+                    .and(matchers::method("GET"))
+                    .and(matchers::header("Authorization", "Bearer MY_TOKEN"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                        "data": [
+                            { "id": "46eebc50", "email": "me@example.com", "default": true },
+                            { "id": "ca0a4e09", "email": "work@example.com", "default": false }
+                        ]
+                    })))
+                    .expect(1),
+            )
+            .await;
+
+        let mailboxes = super::list_mailboxes(&reqwest::Client::new(), "MY_TOKEN".into(), server.uri())
+            .await
+            .unwrap();
+        assert_eq!(
+            mailboxes,
+            vec![
+                super::AddyMailbox {
+                    id: "46eebc50".into(),
+                    email: "me@example.com".into(),
+                    default: true,
+                },
+                super::AddyMailbox {
+                    id: "ca0a4e09".into(),
+                    email: "work@example.com".into(),
+                    default: false,
+                },
+            ]
+        );
+
+        server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn test_list_aliases() {
+        use wiremock::{matchers, Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+
+        server
+            .register(
+                Mock::given(matchers::path("/api/v1/aliases"))
+                    .and(matchers::method("GET"))
+                    .and(matchers::query_param("page", "2"))
+                    .and(matchers::header("Authorization", "Bearer MY_TOKEN"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                        "data": [
+                            {
+                                "id": "50c9e585-e7f5-41c4-9016-9014c15454bc",
+                                "email": "first@myemail.com",
+                                "description": "Generated by Bitwarden for example.com",
+                                "active": true,
+                                "created_at": "2024-01-01 12:00:00"
+                            },
+                            {
+                                "id": "ca0a4e09-c266-4f6f-845c-958db5090f09",
+                                "email": "second@myemail.com",
+                                "description": null,
+                                "active": false,
+                                "created_at": "2024-01-02 12:00:00"
+                            }
+                        ],
+                        "links": { "next": "https://app.addy.io/api/v1/aliases?page=3" },
+                        "meta": { "current_page": 2, "last_page": 3 }
+                    })))
+                    .expect(1),
+            )
+            .await;
+        server
+            .register(
+                Mock::given(matchers::path("/api/v1/aliases"))
+                    .and(matchers::method("GET"))
+                    .and(matchers::query_param("page", "9"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                        "data": [],
+                        "meta": { "current_page": 9, "last_page": 3 }
+                    })))
+                    .expect(1),
+            )
+            .await;
+        server
+            .register(
+                Mock::given(matchers::path("/api/v1/aliases"))
+                    .and(matchers::method("GET"))
+                    .and(matchers::header("Authorization", "Bearer MY_FAKE_TOKEN"))
+                    .respond_with(ResponseTemplate::new(401))
+                    .expect(1),
+            )
+            .await;
+
+        let aliases = super::list_aliases(&reqwest::Client::new(), "MY_TOKEN".into(), server.uri(), 2)
+            .await
+            .unwrap();
+        assert_eq!(aliases.len(), 2);
+        assert_eq!(aliases[0].email, "first@myemail.com");
+        assert_eq!(
+            aliases[0].description.as_deref(),
+            Some("Generated by Bitwarden for example.com")
+        );
+        assert!(aliases[0].active);
+        assert_eq!(aliases[1].id, "ca0a4e09-c266-4f6f-845c-958db5090f09");
+        assert_eq!(aliases[1].description, None);
+        assert!(!aliases[1].active);
+        assert_eq!(aliases[1].created_at, "2024-01-02 12:00:00");
+
+        let empty = super::list_aliases(&reqwest::Client::new(), "MY_TOKEN".into(), server.uri(), 9)
+            .await
+            .unwrap();
+        assert!(empty.is_empty());
+
+        let error = super::list_aliases(&reqwest::Client::new(), "MY_FAKE_TOKEN".into(), server.uri(), 1)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, UsernameError::InvalidApiKey));
+
+        server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn test_deactivate_alias() {
+        use wiremock::{matchers, Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+
+        server
+            .register(
+                Mock::given(matchers::path("/api/v1/aliases/50c9e585-e7f5-41c4-9016-9014c15454bc"))
+                    .and(matchers::method("PATCH"))
+                    .and(matchers::header("Authorization", "Bearer MY_TOKEN"))
+                    .and(matchers::body_json(json!({ "active": false })))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                        "data": {
+                            "id": "50c9e585-e7f5-41c4-9016-9014c15454bc",
+                            "active": false
+                        }
+                    })))
+                    .expect(1),
+            )
+            .await;
+        server
+            .register(
+                Mock::given(matchers::path("/api/v1/aliases/missing"))
+                    .and(matchers::method("PATCH"))
+                    .respond_with(ResponseTemplate::new(404))
+                    .expect(1),
+            )
+            .await;
+
+        super::deactivate_alias(
+            &reqwest::Client::new(),
+            "MY_TOKEN".into(),
+            server.uri(),
+            "50c9e585-e7f5-41c4-9016-9014c15454bc".into(),
+        )
+        .await
+        .unwrap();
+
+        let error = super::deactivate_alias(&reqwest::Client::new(), "MY_TOKEN".into(), server.uri(), "missing".into())
+            .await
+            .unwrap_err();
+        assert!(matches!(error, UsernameError::AliasNotFound));
+
+        server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn test_mock_http_client() {
+        use reqwest::StatusCode;
+
+        use crate::generators::username_forwarders::test_utils::MockHttpClient;
+
+        let http = MockHttpClient::new(vec![
+            (
+                StatusCode::OK,
+                json!({ "data": [{ "domain": "example.com", "active": true, "aliases_count": 3 }] }),
+            ),
+            (StatusCode::CREATED, json!({ "data": { "email": "wertg8ad@example.com" } })),
+            // The domain lookup failing doesn't block the create request
+            (StatusCode::INTERNAL_SERVER_ERROR, json!({})),
+            (StatusCode::FORBIDDEN, json!({})),
+        ]);
+
+        let address = super::generate(
+            &http,
+            "MY_TOKEN".into(),
+            "example.com".into(),
+            "https://app.addy.io".into(),
+            None,
+            Some("example.com".into()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(address, "wertg8ad@example.com");
+
+        let error = super::generate(
+            &http,
+            "MY_TOKEN".into(),
+            "not-my-domain.com".into(),
+            "https://app.addy.io".into(),
+            None,
+            None,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(error, UsernameError::DomainRejected));
+
+        let requests = http.requests();
+        assert_eq!(requests[0].url().as_str(), "https://app.addy.io/api/v1/domains");
+        assert_eq!(requests[1].url().as_str(), "https://app.addy.io/api/v1/aliases");
+        let body: serde_json::Value =
+            serde_json::from_slice(requests[1].body().unwrap().as_bytes().unwrap()).unwrap();
+        assert_eq!(body["domain"], "example.com");
+        assert!(body.get("mailbox_id").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_list_domains() {
+        use wiremock::{matchers, Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+
+        server
+            .register(
+                Mock::given(matchers::path("/api/v1/domains"))
+                    .and(matchers::method("GET"))
+                    .and(matchers::header("Authorization", "Bearer MY_TOKEN"))
+                    .and(matchers::header("X-Requested-With", "XMLHttpRequest"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                        "data": [
+                            {
+                                "id": "50c9e585-e7f5-41c4-9016-9014c15454bc",
+                                "domain": "example.com",
+                                "description": null,
+                                "aliases_count": 12,
+                                "active": true,
+                                "catch_all": true
+                            },
+                            {
+                                "id": "8e3a2ea5-3b40-4b36-a0ee-c3a3c5d0cd9c",
+                                "domain": "old.example.com",
+                                "description": "Retired",
+                                "aliases_count": 0,
+                                "active": false,
+                                "catch_all": false
+                            }
+                        ]
+                    })))
+                    .expect(1),
+            )
+            .await;
+        server
+            .register(
+                Mock::given(matchers::path("/api/v1/domains"))
+                    .and(matchers::header("Authorization", "Bearer MY_FAKE_TOKEN"))
+                    .respond_with(ResponseTemplate::new(401))
+                    .expect(1),
+            )
+            .await;
+
+        let domains = super::list_domains(&reqwest::Client::new(), "MY_TOKEN".into(), format!("{}/", server.uri()))
+            .await
+            .unwrap();
+        assert_eq!(
+            domains,
+            vec![
+                super::AddyDomain {
+                    domain: "example.com".into(),
+                    active: true,
+                    aliases_count: 12,
+                },
+                super::AddyDomain {
+                    domain: "old.example.com".into(),
+                    active: false,
+                    aliases_count: 0,
+                },
+            ]
+        );
+
+        let error = super::list_domains(&reqwest::Client::new(), "MY_FAKE_TOKEN".into(), server.uri())
+            .await
+            .unwrap_err();
+        assert!(matches!(error, UsernameError::InvalidApiKey));
+
+        server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn test_generate_rejects_unknown_domain() {
+        use wiremock::{matchers, Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+
+        server
+            .register(
+                Mock::given(matchers::path("/api/v1/domains"))
+                    .and(matchers::method("GET"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                        "data": [{ "domain": "example.com", "active": true, "aliases_count": 1 }]
+                    })))
+                    .expect(2),
+            )
+            .await;
+        // Only the alias on the known domain is created
+        server
+            .register(
+                Mock::given(matchers::path("/api/v1/aliases"))
+                    .and(matchers::method("POST"))
+                    .and(matchers::body_partial_json(json!({ "domain": "Example.com" })))
+                    .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                        "data": { "email": "wertg8ad@example.com" }
+                    })))
+                    .expect(1),
+            )
+            .await;
+
+        let http = reqwest::Client::new();
+        let generate = |domain: &str| {
+            super::generate(
+                &http,
+                "MY_TOKEN".into(),
+                domain.into(),
+                server.uri(),
+                None,
+                None,
+            )
+        };
+
+        assert_eq!(generate("Example.com").await.unwrap(), "wertg8ad@example.com");
+        assert!(matches!(
+            generate("myexample.com").await.unwrap_err(),
+            UsernameError::DomainRejected
+        ));
+
+        server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn test_generate_skips_lookup_for_shared_domains() {
+        use wiremock::{matchers, Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+
+        server
+            .register(
+                Mock::given(matchers::path("/api/v1/domains"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "data": [] })))
+                    .expect(0),
+            )
+            .await;
+        server
+            .register(
+                Mock::given(matchers::path("/api/v1/aliases"))
+                    .and(matchers::method("POST"))
+                    .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                        "data": { "email": "wertg8ad@anonaddy.me" }
+                    })))
+                    .expect(2),
+            )
+            .await;
+
+        for domain in ["anonaddy.me", "johndoe.anonaddy.com"] {
+            super::generate(&reqwest::Client::new(), "MY_TOKEN".into(), domain.into(), server.uri(), None, None)
+                .await
+                .unwrap();
+        }
+
+        server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn test_empty_email_retried() {
+        use wiremock::{matchers, Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+
+        // The first answer is an empty alias, the retry gets a real one
+        server
+            .register(
+                Mock::given(matchers::path("/api/v1/aliases"))
+                    .and(matchers::method("POST"))
+                    .respond_with(ResponseTemplate::new(201).set_body_json(json!({ "data": { "email": "" } })))
+                    .up_to_n_times(1)
+                    .expect(1),
+            )
+            .await;
+        server
+            .register(
+                Mock::given(matchers::path("/api/v1/aliases"))
+                    .and(matchers::method("POST"))
+                    .respond_with(ResponseTemplate::new(201).set_body_json(json!({ "data": { "email": "wertg8ad@anonaddy.me" } })))
+                    .expect(1),
+            )
+            .await;
+
+        let address = super::generate(
+            &reqwest::Client::new(),
+            "MY_TOKEN".into(),
+            "anonaddy.me".into(),
+            server.uri(),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(address, "wertg8ad@anonaddy.me");
+
+        server.verify().await;
+    }
+}
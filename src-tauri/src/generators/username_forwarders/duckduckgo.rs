@@ -1,22 +1,29 @@
 use reqwest::{header::CONTENT_TYPE, StatusCode};
 
+use super::HttpClientTrait;
 use crate::generators::username::UsernameError;
 
-pub async fn generate(http: &reqwest::Client, token: String) -> Result<String, UsernameError> {
+pub async fn generate(http: &impl HttpClientTrait, token: String) -> Result<String, UsernameError> {
     generate_with_api_url(http, token, "https://quack.duckduckgo.com".into()).await
 }
 
 pub async fn generate_with_api_url(
-    http: &reqwest::Client,
+    http: &impl HttpClientTrait,
     token: String,
     api_url: String,
 ) -> Result<String, UsernameError> {
-    let response = http
+    super::with_retries(|| create_alias(http, &token, &api_url)).await
+}
+
+async fn create_alias(http: &impl HttpClientTrait, token: &str, api_url: &str) -> Result<String, UsernameError> {
+    let request = http
         .post(format!("{api_url}/api/email/addresses"))
         .header(CONTENT_TYPE, "application/json")
         .bearer_auth(token)
-        .send()
-        .await?;
+        .build()?;
+    let response = http.execute(request).await?;
+
+    super::check_service_available(response.status())?;
 
     if response.status() == StatusCode::UNAUTHORIZED {
         return Err(UsernameError::InvalidApiKey);
@@ -27,11 +34,12 @@ pub async fn generate_with_api_url(
 
     #[derive(serde::Deserialize)]
     struct Response {
-        address: String,
+        address: Option<String>,
     }
     let response: Response = response.json().await?;
+    let address = super::require_alias("DuckDuckGo", response.address)?;
 
-    Ok(format!("{}@duck.com", response.address))
+    Ok(format!("{}@duck.com", address))
 }
 
 #[cfg(test)]
@@ -95,4 +103,61 @@ mod tests {
 
         server.verify().await;
     }
-} 
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_mock_http_client() {
+        use reqwest::StatusCode;
+
+        use crate::generators::username_forwarders::test_utils::MockHttpClient;
+
+        let http = MockHttpClient::new(vec![
+            (StatusCode::CREATED, json!({ "address": "bw7prt" })),
+            (StatusCode::UNAUTHORIZED, json!({})),
+        ]);
+
+        let address = super::generate(&http, "MY_TOKEN".into()).await.unwrap();
+        assert_eq!(address, "bw7prt@duck.com");
+
+        let error = super::generate(&http, "MY_FAKE_TOKEN".into()).await.unwrap_err();
+        assert!(matches!(error, UsernameError::InvalidApiKey));
+
+        let requests = http.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].method(), reqwest::Method::POST);
+        assert_eq!(requests[0].url().as_str(), "https://quack.duckduckgo.com/api/email/addresses");
+        assert_eq!(requests[0].headers()["Authorization"], "Bearer MY_TOKEN");
+    }
+
+    #[tokio::test]
+    async fn test_null_address_retried() {
+        use wiremock::{matchers, Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+
+        // The first answer is an empty alias, the retry gets a real one
+        server
+            .register(
+                Mock::given(matchers::path("/api/email/addresses"))
+                    .and(matchers::method("POST"))
+                    .respond_with(ResponseTemplate::new(201).set_body_json(json!({ "address": null })))
+                    .up_to_n_times(1)
+                    .expect(1),
+            )
+            .await;
+        server
+            .register(
+                Mock::given(matchers::path("/api/email/addresses"))
+                    .and(matchers::method("POST"))
+                    .respond_with(ResponseTemplate::new(201).set_body_json(json!({ "address": "bw7prt" })))
+                    .expect(1),
+            )
+            .await;
+
+        let address = super::generate_with_api_url(&reqwest::Client::new(), "MY_TOKEN".into(), server.uri())
+            .await
+            .unwrap();
+        assert_eq!(address, "bw7prt@duck.com");
+
+        server.verify().await;
+    }
+}
@@ -3,35 +3,86 @@ use std::collections::HashMap;
 use reqwest::{header::CONTENT_TYPE, StatusCode};
 use serde_json::json;
 
+use super::HttpClientTrait;
 use crate::generators::username::UsernameError;
 
+const API_URL: &str = "https://api.fastmail.com";
+const MASKED_EMAIL_CAPABILITY: &str = "https://www.fastmail.com/dev/maskedemail";
+const SUBMISSION_CAPABILITY: &str = "urn:ietf:params:jmap:submission";
+
+/// A sender identity of a Fastmail account
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct FastmailIdentity {
+    pub id: String,
+    pub email: String,
+    pub display_name: String,
+}
+
 pub async fn generate(
-    http: &reqwest::Client,
+    http: &impl HttpClientTrait,
     api_token: String,
+    identity_id: Option<String>,
     website: Option<String>,
 ) -> Result<String, UsernameError> {
-    generate_internal(http, api_token, website, "https://api.fastmail.com".into()).await
+    generate_internal(http, api_token, identity_id, website, API_URL.into()).await
+}
+
+pub async fn list_identities(
+    http: &reqwest::Client,
+    api_token: String,
+) -> Result<Vec<FastmailIdentity>, UsernameError> {
+    list_identities_internal(http, &api_token, API_URL).await
 }
 
 #[cfg(test)]
 pub async fn generate_with_api_url(
-    http: &reqwest::Client,
+    http: &impl HttpClientTrait,
     api_token: String,
     website: Option<String>,
     api_url: String,
 ) -> Result<String, UsernameError> {
-    generate_internal(http, api_token, website, api_url).await
+    generate_internal(http, api_token, None, website, api_url).await
 }
 
 async fn generate_internal(
-    http: &reqwest::Client,
+    http: &impl HttpClientTrait,
     api_token: String,
+    identity_id: Option<String>,
     website: Option<String>,
     api_url: String,
 ) -> Result<String, UsernameError> {
-    let account_id = get_account_id(http, &api_token, &api_url).await?;
+    if let Some(identity_id) = &identity_id {
+        let identities = list_identities_internal(http, &api_token, &api_url).await?;
+        if !identities.iter().any(|identity| &identity.id == identity_id) {
+            return Err(UsernameError::InvalidIdentity {
+                identity_id: identity_id.clone(),
+            });
+        }
+    }
+
+    let account_id = get_account_id(http, &api_token, &api_url, MASKED_EMAIL_CAPABILITY).await?;
+
+    let mut masked_email = json!({
+        "state": "enabled",
+        "description": "",
+        "forDomain": website,
+        "emailPrefix": null,
+    });
+    if let Some(identity_id) = identity_id {
+        masked_email["identityId"] = json!(identity_id);
+    }
 
-    let response = http
+    super::with_retries(|| create_masked_email(http, &api_token, &account_id, &masked_email, &api_url)).await
+}
+
+async fn create_masked_email(
+    http: &impl HttpClientTrait,
+    api_token: &str,
+    account_id: &str,
+    masked_email: &serde_json::Value,
+    api_url: &str,
+) -> Result<String, UsernameError> {
+    let request = http
         .post(format!("{api_url}/jmap/api/"))
         .header(CONTENT_TYPE, "application/json")
         .bearer_auth(api_token)
@@ -41,19 +92,16 @@ async fn generate_internal(
                 "MaskedEmail/set", {
                     "accountId": account_id,
                     "create": {
-                        "new-masked-email": {
-                            "state": "enabled",
-                            "description": "",
-                            "forDomain": website,
-                            "emailPrefix": null,
-                        },
+                        "new-masked-email": masked_email,
                     },
                 },
                 "0",
             ]],
         }))
-        .send()
-        .await?;
+        .build()?;
+    let response = http.execute(request).await?;
+
+    super::check_service_available(response.status())?;
 
     let status_code = response.status();
     if status_code == StatusCode::UNAUTHORIZED {
@@ -68,13 +116,12 @@ async fn generate_internal(
     let response_value = r.get(1);
 
     if method_response == Some("MaskedEmail/set") {
-        if let Some(email) = response_value
+        if let Some(created) = response_value
             .and_then(|r| r.get("created"))
             .and_then(|r| r.get("new-masked-email"))
-            .and_then(|r| r.get("email"))
-            .and_then(|r| r.as_str())
         {
-            return Ok(email.to_owned());
+            let email = created.get("email").and_then(|r| r.as_str()).map(str::to_owned);
+            return super::require_alias("Fastmail", email);
         };
 
         let error_description = response_value
@@ -103,21 +150,92 @@ async fn generate_internal(
     Err(UsernameError::Unknown)
 }
 
+async fn list_identities_internal(
+    http: &impl HttpClientTrait,
+    api_token: &str,
+    api_url: &str,
+) -> Result<Vec<FastmailIdentity>, UsernameError> {
+    let account_id = get_account_id(http, api_token, api_url, SUBMISSION_CAPABILITY).await?;
+
+    let request = http
+        .post(format!("{api_url}/jmap/api/"))
+        .header(CONTENT_TYPE, "application/json")
+        .bearer_auth(api_token)
+        .json(&json!({
+            "using": ["urn:ietf:params:jmap:core", SUBMISSION_CAPABILITY],
+            "methodCalls": [[
+                "Identity/get", {
+                    "accountId": account_id,
+                    "ids": null,
+                },
+                "0",
+            ]],
+        }))
+        .build()?;
+    let response = http.execute(request).await?;
+
+    super::check_service_available(response.status())?;
+
+    let status_code = response.status();
+    if status_code == StatusCode::UNAUTHORIZED {
+        return Err(UsernameError::InvalidApiKey);
+    }
+
+    let response_json: serde_json::Value = response.json().await?;
+    let Some(r) = response_json.get("methodResponses").and_then(|r| r.get(0)) else {
+        return Err(UsernameError::Unknown);
+    };
+    let method_response = r.get(0).and_then(|r| r.as_str());
+    let response_value = r.get(1);
+
+    if method_response == Some("Identity/get") {
+        let Some(list) = response_value.and_then(|r| r.get("list")).and_then(|r| r.as_array()) else {
+            return Err(UsernameError::Unknown);
+        };
+        let field = |identity: &serde_json::Value, name: &str| {
+            identity.get(name).and_then(|v| v.as_str()).unwrap_or_default().to_owned()
+        };
+        return Ok(list
+            .iter()
+            .map(|identity| FastmailIdentity {
+                id: field(identity, "id"),
+                email: field(identity, "email"),
+                display_name: field(identity, "name"),
+            })
+            .collect());
+    } else if method_response == Some("error") {
+        let error_description = response_value
+            .and_then(|r| r.get("description"))
+            .and_then(|r| r.as_str())
+            .unwrap_or("Unknown error");
+
+        return Err(UsernameError::ResponseContent {
+            status: status_code,
+            message: error_description.to_owned(),
+        });
+    }
+
+    Err(UsernameError::Unknown)
+}
+
 async fn get_account_id(
-    client: &reqwest::Client,
+    client: &impl HttpClientTrait,
     api_token: &str,
     api_url: &str,
+    capability: &str,
 ) -> Result<String, UsernameError> {
     #[derive(serde::Deserialize)]
     struct Response {
         #[serde(rename = "primaryAccounts")]
         primary_accounts: HashMap<String, String>,
     }
-    let response = client
+    let request = client
         .get(format!("{api_url}/.well-known/jmap"))
         .bearer_auth(api_token)
-        .send()
-        .await?;
+        .build()?;
+    let response = client.execute(request).await?;
+
+    super::check_service_available(response.status())?;
 
     if response.status() == StatusCode::UNAUTHORIZED {
         return Err(UsernameError::InvalidApiKey);
@@ -128,7 +246,7 @@ async fn get_account_id(
 
     Ok(response
         .primary_accounts
-        .remove("https://www.fastmail.com/dev/maskedemail")
+        .remove(capability)
         .unwrap_or_default())
 }
 
@@ -203,4 +321,166 @@ mod tests {
 
         server.verify().await;
     }
-} 
\ No newline at end of file
+
+    async fn mock_identities(server: &wiremock::MockServer) {
+        use wiremock::{matchers, Mock, ResponseTemplate};
+
+        server
+            .register(
+                Mock::given(matchers::path("/.well-known/jmap"))
+                    .and(matchers::method("GET"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                        "primaryAccounts": {
+                            "https://www.fastmail.com/dev/maskedemail": "masked_account",
+                            "urn:ietf:params:jmap:submission": "submission_account"
+                        }
+                    }))),
+            )
+            .await;
+        server
+            .register(
+                Mock::given(matchers::path("/jmap/api/"))
+                    .and(matchers::method("POST"))
+                    .and(matchers::body_partial_json(json!({
+                        "methodCalls": [["Identity/get", { "accountId": "submission_account" }, "0"]]
+                    })))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                        "methodResponses": [
+                            ["Identity/get", {
+                                "list": [
+                                    { "id": "I1", "email": "me@fastmail.com", "name": "Me" },
+                                    { "id": "I2", "email": "work@example.com", "name": "Work" }
+                                ]
+                            }, "0"]
+                        ]
+                    }))),
+            )
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_list_identities() {
+        let server = wiremock::MockServer::start().await;
+        mock_identities(&server).await;
+
+        let identities = super::list_identities_internal(&reqwest::Client::new(), "MY_TOKEN", &server.uri())
+            .await
+            .unwrap();
+        assert_eq!(
+            identities,
+            vec![
+                super::FastmailIdentity {
+                    id: "I1".into(),
+                    email: "me@fastmail.com".into(),
+                    display_name: "Me".into(),
+                },
+                super::FastmailIdentity {
+                    id: "I2".into(),
+                    email: "work@example.com".into(),
+                    display_name: "Work".into(),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_generate_with_identity() {
+        use wiremock::{matchers, Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+        mock_identities(&server).await;
+        server
+            .register(
+                Mock::given(matchers::path("/jmap/api/"))
+                    .and(matchers::method("POST"))
+                    .and(matchers::body_partial_json(json!({
+                        "methodCalls": [["MaskedEmail/set", {
+                            "accountId": "masked_account",
+                            "create": { "new-masked-email": { "identityId": "I2" } }
+                        }, "0"]]
+                    })))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                        "methodResponses": [
+                            ["MaskedEmail/set", {"created": {"new-masked-email": {"email": "work.masked@example.com"}}}]
+                        ]
+                    })))
+                    .expect(1),
+            )
+            .await;
+
+        let address = super::generate_internal(
+            &reqwest::Client::new(),
+            "MY_TOKEN".into(),
+            Some("I2".into()),
+            Some("example.com".into()),
+            server.uri(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(address, "work.masked@example.com");
+
+        let error = super::generate_internal(
+            &reqwest::Client::new(),
+            "MY_TOKEN".into(),
+            Some("I3".into()),
+            None,
+            server.uri(),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(error, UsernameError::InvalidIdentity { ref identity_id } if identity_id == "I3"));
+
+        server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn test_null_email_retried() {
+        use wiremock::{matchers, Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+
+        server
+            .register(
+                Mock::given(matchers::path("/.well-known/jmap"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                        "primaryAccounts": {
+                            "https://www.fastmail.com/dev/maskedemail": "ca0a4e09-c266-4f6f-845c-958db5090f09"
+                        }
+                    })))
+                    .expect(1),
+            )
+            .await;
+
+        // The first answer creates the masked email without an address, the retry gets one
+        server
+            .register(
+                Mock::given(matchers::path("/jmap/api/"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                        "methodResponses": [
+                            ["MaskedEmail/set", {"created": {"new-masked-email": {"email": null}}}]
+                        ]
+                    })))
+                    .up_to_n_times(1)
+                    .expect(1),
+            )
+            .await;
+        server
+            .register(
+                Mock::given(matchers::path("/jmap/api/"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                        "methodResponses": [
+                            ["MaskedEmail/set", {"created": {"new-masked-email": {"email": "9f823dq23d123ds@mydomain.com"}}}]
+                        ]
+                    })))
+                    .expect(1),
+            )
+            .await;
+
+        let address = super::generate_with_api_url(&reqwest::Client::new(), "MY_TOKEN".into(), None, server.uri())
+            .await
+            .unwrap();
+        assert_eq!(address, "9f823dq23d123ds@mydomain.com");
+
+        server.verify().await;
+    }
+}
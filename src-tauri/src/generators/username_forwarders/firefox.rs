@@ -3,19 +3,59 @@ use reqwest::{
     StatusCode,
 };
 
+use super::HttpClientTrait;
 use crate::generators::username::UsernameError;
 
+const API_URL: &str = "https://relay.firefox.com";
+
+/// Remaining alias allowance of a Firefox Relay account
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct FirefoxRelayQuota {
+    /// `None` when the account has no alias limit
+    #[serde(default)]
+    pub aliases_remaining: Option<u32>,
+    pub has_premium: bool,
+}
+
 pub async fn generate(
-    http: &reqwest::Client,
+    http: &impl HttpClientTrait,
     api_token: String,
     website: Option<String>,
 ) -> Result<String, UsernameError> {
-    generate_internal(http, api_token, website, "https://relay.firefox.com".into()).await
+    generate_internal(http, api_token, website, API_URL.into()).await
+}
+
+pub async fn get_quota_remaining(
+    http: &reqwest::Client,
+    api_token: String,
+) -> Result<FirefoxRelayQuota, UsernameError> {
+    get_quota_internal(http, &api_token, API_URL).await
+}
+
+async fn get_quota_internal(
+    http: &impl HttpClientTrait,
+    api_token: &str,
+    api_url: &str,
+) -> Result<FirefoxRelayQuota, UsernameError> {
+    let request = http
+        .get(format!("{api_url}/api/v1/terms-accepted-user/"))
+        .header(header::AUTHORIZATION, format!("Token {api_token}"))
+        .build()?;
+    let response = http.execute(request).await?;
+
+    super::check_service_available(response.status())?;
+
+    if response.status() == StatusCode::UNAUTHORIZED {
+        return Err(UsernameError::InvalidApiKey);
+    }
+
+    let response = response.error_for_status()?;
+    Ok(response.json().await?)
 }
 
 #[cfg(test)]
 pub async fn generate_with_api_url(
-    http: &reqwest::Client,
+    http: &impl HttpClientTrait,
     api_token: String,
     website: Option<String>,
     api_url: String,
@@ -24,22 +64,39 @@ pub async fn generate_with_api_url(
 }
 
 async fn generate_internal(
-    http: &reqwest::Client,
+    http: &impl HttpClientTrait,
     api_token: String,
     website: Option<String>,
     api_url: String,
+) -> Result<String, UsernameError> {
+    // The quota lookup is best effort, if it fails the create request reports the real error
+    if let Ok(quota) = get_quota_internal(http, &api_token, &api_url).await {
+        if quota.aliases_remaining == Some(0) {
+            tracing::warn!("Firefox Relay alias limit reached, upgrade to premium or delete an existing alias");
+            return Err(UsernameError::RateLimitExceeded);
+        }
+    }
+
+    let description = super::format_description_ff(&website);
+    super::with_retries(|| create_alias(http, &api_token, website.as_deref(), &description, &api_url)).await
+}
+
+async fn create_alias(
+    http: &impl HttpClientTrait,
+    api_token: &str,
+    website: Option<&str>,
+    description: &str,
+    api_url: &str,
 ) -> Result<String, UsernameError> {
     #[derive(serde::Serialize)]
-    struct Request {
+    struct Request<'a> {
         enabled: bool,
         #[serde(skip_serializing_if = "Option::is_none")]
-        generated_for: Option<String>,
-        description: String,
+        generated_for: Option<&'a str>,
+        description: &'a str,
     }
 
-    let description = super::format_description_ff(&website);
-
-    let response = http
+    let request = http
         .post(format!("{api_url}/api/v1/relayaddresses/"))
         .header(header::AUTHORIZATION, format!("Token {api_token}"))
         .json(&Request {
@@ -47,8 +104,10 @@ async fn generate_internal(
             generated_for: website,
             description,
         })
-        .send()
-        .await?;
+        .build()?;
+    let response = http.execute(request).await?;
+
+    super::check_service_available(response.status())?;
 
     if response.status() == StatusCode::UNAUTHORIZED {
         return Err(UsernameError::InvalidApiKey);
@@ -59,11 +118,11 @@ async fn generate_internal(
 
     #[derive(serde::Deserialize)]
     struct Response {
-        full_address: String,
+        full_address: Option<String>,
     }
     let response: Response = response.json().await?;
 
-    Ok(response.full_address)
+    super::require_alias("Firefox", response.full_address)
 }
 
 #[cfg(test)]
@@ -145,6 +204,107 @@ mod tests {
         server.verify().await;
     }
 
+    #[tokio::test]
+    async fn test_quota_remaining() {
+        use wiremock::{matchers, Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+
+        server
+            .register(
+                Mock::given(matchers::path("/api/v1/terms-accepted-user/"))
+                    .and(matchers::method("GET"))
+                    .and(matchers::header("Authorization", "Token FREE_TOKEN"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                        "aliases_remaining": 3,
+                        "has_premium": false
+                    })))
+                    .expect(1),
+            )
+            .await;
+        server
+            .register(
+                Mock::given(matchers::path("/api/v1/terms-accepted-user/"))
+                    .and(matchers::method("GET"))
+                    .and(matchers::header("Authorization", "Token PREMIUM_TOKEN"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                        "has_premium": true
+                    })))
+                    .expect(1),
+            )
+            .await;
+        server
+            .register(
+                Mock::given(matchers::path("/api/v1/terms-accepted-user/"))
+                    .and(matchers::method("GET"))
+                    .and(matchers::header("Authorization", "Token MY_FAKE_TOKEN"))
+                    .respond_with(ResponseTemplate::new(401))
+                    .expect(1),
+            )
+            .await;
+
+        let client = reqwest::Client::new();
+        let uri = server.uri();
+
+        let quota = super::get_quota_internal(&client, "FREE_TOKEN", &uri).await.unwrap();
+        assert_eq!(
+            quota,
+            super::FirefoxRelayQuota {
+                aliases_remaining: Some(3),
+                has_premium: false
+            }
+        );
+
+        let quota = super::get_quota_internal(&client, "PREMIUM_TOKEN", &uri).await.unwrap();
+        assert_eq!(quota.aliases_remaining, None);
+        assert!(quota.has_premium);
+
+        let error = super::get_quota_internal(&client, "MY_FAKE_TOKEN", &uri).await.unwrap_err();
+        assert!(matches!(error, UsernameError::InvalidApiKey));
+
+        server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn test_mock_quota_exhausted() {
+        use wiremock::{matchers, Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+
+        server
+            .register(
+                Mock::given(matchers::path("/api/v1/terms-accepted-user/"))
+                    .and(matchers::method("GET"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                        "aliases_remaining": 0,
+                        "has_premium": false
+                    })))
+                    .expect(1),
+            )
+            .await;
+        // No alias must be created once the quota is used up
+        server
+            .register(
+                Mock::given(matchers::path("/api/v1/relayaddresses/"))
+                    .and(matchers::method("POST"))
+                    .respond_with(ResponseTemplate::new(201))
+                    .expect(0),
+            )
+            .await;
+
+        let error = super::generate_with_api_url(
+            &reqwest::Client::new(),
+            "MY_TOKEN".into(),
+            Some("example.com".into()),
+            server.uri(),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(error, UsernameError::RateLimitExceeded));
+
+        server.verify().await;
+    }
+
     #[tokio::test]
     async fn test_mock_invalid_token() {
         use wiremock::{matchers, Mock, ResponseTemplate};
@@ -180,4 +340,64 @@ mod tests {
 
         server.verify().await;
     }
-} 
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_mock_http_client() {
+        use reqwest::StatusCode;
+
+        use crate::generators::username_forwarders::test_utils::MockHttpClient;
+
+        let http = MockHttpClient::new(vec![
+            (StatusCode::OK, json!({ "aliases_remaining": 3, "has_premium": false })),
+            (StatusCode::CREATED, json!({ "full_address": "ofuj4d4qw@mozmail.com" })),
+            (StatusCode::OK, json!({ "aliases_remaining": 0, "has_premium": false })),
+        ]);
+
+        let address = super::generate(&http, "MY_TOKEN".into(), Some("example.com".into()))
+            .await
+            .unwrap();
+        assert_eq!(address, "ofuj4d4qw@mozmail.com");
+
+        // Once the quota is used up no create request is sent
+        let error = super::generate(&http, "MY_TOKEN".into(), None).await.unwrap_err();
+        assert!(matches!(error, UsernameError::RateLimitExceeded));
+
+        let requests = http.requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[1].method(), reqwest::Method::POST);
+        assert_eq!(requests[1].headers()["Authorization"], "Token MY_TOKEN");
+    }
+
+    #[tokio::test]
+    async fn test_empty_address_retried() {
+        use wiremock::{matchers, Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+
+        // The first answer is an empty alias, the retry gets a real one
+        server
+            .register(
+                Mock::given(matchers::path("/api/v1/relayaddresses/"))
+                    .and(matchers::method("POST"))
+                    .respond_with(ResponseTemplate::new(201).set_body_json(json!({ "full_address": "  " })))
+                    .up_to_n_times(1)
+                    .expect(1),
+            )
+            .await;
+        server
+            .register(
+                Mock::given(matchers::path("/api/v1/relayaddresses/"))
+                    .and(matchers::method("POST"))
+                    .respond_with(ResponseTemplate::new(201).set_body_json(json!({ "full_address": "ofuj4d4qw@mozmail.com" })))
+                    .expect(1),
+            )
+            .await;
+
+        let address = super::generate_with_api_url(&reqwest::Client::new(), "MY_TOKEN".into(), None, server.uri())
+            .await
+            .unwrap();
+        assert_eq!(address, "ofuj4d4qw@mozmail.com");
+
+        server.verify().await;
+    }
+}
@@ -1,14 +1,168 @@
 use reqwest::{header::CONTENT_TYPE, StatusCode};
 
+use super::HttpClientTrait;
 use crate::generators::username::UsernameError;
 
+const API_URL: &str = "https://api.forwardemail.net";
+
+/// DNS verification state of a ForwardEmail domain
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct DomainVerificationStatus {
+    pub verified: bool,
+    pub mx_records_ok: bool,
+    pub txt_records_ok: bool,
+    #[serde(default)]
+    pub errors: Vec<String>,
+}
+
+/// An existing alias on a ForwardEmail domain
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ForwardEmailAlias {
+    pub id: String,
+    pub name: String,
+    pub address: String,
+    pub created_at: String,
+    pub is_enabled: bool,
+}
+
 pub async fn generate(
-    http: &reqwest::Client,
+    http: &impl HttpClientTrait,
     api_token: String,
     domain: String,
     website: Option<String>,
 ) -> Result<String, UsernameError> {
-    generate_internal(http, api_token, domain, website, "https://api.forwardemail.net".into()).await
+    generate_internal(http, api_token, domain, website, API_URL.into()).await
+}
+
+pub async fn verify_domain(
+    http: &reqwest::Client,
+    api_token: String,
+    domain: String,
+) -> Result<DomainVerificationStatus, UsernameError> {
+    verify_domain_internal(http, &api_token, &domain, API_URL).await
+}
+
+/// Fetch the aliases of a domain. `page` and `limit` are passed through to the API, which uses its
+/// own defaults when they are unset.
+pub async fn list_aliases(
+    http: &reqwest::Client,
+    api_token: String,
+    domain: String,
+    page: Option<u32>,
+    limit: Option<u32>,
+) -> Result<Vec<ForwardEmailAlias>, UsernameError> {
+    list_aliases_internal(http, &api_token, &domain, page, limit, API_URL).await
+}
+
+pub async fn delete_alias(
+    http: &reqwest::Client,
+    api_token: String,
+    domain: String,
+    alias_id: String,
+) -> Result<(), UsernameError> {
+    delete_alias_internal(http, &api_token, &domain, &alias_id, API_URL).await
+}
+
+async fn list_aliases_internal(
+    http: &reqwest::Client,
+    api_token: &str,
+    domain: &str,
+    page: Option<u32>,
+    limit: Option<u32>,
+    api_url: &str,
+) -> Result<Vec<ForwardEmailAlias>, UsernameError> {
+    let query: Vec<(&str, u32)> = [("page", page), ("limit", limit)]
+        .into_iter()
+        .filter_map(|(key, value)| value.map(|value| (key, value)))
+        .collect();
+
+    let response = http
+        .get(format!("{api_url}/v1/domains/{domain}/aliases"))
+        .query(&query)
+        .basic_auth(api_token, None::<String>)
+        .send()
+        .await?;
+
+    super::check_service_available(response.status())?;
+
+    match response.status() {
+        StatusCode::UNAUTHORIZED => return Err(UsernameError::InvalidApiKey),
+        StatusCode::NOT_FOUND => return Err(UsernameError::DomainRejected),
+        _ => {}
+    }
+
+    #[derive(serde::Deserialize)]
+    struct Alias {
+        id: String,
+        name: String,
+        created_at: String,
+        is_enabled: bool,
+    }
+    let response = response.error_for_status()?;
+    let aliases: Vec<Alias> = response.json().await?;
+
+    Ok(aliases
+        .into_iter()
+        .map(|alias| ForwardEmailAlias {
+            address: format!("{}@{}", alias.name, domain),
+            id: alias.id,
+            name: alias.name,
+            created_at: alias.created_at,
+            is_enabled: alias.is_enabled,
+        })
+        .collect())
+}
+
+async fn delete_alias_internal(
+    http: &reqwest::Client,
+    api_token: &str,
+    domain: &str,
+    alias_id: &str,
+    api_url: &str,
+) -> Result<(), UsernameError> {
+    let response = http
+        .delete(format!(
+            "{api_url}/v1/domains/{domain}/aliases/{}",
+            urlencoding::encode(alias_id)
+        ))
+        .basic_auth(api_token, None::<String>)
+        .send()
+        .await?;
+
+    super::check_service_available(response.status())?;
+
+    match response.status() {
+        StatusCode::UNAUTHORIZED => return Err(UsernameError::InvalidApiKey),
+        StatusCode::NOT_FOUND => return Err(UsernameError::AliasNotFound),
+        _ => {}
+    }
+
+    response.error_for_status()?;
+    Ok(())
+}
+
+async fn verify_domain_internal(
+    http: &impl HttpClientTrait,
+    api_token: &str,
+    domain: &str,
+    api_url: &str,
+) -> Result<DomainVerificationStatus, UsernameError> {
+    let request = http
+        .get(format!("{api_url}/v1/domains/{domain}/verify"))
+        .basic_auth(api_token, None::<String>)
+        .build()?;
+    let response = http.execute(request).await?;
+
+    super::check_service_available(response.status())?;
+
+    match response.status() {
+        StatusCode::UNAUTHORIZED => return Err(UsernameError::InvalidApiKey),
+        StatusCode::NOT_FOUND => return Err(UsernameError::DomainRejected),
+        _ => {}
+    }
+
+    let response = response.error_for_status()?;
+    Ok(response.json().await?)
 }
 
 #[cfg(test)]
@@ -23,21 +177,40 @@ pub async fn generate_with_api_url(
 }
 
 async fn generate_internal(
-    http: &reqwest::Client,
+    http: &impl HttpClientTrait,
     api_token: String,
     domain: String,
     website: Option<String>,
     api_url: String,
 ) -> Result<String, UsernameError> {
+    // The verification lookup is best effort, if it fails the create request reports the real error
+    if let Ok(status) = verify_domain_internal(http, &api_token, &domain, &api_url).await {
+        if !status.verified {
+            tracing::warn!(%domain, errors = %status.errors.join("; "), "ForwardEmail domain is not verified");
+            return Err(UsernameError::DomainRejected);
+        }
+    }
+
     let description = super::format_description(&website);
+    super::with_retries(|| create_alias(http, &api_token, &domain, website.as_deref(), &description, &api_url))
+        .await
+}
 
+async fn create_alias(
+    http: &impl HttpClientTrait,
+    api_token: &str,
+    domain: &str,
+    website: Option<&str>,
+    description: &str,
+    api_url: &str,
+) -> Result<String, UsernameError> {
     #[derive(serde::Serialize)]
-    struct Request {
-        labels: Option<String>,
-        description: String,
+    struct Request<'a> {
+        labels: Option<&'a str>,
+        description: &'a str,
     }
 
-    let response = http
+    let request = http
         .post(format!("{api_url}/v1/domains/{domain}/aliases"))
         .header(CONTENT_TYPE, "application/json")
         .basic_auth(api_token, None::<String>)
@@ -45,9 +218,10 @@ async fn generate_internal(
             description,
             labels: website,
         })
-        .send()
-        .await
-        .map_err(UsernameError::Http)?;
+        .build()?;
+    let response = http.execute(request).await?;
+
+    super::check_service_available(response.status())?;
 
     if response.status() == StatusCode::UNAUTHORIZED {
         return Err(UsernameError::InvalidApiKey);
@@ -66,17 +240,17 @@ async fn generate_internal(
         error: Option<String>,
     }
     let status = response.status();
-    let response: Response = response.json().await.map_err(UsernameError::Http)?;
+    let response: Response = response.json().await?;
 
+    // An alias object always carries its domain, the name is missing when the service misbehaves
     if status.is_success() {
-        if let Some(name) = response.name {
-            if let Some(response_domain) = response.domain {
-                return Ok(format!(
-                    "{}@{}",
-                    name,
-                    response_domain.name.unwrap_or(domain)
-                ));
-            }
+        if let Some(response_domain) = response.domain {
+            let name = super::require_alias("ForwardEmail", response.name)?;
+            return Ok(format!(
+                "{}@{}",
+                name,
+                response_domain.name.as_deref().unwrap_or(domain)
+            ));
         }
     }
 
@@ -174,4 +348,225 @@ mod tests {
 
         server.verify().await;
     }
-} 
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_list_aliases() {
+        use wiremock::{matchers, Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+
+        server
+            .register(
+                Mock::given(matchers::path("/v1/domains/mydomain.com/aliases"))
+                    .and(matchers::method("GET"))
+                    .and(matchers::query_param("page", "2"))
+                    .and(matchers::query_param("limit", "1"))
+                    .and(matchers::header("Authorization", "Basic TVlfVE9LRU46"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                        {
+                            "id": "6123b6fc1e6a8a0012a0b4e1",
+                            "name": "wertg8ad",
+                            "created_at": "2024-01-01T12:00:00.000Z",
+                            "is_enabled": true,
+                            "labels": ["example.com"]
+                        }
+                    ])))
+                    .expect(1),
+            )
+            .await;
+        server
+            .register(
+                Mock::given(matchers::path("/v1/domains/mydomain.com/aliases"))
+                    .and(matchers::method("GET"))
+                    .and(matchers::query_param_is_missing("page"))
+                    .and(matchers::query_param_is_missing("limit"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+                    .expect(1),
+            )
+            .await;
+
+        let client = reqwest::Client::new();
+        let aliases = super::list_aliases_internal(&client, "MY_TOKEN", "mydomain.com", Some(2), Some(1), &server.uri())
+            .await
+            .unwrap();
+        assert_eq!(
+            aliases,
+            vec![super::ForwardEmailAlias {
+                id: "6123b6fc1e6a8a0012a0b4e1".into(),
+                name: "wertg8ad".into(),
+                address: "wertg8ad@mydomain.com".into(),
+                created_at: "2024-01-01T12:00:00.000Z".into(),
+                is_enabled: true,
+            }]
+        );
+
+        let aliases = super::list_aliases_internal(&client, "MY_TOKEN", "mydomain.com", None, None, &server.uri())
+            .await
+            .unwrap();
+        assert!(aliases.is_empty());
+
+        server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn test_delete_alias() {
+        use wiremock::{matchers, Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+
+        server
+            .register(
+                Mock::given(matchers::path("/v1/domains/mydomain.com/aliases/6123b6fc1e6a8a0012a0b4e1"))
+                    .and(matchers::method("DELETE"))
+                    .and(matchers::header("Authorization", "Basic TVlfVE9LRU46"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+                    .expect(1),
+            )
+            .await;
+        server
+            .register(
+                Mock::given(matchers::path("/v1/domains/mydomain.com/aliases/missing"))
+                    .and(matchers::method("DELETE"))
+                    .respond_with(ResponseTemplate::new(404))
+                    .expect(1),
+            )
+            .await;
+
+        let client = reqwest::Client::new();
+        super::delete_alias_internal(&client, "MY_TOKEN", "mydomain.com", "6123b6fc1e6a8a0012a0b4e1", &server.uri())
+            .await
+            .unwrap();
+
+        let error = super::delete_alias_internal(&client, "MY_TOKEN", "mydomain.com", "missing", &server.uri())
+            .await
+            .unwrap_err();
+        assert!(matches!(error, UsernameError::AliasNotFound));
+
+        server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn test_verify_domain() {
+        use wiremock::{matchers, Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+
+        server
+            .register(
+                Mock::given(matchers::path("/v1/domains/mydomain.com/verify"))
+                    .and(matchers::method("GET"))
+                    .and(matchers::header("Authorization", "Basic TVlfVE9LRU46"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                        "verified": true,
+                        "mx_records_ok": true,
+                        "txt_records_ok": true,
+                        "errors": []
+                    })))
+                    .expect(1),
+            )
+            .await;
+        server
+            .register(
+                Mock::given(matchers::path("/v1/domains/unknown.com/verify"))
+                    .and(matchers::method("GET"))
+                    .respond_with(ResponseTemplate::new(404))
+                    .expect(1),
+            )
+            .await;
+
+        let status = super::verify_domain_internal(&reqwest::Client::new(), "MY_TOKEN", "mydomain.com", &server.uri())
+            .await
+            .unwrap();
+        assert!(status.verified && status.mx_records_ok && status.txt_records_ok);
+        assert!(status.errors.is_empty());
+
+        let error = super::verify_domain_internal(&reqwest::Client::new(), "MY_TOKEN", "unknown.com", &server.uri())
+            .await
+            .unwrap_err();
+        assert!(matches!(error, UsernameError::DomainRejected));
+
+        server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn test_unverified_domain_rejected() {
+        use wiremock::{matchers, Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+
+        server
+            .register(
+                Mock::given(matchers::path("/v1/domains/mydomain.com/verify"))
+                    .and(matchers::method("GET"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                        "verified": false,
+                        "mx_records_ok": false,
+                        "txt_records_ok": true,
+                        "errors": ["MX records are not configured"]
+                    })))
+                    .expect(1),
+            )
+            .await;
+        // No alias must be created on an unverified domain
+        server
+            .register(
+                Mock::given(matchers::path("/v1/domains/mydomain.com/aliases"))
+                    .and(matchers::method("POST"))
+                    .respond_with(ResponseTemplate::new(201))
+                    .expect(0),
+            )
+            .await;
+
+        let error = super::generate_with_api_url(
+            &reqwest::Client::new(),
+            "MY_TOKEN".into(),
+            "mydomain.com".into(),
+            None,
+            server.uri(),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(error, UsernameError::DomainRejected));
+
+        server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn test_empty_name_retried() {
+        use wiremock::{matchers, Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+
+        // The first answer is an empty alias, the retry gets a real one
+        server
+            .register(
+                Mock::given(matchers::path("/v1/domains/mydomain.com/aliases"))
+                    .and(matchers::method("POST"))
+                    .respond_with(ResponseTemplate::new(201).set_body_json(json!({ "name": "", "domain": { "name": "mydomain.com" } })))
+                    .up_to_n_times(1)
+                    .expect(1),
+            )
+            .await;
+        server
+            .register(
+                Mock::given(matchers::path("/v1/domains/mydomain.com/aliases"))
+                    .and(matchers::method("POST"))
+                    .respond_with(ResponseTemplate::new(201).set_body_json(json!({ "name": "wertg8ad", "domain": { "name": "mydomain.com" } })))
+                    .expect(1),
+            )
+            .await;
+
+        let address = super::generate_with_api_url(
+            &reqwest::Client::new(),
+            "MY_TOKEN".into(),
+            "mydomain.com".into(),
+            None,
+            server.uri(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(address, "wertg8ad@mydomain.com");
+
+        server.verify().await;
+    }
+}
@@ -0,0 +1,252 @@
+use reqwest::{header::CONTENT_TYPE, StatusCode};
+
+use super::HttpClientTrait;
+use crate::generators::username::UsernameError;
+
+const API_URL: &str = "https://dnt.abine.com";
+
+pub async fn generate(http: &impl HttpClientTrait, api_token: String) -> Result<String, UsernameError> {
+    generate_with_api_url(http, api_token, API_URL.into()).await
+}
+
+pub async fn generate_with_api_url(
+    http: &impl HttpClientTrait,
+    api_token: String,
+    api_url: String,
+) -> Result<String, UsernameError> {
+    super::with_retries(|| create_alias(http, &api_token, &api_url)).await
+}
+
+async fn create_alias(http: &impl HttpClientTrait, api_token: &str, api_url: &str) -> Result<String, UsernameError> {
+    let request = http
+        .post(format!("{api_url}/account/email/mask"))
+        .header(CONTENT_TYPE, "application/json")
+        .bearer_auth(api_token)
+        .build()?;
+    let response = http.execute(request).await?;
+
+    super::check_service_available(response.status())?;
+
+    match response.status() {
+        StatusCode::UNAUTHORIZED => {
+            return Err(UsernameError::InvalidApiKey);
+        }
+        // Masked emails are not available on every plan
+        StatusCode::FORBIDDEN => {
+            return Err(UsernameError::PermissionDenied);
+        }
+        _ => {}
+    }
+
+    // Throw any other errors
+    response.error_for_status_ref()?;
+
+    #[derive(serde::Deserialize)]
+    struct Response {
+        masked_email: Option<String>,
+    }
+    let response: Response = response.json().await?;
+
+    super::require_alias("IronVest", response.masked_email)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::generators::username::UsernameError;
+
+    #[tokio::test]
+    async fn test_mock_server() {
+        use wiremock::{matchers, Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+
+        // Mock the request to the IronVest API, and verify that the correct request is made
+        server
+            .register(
+                Mock::given(matchers::path("/account/email/mask"))
+                    .and(matchers::method("POST"))
+                    .and(matchers::header("Content-Type", "application/json"))
+                    .and(matchers::header("Authorization", "Bearer MY_TOKEN"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                        "masked_email": "k7vq2m@opayq.com"
+                    })))
+                    .expect(1),
+            )
+            .await;
+
+        // Mock an invalid token request
+        server
+            .register(
+                Mock::given(matchers::path("/account/email/mask"))
+                    .and(matchers::method("POST"))
+                    .and(matchers::header("Authorization", "Bearer MY_FAKE_TOKEN"))
+                    .respond_with(ResponseTemplate::new(401))
+                    .expect(1),
+            )
+            .await;
+
+        let address = super::generate_with_api_url(
+            &reqwest::Client::new(),
+            "MY_TOKEN".into(),
+            format!("http://{}", server.address()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(address, "k7vq2m@opayq.com");
+
+        let fake_token_error = super::generate_with_api_url(
+            &reqwest::Client::new(),
+            "MY_FAKE_TOKEN".into(),
+            format!("http://{}", server.address()),
+        )
+        .await
+        .unwrap_err();
+
+        assert_eq!(
+            fake_token_error.to_string(),
+            UsernameError::InvalidApiKey.to_string()
+        );
+
+        server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn test_permission_denied() {
+        use wiremock::{matchers, Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+
+        server
+            .register(
+                Mock::given(matchers::path("/account/email/mask"))
+                    .and(matchers::method("POST"))
+                    .respond_with(ResponseTemplate::new(403).set_body_json(json!({
+                        "error": "Masked emails are not included in your plan"
+                    })))
+                    .expect(1),
+            )
+            .await;
+
+        let error = super::generate_with_api_url(&reqwest::Client::new(), "MY_TOKEN".into(), server.uri())
+            .await
+            .unwrap_err();
+        assert!(matches!(error, UsernameError::PermissionDenied));
+
+        server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn test_service_unavailable() {
+        use wiremock::{matchers, Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+
+        server
+            .register(
+                Mock::given(matchers::path("/account/email/mask"))
+                    .respond_with(ResponseTemplate::new(503))
+                    .expect(3),
+            )
+            .await;
+
+        let error = super::generate_with_api_url(&reqwest::Client::new(), "MY_TOKEN".into(), server.uri())
+            .await
+            .unwrap_err();
+        assert!(matches!(error, UsernameError::ServiceUnavailable));
+
+        server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn test_service_unavailable_retried() {
+        use wiremock::{matchers, Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+
+        // A single outage is retried transparently
+        server
+            .register(
+                Mock::given(matchers::path("/account/email/mask"))
+                    .respond_with(ResponseTemplate::new(503))
+                    .up_to_n_times(1)
+                    .expect(1),
+            )
+            .await;
+        server
+            .register(
+                Mock::given(matchers::path("/account/email/mask"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                        "masked_email": "k7vq2m@opayq.com"
+                    })))
+                    .expect(1),
+            )
+            .await;
+
+        let address = super::generate_with_api_url(&reqwest::Client::new(), "MY_TOKEN".into(), server.uri())
+            .await
+            .unwrap();
+        assert_eq!(address, "k7vq2m@opayq.com");
+
+        server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn test_unexpected_response() {
+        use wiremock::{matchers, Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+
+        // A success without the masked email, e.g. after an API change, is retried before giving up
+        server
+            .register(
+                Mock::given(matchers::path("/account/email/mask"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                        "email": "k7vq2m@opayq.com"
+                    })))
+                    .expect(3),
+            )
+            .await;
+
+        let error = super::generate_with_api_url(&reqwest::Client::new(), "MY_TOKEN".into(), server.uri())
+            .await
+            .unwrap_err();
+        assert!(matches!(error, UsernameError::EmptyResponse { service } if service == "IronVest"));
+
+        server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn test_empty_masked_email_retried() {
+        use wiremock::{matchers, Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+
+        // The first answer is an empty alias, the retry gets a real one
+        server
+            .register(
+                Mock::given(matchers::path("/account/email/mask"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "masked_email": "" })))
+                    .up_to_n_times(1)
+                    .expect(1),
+            )
+            .await;
+        server
+            .register(
+                Mock::given(matchers::path("/account/email/mask"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                        "masked_email": "k7vq2m@opayq.com"
+                    })))
+                    .expect(1),
+            )
+            .await;
+
+        let address = super::generate_with_api_url(&reqwest::Client::new(), "MY_TOKEN".into(), server.uri())
+            .await
+            .unwrap();
+        assert_eq!(address, "k7vq2m@opayq.com");
+
+        server.verify().await;
+    }
+}
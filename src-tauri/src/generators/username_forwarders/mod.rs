@@ -3,9 +3,100 @@ pub mod duckduckgo;
 pub mod fastmail;
 pub mod firefox;
 pub mod forwardemail;
+pub mod ironvest;
 pub mod simplelogin;
 
+#[cfg(test)]
+pub mod test_utils;
+
+use std::future::Future;
+
+use reqwest::{IntoUrl, Method, RequestBuilder, StatusCode};
+use tracing::Instrument;
+
+use crate::generators::username::UsernameError;
+
+/// HTTP client used by the forwarders. Requests are built with [`HttpClientTrait::request`] and
+/// sent with [`HttpClientTrait::execute`], so tests can answer them in process instead of through
+/// a mock server, see `test_utils::MockHttpClient`.
+pub trait HttpClientTrait: Sync {
+    fn request(&self, method: Method, url: impl IntoUrl) -> RequestBuilder;
+
+    fn execute(&self, request: reqwest::Request) -> impl Future<Output = Result<reqwest::Response, reqwest::Error>> + Send;
+
+    fn get(&self, url: impl IntoUrl) -> RequestBuilder {
+        self.request(Method::GET, url)
+    }
+
+    fn post(&self, url: impl IntoUrl) -> RequestBuilder {
+        self.request(Method::POST, url)
+    }
+}
+
+impl HttpClientTrait for reqwest::Client {
+    fn request(&self, method: Method, url: impl IntoUrl) -> RequestBuilder {
+        reqwest::Client::request(self, method, url)
+    }
+
+    fn execute(&self, request: reqwest::Request) -> impl Future<Output = Result<reqwest::Response, reqwest::Error>> + Send {
+        // Only the path is logged, query strings can carry API tokens
+        let span = tracing::debug_span!("http_request", method = %request.method(), path = request.url().path());
+        reqwest::Client::execute(self, request).instrument(span)
+    }
+}
+
 // Common helper functions
+
+/// Map gateway and availability status codes to [`UsernameError::ServiceUnavailable`], so callers
+/// can tell a service outage apart from other HTTP failures
+pub fn check_service_available(status: StatusCode) -> Result<(), UsernameError> {
+    match status {
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT => {
+            Err(UsernameError::ServiceUnavailable)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Attempts made at a forwarder request while it keeps failing with a retryable error
+pub const MAX_REQUEST_ATTEMPTS: u8 = 3;
+
+/// Run `request` again while it fails with an error that [`UsernameError::is_retryable`] accepts,
+/// up to [`MAX_REQUEST_ATTEMPTS`] times
+pub async fn with_retries<T, F, Fut>(mut request: F) -> Result<T, UsernameError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, UsernameError>>,
+{
+    let mut attempt = 1;
+    loop {
+        match request().await {
+            Err(error) if attempt < MAX_REQUEST_ATTEMPTS && error.is_retryable() => {
+                tracing::warn!(%error, attempt, "Forwarder request failed, retrying");
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// The alias from a create response, rejecting a missing, empty or whitespace only one with
+/// [`UsernameError::EmptyResponse`]
+pub fn require_alias(service: &str, alias: Option<String>) -> Result<String, UsernameError> {
+    match alias {
+        Some(alias) if !alias.trim().is_empty() => Ok(alias),
+        _ => Err(UsernameError::EmptyResponse {
+            service: service.to_string(),
+        }),
+    }
+}
+
+/// Strip surrounding whitespace and trailing slashes from a user supplied base URL, so joining
+/// it with an API path never produces `//`
+pub fn normalize_base_url(base_url: &str) -> String {
+    base_url.trim().trim_end_matches('/').to_string()
+}
+
 pub fn format_description(website: &Option<String>) -> String {
     if let Some(website) = website {
         format!("Generated by Bitwarden for {}", website)
@@ -1,51 +1,124 @@
 use reqwest::{header::CONTENT_TYPE, StatusCode};
 
+use super::HttpClientTrait;
 use crate::generators::username::UsernameError;
 
+const DEFAULT_NOTE: &str = "Generated by SecureGen";
+/// Longest note SimpleLogin accepts
+const MAX_NOTE_LENGTH: usize = 280;
+
 pub async fn generate(
-    http: &reqwest::Client,
+    http: &impl HttpClientTrait,
     api_key: String,
     base_url: String,
+    note: Option<String>,
+    directory: Option<String>,
     website: Option<String>,
 ) -> Result<String, UsernameError> {
-    generate_internal(http, api_key, base_url, website).await
+    generate_internal(http, api_key, base_url, note, directory, website).await
+}
+
+/// A directory of a SimpleLogin premium account
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SimpleLoginDirectory {
+    pub name: String,
+    pub nb_alias: u32,
+    pub enabled: bool,
+}
+
+pub async fn list_directories(
+    http: &reqwest::Client,
+    api_key: String,
+    base_url: String,
+) -> Result<Vec<SimpleLoginDirectory>, UsernameError> {
+    let base_url = super::normalize_base_url(&base_url);
+
+    let response = http
+        .get(format!("{base_url}/api/v2/setting/directory"))
+        .header("Authentication", api_key)
+        .send()
+        .await?;
+
+    super::check_service_available(response.status())?;
+
+    if response.status() == StatusCode::UNAUTHORIZED {
+        return Err(UsernameError::InvalidApiKey);
+    }
+
+    let response = response.error_for_status()?;
+    Ok(response.json().await?)
+}
+
+/// Strip control characters and cap the note at SimpleLogin's limit, falling back to the
+/// default note when nothing is left
+fn sanitize_note(note: Option<String>) -> String {
+    let note: String = note
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| !c.is_control())
+        .collect();
+    let note: String = note.trim().chars().take(MAX_NOTE_LENGTH).collect();
+    if note.is_empty() {
+        DEFAULT_NOTE.to_string()
+    } else {
+        note
+    }
 }
 
 #[allow(dead_code)]
 pub async fn generate_with_api_url(
-    http: &reqwest::Client,
+    http: &impl HttpClientTrait,
     api_key: String,
     api_url: String,
     website: Option<String>,
 ) -> Result<String, UsernameError> {
-    generate_internal(http, api_key, api_url, website).await
+    generate_internal(http, api_key, api_url, None, None, website).await
 }
 
 async fn generate_internal(
-    http: &reqwest::Client,
+    http: &impl HttpClientTrait,
     api_key: String,
     api_url: String,
+    note: Option<String>,
+    directory: Option<String>,
     website: Option<String>,
 ) -> Result<String, UsernameError> {
-    let query = website
-        .as_ref()
-        .map(|w| format!("?hostname={}", urlencoding::encode(w)))
-        .unwrap_or_default();
+    let api_url = super::normalize_base_url(&api_url);
+    let params: Vec<String> = [("hostname", website), ("directory", directory)]
+        .into_iter()
+        .filter_map(|(key, value)| value.map(|value| format!("{}={}", key, urlencoding::encode(&value))))
+        .collect();
+    let query = if params.is_empty() {
+        String::new()
+    } else {
+        format!("?{}", params.join("&"))
+    };
 
+    let note = sanitize_note(note);
+    super::with_retries(|| create_alias(http, &api_key, &api_url, &query, &note)).await
+}
+
+async fn create_alias(
+    http: &impl HttpClientTrait,
+    api_key: &str,
+    api_url: &str,
+    query: &str,
+    note: &str,
+) -> Result<String, UsernameError> {
     #[derive(serde::Serialize)]
-    struct Request {
-        note: String,
+    struct Request<'a> {
+        note: &'a str,
     }
 
-    let response = http
+    let request = http
         .post(format!("{api_url}/api/alias/random/new{query}"))
         .header(CONTENT_TYPE, "application/json")
         .header("Authentication", api_key)
-        .json(&Request {
-            note: "Generated by SecureGen".to_string(),
-        })
-        .send()
-        .await?;
+        .json(&Request { note })
+        .build()?;
+    let response = http.execute(request).await?;
+
+    super::check_service_available(response.status())?;
 
     if response.status() == StatusCode::UNAUTHORIZED {
         return Err(UsernameError::InvalidApiKey);
@@ -56,11 +129,50 @@ async fn generate_internal(
 
     #[derive(serde::Deserialize)]
     struct Response {
-        alias: String,
+        alias: Option<String>,
     }
     let response: Response = response.json().await?;
 
-    Ok(response.alias)
+    super::require_alias("SimpleLogin", response.alias)
+}
+
+/// Disable an alias so it stops forwarding mail, without deleting it
+pub async fn deactivate_alias(
+    http: &reqwest::Client,
+    api_key: String,
+    base_url: String,
+    alias_id: String,
+) -> Result<(), UsernameError> {
+    let base_url = super::normalize_base_url(&base_url);
+
+    let response = http
+        .put(format!("{base_url}/api/aliases/{}", urlencoding::encode(&alias_id)))
+        .header(CONTENT_TYPE, "application/json")
+        .header("Authentication", api_key)
+        .json(&serde_json::json!({
+            "name": null,
+            "mailboxes": [],
+            "note": null,
+            "pinned": false,
+            "disabled": true
+        }))
+        .send()
+        .await?;
+
+    super::check_service_available(response.status())?;
+
+    match response.status() {
+        StatusCode::UNAUTHORIZED => {
+            return Err(UsernameError::InvalidApiKey);
+        }
+        StatusCode::NOT_FOUND => {
+            return Err(UsernameError::AliasNotFound);
+        }
+        _ => {}
+    }
+
+    response.error_for_status()?;
+    Ok(())
 }
 
 #[cfg(test)]
@@ -133,4 +245,283 @@ mod tests {
 
         server.verify().await;
     }
-} 
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_custom_note() {
+        use wiremock::{matchers, Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+
+        server
+            .register(
+                Mock::given(matchers::path("/api/alias/random/new"))
+                    .and(matchers::method("POST"))
+                    .and(matchers::query_param("hostname", "example.com"))
+                    .and(matchers::body_json(json!({
+                        "note": "Newsletter signup"
+                    })))
+                    .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                        "alias": "newsletter@example.com"
+                    })))
+                    .expect(1),
+            )
+            .await;
+
+        let address = super::generate(
+            &reqwest::Client::new(),
+            "MY_TOKEN".into(),
+            server.uri(),
+            Some("News\u{7}letter signup\n".into()),
+            None,
+            Some("example.com".into()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(address, "newsletter@example.com");
+
+        server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn test_directory_query_param() {
+        use wiremock::{matchers, Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+
+        server
+            .register(
+                Mock::given(matchers::path("/api/alias/random/new"))
+                    .and(matchers::method("POST"))
+                    .and(matchers::query_param("hostname", "example.com"))
+                    .and(matchers::query_param("directory", "shopping & travel"))
+                    .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                        "alias": "shopping/deal@example.com"
+                    })))
+                    .expect(1),
+            )
+            .await;
+        server
+            .register(
+                Mock::given(matchers::path("/api/alias/random/new"))
+                    .and(matchers::method("POST"))
+                    .and(matchers::query_param("directory", "news"))
+                    .and(matchers::query_param_is_missing("hostname"))
+                    .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                        "alias": "news/daily@example.com"
+                    })))
+                    .expect(1),
+            )
+            .await;
+
+        let address = super::generate(
+            &reqwest::Client::new(),
+            "MY_TOKEN".into(),
+            server.uri(),
+            None,
+            Some("shopping & travel".into()),
+            Some("example.com".into()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(address, "shopping/deal@example.com");
+
+        let address = super::generate(
+            &reqwest::Client::new(),
+            "MY_TOKEN".into(),
+            server.uri(),
+            None,
+            Some("news".into()),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(address, "news/daily@example.com");
+
+        server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn test_list_directories() {
+        use wiremock::{matchers, Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+
+        server
+            .register(
+                Mock::given(matchers::path("/api/v2/setting/directory"))
+                    .and(matchers::method("GET"))
+                    .and(matchers::header("Authentication", "MY_TOKEN"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                        { "name": "shopping", "nb_alias": 12, "enabled": true },
+                        { "name": "archive", "nb_alias": 0, "enabled": false }
+                    ])))
+                    .expect(1),
+            )
+            .await;
+        server
+            .register(
+                Mock::given(matchers::path("/api/v2/setting/directory"))
+                    .and(matchers::method("GET"))
+                    .and(matchers::header("Authentication", "MY_FAKE_TOKEN"))
+                    .respond_with(ResponseTemplate::new(401))
+                    .expect(1),
+            )
+            .await;
+
+        let directories = super::list_directories(&reqwest::Client::new(), "MY_TOKEN".into(), server.uri())
+            .await
+            .unwrap();
+        assert_eq!(
+            directories,
+            vec![
+                super::SimpleLoginDirectory {
+                    name: "shopping".into(),
+                    nb_alias: 12,
+                    enabled: true,
+                },
+                super::SimpleLoginDirectory {
+                    name: "archive".into(),
+                    nb_alias: 0,
+                    enabled: false,
+                },
+            ]
+        );
+
+        let error = super::list_directories(&reqwest::Client::new(), "MY_FAKE_TOKEN".into(), server.uri())
+            .await
+            .unwrap_err();
+        assert!(matches!(error, UsernameError::InvalidApiKey));
+
+        server.verify().await;
+    }
+
+    #[test]
+    fn test_sanitize_note() {
+        assert_eq!(super::sanitize_note(None), "Generated by SecureGen");
+        assert_eq!(super::sanitize_note(Some(" \t\r\n".into())), "Generated by SecureGen");
+        assert_eq!(super::sanitize_note(Some("for\u{0}um".into())), "forum");
+
+        let long_note = "é".repeat(300);
+        let note = super::sanitize_note(Some(long_note));
+        assert_eq!(note.chars().count(), 280);
+    }
+
+    #[tokio::test]
+    async fn test_deactivate_alias() {
+        use wiremock::{matchers, Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+
+        server
+            .register(
+                Mock::given(matchers::path("/api/aliases/42"))
+                    .and(matchers::method("PUT"))
+                    .and(matchers::header("Authentication", "MY_TOKEN"))
+                    .and(matchers::body_json(json!({
+                        "name": null,
+                        "mailboxes": [],
+                        "note": null,
+                        "pinned": false,
+                        "disabled": true
+                    })))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "ok": true })))
+                    .expect(1),
+            )
+            .await;
+        server
+            .register(
+                Mock::given(matchers::path("/api/aliases/404"))
+                    .and(matchers::method("PUT"))
+                    .respond_with(ResponseTemplate::new(404))
+                    .expect(1),
+            )
+            .await;
+
+        super::deactivate_alias(&reqwest::Client::new(), "MY_TOKEN".into(), server.uri(), "42".into())
+            .await
+            .unwrap();
+
+        let error = super::deactivate_alias(&reqwest::Client::new(), "MY_TOKEN".into(), server.uri(), "404".into())
+            .await
+            .unwrap_err();
+        assert!(matches!(error, UsernameError::AliasNotFound));
+
+        server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn test_mock_http_client() {
+        use reqwest::StatusCode;
+
+        use crate::generators::username_forwarders::test_utils::MockHttpClient;
+
+        let http = MockHttpClient::new(vec![
+            (StatusCode::CREATED, json!({ "alias": "simplelogin.yd7x@slmail.me" })),
+            (StatusCode::UNAUTHORIZED, json!({ "error": "Wrong api key" })),
+        ]);
+
+        let address = super::generate(
+            &http,
+            "MY_TOKEN".into(),
+            "https://app.simplelogin.io".into(),
+            None,
+            None,
+            Some("example.com".into()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(address, "simplelogin.yd7x@slmail.me");
+
+        let error = super::generate(
+            &http,
+            "MY_FAKE_TOKEN".into(),
+            "https://app.simplelogin.io".into(),
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(error, UsernameError::InvalidApiKey));
+
+        let requests = http.requests();
+        assert_eq!(
+            requests[0].url().as_str(),
+            "https://app.simplelogin.io/api/alias/random/new?hostname=example.com"
+        );
+        assert_eq!(requests[0].headers()["Authentication"], "MY_TOKEN");
+    }
+
+    #[tokio::test]
+    async fn test_empty_alias_retried() {
+        use wiremock::{matchers, Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+
+        // The first answer is an empty alias, the retry gets a real one
+        server
+            .register(
+                Mock::given(matchers::path("/api/alias/random/new"))
+                    .and(matchers::method("POST"))
+                    .respond_with(ResponseTemplate::new(201).set_body_json(json!({ "alias": "" })))
+                    .up_to_n_times(1)
+                    .expect(1),
+            )
+            .await;
+        server
+            .register(
+                Mock::given(matchers::path("/api/alias/random/new"))
+                    .and(matchers::method("POST"))
+                    .respond_with(ResponseTemplate::new(201).set_body_json(json!({ "alias": "test-alias@example.com" })))
+                    .expect(1),
+            )
+            .await;
+
+        let address = super::generate_with_api_url(&reqwest::Client::new(), "MY_TOKEN".into(), server.uri(), None)
+            .await
+            .unwrap();
+        assert_eq!(address, "test-alias@example.com");
+
+        server.verify().await;
+    }
+}
@@ -0,0 +1,51 @@
+//! In process HTTP client for forwarder unit tests
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::Mutex;
+
+use reqwest::{header::CONTENT_TYPE, IntoUrl, Method, RequestBuilder, StatusCode};
+
+use super::HttpClientTrait;
+
+/// Answers each request with the next queued response, without any network access. The requests
+/// are kept so tests can check what was sent.
+pub struct MockHttpClient {
+    client: reqwest::Client,
+    responses: Mutex<VecDeque<(StatusCode, serde_json::Value)>>,
+    requests: Mutex<Vec<reqwest::Request>>,
+}
+
+impl MockHttpClient {
+    pub fn new(responses: Vec<(StatusCode, serde_json::Value)>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            responses: Mutex::new(responses.into()),
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// The requests received so far, oldest first
+    pub fn requests(&self) -> Vec<reqwest::Request> {
+        let requests = self.requests.lock().unwrap();
+        requests.iter().map(|request| request.try_clone().expect("request bodies are buffered")).collect()
+    }
+}
+
+impl HttpClientTrait for MockHttpClient {
+    fn request(&self, method: Method, url: impl IntoUrl) -> RequestBuilder {
+        self.client.request(method, url)
+    }
+
+    fn execute(&self, request: reqwest::Request) -> impl Future<Output = Result<reqwest::Response, reqwest::Error>> + Send {
+        let response = self.responses.lock().unwrap().pop_front();
+        self.requests.lock().unwrap().push(request);
+        let (status, body) = response.expect("MockHttpClient received more requests than it has responses");
+        let response = http::Response::builder()
+            .status(status)
+            .header(CONTENT_TYPE, "application/json")
+            .body(body.to_string())
+            .expect("mock response is valid");
+        std::future::ready(Ok(response.into()))
+    }
+}
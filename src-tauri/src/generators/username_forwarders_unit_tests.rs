@@ -44,6 +44,7 @@ mod addyio_unit_tests {
             "test_api_token".to_string(),
             "example.com".to_string(),
             server.uri(),
+            None,
             Some("github.com".to_string()),
         ).await;
 
@@ -75,6 +76,7 @@ mod addyio_unit_tests {
             "token".to_string(),
             "testdomain.com".to_string(),
             server.uri(),
+            None,
             Some("example.org".to_string()),
         ).await;
 
@@ -107,6 +109,7 @@ mod addyio_unit_tests {
             "mydomain.com".to_string(),
             server.uri(),
             None,
+            None,
         ).await;
 
         assert!(result.is_ok());
@@ -135,6 +138,7 @@ mod addyio_unit_tests {
             "domain.com".to_string(),
             server.uri(),
             None,
+            None,
         ).await;
 
         // Should fail to deserialize due to missing email field
@@ -150,6 +154,9 @@ mod addyio_unit_tests {
             (404, "Not Found - Invalid endpoint"),
             (429, "Too Many Requests"),
             (500, "Internal Server Error"),
+            (502, "Bad Gateway"),
+            (503, "Service Unavailable"),
+            (504, "Gateway Timeout"),
         ];
 
         for (status_code, _description) in test_cases {
@@ -169,12 +176,14 @@ mod addyio_unit_tests {
                 "domain.com".to_string(),
                 server.uri(),
                 None,
+                None,
             ).await;
 
             match status_code {
                 401 => assert!(matches!(result, Err(UsernameError::InvalidApiKey))),
                 403 => assert!(matches!(result, Err(UsernameError::DomainRejected))),
                 429 => assert!(matches!(result, Err(UsernameError::RateLimitExceeded))),
+                502..=504 => assert!(matches!(result, Err(UsernameError::ServiceUnavailable))),
                 _ => {
                     assert!(result.is_err());
                     // Should be an http error for other status codes
@@ -187,6 +196,62 @@ mod addyio_unit_tests {
     }
 }
 
+#[cfg(test)]
+mod base_url_unit_tests {
+    use super::*;
+    use crate::generators::username_forwarders::{addyio, simplelogin};
+
+    #[tokio::test]
+    async fn test_trailing_slash_does_not_double_slash_paths() {
+        let server = MockServer::start().await;
+        let client = reqwest::Client::new();
+
+        // The matchers only accept the exact single-slash paths
+        server
+            .register(
+                Mock::given(method("POST"))
+                    .and(path("/api/v1/aliases"))
+                    .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                        "data": { "email": "addy@example.com" }
+                    })))
+                    .expect(1),
+            )
+            .await;
+        server
+            .register(
+                Mock::given(method("POST"))
+                    .and(path("/api/alias/random/new"))
+                    .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                        "alias": "simple@simplelogin.co"
+                    })))
+                    .expect(1),
+            )
+            .await;
+
+        let result = addyio::generate(
+            &client,
+            "token".to_string(),
+            "example.com".to_string(),
+            format!("{}//", server.uri()),
+            None,
+            None,
+        ).await;
+        assert_eq!(result.unwrap(), "addy@example.com");
+
+        let result = simplelogin::generate(
+            &client,
+            "key".to_string(),
+            format!("{}/", server.uri()),
+            None,
+            None,
+            None,
+        ).await;
+        assert_eq!(result.unwrap(), "simple@simplelogin.co");
+
+        server.verify().await;
+    }
+}
+
 #[cfg(test)]
 mod duckduckgo_unit_tests {
     use super::*;
@@ -982,6 +1047,7 @@ mod edge_case_tests {
             "token".to_string(),
             "".to_string(), // Empty domain
             server.uri(),
+            None,
             Some("".to_string()), // Empty website
         ).await;
 
@@ -1060,9 +1126,13 @@ mod edge_case_tests {
             "domain.com".to_string(),
             "http://192.0.2.1:12345".to_string(), // RFC 5737 test address
             None,
+            None,
         ).await;
 
         assert!(result.is_err());
-        assert!(matches!(result, Err(UsernameError::Http(_))));
+        assert!(matches!(
+            result,
+            Err(UsernameError::Timeout) | Err(UsernameError::ServiceUnavailable)
+        ));
     }
 } 
\ No newline at end of file
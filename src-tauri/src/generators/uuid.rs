@@ -0,0 +1,158 @@
+use ::uuid::Uuid;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Which UUID version to generate
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema)]
+pub enum UuidVersion {
+    /// Random (RFC 9562 section 5.4)
+    V4,
+    /// Unix timestamp in milliseconds followed by random bits, so UUIDs sort by creation time
+    /// (RFC 9562 section 5.7)
+    V7,
+}
+
+/// How a UUID is written
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, JsonSchema)]
+pub enum UuidFormat {
+    /// `67e55044-10b1-426f-9247-bb680e5fe0c8`
+    #[default]
+    Hyphenated,
+    /// `67e5504410b1426f9247bb680e5fe0c8`
+    Simple,
+    /// `urn:uuid:67e55044-10b1-426f-9247-bb680e5fe0c8`
+    Urn,
+    /// `{67e55044-10b1-426f-9247-bb680e5fe0c8}`
+    Braced,
+}
+
+/// Predefined namespaces for name-based UUIDs (RFC 9562 section 6.6)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema)]
+pub enum UuidNamespace {
+    /// The name is a fully qualified domain name
+    Dns,
+    /// The name is a URL
+    Url,
+    /// The name is an ISO OID
+    Oid,
+    /// The name is an X.500 DN in DER or text format
+    X500,
+}
+
+impl UuidNamespace {
+    fn uuid(self) -> Uuid {
+        match self {
+            UuidNamespace::Dns => Uuid::NAMESPACE_DNS,
+            UuidNamespace::Url => Uuid::NAMESPACE_URL,
+            UuidNamespace::Oid => Uuid::NAMESPACE_OID,
+            UuidNamespace::X500 => Uuid::NAMESPACE_X500,
+        }
+    }
+}
+
+/// Generate a random (v4) or time-ordered (v7) UUID
+pub fn generate_uuid(version: UuidVersion, format: UuidFormat) -> String {
+    let uuid = match version {
+        UuidVersion::V4 => Uuid::new_v4(),
+        UuidVersion::V7 => Uuid::now_v7(),
+    };
+    format_uuid(uuid, format)
+}
+
+/// Generate the name-based (v5, SHA-1) UUID for `name` in `namespace`. The same namespace and
+/// name always give the same UUID.
+pub fn generate_uuid_namespace(namespace: UuidNamespace, name: &str) -> String {
+    format_uuid(Uuid::new_v5(&namespace.uuid(), name.as_bytes()), UuidFormat::Hyphenated)
+}
+
+fn format_uuid(uuid: Uuid, format: UuidFormat) -> String {
+    match format {
+        UuidFormat::Hyphenated => uuid.hyphenated().to_string(),
+        UuidFormat::Simple => uuid.simple().to_string(),
+        UuidFormat::Urn => uuid.urn().to_string(),
+        UuidFormat::Braced => uuid.braced().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Check the layout of a hyphenated UUID and return its version digit
+    fn version_digit(hyphenated: &str) -> char {
+        assert_eq!(hyphenated.len(), 36);
+        for (i, c) in hyphenated.chars().enumerate() {
+            if [8, 13, 18, 23].contains(&i) {
+                assert_eq!(c, '-', "{hyphenated}");
+            } else {
+                assert!(c.is_ascii_digit() || ('a'..='f').contains(&c), "{hyphenated}");
+            }
+        }
+        // The two most significant bits of the variant are 10 (RFC 9562 section 4.1)
+        assert!(matches!(hyphenated.as_bytes()[19], b'8' | b'9' | b'a' | b'b'), "{hyphenated}");
+        hyphenated.as_bytes()[14] as char
+    }
+
+    #[test]
+    fn test_version_and_variant() {
+        for _ in 0..100 {
+            assert_eq!(version_digit(&generate_uuid(UuidVersion::V4, UuidFormat::Hyphenated)), '4');
+            assert_eq!(version_digit(&generate_uuid(UuidVersion::V7, UuidFormat::Hyphenated)), '7');
+        }
+        for namespace in [UuidNamespace::Dns, UuidNamespace::Url, UuidNamespace::Oid, UuidNamespace::X500] {
+            assert_eq!(version_digit(&generate_uuid_namespace(namespace, "example.com")), '5');
+        }
+    }
+
+    #[test]
+    fn test_v7_timestamp_and_order() {
+        let before = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        let uuids: Vec<String> = (0..50).map(|_| generate_uuid(UuidVersion::V7, UuidFormat::Simple)).collect();
+
+        // The first 48 bits are the Unix timestamp in milliseconds
+        let timestamp = u64::from_str_radix(&uuids[0][..12], 16).unwrap();
+        assert!(timestamp >= before && timestamp < before + 5_000, "{timestamp} vs {before}");
+
+        let mut sorted = uuids.clone();
+        sorted.sort();
+        assert_eq!(sorted, uuids, "v7 UUIDs should sort in creation order");
+    }
+
+    #[test]
+    fn test_formats() {
+        let uuid = Uuid::parse_str("67e55044-10b1-426f-9247-bb680e5fe0c8").unwrap();
+        assert_eq!(format_uuid(uuid, UuidFormat::Hyphenated), "67e55044-10b1-426f-9247-bb680e5fe0c8");
+        assert_eq!(format_uuid(uuid, UuidFormat::Simple), "67e5504410b1426f9247bb680e5fe0c8");
+        assert_eq!(format_uuid(uuid, UuidFormat::Urn), "urn:uuid:67e55044-10b1-426f-9247-bb680e5fe0c8");
+        assert_eq!(format_uuid(uuid, UuidFormat::Braced), "{67e55044-10b1-426f-9247-bb680e5fe0c8}");
+
+        let simple = generate_uuid(UuidVersion::V4, UuidFormat::Simple);
+        assert_eq!(simple.len(), 32);
+        assert_eq!(simple.as_bytes()[12], b'4');
+        let urn = generate_uuid(UuidVersion::V4, UuidFormat::Urn);
+        assert_eq!(version_digit(urn.strip_prefix("urn:uuid:").unwrap()), '4');
+        let braced = generate_uuid(UuidVersion::V7, UuidFormat::Braced);
+        let inner = braced.strip_prefix('{').and_then(|s| s.strip_suffix('}')).unwrap();
+        assert_eq!(version_digit(inner), '7');
+    }
+
+    #[test]
+    fn test_namespace_uuids_are_deterministic() {
+        // Known v5 values, e.g. Python's uuid.uuid5(uuid.NAMESPACE_DNS, "python.org")
+        assert_eq!(
+            generate_uuid_namespace(UuidNamespace::Dns, "python.org"),
+            "886313e1-3b8a-5372-9b90-0c9aee199e5d"
+        );
+        assert_eq!(
+            generate_uuid_namespace(UuidNamespace::Url, "https://example.com"),
+            generate_uuid_namespace(UuidNamespace::Url, "https://example.com")
+        );
+        assert_ne!(
+            generate_uuid_namespace(UuidNamespace::Dns, "example.com"),
+            generate_uuid_namespace(UuidNamespace::Url, "example.com")
+        );
+    }
+}
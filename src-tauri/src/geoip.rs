@@ -0,0 +1,184 @@
+//! GeoIP lookups backed by the MaxMind GeoLite2 Country database.
+//!
+//! The database is embedded at compile time when `resources/GeoLite2-Country.mmdb`
+//! is present (see `build.rs`). GeoLite2 is distributed under MaxMind's EULA, so the
+//! file is not checked in; download it from <https://dev.maxmind.com/geoip/geolite2-free-geolocation-data>
+//! before building a release. Without it, lookups are disabled and every IP resolves
+//! to an empty [`GeoLocation`].
+
+use std::net::IpAddr;
+use std::sync::LazyLock;
+
+use maxminddb::{geoip2, Reader};
+
+#[cfg(geoip_db)]
+static GEOIP_DATABASE: &[u8] = include_bytes!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../resources/GeoLite2-Country.mmdb"
+));
+
+/// The embedded database reader, parsed once on first use.
+static GEOIP_READER: LazyLock<Option<Reader<&'static [u8]>>> = LazyLock::new(|| {
+    #[cfg(geoip_db)]
+    {
+        match Reader::from_source(GEOIP_DATABASE) {
+            Ok(reader) => Some(reader),
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to load embedded GeoIP database");
+                None
+            }
+        }
+    }
+
+    #[cfg(not(geoip_db))]
+    {
+        None
+    }
+});
+
+/// Location information resolved for an IP address
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GeoLocation {
+    /// ISO 3166-1 alpha-2 country code, e.g. `US`
+    pub country: Option<String>,
+    /// ISO 3166-2 subdivision code when the database has one, otherwise the continent code
+    pub region: Option<String>,
+}
+
+/// A source of IP geolocation data
+pub trait GeoLookup {
+    /// Returns `true` when the backing database can answer queries
+    fn is_available(&self) -> bool;
+
+    /// Looks up the location of `ip`, returning `None` when the address is not in the database
+    fn lookup(&self, ip: IpAddr) -> Option<GeoLocation>;
+}
+
+/// [`GeoLookup`] implementation using the embedded GeoLite2 database
+pub struct MaxMindLookup;
+
+impl GeoLookup for MaxMindLookup {
+    fn is_available(&self) -> bool {
+        GEOIP_READER.is_some()
+    }
+
+    fn lookup(&self, ip: IpAddr) -> Option<GeoLocation> {
+        let reader = GEOIP_READER.as_ref()?;
+        // The City record is a superset of the Country record, so decoding as City
+        // works for both editions and picks up subdivisions if a City database is bundled.
+        let record: geoip2::City = reader.lookup(ip).ok()?;
+
+        let country = record
+            .country
+            .and_then(|c| c.iso_code)
+            .map(str::to_string);
+        let region = record
+            .subdivisions
+            .and_then(|s| s.into_iter().next())
+            .and_then(|s| s.iso_code)
+            .or_else(|| record.continent.and_then(|c| c.code))
+            .map(str::to_string);
+
+        Some(GeoLocation { country, region })
+    }
+}
+
+/// Looks up `ip` in the embedded database. Unparseable, private and unknown
+/// addresses resolve to an empty [`GeoLocation`].
+pub fn lookup(ip: &str) -> GeoLocation {
+    lookup_with(&MaxMindLookup, ip)
+}
+
+/// Looks up `ip` using the given [`GeoLookup`] source
+pub fn lookup_with(source: &impl GeoLookup, ip: &str) -> GeoLocation {
+    if !source.is_available() {
+        return GeoLocation::default();
+    }
+
+    ip.parse::<IpAddr>()
+        .ok()
+        .and_then(|ip| source.lookup(ip))
+        .unwrap_or_default()
+}
+
+/// Returns `true` when the GeoLite2 database was embedded in this build
+pub fn is_available() -> bool {
+    MaxMindLookup.is_available()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    struct MockLookup(HashMap<IpAddr, GeoLocation>);
+
+    impl MockLookup {
+        fn new() -> Self {
+            let mut entries = HashMap::new();
+            entries.insert(
+                "8.8.8.8".parse().unwrap(),
+                GeoLocation {
+                    country: Some("US".to_string()),
+                    region: Some("NA".to_string()),
+                },
+            );
+            entries.insert(
+                "2a00:1450:4001::1".parse().unwrap(),
+                GeoLocation {
+                    country: Some("DE".to_string()),
+                    region: Some("EU".to_string()),
+                },
+            );
+            Self(entries)
+        }
+    }
+
+    impl GeoLookup for MockLookup {
+        fn is_available(&self) -> bool {
+            true
+        }
+
+        fn lookup(&self, ip: IpAddr) -> Option<GeoLocation> {
+            self.0.get(&ip).cloned()
+        }
+    }
+
+    struct UnavailableLookup;
+
+    impl GeoLookup for UnavailableLookup {
+        fn is_available(&self) -> bool {
+            false
+        }
+
+        fn lookup(&self, _ip: IpAddr) -> Option<GeoLocation> {
+            panic!("lookup should not be called when the database is unavailable")
+        }
+    }
+
+    #[test]
+    fn test_lookup_known_ips() {
+        let source = MockLookup::new();
+
+        let google = lookup_with(&source, "8.8.8.8");
+        assert_eq!(google.country.as_deref(), Some("US"));
+        assert_eq!(google.region.as_deref(), Some("NA"));
+
+        let ipv6 = lookup_with(&source, "2a00:1450:4001::1");
+        assert_eq!(ipv6.country.as_deref(), Some("DE"));
+    }
+
+    #[test]
+    fn test_lookup_unknown_and_invalid_ips() {
+        let source = MockLookup::new();
+
+        assert_eq!(lookup_with(&source, "192.168.1.10"), GeoLocation::default());
+        assert_eq!(lookup_with(&source, "SYS-LINUX"), GeoLocation::default());
+        assert_eq!(lookup_with(&source, ""), GeoLocation::default());
+    }
+
+    #[test]
+    fn test_lookup_unavailable_database() {
+        assert_eq!(lookup_with(&UnavailableLookup, "8.8.8.8"), GeoLocation::default());
+    }
+}
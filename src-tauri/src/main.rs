@@ -1,24 +1,152 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod deep_link;
 mod generators;
+mod geoip;
+mod security;
+mod stun;
 
 use generators::{
-    password::{generate_password as gen_password, PasswordGeneratorRequest},
-    passphrase::{generate_passphrase as gen_passphrase, PassphraseGeneratorRequest},
-    username::{generate_username as gen_username, UsernameGeneratorRequest},
+    GeneratorError,
+    SerializableError,
+    GENERIC_ERROR_CODE,
+    to_hex,
+    kdf::{derive_key as gen_derive_key, DerivedKey, KdfAlgorithm},
+    password::{
+        estimate_password_entropy as gen_estimate_password_entropy, generate_password as gen_password,
+        generate_password_with_warnings as gen_password_with_warnings,
+        AmbiguousExclusions, PasswordGeneratorRequest, PasswordPreset, PasswordResult,
+    },
+    passphrase::{
+        estimate_passphrase_entropy as gen_estimate_passphrase_entropy, generate_passphrase as gen_passphrase,
+        generate_passphrase_choices as gen_passphrase_choices, generate_passphrase_with_stats as gen_passphrase_with_stats,
+        EmojiSeparator, EntropyEstimate, PassphraseGeneratorRequest, PassphraseResult,
+    },
+    username::{
+        emails_equivalent as gen_emails_equivalent, generate_username as gen_username,
+        normalize_email as gen_normalize_email, validate_base_url, AppendType, ForwarderServiceType, UsernameError,
+        UsernameErrorCode, UsernameGeneratorRequest,
+    },
+    uuid::{
+        generate_uuid as gen_uuid, generate_uuid_namespace as gen_uuid_namespace, UuidFormat, UuidNamespace,
+        UuidVersion,
+    },
+    username_forwarders::addyio::{
+        list_aliases as addy_list_aliases, list_domains as addy_list_domains, list_mailboxes as addy_list_mailboxes,
+        AddyDomain, AddyMailbox, AliasInfo,
+    },
+    username_forwarders::fastmail::{list_identities as fastmail_list_identities, FastmailIdentity},
+    username_forwarders::firefox::{get_quota_remaining as firefox_quota_remaining, FirefoxRelayQuota},
+    username_forwarders::simplelogin::{list_directories as simplelogin_list_directories, SimpleLoginDirectory},
+    username_forwarders::forwardemail::{
+        delete_alias as forwardemail_delete_alias, list_aliases as forwardemail_list_aliases,
+        verify_domain as forwardemail_verify_domain, DomainVerificationStatus, ForwardEmailAlias,
+    },
 };
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use tauri::{ClipboardManager, Manager, SystemTray, SystemTrayMenu, SystemTrayMenuItem, CustomMenuItem, SystemTrayEvent};
+use tauri::{ClipboardManager, GlobalShortcutManager, Manager, SystemTray, SystemTrayMenu, SystemTrayMenuItem, CustomMenuItem, SystemTrayEvent};
 use zxcvbn::zxcvbn;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::num::NonZeroU32;
 use std::fs;
+use std::path::{Path, PathBuf};
 use tauri::api::path;
-use chrono::Utc;
+use chrono::{Datelike, TimeZone, Utc};
 use std::net::{Ipv4Addr, UdpSocket};
 use std::process::Command;
+use std::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+use governor::clock::{Clock, DefaultClock};
+use governor::middleware::NoOpMiddleware;
+use governor::state::keyed::DefaultKeyedStateStore;
+use governor::state::{InMemoryState, NotKeyed};
+use governor::{Quota, RateLimiter};
 
 // Cross-platform network interface detection
 
+/// Version of the response shapes returned by the commands. Bump this whenever a field of a
+/// response struct is added, removed or renamed, and update the field list in the tests.
+const SCHEMA_VERSION: u16 = 7;
+
+/// Envelope for object-shaped command responses. `data` is flattened, so callers that know the
+/// current version can read the response fields directly and ignore `schema_version`.
+#[derive(Debug, Serialize, Deserialize)]
+struct ApiResponse<T> {
+    schema_version: u16,
+    #[serde(flatten)]
+    data: T,
+}
+
+impl<T> ApiResponse<T> {
+    fn new(data: T) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            data,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ApiVersion {
+    schema_version: u16,
+    app_version: String,
+    /// Version of the compiler the backend was built with
+    rust_version: String,
+}
+
+impl ApiVersion {
+    fn new(app_version: String) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            app_version,
+            rust_version: env!("SECUREGEN_RUSTC_VERSION").to_string(),
+        }
+    }
+}
+
+/// Logs the start of a Tauri command, and its end with the duration once dropped
+struct CommandTimer {
+    command: &'static str,
+    started: std::time::Instant,
+}
+
+impl CommandTimer {
+    fn start(command: &'static str) -> Self {
+        tracing::info!(command, "Command started");
+        Self {
+            command,
+            started: std::time::Instant::now(),
+        }
+    }
+}
+
+impl Drop for CommandTimer {
+    fn drop(&mut self) {
+        let duration_ms = self.started.elapsed().as_secs_f64() * 1000.0;
+        tracing::info!(command = self.command, duration_ms, "Command finished");
+    }
+}
+
+/// Send logs to stderr as JSON lines, at the level set by `SECUREGEN_LOG` (`warn` by default).
+/// Accepts any `tracing_subscriber::EnvFilter` directive, e.g. `info` or `app=debug`.
+fn init_logging() {
+    let filter = tracing_subscriber::EnvFilter::try_from_env("SECUREGEN_LOG")
+        .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("warn"));
+    tracing_subscriber::fmt()
+        .json()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .init();
+}
+
+#[tauri::command]
+async fn get_api_version(app_handle: tauri::AppHandle) -> ApiVersion {
+    let _timer = CommandTimer::start("get_api_version");
+    ApiVersion::new(app_handle.package_info().version.to_string())
+}
+
 // Legacy struct for backward compatibility
 #[derive(Debug, Serialize, Deserialize)]
 struct PasswordConfig {
@@ -32,31 +160,70 @@ struct PasswordConfig {
     custom_exclusions: String,
 }
 
-impl From<PasswordConfig> for PasswordGeneratorRequest {
-    fn from(config: PasswordConfig) -> Self {
-        Self {
-            lowercase: config.include_lowercase,
-            uppercase: config.include_uppercase,
-            numbers: config.include_numbers,
-            special: config.include_symbols,
-            length: config.length as u8,
-            avoid_ambiguous: config.exclude_similar,
-            min_lowercase: None,
-            min_uppercase: None,
-            min_number: None,
-            min_special: None,
-        }
+impl TryFrom<PasswordConfig> for PasswordGeneratorRequest {
+    type Error = GeneratorError;
+
+    fn try_from(config: PasswordConfig) -> Result<Self, Self::Error> {
+        let length = u8::try_from(config.length)
+            .map_err(|_| GeneratorError::InvalidInput(format!("Password length {} is too long", config.length)))?;
+
+        let ambiguous_exclusions = if config.exclude_similar {
+            AmbiguousExclusions::all()
+        } else {
+            AmbiguousExclusions::default()
+        };
+
+        Ok(PasswordGeneratorRequest::builder()
+            .lowercase(config.include_lowercase)
+            .uppercase(config.include_uppercase)
+            .numbers(config.include_numbers)
+            .special(config.include_symbols)
+            .length(length)
+            .ambiguous_exclusions(ambiguous_exclusions)
+            .custom_exclusions(config.custom_exclusions)
+            .build()?)
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 struct PasswordStrength {
     score: u8,
     crack_times_display: String,
     feedback: Vec<String>,
+    /// Word of a passphrase that zxcvbn rates lowest on its own, `None` for passwords
+    #[serde(default)]
+    weakest_word: Option<String>,
+    /// Score of `weakest_word` on its own, on the same 0-100 scale as `score`
+    #[serde(default)]
+    weakest_word_score: Option<u8>,
+    /// zxcvbn's estimate of the guesses needed to crack the password, unaffected by the
+    /// bucketing of `score`
+    #[serde(default)]
+    guesses: u64,
+    /// Order of magnitude of `guesses`
+    #[serde(default)]
+    guesses_log10: f64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// How close a new password is to the one it replaces
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+struct PasswordSimilarityResult {
+    /// 1 minus the Levenshtein distance divided by the length of the longer password, 1.0 for
+    /// identical passwords
+    similarity_score: f64,
+    is_too_similar: bool,
+    /// Observations about what the change kept from the old password
+    changes: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+struct BcryptStrengthResult {
+    cost: u8,
+    strength: String,
+    recommendation: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 struct UsernameStrength {
     score: u8,
     security_level: String,
@@ -65,1297 +232,6832 @@ struct UsernameStrength {
     uniqueness_score: u8,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 struct IPResponse {
     ip: String,
     masked_ip: String,
     country: Option<String>,
     region: Option<String>,
+    geo_lookup_available: bool,
+}
+
+impl IPResponse {
+    /// Builds a response for a detected IP, masking it and resolving its location
+    fn from_ip(ip: String) -> Self {
+        let location = geoip::lookup(&ip);
+        Self {
+            masked_ip: mask_ip_address(&ip),
+            ip,
+            country: location.country,
+            region: location.region,
+            geo_lookup_available: geoip::is_available(),
+        }
+    }
+}
+
+/// How long a public IP lookup is reused when the preferences don't set `cache_ttl_seconds`
+const DEFAULT_IP_CACHE_TTL_SECONDS: u64 = 60;
+
+/// The last public IP lookup and how long it stays fresh
+#[derive(Debug, Clone)]
+struct CachedIp {
+    ip: Option<IPResponse>,
+    fetched_at: Option<std::time::Instant>,
+    ttl_seconds: u64,
+}
+
+/// Tauri managed state caching the public IP, the address rarely changes and looking it up
+/// may need several network round trips
+struct IpCacheState(Mutex<CachedIp>);
+
+impl IpCacheState {
+    fn new(ttl_seconds: u64) -> Self {
+        Self(Mutex::new(CachedIp {
+            ip: None,
+            fetched_at: None,
+            ttl_seconds,
+        }))
+    }
+
+    /// The cached response, if it was fetched less than `ttl_seconds` before `now`
+    fn get(&self, now: std::time::Instant) -> Option<IPResponse> {
+        let cached = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let age = now.saturating_duration_since(cached.fetched_at?);
+        if age < std::time::Duration::from_secs(cached.ttl_seconds) {
+            cached.ip.clone()
+        } else {
+            None
+        }
+    }
+
+    fn store(&self, ip: IPResponse, now: std::time::Instant) {
+        let mut cached = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        cached.ip = Some(ip);
+        cached.fetched_at = Some(now);
+    }
+
+    fn invalidate(&self) {
+        let mut cached = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        cached.ip = None;
+        cached.fetched_at = None;
+    }
+
+    fn set_ttl(&self, ttl_seconds: u64) {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).ttl_seconds = ttl_seconds;
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct NetworkInterface {
+    name: String,
+    ip_addresses: Vec<String>,
+    is_loopback: bool,
+    is_up: bool,
+}
+
+/// Where the hardware ID in a [`SystemIdentityResponse`] came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+enum HwidSource {
+    /// Read from the verified on-disk cache
+    Cached,
+    /// Derived from the OS machine identifiers
+    MachineUid,
+    /// Derived from environment variables because the machine identifier was unavailable
+    Fallback,
+}
+
+/// How many independent machine identifiers the hardware ID was derived from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+enum HwidConfidence {
+    /// 3 or more sources
+    High,
+    /// 2 sources
+    Medium,
+    /// A single source
+    Low,
+    /// No machine identifier was available, the ID comes from environment variables
+    Fallback,
+}
+
+impl HwidConfidence {
+    fn from_source_count(count: usize) -> Self {
+        match count {
+            0 => HwidConfidence::Fallback,
+            1 => HwidConfidence::Low,
+            2 => HwidConfidence::Medium,
+            _ => HwidConfidence::High,
+        }
+    }
 }
 
+/// How the hardware ID in a [`SystemIdentityResponse`] is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+enum HwidOutputFormat {
+    /// `HWID-ABCD-1234-EFGH-5678`, for showing to the user
+    #[default]
+    Display,
+    /// The 8 byte hash as 16 lowercase hex characters
+    Hex,
+    /// The 8 byte hash as unpadded URL-safe base64
+    Base64,
+}
+
+/// On-disk format of the hardware ID cache, stored as `{app_data_dir}/hwid.json`
 #[derive(Debug, Serialize, Deserialize)]
+struct HwidCache {
+    hardware_id: String,
+    /// Missing in caches written before the confidence was recorded, which are regenerated
+    #[serde(default)]
+    confidence: Option<HwidConfidence>,
+    /// Hex-encoded HMAC-SHA256 of `hardware_id` and `confidence`, used to detect tampering
+    hmac: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 struct SystemIdentityResponse {
     hardware_id: String,
+    hwid_source: HwidSource,
+    hwid_confidence: HwidConfidence,
     ip_address: String,
     masked_ip: String,
     platform: String,
+    platform_version: Option<String>,
+    cpu_architecture: String,
+    cpu_cores: Option<usize>,
+    total_memory_mb: Option<u64>,
     user_key: String,
     country: Option<String>,
     region: Option<String>,
 }
 
-#[tauri::command]
-async fn generate_password(request: PasswordGeneratorRequest) -> Result<String, String> {
-    gen_password(request).map_err(|e| e.to_string())
+/// The most recently generated value, shared between the generation commands and the tray menu
+#[derive(Debug, Default, Clone, PartialEq)]
+struct LastGenerated {
+    value: Option<String>,
+    generator_type: Option<String>,
 }
 
-#[tauri::command]
-async fn generate_password_legacy(config: PasswordConfig) -> Result<String, String> {
-    let request: PasswordGeneratorRequest = config.into();
-    gen_password(request).map_err(|e| e.to_string())
-}
+/// Tauri managed state wrapper around [`LastGenerated`]
+#[derive(Default)]
+struct LastGeneratedState(Mutex<LastGenerated>);
 
-#[tauri::command]
-async fn generate_passphrase(request: PassphraseGeneratorRequest) -> Result<String, String> {
-    gen_passphrase(request).map_err(|e| e.to_string())
+impl LastGeneratedState {
+    fn record(&self, generator_type: &str, value: &str) {
+        // A poisoned lock only means another thread panicked mid-update, the data is still usable
+        let mut last = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        last.value = Some(value.to_string());
+        last.generator_type = Some(generator_type.to_string());
+    }
+
+    fn get(&self) -> LastGenerated {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
 }
 
-#[tauri::command]
-async fn generate_username(request: UsernameGeneratorRequest) -> Result<String, String> {
-    let client = reqwest::Client::new();
-    gen_username(request, &client).await.map_err(|e| e.to_string())
+/// The most recent successful password and passphrase requests, reused by the global shortcuts
+#[derive(Default)]
+struct LastRequestState {
+    password: Mutex<Option<PasswordGeneratorRequest>>,
+    passphrase: Mutex<Option<PassphraseGeneratorRequest>>,
 }
 
-#[tauri::command]
-async fn get_public_ip_address() -> Result<IPResponse, String> {
-    // Try multiple approaches in order of reliability for production use
-    
-    // 1. Get routable IP using optimized UDP socket method
-    if let Ok(ip) = get_routable_ip() {
-        return Ok(IPResponse {
-            ip: ip.clone(),
-            masked_ip: mask_ip_address(&ip),
-            country: None,
-            region: None,
-        });
+impl LastRequestState {
+    fn password(&self) -> Option<PasswordGeneratorRequest> {
+        self.password.lock().unwrap_or_else(|e| e.into_inner()).clone()
     }
 
-    // 2. Get active network interface IP (most reliable fallback)
-    if let Ok(ip) = get_active_network_interface_ip() {
-        return Ok(IPResponse {
-            ip: ip.clone(),
-            masked_ip: mask_ip_address(&ip),
-            country: None,
-            region: None,
-        });
+    fn passphrase(&self) -> Option<PassphraseGeneratorRequest> {
+        self.passphrase.lock().unwrap_or_else(|e| e.into_inner()).clone()
     }
 
-    // 3. Platform-specific system commands
-    if let Ok(ip) = get_network_interface_ip() {
-        return Ok(IPResponse {
-            ip: ip.clone(),
-            masked_ip: mask_ip_address(&ip),
-            country: None,
-            region: None,
-        });
+    fn record_password(&self, request: PasswordGeneratorRequest) {
+        *self.password.lock().unwrap_or_else(|e| e.into_inner()) = Some(request);
     }
 
-    // Final production fallback - return system identifier instead of mock data
-    Ok(IPResponse {
-        ip: format!("SYS-{}", std::env::consts::OS.to_uppercase()),
-        masked_ip: "System IP".to_string(),
-        country: None,
-        region: None,
-    })
+    fn record_passphrase(&self, request: PassphraseGeneratorRequest) {
+        *self.passphrase.lock().unwrap_or_else(|e| e.into_inner()) = Some(request);
+    }
 }
 
-/// Get the routable IP address by testing connectivity to well-known servers
-/// This method determines which local IP would be used for internet communication
-/// without actually sending data - production-ready and reliable
-fn get_routable_ip() -> Result<String, String> {
-    // Try multiple reliable servers to determine the routable IP
-    let test_servers = [
-        ("1.1.1.1", 53),      // Cloudflare DNS - very reliable
-        ("8.8.8.8", 53),      // Google DNS - fallback
-        ("208.67.222.222", 53), // OpenDNS - second fallback
-    ];
+/// Most entries kept in the audit log, the oldest are dropped first
+const MAX_AUDIT_ENTRIES: usize = 1000;
 
-    for (server, port) in &test_servers {
-        match UdpSocket::bind("0.0.0.0:0") {
-            Ok(socket) => {
-                // Set a timeout for production reliability
-                if let Err(_) = socket.set_read_timeout(Some(std::time::Duration::from_secs(2))) {
-                    continue;
-                }
-                
-                match socket.connect(format!("{}:{}", server, port)) {
-                    Ok(_) => {
-                        match socket.local_addr() {
-                            Ok(addr) => {
-                                let ip = addr.ip().to_string();
-                                // Ensure we got a routable IP, not localhost or link-local
-                                if let Ok(parsed_ip) = ip.parse::<Ipv4Addr>() {
-                                    if !parsed_ip.is_loopback() && 
-                                       !parsed_ip.is_link_local() && 
-                                       !parsed_ip.is_unspecified() {
-                                        return Ok(ip);
-                                    }
-                                }
-                            }
-                            Err(_) => continue,
-                        }
-                    }
-                    Err(_) => continue,
-                }
-            }
-            Err(_) => continue,
-        }
-    }
-    
-    Err("Could not determine routable IP address".to_string())
+/// A record that a value was generated. The value itself is never stored.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct AuditEntry {
+    /// Generator that produced the value, e.g. "password"
+    event_type: String,
+    /// RFC 3339 time of the generation
+    timestamp: String,
+    /// Hex SHA-256 of the JSON serialized request, with forwarder API tokens redacted
+    generator_config_hash: String,
+    /// Random ID of the app run the entry was recorded in
+    session_id: String,
 }
 
-/// Get IP from the active network interface using system APIs
-/// This is a reliable fallback that doesn't require network connectivity
-fn get_active_network_interface_ip() -> Result<String, String> {
-    use std::net::IpAddr;
-    
-    // Get all network interfaces
-    match get_if_addrs::get_if_addrs() {
-        Ok(interfaces) => {
-            let mut best_ip = None;
-            let mut fallback_ip = None;
-            
-            for interface in interfaces {
-                let ip = interface.ip();
-                
-                // Skip loopback and inactive interfaces
-                if interface.is_loopback() {
-                    continue;
-                }
-                
-                match ip {
-                    IpAddr::V4(ipv4) => {
-                        // Skip link-local and other special addresses
-                        if ipv4.is_link_local() || ipv4.is_unspecified() || ipv4.is_broadcast() {
-                            continue;
-                        }
-                        
-                        // Prefer public IPs over private IPs
-                        if !ipv4.is_private() {
-                            best_ip = Some(ipv4.to_string());
-                            break; // Found a public IP, use it immediately
-                        } else if fallback_ip.is_none() {
-                            // Keep the first private IP as fallback
-                            fallback_ip = Some(ipv4.to_string());
-                        }
-                    }
-                    IpAddr::V6(ipv6) => {
-                        // For IPv6, prefer global addresses
-                        if !ipv6.is_loopback() && !ipv6.is_unspecified() {
-                            // IPv6 global addresses start with 2000::/3
-                            let segments = ipv6.segments();
-                            if segments[0] & 0xe000 == 0x2000 {
-                                best_ip = Some(ipv6.to_string());
-                                break;
-                            }
-                        }
-                    }
-                }
-            }
-            
-            // Return the best IP found, or fallback to private IP
-            if let Some(ip) = best_ip {
-                return Ok(ip);
-            } else if let Some(ip) = fallback_ip {
-                return Ok(ip);
-            }
+/// In-memory audit log of generation events, cleared when the app restarts
+struct AuditLogState {
+    session_id: String,
+    entries: Mutex<VecDeque<AuditEntry>>,
+}
+
+impl Default for AuditLogState {
+    fn default() -> Self {
+        Self {
+            session_id: to_hex(&rand::random::<[u8; 16]>()),
+            entries: Mutex::new(VecDeque::new()),
         }
-        Err(_) => {}
     }
-    
-    Err("Could not determine active network interface IP".to_string())
 }
 
-/// Get IP from network interfaces using platform-specific commands
-fn get_network_interface_ip() -> Result<String, String> {
-    #[cfg(target_os = "windows")]
-    {
-        get_windows_network_ip()
-    }
-    
-    #[cfg(target_os = "macos")]
-    {
-        get_macos_network_ip()
+impl AuditLogState {
+    fn record(&self, event_type: &str, request: &impl Serialize) {
+        let entry = AuditEntry {
+            event_type: event_type.to_string(),
+            timestamp: Utc::now().to_rfc3339(),
+            generator_config_hash: config_hash(request),
+            session_id: self.session_id.clone(),
+        };
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries.len() == MAX_AUDIT_ENTRIES {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
     }
-    
-    #[cfg(target_os = "linux")]
-    {
-        get_linux_network_ip()
+
+    fn entries(&self) -> Vec<AuditEntry> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).iter().cloned().collect()
     }
-    
-    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
-    {
-        Err("Unsupported platform for network interface detection".to_string())
+
+    fn clear(&self) {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).clear();
     }
 }
 
-#[cfg(target_os = "windows")]
-fn get_windows_network_ip() -> Result<String, String> {
-    // Use ipconfig to get network information
-    match Command::new("ipconfig").output() {
-        Ok(output) => {
-            let output_str = String::from_utf8_lossy(&output.stdout);
-            
-            // Look for IPv4 addresses that are not loopback or link-local
-            for line in output_str.lines() {
-                if line.contains("IPv4 Address") {
-                    if let Some(ip_part) = line.split(':').nth(1) {
-                        let ip = ip_part.trim();
-                        if let Ok(parsed_ip) = ip.parse::<Ipv4Addr>() {
-                            // Exclude loopback (127.x.x.x) and link-local (169.254.x.x)
-                            if !parsed_ip.is_loopback() && !parsed_ip.is_link_local() {
-                                return Ok(ip.to_string());
-                            }
-                        }
-                    }
-                }
-            }
+/// Calls per second allowed to each of the password, passphrase and username generators
+const GENERATION_RATE_LIMIT: u32 = 100;
+/// Calls per minute allowed to each command that makes requests to a forwarding service
+const FORWARDER_RATE_LIMIT: u32 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GenerationKind {
+    Password,
+    Passphrase,
+    Username,
+}
+
+impl GenerationKind {
+    fn plural(self) -> &'static str {
+        match self {
+            GenerationKind::Password => "passwords",
+            GenerationKind::Passphrase => "passphrases",
+            GenerationKind::Username => "usernames",
         }
-        Err(_) => {}
     }
-    
-    Err("Could not get Windows network IP".to_string())
 }
 
-#[cfg(target_os = "macos")]
-fn get_macos_network_ip() -> Result<String, String> {
-    // Use route command to get the default route interface, then get its IP
-    match Command::new("route").args(&["get", "default"]).output() {
-        Ok(output) => {
-            let output_str = String::from_utf8_lossy(&output.stdout);
-            
-            // Extract interface name
-            let mut interface = None;
-            for line in output_str.lines() {
-                if line.trim().starts_with("interface:") {
-                    interface = line.split(':').nth(1).map(|s| s.trim());
-                    break;
-                }
-            }
-            
-            if let Some(iface) = interface {
-                // Get IP for this interface
-                match Command::new("ifconfig").arg(iface).output() {
-                    Ok(ifconfig_output) => {
-                        let ifconfig_str = String::from_utf8_lossy(&ifconfig_output.stdout);
-                        
-                        for line in ifconfig_str.lines() {
-                            if line.contains("inet ") && !line.contains("inet6") {
-                                let parts: Vec<&str> = line.split_whitespace().collect();
-                                if parts.len() > 1 {
-                                    let ip = parts[1];
-                                    if let Ok(parsed_ip) = ip.parse::<Ipv4Addr>() {
-                                        if !parsed_ip.is_loopback() && !parsed_ip.is_link_local() {
-                                            return Ok(ip.to_string());
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    Err(_) => {}
-                }
-            }
-        }
-        Err(_) => {}
+type DirectLimiter<C> = RateLimiter<NotKeyed, InMemoryState, C, NoOpMiddleware<<C as Clock>::Instant>>;
+
+/// Tauri managed state limiting how often the frontend can call the generation commands, so a
+/// misbehaving frontend can't keep the CPU busy or get the user's forwarder API keys blocked
+struct RateLimits<C: Clock = DefaultClock> {
+    password: DirectLimiter<C>,
+    passphrase: DirectLimiter<C>,
+    username: DirectLimiter<C>,
+    /// Keyed by command name
+    forwarder: RateLimiter<&'static str, DefaultKeyedStateStore<&'static str>, C, NoOpMiddleware<C::Instant>>,
+}
+
+impl Default for RateLimits {
+    fn default() -> Self {
+        Self::with_clock(&DefaultClock::default())
     }
-    
-    Err("Could not get macOS network IP".to_string())
 }
 
-#[cfg(target_os = "linux")]
-fn get_linux_network_ip() -> Result<String, String> {
-    // Use ip route to get the default route, then extract the source IP
-    match Command::new("ip").args(&["route", "get", "8.8.8.8"]).output() {
-        Ok(output) => {
-            let output_str = String::from_utf8_lossy(&output.stdout);
-            
-            // Look for "src" followed by an IP address
-            for part in output_str.split_whitespace() {
-                if let Ok(parsed_ip) = part.parse::<Ipv4Addr>() {
-                    if !parsed_ip.is_loopback() && !parsed_ip.is_link_local() {
-                        return Ok(part.to_string());
-                    }
-                }
-            }
+impl<C: Clock> RateLimits<C> {
+    fn with_clock(clock: &C) -> Self {
+        let generation = Quota::per_second(NonZeroU32::new(GENERATION_RATE_LIMIT).unwrap());
+        let forwarder = Quota::per_minute(NonZeroU32::new(FORWARDER_RATE_LIMIT).unwrap());
+        Self {
+            password: RateLimiter::direct_with_clock(generation, clock),
+            passphrase: RateLimiter::direct_with_clock(generation, clock),
+            username: RateLimiter::direct_with_clock(generation, clock),
+            forwarder: RateLimiter::dashmap_with_clock(forwarder, clock),
         }
-        Err(_) => {}
     }
-    
-    // Fallback: use hostname -I
-    match Command::new("hostname").arg("-I").output() {
-        Ok(output) => {
-            let output_str = String::from_utf8_lossy(&output.stdout);
-            let ip = output_str.trim().split_whitespace().next().unwrap_or("");
-            
-            if let Ok(parsed_ip) = ip.parse::<Ipv4Addr>() {
-                if !parsed_ip.is_loopback() && !parsed_ip.is_link_local() {
-                    return Ok(ip.to_string());
-                }
-            }
+
+    fn check(&self, kind: GenerationKind) -> Result<(), String> {
+        let limiter = match kind {
+            GenerationKind::Password => &self.password,
+            GenerationKind::Passphrase => &self.passphrase,
+            GenerationKind::Username => &self.username,
+        };
+        limiter
+            .check()
+            .map_err(|_| format!("Rate limit exceeded: please wait before generating more {}", kind.plural()))
+    }
+
+    /// Check the limit of `command`, a command that makes requests to a forwarding service
+    fn check_forwarder(&self, command: &'static str) -> Result<(), UsernameError> {
+        self.forwarder.check_key(&command).map_err(|_| UsernameError::LocalRateLimitExceeded {
+            message: "Rate limit exceeded: please wait before requesting more aliases".to_string(),
+        })
+    }
+}
+
+/// Hex SHA-256 of the JSON serialized generator request
+fn config_hash(request: &impl Serialize) -> String {
+    use sha2::{Digest, Sha256};
+
+    let json = serde_json::to_string(request).unwrap_or_default();
+    to_hex(&Sha256::digest(json.as_bytes()))
+}
+
+/// The generation events of this app run, oldest first
+#[tauri::command]
+async fn get_generation_audit_log(audit_log: tauri::State<'_, AuditLogState>) -> Result<Vec<AuditEntry>, String> {
+    let _timer = CommandTimer::start("get_generation_audit_log");
+    Ok(audit_log.entries())
+}
+
+#[tauri::command]
+async fn clear_audit_log(audit_log: tauri::State<'_, AuditLogState>) -> Result<(), String> {
+    let _timer = CommandTimer::start("clear_audit_log");
+    audit_log.clear();
+    Ok(())
+}
+
+#[tauri::command]
+async fn generate_password(
+    request: PasswordGeneratorRequest,
+    last_generated: tauri::State<'_, LastGeneratedState>,
+    last_request: tauri::State<'_, LastRequestState>,
+    audit_log: tauri::State<'_, AuditLogState>,
+    rate_limits: tauri::State<'_, RateLimits>,
+) -> Result<String, String> {
+    let _timer = CommandTimer::start("generate_password");
+    rate_limits.check(GenerationKind::Password)?;
+    let password = gen_password(request.clone()).map_err(|e| e.to_string())?;
+    last_generated.record("password", &password);
+    audit_log.record("password", &request);
+    last_request.record_password(request);
+    Ok(password)
+}
+
+#[tauri::command]
+async fn generate_password_with_warnings(
+    request: PasswordGeneratorRequest,
+    last_generated: tauri::State<'_, LastGeneratedState>,
+    last_request: tauri::State<'_, LastRequestState>,
+    audit_log: tauri::State<'_, AuditLogState>,
+    rate_limits: tauri::State<'_, RateLimits>,
+) -> Result<ApiResponse<PasswordResult>, String> {
+    let _timer = CommandTimer::start("generate_password_with_warnings");
+    rate_limits.check(GenerationKind::Password)?;
+    let result = gen_password_with_warnings(request.clone()).map_err(|e| e.to_string())?;
+    last_generated.record("password", &result.password);
+    audit_log.record("password", &request);
+    last_request.record_password(request);
+    Ok(ApiResponse::new(result))
+}
+
+/// Request options for a password policy preset, so the frontend can fill in its form. Not wrapped
+/// in `ApiResponse`, so the result can be sent back to `generate_password` as is.
+#[tauri::command]
+async fn get_password_preset(preset: PasswordPreset) -> PasswordGeneratorRequest {
+    let _timer = CommandTimer::start("get_password_preset");
+    PasswordGeneratorRequest::preset(preset)
+}
+
+#[tauri::command]
+async fn generate_password_legacy(
+    config: PasswordConfig,
+    last_generated: tauri::State<'_, LastGeneratedState>,
+    last_request: tauri::State<'_, LastRequestState>,
+    audit_log: tauri::State<'_, AuditLogState>,
+    rate_limits: tauri::State<'_, RateLimits>,
+) -> Result<String, String> {
+    let _timer = CommandTimer::start("generate_password_legacy");
+    rate_limits.check(GenerationKind::Password)?;
+    let request = PasswordGeneratorRequest::try_from(config).map_err(|e| e.to_string())?;
+    let password = gen_password(request.clone()).map_err(|e| e.to_string())?;
+    last_generated.record("password", &password);
+    audit_log.record("password", &request);
+    last_request.record_password(request);
+    Ok(password)
+}
+
+#[tauri::command]
+async fn generate_passphrase(
+    request: PassphraseGeneratorRequest,
+    last_generated: tauri::State<'_, LastGeneratedState>,
+    last_request: tauri::State<'_, LastRequestState>,
+    audit_log: tauri::State<'_, AuditLogState>,
+    rate_limits: tauri::State<'_, RateLimits>,
+) -> Result<String, String> {
+    let _timer = CommandTimer::start("generate_passphrase");
+    rate_limits.check(GenerationKind::Passphrase)?;
+    let passphrase = gen_passphrase(request.clone()).map_err(|e| e.to_string())?;
+    last_generated.record("passphrase", &passphrase);
+    audit_log.record("passphrase", &request);
+    last_request.record_passphrase(request);
+    Ok(passphrase)
+}
+
+#[tauri::command]
+async fn generate_passphrase_with_stats(
+    request: PassphraseGeneratorRequest,
+    last_generated: tauri::State<'_, LastGeneratedState>,
+    last_request: tauri::State<'_, LastRequestState>,
+    audit_log: tauri::State<'_, AuditLogState>,
+    rate_limits: tauri::State<'_, RateLimits>,
+) -> Result<ApiResponse<PassphraseResult>, String> {
+    let _timer = CommandTimer::start("generate_passphrase_with_stats");
+    rate_limits.check(GenerationKind::Passphrase)?;
+    let result = gen_passphrase_with_stats(request.clone()).map_err(|e| e.to_string())?;
+    last_generated.record("passphrase", &result.passphrase);
+    audit_log.record("passphrase", &request);
+    last_request.record_passphrase(request);
+    Ok(ApiResponse::new(result))
+}
+
+/// Several passphrases with the same settings, for the user to pick one from. None of them is
+/// recorded as the last generated value, since the user hasn't chosen yet.
+#[tauri::command]
+async fn generate_passphrase_choices(
+    request: PassphraseGeneratorRequest,
+    count: u8,
+    audit_log: tauri::State<'_, AuditLogState>,
+    rate_limits: tauri::State<'_, RateLimits>,
+) -> Result<Vec<PassphraseResult>, String> {
+    let _timer = CommandTimer::start("generate_passphrase_choices");
+    rate_limits.check(GenerationKind::Passphrase)?;
+    let choices = gen_passphrase_choices(request.clone(), count).map_err(|e| e.to_string())?;
+    audit_log.record("passphrase", &request);
+    Ok(choices)
+}
+
+/// Entropy the request's settings would produce, without generating a passphrase
+#[tauri::command]
+async fn estimate_passphrase_entropy(request: PassphraseGeneratorRequest) -> Result<ApiResponse<EntropyEstimate>, String> {
+    let _timer = CommandTimer::start("estimate_passphrase_entropy");
+    gen_estimate_passphrase_entropy(request)
+        .map(ApiResponse::new)
+        .map_err(|e| e.to_string())
+}
+
+/// Settings for either the password or the passphrase generator, told apart by their fields
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum GeneratorConfig {
+    Password(PasswordGeneratorRequest),
+    Passphrase(PassphraseGeneratorRequest),
+}
+
+impl GeneratorConfig {
+    fn entropy_bits(self) -> Result<f64, String> {
+        match self {
+            GeneratorConfig::Password(request) => gen_estimate_password_entropy(request),
+            GeneratorConfig::Passphrase(request) => gen_estimate_passphrase_entropy(request).map(|e| e.entropy_bits),
         }
-        Err(_) => {}
+        .map_err(|e| e.to_string())
     }
-    
-    Err("Could not get Linux network IP".to_string())
 }
 
+/// Which of two generator configurations produces stronger credentials
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+struct StrengthComparison {
+    entropy_a: f64,
+    entropy_b: f64,
+    /// `A`, `B` or `Equal`
+    winner: String,
+    difference_bits: f64,
+}
 
+/// Compare the entropy of two generator configurations, e.g. a 5 word passphrase against a
+/// 16 character password
+#[tauri::command]
+async fn compare_generator_strength(
+    config_a: GeneratorConfig,
+    config_b: GeneratorConfig,
+) -> Result<ApiResponse<StrengthComparison>, String> {
+    let _timer = CommandTimer::start("compare_generator_strength");
+    compare_strength(config_a, config_b).map(ApiResponse::new)
+}
 
-fn mask_ip_address(ip: &str) -> String {
-    let parts: Vec<&str> = ip.split('.').collect();
-    if parts.len() == 4 {
-        // IPv4 - show only first 2 octets for enhanced privacy
-        format!("{}.{}.xxx.xxx", parts[0], parts[1])
+fn compare_strength(config_a: GeneratorConfig, config_b: GeneratorConfig) -> Result<StrengthComparison, String> {
+    let entropy_a = config_a.entropy_bits()?;
+    let entropy_b = config_b.entropy_bits()?;
+    let difference_bits = (entropy_a - entropy_b).abs();
+    let winner = if difference_bits < 1e-9 {
+        "Equal"
+    } else if entropy_a > entropy_b {
+        "A"
     } else {
-        // Handle IPv6 or malformed IPs
-        if ip.contains(':') {
-            // IPv6 - show only first segment for maximum privacy
-            let segments: Vec<&str> = ip.split(':').collect();
-            if !segments.is_empty() && !segments[0].is_empty() {
-                format!("{}::xxxx:xxxx:xxxx:xxxx", segments[0])
-            } else {
-                "xxxx::xxxx:xxxx:xxxx:xxxx".to_string()
+        "B"
+    };
+    Ok(StrengthComparison {
+        entropy_a,
+        entropy_b,
+        winner: winner.to_string(),
+        difference_bits,
+    })
+}
+
+/// Emoji separators offered as presets for the passphrase `word_separator`
+#[tauri::command]
+async fn list_word_separator_presets() -> Vec<String> {
+    let _timer = CommandTimer::start("list_word_separator_presets");
+    EmojiSeparator::ALL
+        .iter()
+        .map(|preset| PassphraseGeneratorRequest::word_separator_preset(*preset))
+        .collect()
+}
+
+/// Payload of the `password-generated` event emitted by `generate_passwords_stream`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PasswordGeneratedEvent {
+    index: u16,
+    total: u16,
+    value: String,
+}
+
+/// Payload of the `generation-complete` event emitted when a stream finishes or is cancelled
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct GenerationCompleteEvent {
+    generated: u16,
+    total: u16,
+    cancelled: bool,
+}
+
+/// Tauri managed state holding the cancellation channel of each running stream, keyed by event target.
+/// Each entry carries an id so a finished stream doesn't remove a newer stream's channel.
+#[derive(Default)]
+struct GenerationStreams {
+    next_id: Mutex<u64>,
+    active: Mutex<HashMap<String, (u64, tokio::sync::watch::Sender<bool>)>>,
+}
+
+impl GenerationStreams {
+    /// Register a new stream for `event_target`, cancelling any stream already running for it
+    fn start(&self, event_target: &str) -> (u64, tokio::sync::watch::Receiver<bool>) {
+        let id = {
+            let mut next_id = self.next_id.lock().unwrap_or_else(|e| e.into_inner());
+            *next_id += 1;
+            *next_id
+        };
+        let (sender, receiver) = tokio::sync::watch::channel(false);
+        let previous = self
+            .active
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(event_target.to_string(), (id, sender));
+        if let Some((_, previous)) = previous {
+            let _ = previous.send(true);
+        }
+        (id, receiver)
+    }
+
+    /// Cancel the stream running for `event_target`, returning `false` when there is none
+    fn cancel(&self, event_target: &str) -> bool {
+        match self.active.lock().unwrap_or_else(|e| e.into_inner()).remove(event_target) {
+            Some((_, sender)) => {
+                let _ = sender.send(true);
+                true
             }
-        } else {
-            "Not available".to_string()
+            None => false,
+        }
+    }
+
+    fn finish(&self, event_target: &str, id: u64) {
+        let mut active = self.active.lock().unwrap_or_else(|e| e.into_inner());
+        if active.get(event_target).map_or(false, |(active_id, _)| *active_id == id) {
+            active.remove(event_target);
         }
     }
 }
 
-/// Calculate password/passphrase strength using zxcvbn library.
-/// 
-/// This function provides a consistent strength scoring system across all generators:
-/// - Uses industry-standard zxcvbn library for cryptographic strength analysis
-/// - Converts zxcvbn's 0-4 scale to consistent 0-100 scale: score * 25
-/// - Mapping: 0→0, 1→25, 2→50, 3→75, 4→100
-/// - Thresholds: Very Weak<25, Weak<50, Fair<75, Good<100, Strong=100
-/// 
-/// This ensures identical zxcvbn scores always produce identical strength ratings
-/// across passwords, passphrases, and any future zxcvbn-based generators.
+/// Generate `count` passwords, passing each to `emit` until done or `cancelled` is set.
+/// Returns the number of passwords generated.
+async fn stream_passwords(
+    request: PasswordGeneratorRequest,
+    count: u16,
+    cancelled: tokio::sync::watch::Receiver<bool>,
+    mut emit: impl FnMut(PasswordGeneratedEvent),
+) -> Result<u16, String> {
+    for index in 0..count {
+        if *cancelled.borrow() {
+            return Ok(index);
+        }
+        let value = gen_password(request.clone()).map_err(|e| e.to_string())?;
+        emit(PasswordGeneratedEvent { index, total: count, value });
+        // Give the cancel command a chance to run between values
+        tokio::task::yield_now().await;
+    }
+    Ok(count)
+}
+
+/// Generate `count` passwords in the background, emitting a `password-generated` event to the
+/// `event_target` window for each one and `generation-complete` at the end. Returns as soon as
+/// the request has been validated.
 #[tauri::command]
-async fn calculate_password_strength(password: String) -> Result<PasswordStrength, String> {
-    let estimate = zxcvbn(&password, &[]).map_err(|e| e.to_string())?;
-    
-    let feedback: Vec<String> = estimate
-        .feedback()
-        .as_ref()
-        .map(|f| {
-            let mut suggestions = Vec::new();
-            if let Some(warning) = &f.warning() {
-                suggestions.push(warning.to_string());
-            }
-            suggestions.extend(f.suggestions().iter().map(|s| s.to_string()));
-            suggestions
+async fn generate_passwords_stream(
+    app_handle: tauri::AppHandle,
+    streams: tauri::State<'_, GenerationStreams>,
+    audit_log: tauri::State<'_, AuditLogState>,
+    rate_limits: tauri::State<'_, RateLimits>,
+    request: PasswordGeneratorRequest,
+    count: u16,
+    event_target: String,
+) -> Result<(), String> {
+    let _timer = CommandTimer::start("generate_passwords_stream");
+    rate_limits.check(GenerationKind::Password)?;
+    request.validate().map_err(|e| e.to_string())?;
+    audit_log.record("password_stream", &request);
+    let (id, cancelled) = streams.start(&event_target);
+
+    tauri::async_runtime::spawn(async move {
+        let generated = stream_passwords(request, count, cancelled.clone(), |event| {
+            let _ = app_handle.emit_to(&event_target, "password-generated", event);
         })
-        .unwrap_or_default();
-    
-    let crack_time_display = match estimate.score() {
-        0 => "Very weak - could be cracked instantly".to_string(),
-        1 => "Weak - could be cracked in minutes".to_string(), 
-        2 => "Fair - could be cracked in hours to days".to_string(),
-        3 => "Good - could take months to crack".to_string(),
-        4 => "Strong - would take years to crack".to_string(),
-        _ => "Unknown strength".to_string(),
-    };
-    
-    Ok(PasswordStrength {
-        score: (estimate.score() as f64 * 25.0) as u8, // Convert 0-4 scale to 0-100 scale consistently
-        crack_times_display: crack_time_display,
-        feedback,
-    })
+        .await
+        .unwrap_or_else(|e| {
+            tracing::error!(%event_target, error = %e, "Password stream failed");
+            0
+        });
+
+        let complete = GenerationCompleteEvent {
+            generated,
+            total: count,
+            cancelled: *cancelled.borrow(),
+        };
+        let _ = app_handle.emit_to(&event_target, "generation-complete", complete);
+        app_handle.state::<GenerationStreams>().finish(&event_target, id);
+    });
+
+    Ok(())
 }
 
+/// Stop the password stream running for `event_target`
 #[tauri::command]
-async fn copy_to_clipboard(app_handle: tauri::AppHandle, text: String) -> Result<bool, String> {
-    app_handle
-        .clipboard_manager()
-        .write_text(text)
-        .map_err(|e| e.to_string())?;
-    Ok(true)
+async fn cancel_generation(streams: tauri::State<'_, GenerationStreams>, event_target: String) -> Result<(), String> {
+    let _timer = CommandTimer::start("cancel_generation");
+    if streams.cancel(&event_target) {
+        Ok(())
+    } else {
+        Err(format!("No generation in progress for '{}'", event_target))
+    }
 }
 
+/// Generate a random (v4) or time-ordered (v7) UUID
 #[tauri::command]
-async fn save_password_to_file(password: String) -> Result<String, String> {
-    // Get the documents directory using Tauri v1 API
-    let documents_dir = path::document_dir()
-        .ok_or("Could not find documents directory")?;
-    
-    // Create secgen directory path
-    let secgen_dir = documents_dir.join("secgen");
-    
-    // Create the directory if it doesn't exist
-    if !secgen_dir.exists() {
-        fs::create_dir_all(&secgen_dir)
-            .map_err(|e| format!("Failed to create secgen directory: {}", e))?;
-    }
-    
-    // Generate timestamp filename
-    let now = Utc::now();
-    let timestamp = now.format("%Y-%m-%d-%H-%M");
-    let filename = format!("{}-pw.txt", timestamp);
-    let file_path = secgen_dir.join(&filename);
-    
-    // Write password to file
-    fs::write(&file_path, &password)
-        .map_err(|e| format!("Failed to write password to file: {}", e))?;
-    
-    // Return the full path as confirmation
-    Ok(file_path.to_string_lossy().to_string())
+async fn generate_uuid(version: UuidVersion, format: UuidFormat) -> Result<String, String> {
+    let _timer = CommandTimer::start("generate_uuid");
+    Ok(gen_uuid(version, format))
 }
 
+/// Generate the name-based (v5) UUID for `name` in one of the predefined namespaces
 #[tauri::command]
-async fn get_system_identity() -> Result<SystemIdentityResponse, String> {
-    // Get IP information
-    let ip_response = get_public_ip_address().await?;
-    
-    // Generate hardware ID (simplified version - in production you'd want more sophisticated logic)
-    let hardware_id = generate_hardware_id().await?;
-    
-    // Generate user storage key based on hardware ID
-    let user_key = generate_user_storage_key(&hardware_id).await?;
-    
-    // Get platform information
-    let platform = std::env::consts::OS.to_string();
-    
-    Ok(SystemIdentityResponse {
-        hardware_id,
-        ip_address: ip_response.ip,
-        masked_ip: ip_response.masked_ip,
-        platform,
-        user_key,
-        country: ip_response.country,
-        region: ip_response.region,
-    })
+async fn generate_uuid_namespace(namespace: UuidNamespace, name: String) -> Result<String, String> {
+    let _timer = CommandTimer::start("generate_uuid_namespace");
+    Ok(gen_uuid_namespace(namespace, &name))
 }
 
-async fn generate_hardware_id() -> Result<String, String> {
-    // First attempt: Use machine-uid crate for secure hardware identification
-    match machine_uid::get() {
-        Ok(machine_id) => {
-            // Successfully obtained machine UID - format it consistently
-            let formatted_id = format_machine_id(&machine_id);
-            return Ok(formatted_id);
+/// Derive an encryption key from a password using PBKDF2 or Argon2id.
+/// A random 16 byte salt is generated when `salt` is not provided.
+#[tauri::command]
+async fn derive_key(
+    password: String,
+    salt: Option<String>,
+    algorithm: KdfAlgorithm,
+    output_length_bytes: u8,
+) -> Result<ApiResponse<DerivedKey>, String> {
+    let _timer = CommandTimer::start("derive_key");
+    // Key derivation is intentionally slow, so keep it off the async runtime's worker threads
+    tokio::task::spawn_blocking(move || gen_derive_key(password, salt, algorithm, output_length_bytes))
+        .await
+        .map_err(|e| e.to_string())?
+        .map(ApiResponse::new)
+        .map_err(|e| e.to_string())
+}
+
+/// Tauri managed state holding the token that cancels in-flight username generations
+#[derive(Default)]
+struct UsernameCancellation(Mutex<CancellationToken>);
+
+impl UsernameCancellation {
+    /// The token for a new generation attempt. A token cancelled while nothing was running is
+    /// replaced so it doesn't cancel the next attempt.
+    fn token(&self) -> CancellationToken {
+        let mut token = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        if token.is_cancelled() {
+            *token = CancellationToken::new();
         }
-        Err(e) => {
-            // Log the error for debugging but continue with fallback
-            eprintln!("Failed to get machine UID: {}, falling back to environment-based method", e);
+        token.clone()
+    }
+
+    fn cancel(&self) {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).cancel();
+    }
+
+    /// Replace a cancelled token once a generation attempt has finished
+    fn reset(&self) {
+        let mut token = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        if token.is_cancelled() {
+            *token = CancellationToken::new();
         }
     }
-    
-    // Fallback method: Use environment variables and system information
-    // This is less secure but ensures the function always returns a value
-    generate_fallback_hardware_id()
 }
 
-/// Format machine ID into a consistent HWID format
-fn format_machine_id(machine_id: &str) -> String {
-    use sha2::{Sha256, Digest};
-    
-    // Hash the machine ID to create a consistent format and length
-    let mut hasher = Sha256::new();
-    hasher.update(machine_id.as_bytes());
-    let result = hasher.finalize();
-    
-    // Take first 16 bytes and format as HWID-XXXX-XXXX-XXXX-XXXX
-    let hex_string: String = result[..8]
-        .iter()
-        .map(|b| format!("{:02X}", b))
-        .collect();
-    
-    format!("HWID-{}-{}-{}-{}", 
-        &hex_string[0..4], 
-        &hex_string[4..8], 
-        &hex_string[8..12], 
-        &hex_string[12..16])
-}
+/// Tauri managed state holding every username generated this session, so `generate_username`
+/// can avoid handing out the same one twice
+#[derive(Default)]
+struct UsernameDeduplicator(Mutex<HashSet<String>>);
 
-/// Fallback hardware ID generation using environment variables and system info
-/// This method is less secure but provides compatibility when machine-uid fails
-fn generate_fallback_hardware_id() -> Result<String, String> {
-    use sha2::{Sha256, Digest};
-    
-    let mut components = Vec::new();
-    
-    // Collect system information for hardware ID generation
-    components.push(std::env::consts::OS.to_string());
-    components.push(std::env::consts::ARCH.to_string());
-    
-    // Add machine name if available (more reliable than username)
-    if let Ok(hostname) = std::env::var("COMPUTERNAME")
-        .or_else(|_| std::env::var("HOSTNAME"))
-        .or_else(|_| std::env::var("HOST")) {
-        components.push(hostname);
+impl UsernameDeduplicator {
+    /// Remember `username`, returns `false` if it was already generated this session
+    fn insert(&self, username: &str) -> bool {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).insert(username.to_string())
     }
-    
-    // Add additional system identifiers if available
-    if let Ok(processor_id) = std::env::var("PROCESSOR_IDENTIFIER") {
-        components.push(processor_id);
+
+    fn len(&self) -> usize {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).len()
     }
-    
-    if let Ok(processor_arch) = std::env::var("PROCESSOR_ARCHITECTURE") {
-        components.push(processor_arch);
+
+    fn clear(&self) {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).clear();
     }
-    
-    // Create a more robust hash using SHA-256 instead of DefaultHasher
-    let combined_info = components.join("|");
-    let mut hasher = Sha256::new();
-    hasher.update(combined_info.as_bytes());
-    
-    // Add a static salt to make the hash less predictable
-    hasher.update(b"SecureGen-HardwareID-Salt-2024");
-    
-    let result = hasher.finalize();
-    
-    // Format as HWID with fallback indicator
-    let hex_string: String = result[..8]
-        .iter()
-        .map(|b| format!("{:02X}", b))
-        .collect();
-    
-    Ok(format!("HWID-FB-{}-{}-{}", 
-        &hex_string[0..4], 
-        &hex_string[4..8], 
-        &hex_string[8..12]))
 }
 
-async fn generate_user_storage_key(hardware_id: &str) -> Result<String, String> {
-    use sha2::{Sha256, Digest};
-    
-    let mut hasher = Sha256::new();
-    hasher.update(hardware_id.as_bytes());
-    let result = hasher.finalize();
-    
-    // Take first 8 bytes and convert to hex
-    let short_key = result[..8]
-        .iter()
-        .map(|b| format!("{:02x}", b))
-        .collect::<String>();
-    
-    Ok(format!("securegen-store-{}", short_key))
+/// Times a username already generated this session is regenerated before giving up
+const MAX_USERNAME_DEDUPLICATION_RETRIES: u8 = 10;
+
+/// Call `generate` until it returns a username `deduplicator` hasn't seen, retrying at most
+/// [`MAX_USERNAME_DEDUPLICATION_RETRIES`] times. Without `deduplicate` the first username is
+/// returned, but it is still remembered.
+async fn generate_deduplicated<F, Fut>(
+    deduplicator: &UsernameDeduplicator,
+    deduplicate: bool,
+    mut generate: F,
+) -> Result<String, SerializableError>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<String, SerializableError>>,
+{
+    for _ in 0..=MAX_USERNAME_DEDUPLICATION_RETRIES {
+        let username = generate().await?;
+        if deduplicator.insert(&username) || !deduplicate {
+            return Ok(username);
+        }
+    }
+    Err(UsernameError::DuplicateUsername {
+        attempts: MAX_USERNAME_DEDUPLICATION_RETRIES + 1,
+    }
+    .into())
 }
 
-/// Calculate username security strength using custom algorithm.
-/// 
-/// Username security differs from password security and requires specialized evaluation:
-/// - Focuses on privacy, uniqueness, and guessability rather than cryptographic strength
-/// - Uses direct 0-100 scale calculation (no conversion needed)
-/// - Considers username-specific factors: dictionary usage, personal info, common patterns
-/// - Thresholds: Very Poor≤25, Poor≤45, Fair≤65, Good≤80, Excellent≥81
-/// 
-/// This custom approach is necessary because username security concerns (privacy, 
-/// uniqueness, non-attribution) differ significantly from password security concerns
-/// (resistance to brute force, dictionary attacks, cryptographic strength).
+/// Generate a username, abandoning any forwarder HTTP request as soon as `cancel` fires
+async fn generate_username_cancellable(
+    request: UsernameGeneratorRequest,
+    client: &reqwest::Client,
+    cancel: CancellationToken,
+) -> Result<String, SerializableError> {
+    match cancel.run_until_cancelled(gen_username(request, client)).await {
+        Some(result) => result.map_err(SerializableError::from),
+        None => Err(UsernameError::Cancelled.into()),
+    }
+}
+
+/// Longest website, domain or email address accepted from the frontend
+const MAX_INPUT_LENGTH: usize = 320;
+/// Longest save directory path accepted from the frontend, in bytes
+const MAX_PATH_LENGTH: usize = 4096;
+
+/// Sanitize a single line input, naming it in the error
+fn sanitize_field(name: &str, value: &str, max_len: usize) -> Result<String, String> {
+    security::sanitize_string_input(value, max_len, false).map_err(|e| format!("Invalid {}: {}", name, e))
+}
+
+/// Check a path from the frontend. Unlike [`sanitize_field`], shell metacharacters are allowed
+/// because they are valid in file names, and a path that is too long is rejected rather than
+/// cut short, which would point at a different directory.
+fn validate_path(path: &str) -> Result<(), String> {
+    if path.contains('\0') {
+        return Err("Invalid path: must not contain NUL bytes".to_string());
+    }
+    if path.len() > MAX_PATH_LENGTH {
+        return Err(format!("Invalid path: must be at most {} bytes", MAX_PATH_LENGTH));
+    }
+    Ok(())
+}
+
+/// Sanitize the website, domain and email fields of a username request, these end up in email
+/// addresses and forwarder API requests
+fn sanitize_username_request(mut request: UsernameGeneratorRequest) -> Result<UsernameGeneratorRequest, String> {
+    fn sanitize_append_type(append_type: &mut AppendType) -> Result<(), String> {
+        if let AppendType::WebsiteName { website } = append_type {
+            *website = sanitize_field("website", website, MAX_INPUT_LENGTH)?;
+        }
+        Ok(())
+    }
+
+    match &mut request {
+        UsernameGeneratorRequest::Subaddress { r#type, email } => {
+            *email = sanitize_field("email", email, MAX_INPUT_LENGTH)?;
+            sanitize_append_type(r#type)?;
+        }
+        UsernameGeneratorRequest::Catchall { r#type, domain, .. } => {
+            *domain = sanitize_field("domain", domain, MAX_INPUT_LENGTH)?;
+            sanitize_append_type(r#type)?;
+        }
+        UsernameGeneratorRequest::Forwarded { service, website } => {
+            if let Some(website) = website {
+                *website = sanitize_field("website", website, MAX_INPUT_LENGTH)?;
+            }
+            if let ForwarderServiceType::AddyIo { domain, .. } | ForwarderServiceType::ForwardEmail { domain, .. } =
+                service
+            {
+                *domain = sanitize_field("domain", domain, MAX_INPUT_LENGTH)?;
+            }
+        }
+        UsernameGeneratorRequest::Word { .. } | UsernameGeneratorRequest::CompoundWord { .. } => {}
+    }
+    Ok(request)
+}
+
+/// Time a forwarded username generation may take when the frontend doesn't set one
+const DEFAULT_USERNAME_TIMEOUT_SECONDS: u8 = 30;
+/// Longest time the frontend may allow a forwarded username generation to take
+const MAX_USERNAME_TIMEOUT_SECONDS: u8 = 120;
+
+/// Generate a username, giving up on forwarded usernames after `timeout`. The other username
+/// types don't make requests and finish straight away, so they are never timed out.
+async fn generate_username_with_timeout(
+    request: UsernameGeneratorRequest,
+    client: &reqwest::Client,
+    cancel: CancellationToken,
+    timeout: std::time::Duration,
+) -> Result<String, SerializableError> {
+    if !matches!(request, UsernameGeneratorRequest::Forwarded { .. }) {
+        return generate_username_cancellable(request, client, cancel).await;
+    }
+    tokio::time::timeout(timeout, generate_username_cancellable(request, client, cancel))
+        .await
+        .unwrap_or_else(|_| {
+            Err(SerializableError {
+                code: UsernameErrorCode::Timeout as u16,
+                message: format!("Username generation timed out after {} seconds", timeout.as_secs()),
+                details: None,
+            })
+        })
+}
+
+/// Generate a username. Forwarded usernames time out after `timeout_seconds`, 30 by default and
+/// at most 120. With `deduplicate`, usernames already generated this session are regenerated,
+/// except forwarded ones: every attempt would create an alias, and the service hands out
+/// unique addresses anyway.
 #[tauri::command]
-async fn calculate_username_strength(username: String) -> Result<UsernameStrength, String> {
-    let result = evaluate_username_security(&username);
-    Ok(result)
+#[tracing::instrument(skip_all)]
+#[allow(clippy::too_many_arguments)]
+async fn generate_username(
+    request: UsernameGeneratorRequest,
+    timeout_seconds: Option<u8>,
+    deduplicate: Option<bool>,
+    last_generated: tauri::State<'_, LastGeneratedState>,
+    cancellation: tauri::State<'_, UsernameCancellation>,
+    audit_log: tauri::State<'_, AuditLogState>,
+    rate_limits: tauri::State<'_, RateLimits>,
+    deduplicator: tauri::State<'_, UsernameDeduplicator>,
+) -> Result<String, SerializableError> {
+    let _timer = CommandTimer::start("generate_username");
+    let timeout_seconds = timeout_seconds.unwrap_or(DEFAULT_USERNAME_TIMEOUT_SECONDS);
+    if !(1..=MAX_USERNAME_TIMEOUT_SECONDS).contains(&timeout_seconds) {
+        return Err(SerializableError {
+            code: GENERIC_ERROR_CODE,
+            message: format!("Timeout must be between 1 and {} seconds", MAX_USERNAME_TIMEOUT_SECONDS),
+            details: None,
+        });
+    }
+    rate_limits
+        .check(GenerationKind::Username)
+        .map_err(|message| UsernameError::LocalRateLimitExceeded { message })?;
+    if matches!(request, UsernameGeneratorRequest::Forwarded { .. }) {
+        rate_limits.check_forwarder("generate_username")?;
+    }
+    let request = sanitize_username_request(request).map_err(|message| SerializableError {
+        code: GENERIC_ERROR_CODE,
+        message,
+        details: None,
+    })?;
+    // Hash the request before it is consumed, without the forwarder API tokens
+    let redacted_request = request.redact_secrets();
+    let client = reqwest::Client::new();
+    let timeout = std::time::Duration::from_secs(timeout_seconds.into());
+    let deduplicate = deduplicate.unwrap_or(false) && !matches!(request, UsernameGeneratorRequest::Forwarded { .. });
+    let result = generate_deduplicated(&deduplicator, deduplicate, || {
+        generate_username_with_timeout(request.clone(), &client, cancellation.token(), timeout)
+    })
+    .await;
+    cancellation.reset();
+
+    let username = result?;
+    last_generated.record("username", &username);
+    audit_log.record("username", &redacted_request);
+    Ok(username)
 }
 
-fn evaluate_username_security(username: &str) -> UsernameStrength {
-    let mut feedback = Vec::new();
-    let mut privacy_score = 0u8;
-    let mut uniqueness_score = 0u8;
+/// Forget the usernames generated this session, so `generate_username` may return them again
+#[tauri::command]
+async fn clear_username_history(deduplicator: tauri::State<'_, UsernameDeduplicator>) -> Result<(), String> {
+    let _timer = CommandTimer::start("clear_username_history");
+    deduplicator.clear();
+    Ok(())
+}
+
+/// Number of different usernames generated this session
+#[tauri::command]
+async fn get_username_generation_count(deduplicator: tauri::State<'_, UsernameDeduplicator>) -> Result<usize, String> {
+    let _timer = CommandTimer::start("get_username_generation_count");
+    Ok(deduplicator.len())
+}
+
+/// Normalize an email address for comparison: the domain is lowercased and converted to its
+/// ASCII compatible form, the local part is kept as entered
+#[tauri::command]
+async fn normalize_email(email: String) -> Result<String, String> {
+    let _timer = CommandTimer::start("normalize_email");
+    gen_normalize_email(&email).map_err(|e| e.to_string())
+}
+
+/// Whether two email addresses, such as two generated subaddresses, are the same address
+#[tauri::command]
+async fn emails_equivalent(a: String, b: String) -> Result<bool, String> {
+    let _timer = CommandTimer::start("emails_equivalent");
+    gen_emails_equivalent(&a, &b).map_err(|e| e.to_string())
+}
+
+/// List one page of the aliases on an addy.io account
+#[tauri::command]
+async fn list_addy_aliases(
+    api_token: String,
+    base_url: String,
+    page: u8,
+    rate_limits: tauri::State<'_, RateLimits>,
+) -> Result<Vec<AliasInfo>, String> {
+    let _timer = CommandTimer::start("list_addy_aliases");
+    rate_limits.check_forwarder("list_addy_aliases").map_err(|e| e.to_string())?;
+    if api_token.trim().is_empty() {
+        return Err(UsernameError::IncompleteApiConfig {
+            service: "AddyIo".to_string(),
+        }
+        .to_string());
+    }
+    validate_base_url(&base_url, "AddyIo").map_err(|e| e.to_string())?;
+
+    let client = reqwest::Client::new();
+    addy_list_aliases(&client, api_token, base_url, page)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// List the mailboxes of an addy.io account, so new aliases can be routed to one of them
+#[tauri::command]
+async fn list_addy_mailboxes(
+    api_token: String,
+    base_url: String,
+    rate_limits: tauri::State<'_, RateLimits>,
+) -> Result<Vec<AddyMailbox>, String> {
+    let _timer = CommandTimer::start("list_addy_mailboxes");
+    rate_limits.check_forwarder("list_addy_mailboxes").map_err(|e| e.to_string())?;
+    if api_token.trim().is_empty() {
+        return Err(UsernameError::IncompleteApiConfig {
+            service: "AddyIo".to_string(),
+        }
+        .to_string());
+    }
+    validate_base_url(&base_url, "AddyIo").map_err(|e| e.to_string())?;
+
+    let client = reqwest::Client::new();
+    addy_list_mailboxes(&client, api_token, base_url)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// List the custom domains of an addy.io account, aliases can only be created on these or on the
+/// shared addy.io domains
+#[tauri::command]
+async fn list_addy_domains(
+    api_token: String,
+    base_url: String,
+    rate_limits: tauri::State<'_, RateLimits>,
+) -> Result<Vec<AddyDomain>, String> {
+    let _timer = CommandTimer::start("list_addy_domains");
+    rate_limits.check_forwarder("list_addy_domains").map_err(|e| e.to_string())?;
+    if api_token.trim().is_empty() {
+        return Err(UsernameError::IncompleteApiConfig {
+            service: "AddyIo".to_string(),
+        }
+        .to_string());
+    }
+    validate_base_url(&base_url, "AddyIo").map_err(|e| e.to_string())?;
+
+    let client = reqwest::Client::new();
+    addy_list_domains(&client, api_token, base_url)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// List the alias directories of a SimpleLogin premium account
+#[tauri::command]
+async fn list_simplelogin_directories(
+    api_key: String,
+    base_url: String,
+    rate_limits: tauri::State<'_, RateLimits>,
+) -> Result<Vec<SimpleLoginDirectory>, String> {
+    let _timer = CommandTimer::start("list_simplelogin_directories");
+    rate_limits.check_forwarder("list_simplelogin_directories").map_err(|e| e.to_string())?;
+    if api_key.trim().is_empty() {
+        return Err(UsernameError::IncompleteApiConfig {
+            service: "SimpleLogin".to_string(),
+        }
+        .to_string());
+    }
+    validate_base_url(&base_url, "SimpleLogin").map_err(|e| e.to_string())?;
+
+    let client = reqwest::Client::new();
+    simplelogin_list_directories(&client, api_key, base_url)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Look up how many aliases the Firefox Relay account can still create
+#[tauri::command]
+async fn get_firefox_relay_quota(
+    api_token: String,
+    rate_limits: tauri::State<'_, RateLimits>,
+) -> Result<ApiResponse<FirefoxRelayQuota>, String> {
+    let _timer = CommandTimer::start("get_firefox_relay_quota");
+    rate_limits.check_forwarder("get_firefox_relay_quota").map_err(|e| e.to_string())?;
+    if api_token.trim().is_empty() {
+        return Err(UsernameError::IncompleteApiConfig {
+            service: "Firefox".to_string(),
+        }
+        .to_string());
+    }
+
+    let client = reqwest::Client::new();
+    firefox_quota_remaining(&client, api_token)
+        .await
+        .map(ApiResponse::new)
+        .map_err(|e| e.to_string())
+}
+
+/// List the sender identities of a Fastmail account, to pick the owner of new masked emails
+#[tauri::command]
+async fn list_fastmail_identities(
+    api_token: String,
+    rate_limits: tauri::State<'_, RateLimits>,
+) -> Result<Vec<FastmailIdentity>, String> {
+    let _timer = CommandTimer::start("list_fastmail_identities");
+    rate_limits.check_forwarder("list_fastmail_identities").map_err(|e| e.to_string())?;
+    if api_token.trim().is_empty() {
+        return Err(UsernameError::IncompleteApiConfig {
+            service: "Fastmail".to_string(),
+        }
+        .to_string());
+    }
+
+    let client = reqwest::Client::new();
+    fastmail_list_identities(&client, api_token)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Check that a ForwardEmail domain has the DNS records needed to create aliases
+#[tauri::command]
+async fn verify_forward_email_domain(
+    api_token: String,
+    domain: String,
+    rate_limits: tauri::State<'_, RateLimits>,
+) -> Result<ApiResponse<DomainVerificationStatus>, String> {
+    let _timer = CommandTimer::start("verify_forward_email_domain");
+    rate_limits.check_forwarder("verify_forward_email_domain").map_err(|e| e.to_string())?;
+    let domain = sanitize_field("domain", &domain, MAX_INPUT_LENGTH)?;
+    if api_token.trim().is_empty() || domain.trim().is_empty() {
+        return Err(UsernameError::IncompleteApiConfig {
+            service: "ForwardEmail".to_string(),
+        }
+        .to_string());
+    }
+
+    let client = reqwest::Client::new();
+    forwardemail_verify_domain(&client, api_token, domain)
+        .await
+        .map(ApiResponse::new)
+        .map_err(|e| e.to_string())
+}
+
+/// List the aliases of a ForwardEmail domain
+#[tauri::command]
+async fn list_forward_email_aliases(
+    api_token: String,
+    domain: String,
+    page: Option<u32>,
+    limit: Option<u32>,
+    rate_limits: tauri::State<'_, RateLimits>,
+) -> Result<Vec<ForwardEmailAlias>, String> {
+    let _timer = CommandTimer::start("list_forward_email_aliases");
+    rate_limits.check_forwarder("list_forward_email_aliases").map_err(|e| e.to_string())?;
+    let domain = sanitize_field("domain", &domain, MAX_INPUT_LENGTH)?;
+    if api_token.trim().is_empty() || domain.trim().is_empty() {
+        return Err(UsernameError::IncompleteApiConfig {
+            service: "ForwardEmail".to_string(),
+        }
+        .to_string());
+    }
+
+    let client = reqwest::Client::new();
+    forwardemail_list_aliases(&client, api_token, domain, page, limit)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Permanently delete an alias from a ForwardEmail domain
+#[tauri::command]
+async fn delete_forward_email_alias(
+    api_token: String,
+    domain: String,
+    alias_id: String,
+    rate_limits: tauri::State<'_, RateLimits>,
+) -> Result<(), String> {
+    let _timer = CommandTimer::start("delete_forward_email_alias");
+    rate_limits.check_forwarder("delete_forward_email_alias").map_err(|e| e.to_string())?;
+    let domain = sanitize_field("domain", &domain, MAX_INPUT_LENGTH)?;
+    if api_token.trim().is_empty() || domain.trim().is_empty() {
+        return Err(UsernameError::IncompleteApiConfig {
+            service: "ForwardEmail".to_string(),
+        }
+        .to_string());
+    }
+
+    let client = reqwest::Client::new();
+    forwardemail_delete_alias(&client, api_token, domain, alias_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Deactivate an alias on a forwarding service so it stops receiving mail
+#[tauri::command]
+async fn deactivate_email_alias(
+    service: ForwarderServiceType,
+    alias_id: String,
+    rate_limits: tauri::State<'_, RateLimits>,
+) -> Result<(), String> {
+    let _timer = CommandTimer::start("deactivate_email_alias");
+    rate_limits.check_forwarder("deactivate_email_alias").map_err(|e| e.to_string())?;
+    let client = reqwest::Client::new();
+    service
+        .deactivate_alias(&client, alias_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Cancel the username generation currently waiting on a forwarder service
+#[tauri::command]
+async fn cancel_username_generation(cancellation: tauri::State<'_, UsernameCancellation>) -> Result<(), String> {
+    let _timer = CommandTimer::start("cancel_username_generation");
+    cancellation.cancel();
+    Ok(())
+}
+
+/// How long to wait for the STUN server before falling back to platform commands
+const STUN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+#[tauri::command]
+async fn get_public_ip_address(ip_cache: tauri::State<'_, IpCacheState>) -> Result<ApiResponse<IPResponse>, String> {
+    let _timer = CommandTimer::start("get_public_ip_address");
+    cached_public_ip(&ip_cache).await.map(ApiResponse::new)
+}
+
+/// Drop the cached public IP so the next lookup goes to the network
+#[tauri::command]
+async fn invalidate_ip_cache(ip_cache: tauri::State<'_, IpCacheState>) -> Result<(), String> {
+    let _timer = CommandTimer::start("invalidate_ip_cache");
+    ip_cache.invalidate();
+    Ok(())
+}
+
+/// Set how long a public IP lookup is reused, 0 disables the cache
+#[tauri::command]
+async fn set_ip_cache_ttl(
+    ip_cache: tauri::State<'_, IpCacheState>,
+    save_preferences: tauri::State<'_, SavePreferencesState>,
+    ttl_seconds: u64,
+) -> Result<(), String> {
+    let _timer = CommandTimer::start("set_ip_cache_ttl");
+    save_preferences.update(|preferences| preferences.cache_ttl_seconds = Some(ttl_seconds))?;
+    ip_cache.set_ttl(ttl_seconds);
+    Ok(())
+}
+
+/// The public IP from `ip_cache`, looked up and cached when missing or expired
+async fn cached_public_ip(ip_cache: &IpCacheState) -> Result<IPResponse, String> {
+    if let Some(ip) = ip_cache.get(std::time::Instant::now()) {
+        return Ok(ip);
+    }
+    let ip = lookup_public_ip().await?;
+    ip_cache.store(ip.clone(), std::time::Instant::now());
+    Ok(ip)
+}
+
+async fn lookup_public_ip() -> Result<IPResponse, String> {
+    // Try multiple approaches in order of reliability for production use
     
-    let username_lower = username.to_lowercase();
+    // 1. Get routable IP using optimized UDP socket method
+    if let Ok(ip) = get_routable_ip() {
+        return Ok(IPResponse::from_ip(ip));
+    }
+
+    // 2. Get active network interface IP (most reliable fallback)
+    if let Ok(ip) = get_active_network_interface_ip() {
+        return Ok(IPResponse::from_ip(ip));
+    }
+
+    // 3. Ask a STUN server for the address after NAT, this needs network connectivity
+    match stun::external_ip(stun::DEFAULT_SERVER, STUN_TIMEOUT) {
+        Ok(ip) => return Ok(IPResponse::from_ip(ip.to_string())),
+        Err(e) => tracing::debug!(error = %e, "STUN lookup failed"),
+    }
+
+    // 4. Platform-specific system commands
+    if let Ok(ip) = get_network_interface_ip() {
+        return Ok(IPResponse::from_ip(ip));
+    }
+
+    // Final production fallback - return system identifier instead of mock data
+    Ok(IPResponse {
+        ip: format!("SYS-{}", std::env::consts::OS.to_uppercase()),
+        masked_ip: "System IP".to_string(),
+        country: None,
+        region: None,
+        geo_lookup_available: geoip::is_available(),
+    })
+}
+
+/// Get the routable IP address by testing connectivity to well-known servers
+/// This method determines which local IP would be used for internet communication
+/// without actually sending data - production-ready and reliable
+fn get_routable_ip() -> Result<String, String> {
+    // Try multiple reliable servers to determine the routable IP
+    let test_servers = [
+        ("1.1.1.1", 53),      // Cloudflare DNS - very reliable
+        ("8.8.8.8", 53),      // Google DNS - fallback
+        ("208.67.222.222", 53), // OpenDNS - second fallback
+    ];
+
+    for (server, port) in &test_servers {
+        match UdpSocket::bind("0.0.0.0:0") {
+            Ok(socket) => {
+                // Set a timeout for production reliability
+                if let Err(_) = socket.set_read_timeout(Some(std::time::Duration::from_secs(2))) {
+                    continue;
+                }
+                
+                match socket.connect(format!("{}:{}", server, port)) {
+                    Ok(_) => {
+                        match socket.local_addr() {
+                            Ok(addr) => {
+                                let ip = addr.ip().to_string();
+                                // Ensure we got a routable IP, not localhost or link-local
+                                if let Ok(parsed_ip) = ip.parse::<Ipv4Addr>() {
+                                    if !parsed_ip.is_loopback() && 
+                                       !parsed_ip.is_link_local() && 
+                                       !parsed_ip.is_unspecified() {
+                                        return Ok(ip);
+                                    }
+                                }
+                            }
+                            Err(_) => continue,
+                        }
+                    }
+                    Err(_) => continue,
+                }
+            }
+            Err(_) => continue,
+        }
+    }
     
-    // First check if this is likely a generated username - this affects how we score
-    let is_generated = is_likely_generated_username(&username_lower);
+    Err("Could not determine routable IP address".to_string())
+}
+
+/// Get IP from the active network interface using system APIs
+/// This is a reliable fallback that doesn't require network connectivity
+fn get_active_network_interface_ip() -> Result<String, String> {
+    use std::net::IpAddr;
     
-    // Length evaluation (different from passwords)
-    let length = username.len();
-    if length < 3 {
-        feedback.push("Username is too short - minimum 3 characters recommended".to_string());
-    } else if length >= 6 && length <= 20 {
-        privacy_score += 20;
-        uniqueness_score += 15;
-    } else if length > 20 {
-        feedback.push("Very long usernames may be memorable and stand out".to_string());
-        privacy_score += 10;
+    // Get all network interfaces
+    match get_if_addrs::get_if_addrs() {
+        Ok(interfaces) => {
+            let mut best_ip = None;
+            let mut fallback_ip = None;
+            
+            for interface in interfaces {
+                let ip = interface.ip();
+                
+                // Skip loopback and inactive interfaces
+                if interface.is_loopback() {
+                    continue;
+                }
+                
+                match ip {
+                    IpAddr::V4(ipv4) => {
+                        // Skip link-local and other special addresses
+                        if ipv4.is_link_local() || ipv4.is_unspecified() || ipv4.is_broadcast() {
+                            continue;
+                        }
+                        
+                        // Prefer public IPs over private IPs
+                        if !ipv4.is_private() {
+                            best_ip = Some(ipv4.to_string());
+                            break; // Found a public IP, use it immediately
+                        } else if fallback_ip.is_none() {
+                            // Keep the first private IP as fallback
+                            fallback_ip = Some(ipv4.to_string());
+                        }
+                    }
+                    IpAddr::V6(ipv6) => {
+                        // For IPv6, prefer global addresses
+                        if !ipv6.is_loopback() && !ipv6.is_unspecified() {
+                            // IPv6 global addresses start with 2000::/3
+                            let segments = ipv6.segments();
+                            if segments[0] & 0xe000 == 0x2000 {
+                                best_ip = Some(ipv6.to_string());
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+            
+            // Return the best IP found, or fallback to private IP
+            if let Some(ip) = best_ip {
+                return Ok(ip);
+            } else if let Some(ip) = fallback_ip {
+                return Ok(ip);
+            }
+        }
+        Err(_) => {}
+    }
+    
+    Err("Could not determine active network interface IP".to_string())
+}
+
+/// List the network interfaces along with their IPv4 and IPv6 addresses.
+/// When `masked` is true, each address is masked with `mask_ip_address`.
+#[tauri::command]
+async fn list_network_interfaces(masked: bool) -> Result<Vec<NetworkInterface>, String> {
+    let _timer = CommandTimer::start("list_network_interfaces");
+    collect_network_interfaces(masked)
+}
+
+fn collect_network_interfaces(masked: bool) -> Result<Vec<NetworkInterface>, String> {
+    let addrs = get_if_addrs::get_if_addrs()
+        .map_err(|e| format!("Failed to enumerate network interfaces: {}", e))?;
+
+    // get_if_addrs returns one entry per address, so group them by interface name
+    // while keeping the order the OS reported them in
+    let mut interfaces: Vec<NetworkInterface> = Vec::new();
+    for addr in addrs {
+        let ip = addr.ip().to_string();
+        let ip = if masked { mask_ip_address(&ip) } else { ip };
+
+        match interfaces.iter_mut().find(|i| i.name == addr.name) {
+            Some(interface) => interface.ip_addresses.push(ip),
+            None => interfaces.push(NetworkInterface {
+                is_up: is_interface_up(&addr.name),
+                is_loopback: addr.is_loopback(),
+                name: addr.name,
+                ip_addresses: vec![ip],
+            }),
+        }
+    }
+
+    Ok(interfaces)
+}
+
+/// Check whether a network interface is operationally up.
+/// get_if_addrs doesn't expose interface flags, so on Linux we read the kernel's operstate.
+/// Elsewhere an interface with an assigned address is treated as up.
+#[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+fn is_interface_up(name: &str) -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        if let Ok(state) = fs::read_to_string(format!("/sys/class/net/{}/operstate", name)) {
+            // Loopback and some virtual interfaces report "unknown" while passing traffic
+            return matches!(state.trim(), "up" | "unknown");
+        }
+    }
+
+    true
+}
+
+/// Runs the system commands the platform specific IP lookups read from, so their parsing can be
+/// tested against recorded output
+trait NetworkCommandRunner {
+    /// Runs `args[0]` with the remaining arguments and returns its standard output
+    fn run(&self, args: &[&str]) -> Result<String, String>;
+}
+
+struct SystemCommandRunner;
+
+impl NetworkCommandRunner for SystemCommandRunner {
+    fn run(&self, args: &[&str]) -> Result<String, String> {
+        let (program, args) = args.split_first().ok_or("No command given")?;
+        let output = Command::new(program)
+            .args(args)
+            .output()
+            .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+/// Get IP from network interfaces using platform-specific commands
+fn get_network_interface_ip() -> Result<String, String> {
+    #[cfg(target_os = "windows")]
+    {
+        get_windows_network_ip(&SystemCommandRunner)
+    }
+    
+    #[cfg(target_os = "macos")]
+    {
+        get_macos_network_ip(&SystemCommandRunner)
+    }
+    
+    #[cfg(target_os = "linux")]
+    {
+        get_linux_network_ip(&SystemCommandRunner)
+    }
+    
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    {
+        Err("Unsupported platform for network interface detection".to_string())
+    }
+}
+
+/// Parses an IPv4 address that is neither loopback nor link-local
+#[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux", test))]
+fn parse_usable_ipv4(ip: &str) -> Option<String> {
+    let parsed_ip = ip.parse::<Ipv4Addr>().ok()?;
+    // Exclude loopback (127.x.x.x) and link-local (169.254.x.x)
+    if parsed_ip.is_loopback() || parsed_ip.is_link_local() {
+        None
     } else {
-        privacy_score += 10;
+        Some(ip.to_string())
+    }
+}
+
+#[cfg(any(target_os = "windows", test))]
+fn get_windows_network_ip(runner: &dyn NetworkCommandRunner) -> Result<String, String> {
+    // Use ipconfig to get network information
+    if let Ok(output) = runner.run(&["ipconfig"]) {
+        // Look for IPv4 addresses that are not loopback or link-local
+        for line in output.lines() {
+            if line.contains("IPv4 Address") {
+                if let Some(ip) = line.split(':').nth(1).and_then(|ip| parse_usable_ipv4(ip.trim())) {
+                    return Ok(ip);
+                }
+            }
+        }
     }
     
-    // Enhanced dictionary word analysis - distinguish between secure and weak dictionary usage
-    // BUT: Apply length-based constraints to ensure consistent Basic username ratings
-    let dict_analysis = analyze_dictionary_usage(&username_lower);
-    match dict_analysis {
-        DictionaryUsage::SecureEFFWord { word_length } => {
-            // EFF words are cryptographically selected - they're GOOD for usernames
-            // However, for Basic strength (3-4 chars), we need consistent Poor ratings
-            if length <= 4 {
-                // Basic usernames (3-4 chars) should be consistently Poor regardless of EFF status
-                // Give minimal bonus to maintain some distinction but keep in Poor range
-                privacy_score += 5;  // Reduced from 10 to keep Basic usernames in Poor category
-                uniqueness_score += 10; // Reduced from 15
-                feedback.push("Short word from secure wordlist - consider longer username".to_string());
-            } else if word_length >= 7 {
-                privacy_score += 20;
-                uniqueness_score += 25;
-                feedback.push("Uses cryptographically strong word - excellent choice".to_string());
-            } else if word_length >= 5 {
-                privacy_score += 15;
-                uniqueness_score += 20;
-                feedback.push("Uses secure word from cryptographic wordlist".to_string());
+    Err("Could not get Windows network IP".to_string())
+}
+
+#[cfg(any(target_os = "macos", test))]
+fn get_macos_network_ip(runner: &dyn NetworkCommandRunner) -> Result<String, String> {
+    // Use route command to get the default route interface, then get its IP
+    if let Ok(output) = runner.run(&["route", "get", "default"]) {
+        // Extract interface name
+        let interface = output
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("interface:"))
+            .map(str::trim);
+        
+        if let Some(iface) = interface {
+            // Get IP for this interface
+            if let Ok(ifconfig_output) = runner.run(&["ifconfig", iface]) {
+                for line in ifconfig_output.lines() {
+                    if line.contains("inet ") && !line.contains("inet6") {
+                        if let Some(ip) = line.split_whitespace().nth(1).and_then(parse_usable_ipv4) {
+                            return Ok(ip);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    
+    Err("Could not get macOS network IP".to_string())
+}
+
+#[cfg(any(target_os = "linux", test))]
+fn get_linux_network_ip(runner: &dyn NetworkCommandRunner) -> Result<String, String> {
+    // Use ip route to get the default route, then extract the source IP
+    if let Ok(output) = runner.run(&["ip", "route", "get", "8.8.8.8"]) {
+        // Look for "src" followed by an IP address, the first address is the destination
+        let parts: Vec<&str> = output.split_whitespace().collect();
+        for pair in parts.windows(2) {
+            if pair[0] == "src" {
+                if let Some(ip) = parse_usable_ipv4(pair[1]) {
+                    return Ok(ip);
+                }
+            }
+        }
+    }
+    
+    // Fallback: use hostname -I
+    if let Ok(output) = runner.run(&["hostname", "-I"]) {
+        if let Some(ip) = output.split_whitespace().next().and_then(parse_usable_ipv4) {
+            return Ok(ip);
+        }
+    }
+    
+    Err("Could not get Linux network IP".to_string())
+}
+
+/// Hide the host part of an IP address before it is shown or stored
+///
+/// # Examples
+///
+/// Run as `tests::test_mask_ip_address`.
+///
+/// ```ignore
+/// assert_eq!(mask_ip_address("203.0.113.42"), "203.0.xxx.xxx");
+/// assert_eq!(mask_ip_address("2001:db8::1"), "2001::xxxx:xxxx:xxxx:xxxx");
+/// assert_eq!(mask_ip_address("not an ip"), "Not available");
+/// ```
+fn mask_ip_address(ip: &str) -> String {
+    let parts: Vec<&str> = ip.split('.').collect();
+    if parts.len() == 4 {
+        // IPv4 - show only first 2 octets for enhanced privacy
+        format!("{}.{}.xxx.xxx", parts[0], parts[1])
+    } else {
+        // Handle IPv6 or malformed IPs
+        if ip.contains(':') {
+            // IPv6 - show only first segment for maximum privacy
+            let segments: Vec<&str> = ip.split(':').collect();
+            if !segments.is_empty() && !segments[0].is_empty() {
+                format!("{}::xxxx:xxxx:xxxx:xxxx", segments[0])
             } else {
-                // This case should be covered by the length <= 4 check above, but keeping for safety
-                privacy_score += 10;
-                uniqueness_score += 15;
-                feedback.push("Uses word from secure wordlist".to_string());
+                "xxxx::xxxx:xxxx:xxxx:xxxx".to_string()
+            }
+        } else {
+            "Not available".to_string()
+        }
+    }
+}
+
+/// Calculate password/passphrase strength using zxcvbn library.
+/// 
+/// This function provides a consistent strength scoring system across all generators:
+/// - Uses industry-standard zxcvbn library for cryptographic strength analysis
+/// - Converts zxcvbn's 0-4 scale to consistent 0-100 scale: score * 25
+/// - Mapping: 0→0, 1→25, 2→50, 3→75, 4→100
+/// - Thresholds: Very Weak<25, Weak<50, Fair<75, Good<100, Strong=100
+/// 
+/// This ensures identical zxcvbn scores always produce identical strength ratings
+/// across passwords, passphrases, and any future zxcvbn-based generators.
+#[tauri::command]
+async fn calculate_password_strength(password: String) -> Result<ApiResponse<PasswordStrength>, String> {
+    let _timer = CommandTimer::start("calculate_password_strength");
+    evaluate_password_strength(&password).map(ApiResponse::new)
+}
+
+/// Calculate passphrase strength the same way as [`calculate_password_strength`], with the
+/// words of the passphrase (split on `word_separator`, a space by default) given to zxcvbn as
+/// user inputs so it can match them as dictionary words.
+#[tauri::command]
+async fn calculate_passphrase_strength(
+    passphrase: String,
+    word_separator: Option<String>,
+) -> Result<ApiResponse<PasswordStrength>, String> {
+    let _timer = CommandTimer::start("calculate_passphrase_strength");
+    evaluate_passphrase_strength(&passphrase, word_separator.as_deref().unwrap_or(" ")).map(ApiResponse::new)
+}
+
+fn evaluate_passphrase_strength(passphrase: &str, word_separator: &str) -> Result<PasswordStrength, String> {
+    if word_separator.is_empty() {
+        return Err("Word separator cannot be empty".to_string());
+    }
+    let words: Vec<&str> = passphrase.split(word_separator).filter(|word| !word.is_empty()).collect();
+    let mut strength = evaluate_strength(passphrase, &words)?;
+
+    // Most words land in the same coarse zxcvbn score, the guess count breaks the tie
+    let mut weakest: Option<(&str, zxcvbn::Entropy)> = None;
+    for word in &words {
+        let estimate = zxcvbn(word, &[]).map_err(|e| e.to_string())?;
+        let is_weaker = weakest.as_ref().map_or(true, |(_, current)| {
+            (estimate.score(), estimate.guesses()) < (current.score(), current.guesses())
+        });
+        if is_weaker {
+            weakest = Some((word, estimate));
+        }
+    }
+    if let Some((word, estimate)) = weakest {
+        strength.weakest_word = Some(word.to_string());
+        strength.weakest_word_score = Some(estimate.score() * 25);
+    }
+    Ok(strength)
+}
+
+fn evaluate_password_strength(password: &str) -> Result<PasswordStrength, String> {
+    evaluate_strength(password, &[])
+}
+
+/// zxcvbn estimate of `password` on the 0-100 scale, `user_inputs` are extra dictionary words
+fn evaluate_strength(password: &str, user_inputs: &[&str]) -> Result<PasswordStrength, String> {
+    let estimate = zxcvbn(password, user_inputs).map_err(|e| e.to_string())?;
+    
+    let feedback: Vec<String> = estimate
+        .feedback()
+        .as_ref()
+        .map(|f| {
+            let mut suggestions = Vec::new();
+            if let Some(warning) = &f.warning() {
+                suggestions.push(warning.to_string());
             }
+            suggestions.extend(f.suggestions().iter().map(|s| s.to_string()));
+            suggestions
+        })
+        .unwrap_or_default();
+    
+    let crack_time_display = match estimate.score() {
+        0 => "Very weak - could be cracked instantly".to_string(),
+        1 => "Weak - could be cracked in minutes".to_string(), 
+        2 => "Fair - could be cracked in hours to days".to_string(),
+        3 => "Good - could take months to crack".to_string(),
+        4 => "Strong - would take years to crack".to_string(),
+        _ => "Unknown strength".to_string(),
+    };
+    
+    Ok(PasswordStrength {
+        score: (estimate.score() as f64 * 25.0) as u8, // Convert 0-4 scale to 0-100 scale consistently
+        crack_times_display: crack_time_display,
+        feedback,
+        weakest_word: None,
+        weakest_word_score: None,
+        guesses: estimate.guesses(),
+        guesses_log10: estimate.guesses_log10(),
+    })
+}
+
+/// Similarity above which a new password is flagged as too close to the old one
+const DEFAULT_MAX_PASSWORD_SIMILARITY: f64 = 0.7;
+/// Longest password [`compare_passwords`] accepts, the longest one the generator can produce.
+/// The edit distance takes time and memory proportional to the product of both lengths.
+const MAX_COMPARED_PASSWORD_LENGTH: usize = u8::MAX as usize;
+
+/// Shortest shared prefix or suffix reported as retained from the old password
+const MIN_RETAINED_AFFIX_LENGTH: usize = 3;
+
+/// Compare a new password against the one it replaces. NIST SP 800-63B advises against
+/// predictable changes such as incrementing a number, so a new password scoring above
+/// `max_similarity` (0.7 unless the policy sets it) is flagged.
+#[tauri::command]
+async fn compare_passwords(
+    new_password: String,
+    old_password: String,
+    max_similarity: Option<f64>,
+) -> Result<ApiResponse<PasswordSimilarityResult>, String> {
+    let _timer = CommandTimer::start("compare_passwords");
+    let max_similarity = max_similarity.unwrap_or(DEFAULT_MAX_PASSWORD_SIMILARITY);
+    if !(0.0..=1.0).contains(&max_similarity) {
+        return Err("Maximum similarity must be between 0 and 1".to_string());
+    }
+    if [&new_password, &old_password].iter().any(|p| p.chars().count() > MAX_COMPARED_PASSWORD_LENGTH) {
+        return Err(format!("Passwords must be at most {} characters", MAX_COMPARED_PASSWORD_LENGTH));
+    }
+    Ok(ApiResponse::new(evaluate_password_similarity(&new_password, &old_password, max_similarity)))
+}
+
+fn evaluate_password_similarity(new_password: &str, old_password: &str, max_similarity: f64) -> PasswordSimilarityResult {
+    let new_chars: Vec<char> = new_password.chars().collect();
+    let old_chars: Vec<char> = old_password.chars().collect();
+    let distance = levenshtein_distance(&new_chars, &old_chars);
+    let longest = new_chars.len().max(old_chars.len());
+    let similarity_score = if longest == 0 {
+        1.0
+    } else {
+        1.0 - distance as f64 / longest as f64
+    };
+
+    let mut changes = Vec::new();
+    match distance {
+        0 => changes.push("identical to the old password".to_string()),
+        1 => changes.push("differs by only 1 character".to_string()),
+        _ => {}
+    }
+    if distance > 0 {
+        let prefix = new_chars.iter().zip(&old_chars).take_while(|(a, b)| a == b).count();
+        let suffix = new_chars.iter().rev().zip(old_chars.iter().rev()).take_while(|(a, b)| a == b).count();
+        if prefix >= MIN_RETAINED_AFFIX_LENGTH {
+            changes.push("same prefix retained".to_string());
+        }
+        if suffix >= MIN_RETAINED_AFFIX_LENGTH {
+            changes.push("same suffix retained".to_string());
+        }
+        if new_chars.len() == old_chars.len() {
+            changes.push("same length retained".to_string());
+        }
+    }
+
+    PasswordSimilarityResult {
+        similarity_score,
+        is_too_similar: similarity_score > max_similarity,
+        changes,
+    }
+}
+
+/// Number of single character insertions, deletions and substitutions turning `a` into `b`
+fn levenshtein_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, a_char) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// Recommended minimum bcrypt cost by year. Each +1 to the cost doubles the hashing time,
+/// so the minimum moves up as attacker hardware gets faster. Approximate single-core timings
+/// of a hash at the recommended cost on a typical desktop CPU of the time:
+/// - 2014: cost 10, ~70ms on Haswell (also OWASP's long-standing minimum)
+/// - 2019: cost 11, ~90ms on Coffee Lake / Zen 2
+/// - 2023: cost 12, ~120ms on Alder Lake / Zen 4
+///
+/// Add a row here when new benchmarks justify raising the minimum.
+const BCRYPT_COST_RECOMMENDATIONS: &[(i32, u8)] = &[(2014, 10), (2019, 11), (2023, 12)];
+
+fn recommended_bcrypt_cost(year: i32) -> u8 {
+    BCRYPT_COST_RECOMMENDATIONS
+        .iter()
+        .rev()
+        .find(|(since, _)| year >= *since)
+        .map(|(_, cost)| *cost)
+        .unwrap_or(BCRYPT_COST_RECOMMENDATIONS[0].1)
+}
+
+/// Evaluate the cost factor of a stored bcrypt hash (e.g. `$2b$12$...`).
+/// The hash is only parsed, never verified, so no password is needed.
+#[tauri::command]
+async fn evaluate_bcrypt_strength(hash: String) -> Result<ApiResponse<BcryptStrengthResult>, String> {
+    let _timer = CommandTimer::start("evaluate_bcrypt_strength");
+    evaluate_bcrypt_hash(&hash, Utc::now().year()).map(ApiResponse::new)
+}
+
+fn evaluate_bcrypt_hash(hash: &str, year: i32) -> Result<BcryptStrengthResult, String> {
+    let parts: bcrypt::HashParts = hash
+        .trim()
+        .parse()
+        .map_err(|e: bcrypt::BcryptError| format!("Invalid bcrypt hash: {}", e))?;
+
+    // bcrypt only defines costs 4 through 31
+    let cost = parts.get_cost();
+    if !(4..=31).contains(&cost) {
+        return Err(format!("Invalid bcrypt cost factor: {}", cost));
+    }
+    let cost = cost as u8;
+
+    let strength = match cost {
+        0..=9 => "Weak",
+        10..=11 => "Fair",
+        12..=13 => "Good",
+        _ => "Strong",
+    };
+
+    let recommended = recommended_bcrypt_cost(year);
+    let recommendation = if cost < recommended {
+        format!(
+            "Cost {} is below the {} recommended minimum of {} - rehash with cost {} or higher the next time each user logs in",
+            cost, year, recommended, recommended
+        )
+    } else {
+        format!("Cost {} meets the {} recommended minimum of {}", cost, year, recommended)
+    };
+
+    Ok(BcryptStrengthResult {
+        cost,
+        strength: strength.to_string(),
+        recommendation,
+    })
+}
+
+#[tauri::command]
+async fn copy_to_clipboard<R: tauri::Runtime>(app_handle: tauri::AppHandle<R>, text: String) -> Result<bool, String> {
+    let _timer = CommandTimer::start("copy_to_clipboard");
+    app_handle
+        .clipboard_manager()
+        .write_text(text)
+        .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// File name of the persisted preferences, stored in the app config directory
+const PREFERENCES_FILE: &str = "preferences.json";
+
+/// User preferences, persisted as `{app_config_dir}/preferences.json`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct SavePreferences {
+    /// Directory credential files are written to, `None` for `{documents}/secgen`
+    save_directory: Option<PathBuf>,
+    /// Keep at most this many saved credential files, deleting the oldest. `None` keeps everything.
+    max_saved_files: Option<u32>,
+    /// Keep the main window above other windows
+    always_on_top: bool,
+    /// Seconds a public IP lookup is reused, `None` for [`DEFAULT_IP_CACHE_TTL_SECONDS`]
+    cache_ttl_seconds: Option<u64>,
+    /// Date at the start of saved file names, a preset name or a `strftime` format.
+    /// `None` for [`ISO8601_DATE_PRESET`].
+    filename_date_format: Option<String>,
+}
+
+impl SavePreferences {
+    /// Load preferences from `config_dir`, falling back to the defaults when missing or unreadable
+    fn load(config_dir: &Path) -> Self {
+        let contents = match fs::read_to_string(config_dir.join(PREFERENCES_FILE)) {
+            Ok(contents) => contents,
+            Err(_) => return Self::default(),
+        };
+        serde_json::from_str(&contents).unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "Ignoring invalid preferences file");
+            Self::default()
+        })
+    }
+
+    fn store(&self, config_dir: &Path) -> Result<(), String> {
+        fs::create_dir_all(config_dir).map_err(|e| format!("Failed to create config directory: {}", e))?;
+        let contents = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(config_dir.join(PREFERENCES_FILE), contents)
+            .map_err(|e| format!("Failed to write preferences: {}", e))
+    }
+}
+
+/// Tauri managed state holding the current [`SavePreferences`] and where they are persisted
+struct SavePreferencesState {
+    preferences: Mutex<SavePreferences>,
+    config_dir: Option<PathBuf>,
+}
+
+impl SavePreferencesState {
+    fn load(config_dir: Option<PathBuf>) -> Self {
+        let preferences = config_dir.as_deref().map(SavePreferences::load).unwrap_or_default();
+        Self {
+            preferences: Mutex::new(preferences),
+            config_dir,
+        }
+    }
+
+    fn get(&self) -> SavePreferences {
+        self.preferences.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Apply `update` and persist the result. The in-memory preferences are only changed
+    /// once they have been written successfully.
+    fn update(&self, update: impl FnOnce(&mut SavePreferences)) -> Result<(), String> {
+        let mut preferences = self.preferences.lock().unwrap_or_else(|e| e.into_inner());
+        let mut updated = preferences.clone();
+        update(&mut updated);
+        if let Some(config_dir) = &self.config_dir {
+            updated.store(config_dir)?;
+        }
+        *preferences = updated;
+        Ok(())
+    }
+}
+
+/// Payload of the `save-directory-fallback` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SaveDirectoryFallback {
+    requested: String,
+    fallback: String,
+    reason: String,
+}
+
+/// The default save location, `{documents}/secgen`
+fn default_save_directory() -> Result<PathBuf, String> {
+    // Get the documents directory using Tauri v1 API
+    let documents_dir = path::document_dir()
+        .ok_or("Could not find documents directory")?;
+    Ok(documents_dir.join("secgen"))
+}
+
+/// Make sure `dir` exists and is a directory, creating it if needed
+fn ensure_save_directory(dir: &Path) -> Result<PathBuf, String> {
+    if !dir.exists() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create directory '{}': {}", dir.display(), e))?;
+    }
+    if !dir.is_dir() {
+        return Err(format!("'{}' is not a directory", dir.display()));
+    }
+    dir.canonicalize()
+        .map_err(|e| format!("Failed to resolve directory '{}': {}", dir.display(), e))
+}
+
+/// Resolve the directory to save into. When the configured directory is no longer usable the
+/// default directory is used instead, and the reason is returned alongside it.
+fn resolve_save_directory(
+    configured: Option<&Path>,
+    default_dir: &Path,
+) -> Result<(PathBuf, Option<SaveDirectoryFallback>), String> {
+    if let Some(dir) = configured {
+        match ensure_save_directory(dir) {
+            Ok(dir) => return Ok((dir, None)),
+            Err(reason) => {
+                let fallback = ensure_save_directory(default_dir)?;
+                let event = SaveDirectoryFallback {
+                    requested: dir.to_string_lossy().to_string(),
+                    fallback: fallback.to_string_lossy().to_string(),
+                    reason,
+                };
+                return Ok((fallback, Some(event)));
+            }
+        }
+    }
+    Ok((ensure_save_directory(default_dir)?, None))
+}
+
+/// Filename date preset for `{year}-{month}-{day}-{hour}-{minute}` in UTC
+const ISO8601_DATE_PRESET: &str = "Iso8601";
+/// Filename date preset using the date order of the OS locale, in local time
+const LOCALE_DEFAULT_DATE_PRESET: &str = "LocaleDefault";
+
+const ISO8601_FILENAME_DATE_FORMAT: &str = "%Y-%m-%d-%H-%M";
+
+/// Characters that aren't allowed in file names on at least one of Windows, macOS and Linux
+const INVALID_FILENAME_CHARS: &[char] = &['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+/// Longest formatted date accepted at the start of a file name
+const MAX_FILENAME_DATE_LENGTH: usize = 64;
+
+/// Regions writing dates month first, by ISO 3166 code
+const MONTH_FIRST_REGIONS: &[&str] = &["US", "PH", "FM", "MH", "PW", "AS", "GU", "MP", "PR", "VI"];
+/// Regions writing dates year first, by ISO 3166 code
+const YEAR_FIRST_REGIONS: &[&str] = &["CN", "JP", "KR", "KP", "TW", "HU", "LT", "MN", "IR", "SE"];
+
+/// The filename `strftime` format following the date order of `locale`, such as `en-US` or
+/// `de_DE.UTF-8`. Locales without a region keep the ISO order.
+fn locale_filename_date_format(locale: &str) -> &'static str {
+    let region = locale
+        .split(['.', '@'])
+        .next()
+        .and_then(|tag| tag.split(['-', '_']).nth(1))
+        .map(|region| region.to_ascii_uppercase());
+    match region.as_deref() {
+        None => ISO8601_FILENAME_DATE_FORMAT,
+        Some(region) if MONTH_FIRST_REGIONS.contains(&region) => "%m-%d-%Y-%H-%M",
+        Some(region) if YEAR_FIRST_REGIONS.contains(&region) => ISO8601_FILENAME_DATE_FORMAT,
+        Some(_) => "%d-%m-%Y-%H-%M",
+    }
+}
+
+/// Format `date` with the `strftime` `format`, making sure the result can start a file name on
+/// every platform
+fn format_filename_date<Tz: chrono::TimeZone>(date: &chrono::DateTime<Tz>, format: &str) -> Result<String, String>
+where
+    Tz::Offset: std::fmt::Display,
+{
+    use chrono::format::{Item, StrftimeItems};
+    use std::fmt::Write;
+
+    let items: Vec<Item> = StrftimeItems::new(format).collect();
+    if items.iter().any(|item| matches!(item, Item::Error)) {
+        return Err(format!("Invalid date format '{}'", format));
+    }
+    let mut formatted = String::new();
+    write!(formatted, "{}", date.format_with_items(items.into_iter()))
+        .map_err(|_| format!("Invalid date format '{}'", format))?;
+
+    if formatted.trim().is_empty() {
+        return Err("Date format produces an empty file name".to_string());
+    }
+    if formatted.len() > MAX_FILENAME_DATE_LENGTH {
+        return Err(format!("Formatted date must be at most {} characters", MAX_FILENAME_DATE_LENGTH));
+    }
+    if let Some(c) = formatted.chars().find(|c| INVALID_FILENAME_CHARS.contains(c) || c.is_control()) {
+        return Err(format!("Date format produces '{}', which isn't allowed in file names", c.escape_default()));
+    }
+    Ok(formatted)
+}
+
+/// Check a `filename_date_format` preference before it is saved
+fn validate_filename_date_format(format: &str) -> Result<(), String> {
+    if format == ISO8601_DATE_PRESET || format == LOCALE_DEFAULT_DATE_PRESET {
+        return Ok(());
+    }
+    // Two digit fields and the longest month name, in local time like the real file names
+    let sample = chrono::Local
+        .with_ymd_and_hms(2024, 9, 30, 23, 59, 59)
+        .earliest()
+        .ok_or("Could not build a sample date")?;
+    format_filename_date(&sample, format).map(|_| ())
+}
+
+/// The date at the start of a saved file name. Custom formats use local time and fall back to
+/// the ISO preset if the stored format doesn't produce a valid file name.
+fn filename_timestamp(date_format: Option<&str>) -> String {
+    let iso = || Utc::now().format(ISO8601_FILENAME_DATE_FORMAT).to_string();
+    match date_format {
+        None | Some(ISO8601_DATE_PRESET) => iso(),
+        Some(LOCALE_DEFAULT_DATE_PRESET) => {
+            let locale = sys_locale::get_locale().unwrap_or_default();
+            chrono::Local::now().format(locale_filename_date_format(&locale)).to_string()
+        }
+        Some(format) => format_filename_date(&chrono::Local::now(), format).unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "Ignoring filename date format");
+            iso()
+        }),
+    }
+}
+
+/// A path in `dir` named `{timestamp}-{kind}.{extension}` that doesn't exist yet, with the
+/// timestamp written in `date_format`. Saves made within the same minute get a numeric suffix
+/// instead of overwriting each other.
+fn unique_save_path(dir: &Path, date_format: Option<&str>, kind: &str, extension: &str) -> PathBuf {
+    let timestamp = filename_timestamp(date_format);
+    let mut file_path = dir.join(format!("{}-{}.{}", timestamp, kind, extension));
+    let mut suffix = 1;
+    while file_path.exists() {
+        file_path = dir.join(format!("{}-{}-{}.{}", timestamp, kind, suffix, extension));
+        suffix += 1;
+    }
+    file_path
+}
+
+/// Write `password` to a timestamped file in `dir`, returning the file path
+fn write_password_file(dir: &Path, date_format: Option<&str>, password: &str) -> Result<PathBuf, String> {
+    let file_path = unique_save_path(dir, date_format, "pw", "txt");
+    
+    // Write password to file
+    fs::write(&file_path, password)
+        .map_err(|e| format!("Failed to write password to file: {}", e))?;
+    
+    Ok(file_path)
+}
+
+/// Extensions of the files written by the save commands. Credential JSON files and password
+/// manager exports hold plaintext secrets too, so they are listed and rotated like the rest.
+const SAVED_FILE_EXTENSIONS: [&str; 5] = ["txt", "sgenc", "json", "xml", "1pux"];
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedFileInfo {
+    filename: String,
+    /// RFC 3339 timestamp, the modification time on platforms without creation times
+    created_at: String,
+    size_bytes: u64,
+}
+
+/// Saved credential files in `dir`, oldest first by modification time
+fn saved_files(dir: &Path) -> Result<Vec<(PathBuf, fs::Metadata)>, String> {
+    let entries = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read directory '{}': {}", dir.display(), e))?;
+
+    let mut files: Vec<(PathBuf, fs::Metadata)> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .map_or(false, |ext| SAVED_FILE_EXTENSIONS.contains(&ext))
+        })
+        .filter_map(|path| {
+            let metadata = fs::metadata(&path).ok()?;
+            if metadata.is_file() {
+                Some((path, metadata))
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    // Fall back to the file name so files written within the timestamp resolution sort stably
+    files.sort_by(|(a_path, a_meta), (b_path, b_meta)| {
+        let a_modified = a_meta.modified().ok();
+        let b_modified = b_meta.modified().ok();
+        a_modified.cmp(&b_modified).then_with(|| a_path.cmp(b_path))
+    });
+
+    Ok(files)
+}
+
+/// Delete the oldest saved credential files in `dir` so at most `max_files` remain.
+/// Returns the number of files deleted.
+fn rotate_saved_files(dir: &Path, max_files: u32) -> Result<usize, String> {
+    let files = saved_files(dir)?;
+    let excess = files.len().saturating_sub(max_files as usize);
+
+    for (path, _) in files.iter().take(excess) {
+        fs::remove_file(path)
+            .map_err(|e| format!("Failed to delete '{}': {}", path.display(), e))?;
+    }
+
+    Ok(excess)
+}
+
+/// Describe the saved credential files in `dir`, newest first
+fn collect_saved_files(dir: &Path) -> Result<Vec<SavedFileInfo>, String> {
+    let mut files: Vec<SavedFileInfo> = saved_files(dir)?
+        .into_iter()
+        .map(|(path, metadata)| {
+            let created_at = metadata
+                .created()
+                .or_else(|_| metadata.modified())
+                .map(|time| chrono::DateTime::<Utc>::from(time).to_rfc3339())
+                .unwrap_or_default();
+            SavedFileInfo {
+                filename: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+                created_at,
+                size_bytes: metadata.len(),
+            }
+        })
+        .collect();
+    files.reverse();
+    Ok(files)
+}
+
+/// The directory to save credential files to, emitting `save-directory-fallback` when the
+/// configured directory is unusable and the default is used instead
+fn current_save_directory<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    preferences: &SavePreferences,
+) -> Result<PathBuf, String> {
+    let default_dir = default_save_directory()?;
+    let (save_dir, fallback) = resolve_save_directory(preferences.save_directory.as_deref(), &default_dir)?;
+
+    if let Some(fallback) = fallback {
+        tracing::warn!(
+            requested = %fallback.requested,
+            reason = %fallback.reason,
+            fallback = %fallback.fallback,
+            "Save directory is unavailable, saving to the fallback directory"
+        );
+        let _ = app_handle.emit_all("save-directory-fallback", fallback);
+    }
+
+    Ok(save_dir)
+}
+
+#[tauri::command]
+async fn save_password_to_file<R: tauri::Runtime>(
+    app_handle: tauri::AppHandle<R>,
+    save_preferences: tauri::State<'_, SavePreferencesState>,
+    password: String,
+) -> Result<String, String> {
+    let _timer = CommandTimer::start("save_password_to_file");
+    let preferences = save_preferences.get();
+    let save_dir = current_save_directory(&app_handle, &preferences)?;
+    let file_path = write_password_file(&save_dir, preferences.filename_date_format.as_deref(), &password)?;
+
+    // The password is already saved, so a rotation failure is only logged
+    if let Some(max_files) = preferences.max_saved_files {
+        if let Err(e) = rotate_saved_files(&save_dir, max_files) {
+            tracing::error!(error = %e, "Failed to rotate saved files");
+        }
+    }
+    
+    // Return the full path as confirmation
+    Ok(file_path.to_string_lossy().to_string())
+}
+
+/// The generator request that produced an exported credential, tagged with the credential type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "generator_settings", rename_all = "lowercase")]
+enum CredentialSettings {
+    Password(PasswordGeneratorRequest),
+    Passphrase(PassphraseGeneratorRequest),
+    Username(UsernameGeneratorRequest),
+}
+
+impl CredentialSettings {
+    fn kind(&self) -> &'static str {
+        match self {
+            CredentialSettings::Password(_) => "password",
+            CredentialSettings::Passphrase(_) => "passphrase",
+            CredentialSettings::Username(_) => "username",
+        }
+    }
+}
+
+/// A generated credential to export, sent by the frontend as
+/// `{ "value": "...", "type": "password", "generator_settings": { ... } }`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CredentialExportRecord {
+    value: String,
+    #[serde(flatten)]
+    settings: CredentialSettings,
+}
+
+/// On-disk format written by `save_credential_json`
+#[derive(Debug, Serialize, Deserialize)]
+struct CredentialExport {
+    value: String,
+    #[serde(flatten)]
+    settings: CredentialSettings,
+    /// RFC 3339 timestamp
+    created_at: String,
+    /// Strength on a 0-100 scale, using the same scoring as the strength commands
+    strength_score: u8,
+}
+
+impl CredentialExport {
+    fn new(record: CredentialExportRecord) -> Result<Self, String> {
+        let strength_score = match &record.settings {
+            CredentialSettings::Username(_) => evaluate_username_security(&record.value).score,
+            CredentialSettings::Password(_) | CredentialSettings::Passphrase(_) => {
+                evaluate_password_strength(&record.value)?.score
+            }
+        };
+        // Forwarder API tokens must never end up in an exported file
+        let settings = match record.settings {
+            CredentialSettings::Username(request) => CredentialSettings::Username(request.redact_secrets()),
+            other => other,
+        };
+
+        Ok(Self {
+            value: record.value,
+            settings,
+            created_at: Utc::now().to_rfc3339(),
+            strength_score,
+        })
+    }
+}
+
+/// Write `record` as JSON to a timestamped file in `dir`, returning the file path
+fn write_credential_json(
+    dir: &Path,
+    date_format: Option<&str>,
+    record: CredentialExportRecord,
+) -> Result<PathBuf, String> {
+    let export = CredentialExport::new(record)?;
+    let file_path = unique_save_path(dir, date_format, export.settings.kind(), "json");
+    let contents = serde_json::to_string_pretty(&export).map_err(|e| e.to_string())?;
+
+    fs::write(&file_path, contents)
+        .map_err(|e| format!("Failed to write credential to file: {}", e))?;
+
+    Ok(file_path)
+}
+
+/// Save a generated credential with its generator settings as a JSON file in the save directory
+#[tauri::command]
+async fn save_credential_json(
+    app_handle: tauri::AppHandle,
+    save_preferences: tauri::State<'_, SavePreferencesState>,
+    credential: CredentialExportRecord,
+) -> Result<String, String> {
+    let _timer = CommandTimer::start("save_credential_json");
+    let preferences = save_preferences.get();
+    let save_dir = current_save_directory(&app_handle, &preferences)?;
+    let file_path = write_credential_json(&save_dir, preferences.filename_date_format.as_deref(), credential)?;
+    Ok(file_path.to_string_lossy().to_string())
+}
+
+/// A login to export to a password manager
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CredentialRecord {
+    name: String,
+    #[serde(default)]
+    username: Option<String>,
+    #[serde(default)]
+    password: Option<String>,
+    #[serde(default)]
+    uris: Vec<String>,
+    #[serde(default)]
+    notes: Option<String>,
+}
+
+/// Bitwarden item type of a login
+const BITWARDEN_LOGIN_ITEM_TYPE: u8 = 1;
+
+/// Bitwarden's unencrypted JSON export, as accepted by its "Bitwarden (json)" import
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BitwardenExport {
+    encrypted: bool,
+    folders: Vec<serde_json::Value>,
+    items: Vec<BitwardenItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BitwardenItem {
+    id: String,
+    organization_id: Option<String>,
+    folder_id: Option<String>,
+    #[serde(rename = "type")]
+    item_type: u8,
+    reprompt: u8,
+    name: String,
+    notes: Option<String>,
+    favorite: bool,
+    login: BitwardenLogin,
+    collection_ids: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BitwardenLogin {
+    uris: Vec<BitwardenUri>,
+    username: Option<String>,
+    password: Option<String>,
+    totp: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BitwardenUri {
+    /// URI match detection, `None` for the account's default
+    #[serde(rename = "match")]
+    match_type: Option<u8>,
+    uri: String,
+}
+
+impl BitwardenExport {
+    fn new(credentials: Vec<CredentialRecord>) -> Result<Self, String> {
+        let items = credentials
+            .into_iter()
+            .map(|credential| {
+                if credential.name.trim().is_empty() {
+                    return Err("Credential name cannot be empty".to_string());
+                }
+                Ok(BitwardenItem {
+                    id: ::uuid::Uuid::new_v4().to_string(),
+                    organization_id: None,
+                    folder_id: None,
+                    item_type: BITWARDEN_LOGIN_ITEM_TYPE,
+                    reprompt: 0,
+                    name: credential.name,
+                    notes: credential.notes,
+                    favorite: false,
+                    login: BitwardenLogin {
+                        uris: credential
+                            .uris
+                            .into_iter()
+                            .map(|uri| BitwardenUri { match_type: None, uri })
+                            .collect(),
+                        username: credential.username,
+                        password: credential.password,
+                        totp: None,
+                    },
+                    collection_ids: None,
+                })
+            })
+            .collect::<Result<_, String>>()?;
+
+        Ok(Self {
+            encrypted: false,
+            folders: Vec::new(),
+            items,
+        })
+    }
+}
+
+/// Serialize `credentials` as a Bitwarden JSON export, ready for Bitwarden's import dialog
+#[tauri::command]
+async fn export_as_bitwarden_json(credentials: Vec<CredentialRecord>) -> Result<String, String> {
+    let _timer = CommandTimer::start("export_as_bitwarden_json");
+    let export = BitwardenExport::new(credentials)?;
+    serde_json::to_string_pretty(&export).map_err(|e| e.to_string())
+}
+
+/// Write `credentials` as a Bitwarden JSON export to a timestamped file in the save directory.
+/// The file is unencrypted, like Bitwarden's own JSON export.
+#[tauri::command]
+async fn save_bitwarden_export(
+    app_handle: tauri::AppHandle,
+    save_preferences: tauri::State<'_, SavePreferencesState>,
+    credentials: Vec<CredentialRecord>,
+) -> Result<String, String> {
+    let _timer = CommandTimer::start("save_bitwarden_export");
+    let preferences = save_preferences.get();
+    let save_dir = current_save_directory(&app_handle, &preferences)?;
+    let file_path = write_bitwarden_export(&save_dir, preferences.filename_date_format.as_deref(), credentials)?;
+    Ok(file_path.to_string_lossy().to_string())
+}
+
+fn write_bitwarden_export(
+    dir: &Path,
+    date_format: Option<&str>,
+    credentials: Vec<CredentialRecord>,
+) -> Result<PathBuf, String> {
+    let export = BitwardenExport::new(credentials)?;
+    let file_path = unique_save_path(dir, date_format, "bitwarden", "json");
+    let contents = serde_json::to_string_pretty(&export).map_err(|e| e.to_string())?;
+
+    fs::write(&file_path, contents)
+        .map_err(|e| format!("Failed to write Bitwarden export: {}", e))?;
+
+    Ok(file_path)
+}
+
+/// Name of the application written to the `Generator` field of KeePass exports
+const KEEPASS_GENERATOR: &str = "SecureGen";
+
+/// A KeePass UUID, base64 encoded random bytes
+fn keepass_uuid() -> String {
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+
+    STANDARD.encode(::uuid::Uuid::new_v4().as_bytes())
+}
+
+/// Build a KeePass 2.x XML export holding `credentials` as entries of a single group named
+/// `database_name`. Creation times are set to now.
+fn build_keepass_xml(credentials: &[CredentialRecord], database_name: &str) -> Result<String, String> {
+    use quick_xml::events::{BytesDecl, BytesText, Event};
+    use quick_xml::Writer;
+
+    if database_name.trim().is_empty() {
+        return Err("Database name cannot be empty".to_string());
+    }
+    if credentials.iter().any(|credential| credential.name.trim().is_empty()) {
+        return Err("Credential name cannot be empty".to_string());
+    }
+    // XML 1.0 can't represent control characters other than tab and line breaks, even escaped
+    let fields = credentials.iter().flat_map(|credential| {
+        [&credential.username, &credential.password, &credential.notes]
+            .into_iter()
+            .flatten()
+            .chain(&credential.uris)
+            .chain(std::iter::once(&credential.name))
+            .map(String::as_str)
+    });
+    for field in std::iter::once(database_name).chain(fields) {
+        if field.chars().any(|c| c < ' ' && !matches!(c, '\t' | '\n' | '\r')) {
+            return Err("Credentials must not contain control characters".to_string());
+        }
+    }
+
+    let now = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    let string_field = |writer: &mut Writer<Vec<u8>>, key: &str, value: &str, protect: bool| -> std::io::Result<()> {
+        writer.create_element("String").write_inner_content(|writer| {
+            writer.create_element("Key").write_text_content(BytesText::new(key))?;
+            let value_element = writer.create_element("Value");
+            let value_element = if protect {
+                value_element.with_attribute(("ProtectInMemory", "True"))
+            } else {
+                value_element
+            };
+            value_element.write_text_content(BytesText::new(value))?;
+            Ok(())
+        })?;
+        Ok(())
+    };
+
+    let mut writer = Writer::new_with_indent(Vec::new(), b'\t', 1);
+    let written: std::io::Result<()> = (|| {
+        writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("utf-8"), Some("yes"))))?;
+        writer.create_element("KeePassFile").write_inner_content(|writer| {
+            writer.create_element("Meta").write_inner_content(|writer| {
+                writer.create_element("Generator").write_text_content(BytesText::new(KEEPASS_GENERATOR))?;
+                writer.create_element("DatabaseName").write_text_content(BytesText::new(database_name))?;
+                Ok(())
+            })?;
+            writer.create_element("Root").write_inner_content(|writer| {
+                writer.create_element("Group").write_inner_content(|writer| {
+                    writer.create_element("UUID").write_text_content(BytesText::new(&keepass_uuid()))?;
+                    writer.create_element("Name").write_text_content(BytesText::new(database_name))?;
+                    for credential in credentials {
+                        writer.create_element("Entry").write_inner_content(|writer| {
+                            writer.create_element("UUID").write_text_content(BytesText::new(&keepass_uuid()))?;
+                            writer.create_element("Times").write_inner_content(|writer| {
+                                for time in ["CreationTime", "LastModificationTime", "LastAccessTime"] {
+                                    writer.create_element(time).write_text_content(BytesText::new(&now))?;
+                                }
+                                Ok(())
+                            })?;
+                            string_field(writer, "Title", &credential.name, false)?;
+                            string_field(writer, "UserName", credential.username.as_deref().unwrap_or_default(), false)?;
+                            string_field(writer, "Password", credential.password.as_deref().unwrap_or_default(), true)?;
+                            string_field(writer, "URL", credential.uris.first().map_or("", String::as_str), false)?;
+                            string_field(writer, "Notes", credential.notes.as_deref().unwrap_or_default(), false)?;
+                            Ok(())
+                        })?;
+                    }
+                    Ok(())
+                })?;
+                Ok(())
+            })?;
+            Ok(())
+        })?;
+        Ok(())
+    })();
+    written.map_err(|e| format!("Failed to write KeePass XML: {}", e))?;
+
+    let xml = String::from_utf8(writer.into_inner()).map_err(|e| e.to_string())?;
+    check_well_formed_xml(&xml)?;
+    Ok(xml)
+}
+
+/// Make sure `xml` parses and every element is closed
+fn check_well_formed_xml(xml: &str) -> Result<(), String> {
+    use quick_xml::events::Event;
+
+    let mut reader = quick_xml::Reader::from_str(xml);
+    let mut depth = 0usize;
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(_)) => depth += 1,
+            Ok(Event::End(_)) => depth -= 1,
+            Ok(Event::Eof) if depth == 0 => return Ok(()),
+            Ok(Event::Eof) => return Err("Malformed XML: unclosed element".to_string()),
+            Ok(_) => {}
+            Err(e) => return Err(format!("Malformed XML at byte {}: {}", reader.error_position(), e)),
+        }
+    }
+}
+
+/// Serialize `credentials` as KeePass 2.x XML, for KeePass' "KeePass XML (2.x)" import
+#[tauri::command]
+async fn export_as_keepass_xml(credentials: Vec<CredentialRecord>, database_name: String) -> Result<String, String> {
+    let _timer = CommandTimer::start("export_as_keepass_xml");
+    build_keepass_xml(&credentials, &database_name)
+}
+
+/// Write `credentials` as KeePass 2.x XML to a timestamped file in the save directory.
+/// The file is unencrypted, like KeePass' own XML export.
+#[tauri::command]
+async fn save_keepass_export(
+    app_handle: tauri::AppHandle,
+    save_preferences: tauri::State<'_, SavePreferencesState>,
+    credentials: Vec<CredentialRecord>,
+    database_name: String,
+) -> Result<String, String> {
+    let _timer = CommandTimer::start("save_keepass_export");
+    let preferences = save_preferences.get();
+    let save_dir = current_save_directory(&app_handle, &preferences)?;
+    let file_path = write_keepass_export(
+        &save_dir,
+        preferences.filename_date_format.as_deref(),
+        &credentials,
+        &database_name,
+    )?;
+    Ok(file_path.to_string_lossy().to_string())
+}
+
+fn write_keepass_export(
+    dir: &Path,
+    date_format: Option<&str>,
+    credentials: &[CredentialRecord],
+    database_name: &str,
+) -> Result<PathBuf, String> {
+    let xml = build_keepass_xml(credentials, database_name)?;
+    let file_path = unique_save_path(dir, date_format, "keepass", "xml");
+
+    fs::write(&file_path, xml)
+        .map_err(|e| format!("Failed to write KeePass export: {}", e))?;
+
+    Ok(file_path)
+}
+
+/// Version of the 1PUX format written to `export.attributes`
+const ONEPUX_FORMAT_VERSION: u32 = 3;
+
+/// 1Password category of a login item
+const ONEPASSWORD_LOGIN_CATEGORY: &str = "001";
+
+/// The `export.attributes` file of a 1PUX archive
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OnePuxAttributes {
+    version: u32,
+    description: String,
+    created_at: i64,
+}
+
+/// The `export.data` file of a 1PUX archive
+#[derive(Debug, Serialize, Deserialize)]
+struct OnePuxData {
+    accounts: Vec<OnePuxAccount>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OnePuxAccount {
+    attrs: OnePuxAccountAttrs,
+    vaults: Vec<OnePuxVault>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OnePuxAccountAttrs {
+    account_name: String,
+    name: String,
+    avatar: String,
+    email: String,
+    uuid: String,
+    domain: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OnePuxVault {
+    attrs: OnePuxVaultAttrs,
+    items: Vec<OnePuxItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OnePuxVaultAttrs {
+    uuid: String,
+    desc: String,
+    avatar: String,
+    name: String,
+    /// `P` for a personal vault
+    #[serde(rename = "type")]
+    vault_type: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OnePuxItem {
+    uuid: String,
+    fav_index: u32,
+    created_at: i64,
+    updated_at: i64,
+    state: String,
+    category_uuid: String,
+    details: OnePuxItemDetails,
+    overview: OnePuxItemOverview,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OnePuxItemDetails {
+    login_fields: Vec<OnePuxLoginField>,
+    notes_plain: Option<String>,
+    sections: Vec<serde_json::Value>,
+    password_history: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OnePuxLoginField {
+    value: String,
+    id: String,
+    name: String,
+    /// `T` for text, `P` for a concealed password
+    field_type: String,
+    designation: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OnePuxItemOverview {
+    subtitle: String,
+    urls: Vec<OnePuxUrl>,
+    title: String,
+    url: String,
+    tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OnePuxUrl {
+    label: String,
+    url: String,
+}
+
+impl OnePuxLoginField {
+    fn new(designation: &str, field_type: &str, value: String) -> Self {
+        Self {
+            value,
+            id: String::new(),
+            name: designation.to_string(),
+            field_type: field_type.to_string(),
+            designation: designation.to_string(),
+        }
+    }
+}
+
+impl OnePuxData {
+    /// A single account with one personal vault holding `credentials` as login items
+    fn new(credentials: Vec<CredentialRecord>, created_at: i64) -> Result<Self, String> {
+        let items = credentials
+            .into_iter()
+            .map(|credential| {
+                if credential.name.trim().is_empty() {
+                    return Err("Credential name cannot be empty".to_string());
+                }
+                let mut login_fields = Vec::new();
+                if let Some(username) = &credential.username {
+                    login_fields.push(OnePuxLoginField::new("username", "T", username.clone()));
+                }
+                if let Some(password) = credential.password {
+                    login_fields.push(OnePuxLoginField::new("password", "P", password));
+                }
+                Ok(OnePuxItem {
+                    uuid: ::uuid::Uuid::new_v4().simple().to_string(),
+                    fav_index: 0,
+                    created_at,
+                    updated_at: created_at,
+                    state: "active".to_string(),
+                    category_uuid: ONEPASSWORD_LOGIN_CATEGORY.to_string(),
+                    details: OnePuxItemDetails {
+                        login_fields,
+                        notes_plain: credential.notes,
+                        sections: Vec::new(),
+                        password_history: Vec::new(),
+                    },
+                    overview: OnePuxItemOverview {
+                        subtitle: credential.username.unwrap_or_default(),
+                        url: credential.uris.first().cloned().unwrap_or_default(),
+                        urls: credential
+                            .uris
+                            .into_iter()
+                            .map(|url| OnePuxUrl { label: String::new(), url })
+                            .collect(),
+                        title: credential.name,
+                        tags: Vec::new(),
+                    },
+                })
+            })
+            .collect::<Result<_, String>>()?;
+
+        Ok(Self {
+            accounts: vec![OnePuxAccount {
+                attrs: OnePuxAccountAttrs {
+                    account_name: "SecureGen".to_string(),
+                    name: "SecureGen".to_string(),
+                    avatar: String::new(),
+                    email: String::new(),
+                    uuid: ::uuid::Uuid::new_v4().simple().to_string(),
+                    domain: String::new(),
+                },
+                vaults: vec![OnePuxVault {
+                    attrs: OnePuxVaultAttrs {
+                        uuid: ::uuid::Uuid::new_v4().simple().to_string(),
+                        desc: "Imported from SecureGen".to_string(),
+                        avatar: String::new(),
+                        name: "SecureGen".to_string(),
+                        vault_type: "P".to_string(),
+                    },
+                    items,
+                }],
+            }],
+        })
+    }
+}
+
+/// Pack `credentials` into a 1PUX archive: a zip holding `export.attributes`, `export.data`
+/// and an empty `files/` directory for attachments
+fn build_1pux_archive(credentials: Vec<CredentialRecord>) -> Result<Vec<u8>, String> {
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    let created_at = Utc::now().timestamp();
+    let attributes = OnePuxAttributes {
+        version: ONEPUX_FORMAT_VERSION,
+        description: "1Password Unencrypted Export".to_string(),
+        created_at,
+    };
+    let data = OnePuxData::new(credentials, created_at)?;
+
+    let zip_error = |e: zip::result::ZipError| format!("Failed to build 1PUX archive: {}", e);
+    let io_error = |e: std::io::Error| format!("Failed to build 1PUX archive: {}", e);
+    let options = SimpleFileOptions::default();
+    let mut archive = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+
+    archive.start_file("export.attributes", options).map_err(zip_error)?;
+    serde_json::to_writer(&mut archive, &attributes).map_err(|e| e.to_string())?;
+    archive.start_file("export.data", options).map_err(zip_error)?;
+    serde_json::to_writer(&mut archive, &data).map_err(|e| e.to_string())?;
+    archive.add_directory("files/", options).map_err(zip_error)?;
+    archive.flush().map_err(io_error)?;
+
+    Ok(archive.finish().map_err(zip_error)?.into_inner())
+}
+
+/// Build a 1Password 1PUX archive of `credentials`, for 1Password's "1Password Unencrypted Export"
+/// import
+#[tauri::command]
+async fn export_as_1password_pux(credentials: Vec<CredentialRecord>) -> Result<Vec<u8>, String> {
+    let _timer = CommandTimer::start("export_as_1password_pux");
+    build_1pux_archive(credentials)
+}
+
+/// Write `credentials` as a 1PUX archive to a timestamped file in the save directory.
+/// The archive is unencrypted, like 1Password's own 1PUX export.
+#[tauri::command]
+async fn save_1password_export(
+    app_handle: tauri::AppHandle,
+    save_preferences: tauri::State<'_, SavePreferencesState>,
+    credentials: Vec<CredentialRecord>,
+) -> Result<String, String> {
+    let _timer = CommandTimer::start("save_1password_export");
+    let preferences = save_preferences.get();
+    let save_dir = current_save_directory(&app_handle, &preferences)?;
+    let file_path = write_1password_export(&save_dir, preferences.filename_date_format.as_deref(), credentials)?;
+    Ok(file_path.to_string_lossy().to_string())
+}
+
+fn write_1password_export(
+    dir: &Path,
+    date_format: Option<&str>,
+    credentials: Vec<CredentialRecord>,
+) -> Result<PathBuf, String> {
+    let archive = build_1pux_archive(credentials)?;
+    let file_path = unique_save_path(dir, date_format, "1password", "1pux");
+
+    fs::write(&file_path, archive)
+        .map_err(|e| format!("Failed to write 1Password export: {}", e))?;
+
+    Ok(file_path)
+}
+
+/// Set the directory credential files are saved to. The directory is created if it doesn't exist.
+#[tauri::command]
+async fn set_save_directory(
+    save_preferences: tauri::State<'_, SavePreferencesState>,
+    path: String,
+) -> Result<(), String> {
+    let _timer = CommandTimer::start("set_save_directory");
+    validate_path(&path)?;
+    if path.trim().is_empty() {
+        return Err("Save directory cannot be empty".to_string());
+    }
+    let dir = ensure_save_directory(Path::new(&path))?;
+    save_preferences.update(|preferences| preferences.save_directory = Some(dir))
+}
+
+/// Limit how many credential files are kept in the save directory, `None` for no limit
+#[tauri::command]
+async fn set_max_saved_files(
+    save_preferences: tauri::State<'_, SavePreferencesState>,
+    max_saved_files: Option<u32>,
+) -> Result<(), String> {
+    let _timer = CommandTimer::start("set_max_saved_files");
+    if max_saved_files == Some(0) {
+        return Err("Maximum saved files must be at least 1".to_string());
+    }
+    save_preferences.update(|preferences| preferences.max_saved_files = max_saved_files)
+}
+
+/// Set how the date at the start of saved file names is written: `Iso8601`, `LocaleDefault` or
+/// a `strftime` format such as `%d-%m-%Y`. `None` restores the ISO default.
+#[tauri::command]
+async fn set_filename_date_format(
+    save_preferences: tauri::State<'_, SavePreferencesState>,
+    format: Option<String>,
+) -> Result<(), String> {
+    let _timer = CommandTimer::start("set_filename_date_format");
+    if let Some(format) = &format {
+        validate_filename_date_format(format)?;
+    }
+    save_preferences.update(|preferences| preferences.filename_date_format = format)
+}
+
+/// List the credential files in the save directory, newest first
+#[tauri::command]
+async fn list_saved_files(save_preferences: tauri::State<'_, SavePreferencesState>) -> Result<Vec<SavedFileInfo>, String> {
+    let _timer = CommandTimer::start("list_saved_files");
+    let dir = match save_preferences.get().save_directory {
+        Some(dir) => dir,
+        None => default_save_directory()?,
+    };
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    collect_saved_files(&dir)
+}
+
+/// Get the directory credential files are saved to
+#[tauri::command]
+async fn get_save_directory(save_preferences: tauri::State<'_, SavePreferencesState>) -> Result<String, String> {
+    let _timer = CommandTimer::start("get_save_directory");
+    let dir = match save_preferences.get().save_directory {
+        Some(dir) => dir,
+        None => default_save_directory()?,
+    };
+    Ok(dir.to_string_lossy().to_string())
+}
+
+/// Keep the main window above other windows. The setting is persisted and restored on startup.
+#[tauri::command]
+async fn set_always_on_top<R: tauri::Runtime>(
+    app_handle: tauri::AppHandle<R>,
+    save_preferences: tauri::State<'_, SavePreferencesState>,
+    enabled: bool,
+) -> Result<(), String> {
+    let _timer = CommandTimer::start("set_always_on_top");
+    save_preferences.update(|preferences| preferences.always_on_top = enabled)?;
+    apply_always_on_top(&app_handle, enabled)
+}
+
+/// Whether the main window is kept above other windows
+#[tauri::command]
+async fn get_always_on_top(save_preferences: tauri::State<'_, SavePreferencesState>) -> Result<bool, String> {
+    let _timer = CommandTimer::start("get_always_on_top");
+    Ok(save_preferences.get().always_on_top)
+}
+
+/// Apply the always on top setting to the main window and the tray menu checkmark
+fn apply_always_on_top<R: tauri::Runtime>(app: &tauri::AppHandle<R>, enabled: bool) -> Result<(), String> {
+    if let Some(window) = app.get_window("main") {
+        window
+            .set_always_on_top(enabled)
+            .map_err(|e| format!("Failed to set always on top: {}", e))?;
+    }
+    // There is no tray in tests
+    if let Some(tray) = app.tray_handle_by_id(TRAY_ID) {
+        if let Err(e) = tray.get_item(ALWAYS_ON_TOP_MENU_ITEM).set_selected(enabled) {
+            tracing::warn!(error = %e, "Failed to update always on top tray item");
+        }
+    }
+    Ok(())
+}
+
+/// `hwid_format` defaults to [`HwidOutputFormat::Display`]
+#[tauri::command]
+async fn get_system_identity(
+    app_handle: tauri::AppHandle,
+    ip_cache: tauri::State<'_, IpCacheState>,
+    hwid_format: Option<HwidOutputFormat>,
+) -> Result<ApiResponse<SystemIdentityResponse>, String> {
+    let _timer = CommandTimer::start("get_system_identity");
+    let cache_dir = app_handle.path_resolver().app_data_dir();
+    build_system_identity(&ip_cache, cache_dir.as_deref(), hwid_format.unwrap_or_default())
+        .await
+        .map(ApiResponse::new)
+}
+
+async fn build_system_identity(
+    ip_cache: &IpCacheState,
+    hwid_cache_dir: Option<&std::path::Path>,
+    hwid_format: HwidOutputFormat,
+) -> Result<SystemIdentityResponse, String> {
+    // Get IP information
+    let ip_response = cached_public_ip(ip_cache).await?;
+    
+    // Load the hardware ID from the cache, generating and caching it on first use
+    let (hardware_id, hwid_source, hwid_confidence) = generate_hardware_id(hwid_cache_dir).await?;
+    
+    // Generate user storage key based on hardware ID
+    let user_key = generate_user_storage_key(&hardware_id).await?;
+    let hardware_id = format_hardware_id(&hardware_id, hwid_format)?;
+    
+    // Get platform information
+    let platform = std::env::consts::OS.to_string();
+    let cpu_cores = std::thread::available_parallelism().ok().map(|n| n.get());
+    
+    Ok(SystemIdentityResponse {
+        hardware_id,
+        hwid_source,
+        hwid_confidence,
+        ip_address: ip_response.ip,
+        masked_ip: ip_response.masked_ip,
+        platform,
+        platform_version: get_platform_version(),
+        cpu_architecture: std::env::consts::ARCH.to_string(),
+        cpu_cores,
+        total_memory_mb: get_total_memory_mb(),
+        user_key,
+        country: ip_response.country,
+        region: ip_response.region,
+    })
+}
+
+/// Get the operating system version using platform-specific commands.
+/// Returns None if the command is unavailable or its output can't be parsed.
+fn get_platform_version() -> Option<String> {
+    #[cfg(target_os = "windows")]
+    {
+        // `ver` is a cmd builtin and prints e.g. "Microsoft Windows [Version 10.0.19045.3570]"
+        let output = Command::new("cmd").args(["/C", "ver"]).output().ok()?;
+        let output_str = String::from_utf8_lossy(&output.stdout);
+        let version = output_str.split("Version").nth(1)?;
+        let version = version.trim().trim_end_matches(']').trim();
+        if version.is_empty() { None } else { Some(version.to_string()) }
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let output = Command::new("sw_vers").arg("-productVersion").output().ok()?;
+        let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if version.is_empty() { None } else { Some(version) }
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let output = Command::new("uname").arg("-r").output().ok()?;
+        let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if version.is_empty() { None } else { Some(version) }
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    {
+        None
+    }
+}
+
+/// Get the total physical memory in megabytes.
+/// Returns None if the platform doesn't expose it or the lookup fails.
+fn get_total_memory_mb() -> Option<u64> {
+    #[cfg(target_os = "windows")]
+    {
+        let output = Command::new("powershell")
+            .args([
+                "-NoProfile",
+                "-Command",
+                "(Get-CimInstance Win32_ComputerSystem).TotalPhysicalMemory",
+            ])
+            .output()
+            .ok()?;
+        let bytes: u64 = String::from_utf8_lossy(&output.stdout).trim().parse().ok()?;
+        Some(bytes / 1024 / 1024)
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let output = Command::new("sysctl").args(["-n", "hw.memsize"]).output().ok()?;
+        let bytes: u64 = String::from_utf8_lossy(&output.stdout).trim().parse().ok()?;
+        Some(bytes / 1024 / 1024)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        // The MemTotal line looks like "MemTotal:       16314436 kB"
+        let meminfo = fs::read_to_string("/proc/meminfo").ok()?;
+        let kilobytes: u64 = meminfo
+            .lines()
+            .find(|line| line.starts_with("MemTotal:"))?
+            .split_whitespace()
+            .nth(1)?
+            .parse()
+            .ok()?;
+        Some(kilobytes / 1024)
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    {
+        None
+    }
+}
+
+const HWID_CACHE_FILE: &str = "hwid.json";
+
+/// Compile-time key for the hardware ID cache HMAC. This doesn't make the cache secret,
+/// it only lets us notice when the file was edited by hand or by another program.
+const HWID_CACHE_KEY: &[u8] = b"SecureGen-HardwareID-Cache-2024";
+
+type HmacSha256 = hmac::Hmac<sha2::Sha256>;
+
+/// Get the hardware ID, preferring the cached copy in `cache_dir` when its HMAC verifies.
+/// A missing, unreadable or tampered cache is regenerated and overwritten.
+async fn generate_hardware_id(
+    cache_dir: Option<&std::path::Path>,
+) -> Result<(String, HwidSource, HwidConfidence), String> {
+    if let Some(dir) = cache_dir {
+        if let Some((hardware_id, confidence)) = read_cached_hardware_id(dir) {
+            return Ok((hardware_id, HwidSource::Cached, confidence));
+        }
+    }
+
+    let (hardware_id, source, confidence) = compute_hardware_id()?;
+
+    if let Some(dir) = cache_dir {
+        // Caching is an optimization, so a failure here shouldn't fail the whole request
+        if let Err(e) = write_cached_hardware_id(dir, &hardware_id, confidence) {
+            tracing::warn!(error = %e, "Failed to cache hardware ID");
+        }
+    }
+
+    Ok((hardware_id, source, confidence))
+}
+
+fn compute_hardware_id() -> Result<(String, HwidSource, HwidConfidence), String> {
+    // Combine every machine identifier the OS offers, so the ID survives one of them going away
+    let sources = collect_machine_ids();
+    let confidence = HwidConfidence::from_source_count(sources.len());
+    if let Some(hardware_id) = combine_machine_ids(&sources) {
+        return Ok((hardware_id, HwidSource::MachineUid, confidence));
+    }
+    
+    // Fallback method: Use environment variables and system information
+    // This is less secure but ensures the function always returns a value
+    tracing::warn!("No machine identifier available, falling back to environment-based method");
+    Ok((generate_fallback_hardware_id()?, HwidSource::Fallback, confidence))
+}
+
+/// Collect the machine identifiers available on this system. Several sources often read the same
+/// underlying value (`machine_uid` reads `/etc/machine-id` on Linux), so duplicates are dropped.
+fn collect_machine_ids() -> Vec<String> {
+    let mut candidates = Vec::new();
+
+    match machine_uid::get() {
+        Ok(machine_id) => candidates.push(machine_id),
+        Err(e) => tracing::warn!(error = %e, "Failed to get machine UID"),
+    }
+
+    #[cfg(target_os = "linux")]
+    if let Ok(machine_id) = fs::read_to_string("/etc/machine-id") {
+        candidates.push(machine_id);
+    }
+
+    #[cfg(target_os = "macos")]
+    if let Ok(output) = Command::new("ioreg").args(["-rd1", "-c", "IOPlatformExpertDevice"]).output() {
+        candidates.extend(parse_ioreg_platform_uuid(&String::from_utf8_lossy(&output.stdout)));
+    }
+
+    #[cfg(target_os = "windows")]
+    if let Ok(output) = Command::new("reg")
+        .args(["query", r"HKLM\SOFTWARE\Microsoft\Cryptography", "/v", "MachineGuid"])
+        .output()
+    {
+        candidates.extend(parse_reg_machine_guid(&String::from_utf8_lossy(&output.stdout)));
+    }
+
+    let mut machine_ids: Vec<String> = Vec::new();
+    for candidate in candidates {
+        let candidate = candidate.trim();
+        if !candidate.is_empty() && !machine_ids.iter().any(|id| id.eq_ignore_ascii_case(candidate)) {
+            machine_ids.push(candidate.to_string());
+        }
+    }
+    machine_ids
+}
+
+/// The `IOPlatformUUID` value from `ioreg -rd1 -c IOPlatformExpertDevice` output
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_ioreg_platform_uuid(output: &str) -> Option<String> {
+    let line = output.lines().find(|line| line.contains("\"IOPlatformUUID\""))?;
+    let value = line.split('=').nth(1)?.trim().trim_matches('"');
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// The `MachineGuid` value from `reg query HKLM\SOFTWARE\Microsoft\Cryptography /v MachineGuid` output
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn parse_reg_machine_guid(output: &str) -> Option<String> {
+    let line = output.lines().find(|line| line.trim_start().starts_with("MachineGuid"))?;
+    // "    MachineGuid    REG_SZ    3f2504e0-4f89-11d3-9a0c-0305e82c3301"
+    let mut fields = line.split_whitespace();
+    match (fields.next(), fields.next(), fields.next()) {
+        (Some("MachineGuid"), Some("REG_SZ"), Some(value)) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+/// Derive the hardware ID from the machine identifiers. A single identifier is formatted directly,
+/// so the ID matches the one from before multiple sources were combined; several have their
+/// SHA-256 hashes XORed together.
+fn combine_machine_ids(machine_ids: &[String]) -> Option<String> {
+    use sha2::{Sha256, Digest};
+
+    match machine_ids {
+        [] => None,
+        [machine_id] => Some(format_machine_id(machine_id)),
+        _ => {
+            let combined = machine_ids.iter().fold([0u8; 32], |mut combined, machine_id| {
+                let hash = Sha256::digest(machine_id.as_bytes());
+                for (byte, hash_byte) in combined.iter_mut().zip(hash.iter()) {
+                    *byte ^= hash_byte;
+                }
+                combined
+            });
+            Some(format_hwid_bytes(&combined))
+        }
+    }
+}
+
+fn hardware_id_mac(hardware_id: &str, confidence: HwidConfidence) -> HmacSha256 {
+    use hmac::Mac;
+
+    let mut mac = HmacSha256::new_from_slice(HWID_CACHE_KEY).expect("HMAC accepts keys of any length");
+    mac.update(hardware_id.as_bytes());
+    mac.update(format!("{:?}", confidence).as_bytes());
+    mac
+}
+
+/// Read the cached hardware ID and its confidence, returning None if the file is missing,
+/// malformed or fails verification
+fn read_cached_hardware_id(cache_dir: &std::path::Path) -> Option<(String, HwidConfidence)> {
+    use hmac::Mac;
+
+    let contents = fs::read_to_string(cache_dir.join(HWID_CACHE_FILE)).ok()?;
+    let cache: HwidCache = serde_json::from_str(&contents).ok()?;
+    let confidence = cache.confidence?;
+    let expected = decode_hex(&cache.hmac)?;
+
+    let actual = hardware_id_mac(&cache.hardware_id, confidence).finalize().into_bytes();
+    if security::constant_time_eq_bytes(&actual, &expected) {
+        Some((cache.hardware_id, confidence))
+    } else {
+        tracing::warn!("Hardware ID cache failed verification, regenerating");
+        None
+    }
+}
+
+fn write_cached_hardware_id(
+    cache_dir: &std::path::Path,
+    hardware_id: &str,
+    confidence: HwidConfidence,
+) -> Result<(), String> {
+    use hmac::Mac;
+
+    fs::create_dir_all(cache_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    let hmac = to_hex(&hardware_id_mac(hardware_id, confidence).finalize().into_bytes());
+    let cache = HwidCache {
+        hardware_id: hardware_id.to_string(),
+        confidence: Some(confidence),
+        hmac,
+    };
+    let contents = serde_json::to_string_pretty(&cache)
+        .map_err(|e| format!("Failed to serialize hardware ID cache: {}", e))?;
+
+    fs::write(cache_dir.join(HWID_CACHE_FILE), contents)
+        .map_err(|e| format!("Failed to write hardware ID cache: {}", e))
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 || !hex.is_ascii() {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+/// Format machine ID into a consistent HWID format
+fn format_machine_id(machine_id: &str) -> String {
+    use sha2::{Sha256, Digest};
+    
+    // Hash the machine ID to create a consistent format and length
+    let mut hasher = Sha256::new();
+    hasher.update(machine_id.as_bytes());
+    format_hwid_bytes(&hasher.finalize())
+}
+
+/// Format the first 8 bytes of a hash as HWID-XXXX-XXXX-XXXX-XXXX
+fn format_hwid_bytes(hash: &[u8]) -> String {
+    let hex_string: String = hash[..8]
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect();
+    
+    format!("HWID-{}-{}-{}-{}", 
+        &hex_string[0..4], 
+        &hex_string[4..8], 
+        &hex_string[8..12], 
+        &hex_string[12..16])
+}
+
+/// Parse a `HWID-XXXX-XXXX-XXXX-XXXX` hardware ID back into the hash bytes it was formatted from
+fn from_hwid_display(s: &str) -> Result<[u8; 8], String> {
+    let invalid = || format!("Invalid hardware ID '{}', expected HWID-XXXX-XXXX-XXXX-XXXX", s);
+    let groups: Vec<&str> = s.strip_prefix("HWID-").ok_or_else(invalid)?.split('-').collect();
+    if groups.len() != 4 || groups.iter().any(|group| group.len() != 4) {
+        return Err(invalid());
+    }
+    let bytes = decode_hex(&groups.concat()).ok_or_else(invalid)?;
+    bytes.try_into().map_err(|_| invalid())
+}
+
+/// Write a display formatted hardware ID in `format`. IDs from the environment fallback are
+/// shorter than a machine ID hash and only exist in the display format.
+fn format_hardware_id(hardware_id: &str, format: HwidOutputFormat) -> Result<String, String> {
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use base64::Engine;
+
+    match format {
+        HwidOutputFormat::Display => Ok(hardware_id.to_string()),
+        HwidOutputFormat::Hex => Ok(to_hex(&from_hwid_display(hardware_id)?)),
+        HwidOutputFormat::Base64 => Ok(URL_SAFE_NO_PAD.encode(from_hwid_display(hardware_id)?)),
+    }
+}
+
+/// Fallback hardware ID generation using environment variables and system info
+/// This method is less secure but provides compatibility when machine-uid fails
+fn generate_fallback_hardware_id() -> Result<String, String> {
+    use sha2::{Sha256, Digest};
+    
+    let mut components = Vec::new();
+    
+    // Collect system information for hardware ID generation
+    components.push(std::env::consts::OS.to_string());
+    components.push(std::env::consts::ARCH.to_string());
+    
+    // Add machine name if available (more reliable than username)
+    if let Ok(hostname) = std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .or_else(|_| std::env::var("HOST")) {
+        components.push(hostname);
+    }
+    
+    // Add additional system identifiers if available
+    if let Ok(processor_id) = std::env::var("PROCESSOR_IDENTIFIER") {
+        components.push(processor_id);
+    }
+    
+    if let Ok(processor_arch) = std::env::var("PROCESSOR_ARCHITECTURE") {
+        components.push(processor_arch);
+    }
+    
+    // Create a more robust hash using SHA-256 instead of DefaultHasher
+    let combined_info = components.join("|");
+    let mut hasher = Sha256::new();
+    hasher.update(combined_info.as_bytes());
+    
+    // Add a static salt to make the hash less predictable
+    hasher.update(b"SecureGen-HardwareID-Salt-2024");
+    
+    let result = hasher.finalize();
+    
+    // Format as HWID with fallback indicator
+    let hex_string: String = result[..8]
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect();
+    
+    Ok(format!("HWID-FB-{}-{}-{}", 
+        &hex_string[0..4], 
+        &hex_string[4..8], 
+        &hex_string[8..12]))
+}
+
+async fn generate_user_storage_key(hardware_id: &str) -> Result<String, String> {
+    use sha2::{Sha256, Digest};
+    
+    let mut hasher = Sha256::new();
+    hasher.update(hardware_id.as_bytes());
+    let result = hasher.finalize();
+    
+    // Take first 8 bytes and convert to hex
+    let short_key = to_hex(&result[..8]);
+    
+    Ok(format!("securegen-store-{}", short_key))
+}
+
+/// Calculate username security strength using custom algorithm.
+/// 
+/// Username security differs from password security and requires specialized evaluation:
+/// - Focuses on privacy, uniqueness, and guessability rather than cryptographic strength
+/// - Uses direct 0-100 scale calculation (no conversion needed)
+/// - Considers username-specific factors: dictionary usage, personal info, common patterns
+/// - Thresholds: Very Poor≤25, Poor≤45, Fair≤65, Good≤80, Excellent≥81
+/// 
+/// This custom approach is necessary because username security concerns (privacy, 
+/// uniqueness, non-attribution) differ significantly from password security concerns
+/// (resistance to brute force, dictionary attacks, cryptographic strength).
+#[tauri::command]
+async fn calculate_username_strength(username: String) -> Result<ApiResponse<UsernameStrength>, String> {
+    let _timer = CommandTimer::start("calculate_username_strength");
+    let result = evaluate_username_security(&username);
+    Ok(ApiResponse::new(result))
+}
+
+/// Score a username for [`calculate_username_strength`]
+///
+/// # Examples
+///
+/// Run as `tests::test_evaluate_username_security_examples`.
+///
+/// ```ignore
+/// let strength = evaluate_username_security("QuietRiver4821");
+/// assert!(!strength.security_level.is_empty());
+///
+/// // Brand names and common handles score low and come with feedback
+/// let strength = evaluate_username_security("Skype");
+/// assert!(strength.score < 40);
+/// assert!(!strength.feedback.is_empty());
+/// ```
+fn evaluate_username_security(username: &str) -> UsernameStrength {
+    let mut feedback = Vec::new();
+    let mut privacy_score = 0u8;
+    let mut uniqueness_score = 0u8;
+    
+    let username_lower = username.to_lowercase();
+    
+    // First check if this is likely a generated username - this affects how we score
+    let is_generated = is_likely_generated_username(&username_lower);
+    
+    // Length evaluation (different from passwords)
+    let length = username.len();
+    if length < 3 {
+        feedback.push("Username is too short - minimum 3 characters recommended".to_string());
+    } else if length >= 6 && length <= 20 {
+        privacy_score += 20;
+        uniqueness_score += 15;
+    } else if length > 20 {
+        feedback.push("Very long usernames may be memorable and stand out".to_string());
+        privacy_score += 10;
+    } else {
+        privacy_score += 10;
+    }
+    
+    // Enhanced dictionary word analysis - distinguish between secure and weak dictionary usage
+    // BUT: Apply length-based constraints to ensure consistent Basic username ratings
+    let dict_analysis = analyze_dictionary_usage(&username_lower);
+    match dict_analysis {
+        DictionaryUsage::SecureEFFWord { word_length } => {
+            // EFF words are cryptographically selected - they're GOOD for usernames
+            // However, for Basic strength (3-4 chars), we need consistent Poor ratings
+            if length <= 4 {
+                // Basic usernames (3-4 chars) should be consistently Poor regardless of EFF status
+                // Give minimal bonus to maintain some distinction but keep in Poor range
+                privacy_score += 5;  // Reduced from 10 to keep Basic usernames in Poor category
+                uniqueness_score += 10; // Reduced from 15
+                feedback.push("Short word from secure wordlist - consider longer username".to_string());
+            } else if word_length >= 7 {
+                privacy_score += 20;
+                uniqueness_score += 25;
+                feedback.push("Uses cryptographically strong word - excellent choice".to_string());
+            } else if word_length >= 5 {
+                privacy_score += 15;
+                uniqueness_score += 20;
+                feedback.push("Uses secure word from cryptographic wordlist".to_string());
+            } else {
+                // This case should be covered by the length <= 4 check above, but keeping for safety
+                privacy_score += 10;
+                uniqueness_score += 15;
+                feedback.push("Uses word from secure wordlist".to_string());
+            }
+        }
+        DictionaryUsage::WeakDictionary => {
+            // Non-EFF dictionary words or obvious patterns
+            privacy_score = privacy_score.saturating_sub(15);
+            uniqueness_score = uniqueness_score.saturating_sub(10);
+            feedback.push("Contains easily guessable dictionary pattern".to_string());
+        }
+        DictionaryUsage::CompoundWords => {
+            // Multiple dictionary words - depends on context
+            privacy_score = privacy_score.saturating_sub(10);
+            uniqueness_score += 5; // Compound can be more unique
+            feedback.push("Contains compound words - moderate security".to_string());
+        }
+        DictionaryUsage::NonDictionary => {
+            // Not a dictionary word - good for uniqueness, but for Basic length still weak
+            if length <= 4 {
+                // Basic non-dictionary usernames are still weak due to short length
+                uniqueness_score += 10; // Reduced from 15 to keep Basic usernames in Poor range
+                feedback.push("Short username - consider adding length for better security".to_string());
+            } else {
+                uniqueness_score += 15;
+            }
+        }
+    }
+    
+    // Check for common words/brands - but be more lenient for generated usernames
+    if !is_generated {
+        let common_words = [
+            "admin", "administrator", "user", "guest", "test", "demo", "example",
+            "facebook", "google", "apple", "microsoft", "amazon", "twitter", "instagram",
+            "skype", "discord", "telegram", "whatsapp", "youtube", "netflix", "spotify",
+            "password", "login", "email", "mail", "contact", "support", "help",
+            "john", "jane", "mike", "sarah", "alex", "chris", "david", "mary",
+            "2023", "2024", "abc", "qwerty", "asdf"
+        ];
+        
+        let mut contains_common_word = false;
+        
+        for word in &common_words {
+            if username_lower.contains(word) {
+                contains_common_word = true;
+                feedback.push(format!("Contains common word '{}' - reduces privacy", word));
+                break;
+            }
+        }
+        
+        if contains_common_word {
+            privacy_score = privacy_score.saturating_sub(30);
+            uniqueness_score = uniqueness_score.saturating_sub(25);
+        } else {
+            // Apply length-based bonus scaling for consistency
+            if length <= 4 {
+                // Basic usernames get reduced bonus to stay in Poor category
+                privacy_score += 20; // Reduced from 25
+                uniqueness_score += 15; // Reduced from 20
+            } else {
+                privacy_score += 25;
+                uniqueness_score += 20;
+            }
+        }
+    } else {
+        // For generated usernames, give a privacy bonus since they're designed to be secure
+        privacy_score += 25;
+        uniqueness_score += 20;
+    }
+    
+    // Personal information patterns
+    if contains_personal_info_patterns(&username_lower) {
+        feedback.push("May contain personal information patterns".to_string());
+        privacy_score = privacy_score.saturating_sub(25);
+    } else {
+        // Apply length-based bonus scaling
+        if length <= 4 {
+            // Basic usernames get reduced bonus to maintain Poor rating
+            privacy_score += 10; // Reduced from 15
+        } else {
+            privacy_score += 15;
+        }
+    }
+    
+    // Complexity evaluation (less important for usernames than passwords)
+    let has_numbers = username.chars().any(|c| c.is_numeric());
+    let has_letters = username.chars().any(|c| c.is_alphabetic());
+    let has_special = username.chars().any(|c| !c.is_alphanumeric());
+    
+    if has_letters && has_numbers {
+        uniqueness_score += 10;
+        feedback.push("Good mix of letters and numbers".to_string());
+    }
+    
+    if has_special {
+        uniqueness_score += 5;
+        feedback.push("Special characters add uniqueness".to_string());
+    }
+    
+    // Sequential patterns - be more lenient for generated usernames with intentional sequences
+    if !is_generated && contains_sequential_patterns(&username_lower) {
+        feedback.push("Contains sequential patterns - less secure".to_string());
+        uniqueness_score = uniqueness_score.saturating_sub(15);
+    }
+    
+    // Boost score for patterns that indicate strong generation
+    if is_generated {
+        privacy_score += 15; // Increased bonus for generated usernames
+        uniqueness_score += 15;
+        feedback.push("Appears to be securely generated".to_string());
+    }
+    
+    // Calculate overall score (weighted average)
+    let overall_score = ((privacy_score as f32 * 0.6) + (uniqueness_score as f32 * 0.4)) as u8;
+    let mut clamped_score = overall_score.min(100);
+    
+    // Hard cap for very short usernames - they should never exceed "Very Poor" regardless of other factors
+    // 3-4 character usernames are fundamentally insecure due to small namespace and easy guessing
+    if length <= 4 {
+        clamped_score = clamped_score.min(25);  // Cap at 25 to ensure "Very Poor" rating
+        if !feedback.iter().any(|f| f.contains("too short") || f.contains("Short")) {
+            feedback.insert(0, "Very short username - highly vulnerable to guessing attacks".to_string());
+        }
+    }
+    
+    // Add recommendations based on score
+    if clamped_score < 30 {
+        feedback.push("Consider using a more unique username".to_string());
+        feedback.push("Avoid common words and personal information".to_string());
+    } else if clamped_score < 60 {
+        feedback.push("Username security could be improved".to_string());
+        feedback.push("Consider adding numbers or making it more unique".to_string());
+    } else if clamped_score < 80 {
+        feedback.push("Good username security".to_string());
+    } else {
+        feedback.push("Excellent username security".to_string());
+    }
+    
+    let security_level = match clamped_score {
+        0..=25 => "Very Poor - High Risk",
+        26..=45 => "Poor - Easily Guessable",
+        46..=65 => "Fair - Some Privacy Concerns",
+        66..=80 => "Good - Reasonably Secure",
+        81..=100 => "Excellent - Highly Secure",
+        _ => "Excellent - Highly Secure", // Fallback for any edge cases
+    };
+    
+    UsernameStrength {
+        score: clamped_score,
+        security_level: security_level.to_string(),
+        feedback,
+        privacy_score,
+        uniqueness_score,
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum DictionaryUsage {
+    SecureEFFWord { word_length: usize },
+    WeakDictionary,
+    CompoundWords,
+    NonDictionary,
+}
+
+fn analyze_dictionary_usage(username: &str) -> DictionaryUsage {
+    use crate::generators::wordlist::is_eff_word;
+    
+    let username_lower = username.to_lowercase();
+    
+    // First check if it's a pure EFF word (these are cryptographically secure)
+    if is_eff_word(&username_lower) {
+        return DictionaryUsage::SecureEFFWord { 
+            word_length: username_lower.len() 
+        };
+    }
+    
+    // Extract base word by removing numbers from the end
+    let base_word = username_lower.trim_end_matches(|c: char| c.is_numeric());
+    let has_numbers = base_word != username_lower;
+    
+    if has_numbers && base_word.len() >= 3 {
+        // Check if base word is a pure EFF word
+        if is_eff_word(base_word) {
+            return DictionaryUsage::SecureEFFWord { 
+                word_length: base_word.len() 
+            };
+        }
+        
+        // Check if base word is compound EFF words
+        if is_compound_eff_word(&base_word) {
+            return DictionaryUsage::SecureEFFWord { 
+                word_length: base_word.len() 
+            };
+        }
+    }
+    
+    // Check for compound EFF words (like from our generator)
+    if is_compound_eff_word(&username_lower) {
+        return DictionaryUsage::SecureEFFWord { 
+            word_length: username_lower.len() 
+        };
+    }
+    
+    // Check for compound words (multiple dictionary words)
+    if is_compound_word(&username_lower) {
+        return DictionaryUsage::CompoundWords;
+    }
+    
+    // Check for weak dictionary patterns
+    if is_weak_dictionary_pattern(&username_lower) {
+        return DictionaryUsage::WeakDictionary;
+    }
+    
+    DictionaryUsage::NonDictionary
+}
+
+fn is_compound_eff_word(word: &str) -> bool {
+    use crate::generators::wordlist::is_eff_word;
+    
+    // Only check reasonable length words to avoid performance issues
+    if word.len() < 6 || word.len() > 20 {
+        return false;
+    }
+    
+    // Try splitting the word at different positions
+    for i in 3..=(word.len() - 3) {
+        let (first_part, second_part) = word.split_at(i);
+        
+        // Both parts must be at least 3 characters and in the EFF list
+        if first_part.len() >= 3 && second_part.len() >= 3 {
+            if is_eff_word(first_part) && is_eff_word(second_part) {
+                return true;
+            }
+        }
+    }
+    
+    false
+}
+
+fn is_weak_dictionary_pattern(word: &str) -> bool {
+    // Check for common dictionary word variations that are NOT in EFF list
+    if is_word_variation(word) || is_word_with_common_suffix(word) {
+        return true;
+    }
+    
+    // Check for common weak patterns
+    let weak_patterns = [
+        "password", "username", "account", "profile", "login",
+        "admin", "user", "guest", "test", "demo", "temp"
+    ];
+    
+    for pattern in &weak_patterns {
+        if word.contains(pattern) {
+            return true;
+        }
+    }
+    
+    false
+}
+
+fn is_likely_generated_username(username: &str) -> bool {
+    use crate::generators::wordlist::is_eff_word;
+    
+    let username_lower = username.to_lowercase();
+    
+    // Pattern 1: EFF word + numbers (our generator pattern)
+    let base_word = username_lower.trim_end_matches(|c: char| c.is_numeric());
+    if base_word.len() >= 3 && base_word != username_lower {
+        if is_eff_word(base_word) {
+            let number_part = &username_lower[base_word.len()..];
+            // Check if it's exactly 4 digits (our generator uses 4-digit numbers)
+            if number_part.len() == 4 && number_part.chars().all(|c| c.is_numeric()) {
+                return true;
+            }
+        }
+    }
+    
+    // Pattern 2: Single EFF word with good length (7+ chars indicates Strong/Maximum generation)
+    if username_lower.len() >= 7 && is_eff_word(&username_lower) {
+        return true;
+    }
+    
+    // Pattern 3: Two EFF words combined (compound generation)
+    if is_compound_eff_word(&username_lower) {
+        return true;
+    }
+    
+    false
+}
+
+fn is_word_variation(word: &str) -> bool {
+    use crate::generators::wordlist::is_eff_word;
+    
+    // Check common variations: plurals, past tense, etc.
+    let variations = [
+        // Remove common suffixes to find root word
+        word.strip_suffix("s"),
+        word.strip_suffix("es"),
+        word.strip_suffix("ed"),
+        word.strip_suffix("ing"),
+        word.strip_suffix("er"),
+        word.strip_suffix("est"),
+        word.strip_suffix("ly"),
+        word.strip_suffix("tion"),
+        word.strip_suffix("sion"),
+        word.strip_suffix("ness"),
+        word.strip_suffix("ment"),
+        word.strip_suffix("able"),
+        word.strip_suffix("ible"),
+    ];
+    
+    for variation in variations.iter().flatten() {
+        if variation.len() >= 3 && is_eff_word(variation) {
+            return true;
+        }
+    }
+    
+    // Check if adding common prefixes creates a dictionary word
+    let prefixes = ["un", "re", "pre", "dis", "mis", "over", "under", "out"];
+    for prefix in &prefixes {
+        if word.starts_with(prefix) {
+            let root = &word[prefix.len()..];
+            if root.len() >= 3 && is_eff_word(root) {
+                return true;
+            }
+        }
+    }
+    
+    false
+}
+
+fn is_compound_word(word: &str) -> bool {
+    use crate::generators::wordlist::is_eff_word;
+    
+    // Only check reasonable length words to avoid performance issues
+    if word.len() < 6 || word.len() > 20 {
+        return false;
+    }
+    
+    // Try splitting the word at different positions
+    for i in 3..=(word.len() - 3) {
+        let (first_part, second_part) = word.split_at(i);
+        
+        // Both parts must be at least 3 characters and in the dictionary
+        if first_part.len() >= 3 && second_part.len() >= 3 {
+            if is_eff_word(first_part) && is_eff_word(second_part) {
+                return true;
+            }
+        }
+    }
+    
+    false
+}
+
+fn is_word_with_common_suffix(word: &str) -> bool {
+    use crate::generators::wordlist::is_eff_word;
+    
+    // Check for dictionary words with common numeric or simple suffixes
+    let numeric_suffixes = ["1", "2", "3", "4", "5", "6", "7", "8", "9", "0", 
+                           "12", "123", "01", "02", "03", "99", "00"];
+    
+    for suffix in &numeric_suffixes {
+        if word.ends_with(suffix) {
+            let root = &word[..word.len() - suffix.len()];
+            if root.len() >= 3 && is_eff_word(root) {
+                return true;
+            }
+        }
+    }
+    
+    // Check for words with year suffixes (common in usernames)
+    if word.len() >= 7 {
+        let potential_year_suffixes = ["1990", "1991", "1992", "1993", "1994", "1995", 
+                                     "1996", "1997", "1998", "1999", "2000", "2001", 
+                                     "2002", "2003", "2004", "2005", "2006", "2007", 
+                                     "2008", "2009", "2010", "2011", "2012", "2013", 
+                                     "2014", "2015", "2016", "2017", "2018", "2019", 
+                                     "2020", "2021", "2022", "2023", "2024"];
+        
+        for year in &potential_year_suffixes {
+            if word.ends_with(year) {
+                let root = &word[..word.len() - 4];
+                if root.len() >= 3 && is_eff_word(root) {
+                    return true;
+                }
+            }
+        }
+    }
+    
+    false
+}
+
+fn contains_personal_info_patterns(username: &str) -> bool {
+    // Check for birth year patterns
+    if username.contains("199") || username.contains("200") || username.contains("201") {
+        return true;
+    }
+    
+    // Check for common age patterns
+    let age_patterns = ["18", "19", "20", "21", "22", "23", "24", "25", "30", "40", "50"];
+    for pattern in &age_patterns {
+        if username.ends_with(pattern) {
+            return true;
+        }
+    }
+    
+    // Check for common personal patterns
+    let personal_patterns = ["name", "real", "official", "personal", "my", "the"];
+    for pattern in &personal_patterns {
+        if username.contains(pattern) {
+            return true;
+        }
+    }
+    
+    false
+}
+
+fn contains_sequential_patterns(username: &str) -> bool {
+    let sequential_patterns = ["123", "234", "345", "456", "567", "678", "789", "abc", "bcd", "cde"];
+    for pattern in &sequential_patterns {
+        if username.contains(pattern) {
+            return true;
+        }
+    }
+    false
+}
+
+/// Id of the system tray
+const TRAY_ID: &str = "main";
+
+/// Id of the tray menu item toggling always on top
+const ALWAYS_ON_TOP_MENU_ITEM: &str = "always_on_top";
+
+fn create_system_tray() -> SystemTray {
+    let show = CustomMenuItem::new("show".to_string(), "Show SecureGen");
+    let hide = CustomMenuItem::new("hide".to_string(), "Hide to Tray");
+    // The checkmark is set from the saved preference during setup
+    let always_on_top = CustomMenuItem::new(ALWAYS_ON_TOP_MENU_ITEM.to_string(), "Always on Top");
+    let generate_password = CustomMenuItem::new("generate_password".to_string(), "Generate Password");
+    let generate_passphrase = CustomMenuItem::new("generate_passphrase".to_string(), "Generate Passphrase");
+    let generate_username = CustomMenuItem::new("generate_username".to_string(), "Generate Username");
+    let generate_and_copy_password =
+        CustomMenuItem::new("generate_and_copy_password".to_string(), "Generate and Copy Password");
+    let generate_and_copy_passphrase =
+        CustomMenuItem::new("generate_and_copy_passphrase".to_string(), "Generate and Copy Passphrase");
+    let copy_last = CustomMenuItem::new("copy_last".to_string(), "Copy Last Generated");
+    let quit = CustomMenuItem::new("quit".to_string(), "Quit");
+    
+    let tray_menu = SystemTrayMenu::new()
+        .add_item(show)
+        .add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(generate_password)
+        .add_item(generate_passphrase)
+        .add_item(generate_username)
+        .add_item(generate_and_copy_password)
+        .add_item(generate_and_copy_passphrase)
+        .add_item(copy_last)
+        .add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(always_on_top)
+        .add_item(hide)
+        .add_item(quit);
+
+    SystemTray::new().with_id(TRAY_ID).with_menu(tray_menu)
+}
+
+fn handle_system_tray_event(app: &tauri::AppHandle, event: SystemTrayEvent) {
+    match event {
+        SystemTrayEvent::LeftClick {
+            position: _,
+            size: _,
+            ..
+        } => {
+            // Left click shows/focuses the window
+            if let Some(window) = app.get_window("main") {
+                if window.is_visible().unwrap_or(false) {
+                    let _ = window.set_focus();
+                } else {
+                    let _ = window.show();
+                    let _ = window.set_focus();
+                }
+            }
+        }
+        SystemTrayEvent::RightClick {
+            position: _,
+            size: _,
+            ..
+        } => {
+            // Right click shows the context menu (handled automatically)
+        }
+        SystemTrayEvent::DoubleClick {
+            position: _,
+            size: _,
+            ..
+        } => {
+            // Double click shows the window
+            if let Some(window) = app.get_window("main") {
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+        }
+        SystemTrayEvent::MenuItemClick { id, .. } => {
+            match id.as_str() {
+                "show" => {
+                    if let Some(window) = app.get_window("main") {
+                        let _ = window.show();
+                        let _ = window.set_focus();
+                        let _ = window.unminimize();
+                    }
+                }
+                "hide" => {
+                    if let Some(window) = app.get_window("main") {
+                        let _ = window.hide();
+                    }
+                }
+                "generate_password" => {
+                    // Show window and trigger password generation
+                    if let Some(window) = app.get_window("main") {
+                        let _ = window.show();
+                        let _ = window.set_focus();
+                        // Emit an event to the frontend to generate a password
+                        let _ = window.emit("tray-generate-password", ());
+                    }
+                    show_tray_badge(app, "password");
+                }
+                "generate_passphrase" => {
+                    // Show window and trigger passphrase generation
+                    if let Some(window) = app.get_window("main") {
+                        let _ = window.show();
+                        let _ = window.set_focus();
+                        // Emit an event to the frontend to generate a passphrase
+                        let _ = window.emit("tray-generate-passphrase", ());
+                    }
+                    show_tray_badge(app, "passphrase");
+                }
+                "generate_username" => {
+                    // Show window and trigger username generation
+                    if let Some(window) = app.get_window("main") {
+                        let _ = window.show();
+                        let _ = window.set_focus();
+                        // Emit an event to the frontend to generate a username
+                        let _ = window.emit("tray-generate-username", ());
+                    }
+                    show_tray_badge(app, "username");
+                }
+                // Generated in the backend, the window stays as it is
+                "generate_and_copy_password" => {
+                    quick_copy(app, ShortcutAction::CopyPassword);
+                }
+                "generate_and_copy_passphrase" => {
+                    quick_copy(app, ShortcutAction::CopyPassphrase);
+                }
+                "copy_last" => {
+                    copy_last_generated(app);
+                }
+                ALWAYS_ON_TOP_MENU_ITEM => {
+                    let save_preferences = app.state::<SavePreferencesState>();
+                    let enabled = !save_preferences.get().always_on_top;
+                    let result = save_preferences
+                        .update(|preferences| preferences.always_on_top = enabled)
+                        .and_then(|_| apply_always_on_top(app, enabled));
+                    if let Err(e) = result {
+                        tracing::warn!(error = %e, "Failed to toggle always on top");
+                    }
+                }
+                "quit" => {
+                    app.exit(0);
+                }
+                _ => {}
+            }
+        }
+        _ => {}
+    }
+}
+
+/// How long a generation badge stays on the tray icon before the default icon is restored
+const TRAY_ICON_RESET_DELAY: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Tauri managed state for the tray icon badge. Holds the default icon to restore and the
+/// pending reset task, so a new generation action can cancel the previous reset.
+struct TrayIconState {
+    default_icon: Option<tauri::Icon>,
+    pending_reset: Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
+}
+
+impl TrayIconState {
+    fn new(default_icon: Option<tauri::Icon>) -> Self {
+        Self {
+            default_icon,
+            pending_reset: Mutex::new(None),
+        }
+    }
+}
+
+/// Get the tray badge icon for a generator type
+fn tray_icon_for(generator_type: &str) -> Option<tauri::Icon> {
+    let bytes: &[u8] = match generator_type {
+        "password" => include_bytes!("../icons/tray-password.png"),
+        "passphrase" => include_bytes!("../icons/tray-passphrase.png"),
+        "username" => include_bytes!("../icons/tray-username.png"),
+        _ => return None,
+    };
+    Some(tauri::Icon::Raw(bytes.to_vec()))
+}
+
+/// Swap the tray icon to the badge for `generator_type`, then restore the default icon
+/// after [`TRAY_ICON_RESET_DELAY`]:
+/// - "Generate Password" shows a lock
+/// - "Generate Passphrase" shows a chain
+/// - "Generate Username" shows a person
+///
+/// Triggering another action before the delay elapses shows the new badge and restarts the delay.
+fn show_tray_badge(app: &tauri::AppHandle, generator_type: &str) {
+    let Some(icon) = tray_icon_for(generator_type) else {
+        return;
+    };
+    if let Err(e) = app.tray_handle().set_icon(icon) {
+        tracing::error!(%generator_type, error = %e, "Failed to set tray icon");
+        return;
+    }
+
+    let state = app.state::<TrayIconState>();
+    let mut pending_reset = state.pending_reset.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(handle) = pending_reset.take() {
+        handle.abort();
+    }
+
+    let app = app.clone();
+    *pending_reset = Some(tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(TRAY_ICON_RESET_DELAY).await;
+        if let Some(icon) = app.state::<TrayIconState>().default_icon.clone() {
+            if let Err(e) = app.tray_handle().set_icon(icon) {
+                tracing::error!(error = %e, "Failed to reset tray icon");
+            }
+        }
+    }));
+}
+
+/// Actions that can be bound to a global keyboard shortcut
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+enum ShortcutAction {
+    /// Generate a password and copy it to the clipboard
+    CopyPassword,
+    /// Generate a passphrase and copy it to the clipboard
+    CopyPassphrase,
+}
+
+impl ShortcutAction {
+    fn default_accelerator(&self) -> &'static str {
+        match self {
+            ShortcutAction::CopyPassword => "CmdOrCtrl+Shift+G",
+            ShortcutAction::CopyPassphrase => "CmdOrCtrl+Shift+P",
+        }
+    }
+}
+
+/// Tauri managed state tracking which accelerator each shortcut action is bound to
+#[derive(Default)]
+struct GlobalShortcutState(Mutex<HashMap<ShortcutAction, String>>);
+
+/// Bind `action` to `accelerator`, replacing any previous binding for the action
+fn bind_global_shortcut(app: &tauri::AppHandle, action: ShortcutAction, accelerator: &str) -> Result<(), String> {
+    let mut manager = app.global_shortcut_manager();
+    let state = app.state::<GlobalShortcutState>();
+    let mut bindings = state.0.lock().unwrap_or_else(|e| e.into_inner());
+
+    let previous = bindings.get(&action).cloned();
+    if previous.as_deref() == Some(accelerator) {
+        return Ok(());
+    }
+
+    // Register the new accelerator first, so the previous shortcut keeps working when it is
+    // taken, e.g. by another app
+    let handle = app.clone();
+    manager
+        .register(accelerator, move || quick_copy(&handle, action))
+        .map_err(|e| format!("Failed to register shortcut '{}': {}", accelerator, e))?;
+
+    if let Some(previous) = previous {
+        if let Err(e) = manager.unregister(&previous) {
+            // Keep the binding that is still registered rather than leaving both active
+            let _ = manager.unregister(accelerator);
+            return Err(format!("Failed to unregister shortcut '{}': {}", previous, e));
+        }
+    }
+    bindings.insert(action, accelerator.to_string());
+
+    Ok(())
+}
+
+/// Register a global keyboard shortcut for `action`. The frontend calls this with the
+/// accelerator from the user's preferences, e.g. `CmdOrCtrl+Shift+G`.
+#[tauri::command]
+async fn register_global_shortcut(
+    app_handle: tauri::AppHandle,
+    action: ShortcutAction,
+    accelerator: String,
+) -> Result<(), String> {
+    let _timer = CommandTimer::start("register_global_shortcut");
+    bind_global_shortcut(&app_handle, action, &accelerator)
+}
+
+/// Remove the global keyboard shortcut bound to `action`, if any
+#[tauri::command]
+async fn unregister_global_shortcut(app_handle: tauri::AppHandle, action: ShortcutAction) -> Result<(), String> {
+    let _timer = CommandTimer::start("unregister_global_shortcut");
+    let state = app_handle.state::<GlobalShortcutState>();
+    let mut bindings = state.0.lock().unwrap_or_else(|e| e.into_inner());
+
+    if let Some(accelerator) = bindings.remove(&action) {
+        app_handle
+            .global_shortcut_manager()
+            .unregister(&accelerator)
+            .map_err(|e| format!("Failed to unregister shortcut '{}': {}", accelerator, e))?;
+    }
+
+    Ok(())
+}
+
+/// Generate a value for a shortcut or tray action and copy it to the clipboard without opening
+/// the window. Uses the last request made from the UI, falling back to the generator defaults.
+fn quick_copy(app: &tauri::AppHandle, action: ShortcutAction) {
+    let (generator_type, value) = match generate_and_copy(app, action) {
+        Ok(generated) => generated,
+        Err(e) => {
+            show_notification(app, &e);
+            return;
+        }
+    };
+
+    show_tray_badge(app, generator_type);
+    let message = match action {
+        ShortcutAction::CopyPassword => "Password copied to clipboard",
+        ShortcutAction::CopyPassphrase => "Passphrase copied to clipboard",
+    };
+    show_notification(app, &format!("{}: {}", message, mask_preview(&value)));
+}
+
+/// Generate the value for `action`, record it as the last generated value and copy it to the
+/// clipboard. Returns the generator type and the value.
+fn generate_and_copy<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    action: ShortcutAction,
+) -> Result<(&'static str, String), String> {
+    let last_request = app.state::<LastRequestState>();
+    let audit_log = app.state::<AuditLogState>();
+    let (generator_type, result) = match action {
+        ShortcutAction::CopyPassword => {
+            let request = last_request.password().unwrap_or_default();
+            let result = gen_password(request.clone()).map_err(|e| e.to_string());
+            if result.is_ok() {
+                audit_log.record("password", &request);
+            }
+            ("password", result)
+        }
+        ShortcutAction::CopyPassphrase => {
+            let request = last_request.passphrase().unwrap_or_default();
+            let result = gen_passphrase(request.clone()).map_err(|e| e.to_string());
+            if result.is_ok() {
+                audit_log.record("passphrase", &request);
+            }
+            ("passphrase", result)
+        }
+    };
+
+    let value = result.map_err(|e| format!("Failed to generate {}: {}", generator_type, e))?;
+
+    app.state::<LastGeneratedState>().record(generator_type, &value);
+    app.clipboard_manager()
+        .write_text(value.clone())
+        .map_err(|e| format!("Failed to copy {} to clipboard: {}", generator_type, e))?;
+
+    Ok((generator_type, value))
+}
+
+/// Shortest value whose first and last characters are shown by [`mask_preview`]
+const MIN_PREVIEW_LENGTH: usize = 12;
+
+/// Preview of a generated value for notifications, showing only its first and last 2 characters.
+/// Shorter values are masked completely.
+fn mask_preview(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() < MIN_PREVIEW_LENGTH {
+        return "•".repeat(chars.len());
+    }
+    let start: String = chars[..2].iter().collect();
+    let end: String = chars[chars.len() - 2..].iter().collect();
+    format!("{}••••{}", start, end)
+}
+
+/// Copy the last generated value to the clipboard without opening the window
+fn copy_last_generated(app: &tauri::AppHandle) {
+    let last = app.state::<LastGeneratedState>().get();
+    match (last.value, last.generator_type) {
+        (Some(value), generator_type) => {
+            if let Err(e) = app.clipboard_manager().write_text(value) {
+                tracing::error!(error = %e, "Failed to copy last generated value");
+                return;
+            }
+            let generator_type = generator_type.unwrap_or_else(|| "value".to_string());
+            show_notification(app, &format!("Last generated {} copied to clipboard", generator_type));
+        }
+        (None, _) => {
+            show_notification(app, "Nothing to copy yet - generate a password, passphrase or username first");
+        }
+    }
+}
+
+fn show_notification(app: &tauri::AppHandle, body: &str) {
+    let result = tauri::api::notification::Notification::new(&app.config().tauri.bundle.identifier)
+        .title("SecureGen")
+        .body(body)
+        .show();
+    if let Err(e) = result {
+        tracing::error!(error = %e, "Failed to show notification");
+    }
+}
+
+const WINDOW_STATE_FILE: &str = "window_state.json";
+
+/// Position and size of the main window in physical pixels, restored on the next start
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct WindowState {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+}
+
+impl WindowState {
+    /// The saved state in `data_dir`, `None` when missing or unreadable
+    fn load(data_dir: &Path) -> Option<Self> {
+        let contents = fs::read_to_string(data_dir.join(WINDOW_STATE_FILE)).ok()?;
+        serde_json::from_str(&contents)
+            .map_err(|e| tracing::warn!(error = %e, "Ignoring invalid window state file"))
+            .ok()
+    }
+
+    fn store(&self, data_dir: &Path) -> Result<(), String> {
+        fs::create_dir_all(data_dir).map_err(|e| format!("Failed to create data directory: {}", e))?;
+        let contents = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(data_dir.join(WINDOW_STATE_FILE), contents)
+            .map_err(|e| format!("Failed to write window state: {}", e))
+    }
+
+    /// Whether the top left corner is on one of `monitors`, given as their position and size. A
+    /// monitor the window was on may have been disconnected or rearranged since it was saved.
+    fn is_on_screen(&self, monitors: &[(tauri::PhysicalPosition<i32>, tauri::PhysicalSize<u32>)]) -> bool {
+        monitors.iter().any(|(position, size)| {
+            let x = i64::from(self.x) - i64::from(position.x);
+            let y = i64::from(self.y) - i64::from(position.y);
+            (0..i64::from(size.width)).contains(&x) && (0..i64::from(size.height)).contains(&y)
+        })
+    }
+}
+
+/// Tauri managed state tracking the main window's position and size, saved whenever it changes
+struct WindowStateStore {
+    state: Mutex<WindowState>,
+    data_dir: Option<PathBuf>,
+}
+
+impl WindowStateStore {
+    fn new(initial: WindowState, data_dir: Option<PathBuf>) -> Self {
+        Self {
+            state: Mutex::new(initial),
+            data_dir,
+        }
+    }
+
+    fn record_move(&self, position: tauri::PhysicalPosition<i32>) {
+        self.update(|state| {
+            state.x = position.x;
+            state.y = position.y;
+        });
+    }
+
+    fn record_resize(&self, size: tauri::PhysicalSize<u32>) {
+        // Minimizing on Windows reports a 0x0 size, which shouldn't be restored
+        if size.width == 0 || size.height == 0 {
+            return;
+        }
+        self.update(|state| {
+            state.width = size.width;
+            state.height = size.height;
+        });
+    }
+
+    fn update(&self, update: impl FnOnce(&mut WindowState)) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        update(&mut state);
+        if let Some(data_dir) = &self.data_dir {
+            if let Err(e) = state.store(data_dir) {
+                tracing::warn!(error = %e, "Failed to save window state");
+            }
+        }
+    }
+}
+
+/// Move and resize `window` to the state saved in `data_dir`, unless it would be off screen.
+/// Returns the state the window ends up in.
+fn restore_window_state<R: tauri::Runtime>(window: &tauri::Window<R>, data_dir: Option<&Path>) -> WindowState {
+    let position = window.outer_position().unwrap_or_default();
+    let size = window.outer_size().unwrap_or_default();
+    let current = WindowState {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+    };
+    let Some(saved) = data_dir.and_then(WindowState::load) else {
+        return current;
+    };
+
+    let monitors: Vec<_> = window
+        .available_monitors()
+        .unwrap_or_default()
+        .iter()
+        .map(|monitor| (*monitor.position(), *monitor.size()))
+        .collect();
+    if !saved.is_on_screen(&monitors) {
+        tracing::info!("Saved window position is off screen, using the default position");
+        return current;
+    }
+
+    let position = window.set_position(tauri::PhysicalPosition::new(saved.x, saved.y));
+    let size = window.set_size(tauri::PhysicalSize::new(saved.width, saved.height));
+    if let Err(e) = position.and(size) {
+        tracing::warn!(error = %e, "Failed to restore window state");
+        return current;
+    }
+    saved
+}
+
+/// URL schemes listed under `plugins > deep-link > schemes` in `tauri.conf.json`
+fn deep_link_schemes(config: &tauri::Config) -> Vec<String> {
+    config
+        .plugins
+        .0
+        .get("deep-link")
+        .and_then(|plugin| plugin.get("schemes"))
+        .and_then(|schemes| serde_json::from_value(schemes.clone()).ok())
+        .unwrap_or_default()
+}
+
+/// Pass a `securegen://` link on to the frontend as a `deep-link-config` or `deep-link-fill`
+/// event, and bring the main window to the front
+fn handle_deep_link<R: tauri::Runtime>(app: &tauri::AppHandle<R>, link: &str) {
+    // The link itself isn't logged, a fill link carries a credential
+    let result = match deep_link::parse(link) {
+        Ok(deep_link::DeepLink::Generate(config)) => app.emit_all("deep-link-config", config),
+        Ok(deep_link::DeepLink::Fill(value)) => app.emit_all("deep-link-fill", value),
+        Err(e) => {
+            tracing::warn!(error = %e, "Ignoring invalid deep link");
+            return;
+        }
+    };
+    if let Err(e) = result {
+        tracing::error!(error = %e, "Failed to send deep link to the frontend");
+    }
+    if let Some(window) = app.get_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+fn main() {
+    init_logging();
+    let system_tray = create_system_tray();
+    let context = tauri::generate_context!();
+    // Hands links opened while the app is running over to the running instance
+    tauri_plugin_deep_link::prepare(&context.config().tauri.bundle.identifier);
+    let default_tray_icon = context.system_tray_icon().cloned();
+    
+    tauri::Builder::default()
+        .manage(LastGeneratedState::default())
+        .manage(LastRequestState::default())
+        .manage(GlobalShortcutState::default())
+        .manage(GenerationStreams::default())
+        .manage(UsernameCancellation::default())
+        .manage(UsernameDeduplicator::default())
+        .manage(AuditLogState::default())
+        .manage(RateLimits::default())
+        .manage(TrayIconState::new(default_tray_icon))
+        .system_tray(system_tray)
+        .on_system_tray_event(handle_system_tray_event)
+        .invoke_handler(tauri::generate_handler![
+            generate_password,
+            generate_password_with_warnings,
+            get_password_preset,
+            generate_password_legacy,
+            generate_passphrase,
+            generate_passphrase_with_stats,
+            generate_passphrase_choices,
+            estimate_passphrase_entropy,
+            compare_generator_strength,
+            list_word_separator_presets,
+            generate_username,
+            cancel_username_generation,
+            clear_username_history,
+            get_username_generation_count,
+            normalize_email,
+            emails_equivalent,
+            get_generation_audit_log,
+            clear_audit_log,
+            list_addy_aliases,
+            list_addy_mailboxes,
+            list_addy_domains,
+            list_simplelogin_directories,
+            deactivate_email_alias,
+            get_firefox_relay_quota,
+            list_fastmail_identities,
+            verify_forward_email_domain,
+            list_forward_email_aliases,
+            delete_forward_email_alias,
+            generate_passwords_stream,
+            cancel_generation,
+            derive_key,
+            generate_uuid,
+            generate_uuid_namespace,
+            calculate_password_strength,
+            calculate_passphrase_strength,
+            calculate_username_strength,
+            evaluate_bcrypt_strength,
+            compare_passwords,
+            register_global_shortcut,
+            unregister_global_shortcut,
+            copy_to_clipboard,
+            save_password_to_file,
+            save_credential_json,
+            export_as_bitwarden_json,
+            save_bitwarden_export,
+            export_as_keepass_xml,
+            save_keepass_export,
+            export_as_1password_pux,
+            save_1password_export,
+            set_save_directory,
+            get_save_directory,
+            set_max_saved_files,
+            set_filename_date_format,
+            list_saved_files,
+            set_always_on_top,
+            get_always_on_top,
+            get_public_ip_address,
+            invalidate_ip_cache,
+            set_ip_cache_ttl,
+            list_network_interfaces,
+            get_system_identity,
+            get_api_version
+        ])
+        .setup(|app| {
+            // Handle window close event to hide to tray instead of closing
+            let window = app.get_window("main").unwrap();
+            let app_handle = app.handle();
+
+            let save_preferences = SavePreferencesState::load(app.path_resolver().app_config_dir());
+            if let Err(e) = apply_always_on_top(&app_handle, save_preferences.get().always_on_top) {
+                tracing::warn!(error = %e, "Failed to restore always on top");
+            }
+            let ip_cache_ttl = save_preferences.get().cache_ttl_seconds.unwrap_or(DEFAULT_IP_CACHE_TTL_SECONDS);
+            app.manage(IpCacheState::new(ip_cache_ttl));
+            app.manage(save_preferences);
+
+            // The window starts hidden so it doesn't jump from the default position
+            let data_dir = app.path_resolver().app_data_dir();
+            let window_state = restore_window_state(&window, data_dir.as_deref());
+            app.manage(WindowStateStore::new(window_state, data_dir));
+            let _ = window.show();
+
+            // Register the default quick copy shortcuts. Another application may already own
+            // them, so failures are logged rather than aborting startup.
+            for action in [ShortcutAction::CopyPassword, ShortcutAction::CopyPassphrase] {
+                if let Err(e) = bind_global_shortcut(&app_handle, action, action.default_accelerator()) {
+                    tracing::warn!(?action, error = %e, "Failed to register global shortcut");
+                }
+            }
+
+            for scheme in deep_link_schemes(&app.config()) {
+                let handle = app.handle();
+                if let Err(e) = tauri_plugin_deep_link::register(&scheme, move |link| handle_deep_link(&handle, &link)) {
+                    tracing::warn!(%scheme, error = %e, "Failed to register deep link scheme");
+                }
+            }
+            // On macOS the link arrives through the handler above, elsewhere a link that started
+            // the app is its first argument
+            #[cfg(not(target_os = "macos"))]
+            if let Some(link) = std::env::args().nth(1).filter(|arg| arg.starts_with(&format!("{}:", deep_link::SCHEME))) {
+                handle_deep_link(&app_handle, &link);
+            }
+            
+            window.on_window_event(move |event| {
+                match event {
+                    tauri::WindowEvent::Moved(position) => {
+                        app_handle.state::<WindowStateStore>().record_move(*position);
+                    }
+                    tauri::WindowEvent::Resized(size) => {
+                        app_handle.state::<WindowStateStore>().record_resize(*size);
+                    }
+                    tauri::WindowEvent::CloseRequested { api, .. } => {
+                        // Prevent the window from closing and hide it instead
+                        api.prevent_close();
+                        if let Some(window) = app_handle.get_window("main") {
+                            let _ = window.hide();
+                        }
+                    }
+                    _ => {}
+                }
+            });
+            
+            Ok(())
+        })
+        .run(context)
+        .expect("error while running tauri application");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_passphrase_strength_eff_words() {
+        for _ in 0..10 {
+            let passphrase = gen_passphrase(PassphraseGeneratorRequest {
+                num_words: 4,
+                word_separator: "-".to_string(),
+                ..Default::default()
+            })
+            .unwrap();
+            let strength = evaluate_passphrase_strength(&passphrase, "-").unwrap();
+            assert!(strength.score >= 75, "{} scored {}", passphrase, strength.score);
+        }
+
+        assert!(evaluate_passphrase_strength("correct horse battery staple", "").is_err());
+    }
+
+    #[test]
+    fn test_passphrase_strength_weakest_word() {
+        let strength = evaluate_passphrase_strength("horse staple battery correct", " ").unwrap();
+        assert_eq!(strength.weakest_word.as_deref(), Some("horse"));
+        assert_eq!(strength.weakest_word_score, Some(0));
+
+        let passphrase = gen_passphrase(PassphraseGeneratorRequest {
+            num_words: 6,
+            word_separator: " ".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+        let strength = evaluate_passphrase_strength(&passphrase, " ").unwrap();
+        let weakest_word = strength.weakest_word.unwrap();
+        assert!(passphrase.split(' ').any(|word| word == weakest_word), "{} not in {}", weakest_word, passphrase);
+        assert!(strength.weakest_word_score.unwrap() <= strength.score);
+
+        let password = evaluate_password_strength("Tr0ub4dor&3").unwrap();
+        assert!(password.weakest_word.is_none() && password.weakest_word_score.is_none());
+    }
+
+    #[test]
+    fn test_password_strength_guesses() {
+        for password in ["a", "password", "Tr0ub4dor&3", "correct horse battery staple", "xK#9vLq2$mN7pR4w"] {
+            let strength = evaluate_password_strength(password).unwrap();
+            assert!(strength.guesses >= 1, "{} has {} guesses", password, strength.guesses);
+            assert!(
+                (strength.guesses_log10 - (strength.guesses as f64).log10()).abs() < 1e-9,
+                "{}: {} != log10({})",
+                password,
+                strength.guesses_log10,
+                strength.guesses
+            );
+        }
+
+        // Both score 0, the guess count still orders them
+        let weaker = evaluate_password_strength("password").unwrap();
+        let stronger = evaluate_password_strength("password1").unwrap();
+        assert!(weaker.guesses < stronger.guesses);
+    }
+
+    #[test]
+    fn test_evaluate_username_security_examples() {
+        let strength = evaluate_username_security("QuietRiver4821");
+        assert!(!strength.security_level.is_empty());
+
+        // Brand names and common handles score low and come with feedback
+        let strength = evaluate_username_security("Skype");
+        assert!(strength.score < 40);
+        assert!(!strength.feedback.is_empty());
+    }
+
+    #[test]
+    fn test_username_strength_skype() {
+        let result = evaluate_username_security("Skype");
+        
+        // "Skype" should get a low score due to being a common brand name
+        assert!(result.score < 40, "Skype should have low security score, got: {}", result.score);
+        assert!(result.feedback.len() > 0, "Should have feedback for improvement");
+        
+        // Check that it identifies the brand name issue
+        let has_brand_feedback = result.feedback.iter().any(|f| f.to_lowercase().contains("skype"));
+        assert!(has_brand_feedback, "Should identify Skype as a problematic brand name");
+    }
+
+    #[test]
+    fn test_username_strength_good_username() {
+        let result = evaluate_username_security("QuietRaven47");
+        
+        // This should get a high score - it's a compound EFF word with numbers (generated pattern)
+        // Actual calculation: Privacy 80, Uniqueness 70 -> (80*0.6 + 70*0.4) = 76
+        assert!(result.score >= 75, "QuietRaven47 should have excellent security score, got: {}", result.score);
+    }
+
+    #[test]
+    fn test_username_strength_basic_usernames() {
+        // Test that Basic usernames (3-4 characters) are consistently rated as Very Poor due to hard cap
+        let basic_usernames = ["ace", "act", "add", "age", "aid", "aim", "air", "all", "and", "any"];
+        
+        for username in &basic_usernames {
+            let result = evaluate_username_security(username);
+            
+            // Basic usernames should be capped at Very Poor (≤25) regardless of other factors
+            assert!(result.score <= 25, 
+                "Basic username '{}' should be Very Poor (≤25) due to hard cap, got: {} ({})", 
+                username, result.score, result.security_level);
+            
+            // Should have feedback about being very short
+            let has_short_feedback = result.feedback.iter().any(|f| 
+                f.contains("Very short") || f.contains("Short") || f.contains("too short"));
+            assert!(has_short_feedback, 
+                "Basic username '{}' should have feedback about being too short", username);
+        }
+    }
+
+    #[test]
+    fn test_username_strength_eff_words() {
+        // Test single EFF words of different lengths (using actual EFF words not in common_words list)
+        let weak_eff = evaluate_username_security("able"); // 4 chars - Basic strength (hard capped)
+        let standard_eff = evaluate_username_security("abide"); // 5 chars - Standard strength  
+        let strong_eff = evaluate_username_security("outcome"); // 7 chars - Strong strength
+        let maximum_eff = evaluate_username_security("transport"); // 9 chars - Maximum strength
+        
+        // EFF words should get reasonable scores, with longer words scoring higher
+        // Updated expectations with hard cap for basic length:
+        // - 4 char word is capped at 25 (Very Poor due to fundamental length vulnerability)
+        // - 5 char word gets ~55-65 points (better length + EFF bonus)
+        // - 7+ char words get ~75+ points (good length + strong EFF bonus)
+        assert!(weak_eff.score <= 25, "Short EFF word should be capped at Very Poor due to length, got: {}", weak_eff.score);
+        assert!(standard_eff.score >= 50, "Standard EFF word should be good, got: {}", standard_eff.score);
+        assert!(strong_eff.score >= 70, "Strong EFF word should be very good, got: {}", strong_eff.score);
+        assert!(maximum_eff.score >= 75, "Maximum EFF word should be excellent, got: {}", maximum_eff.score);
+        
+        // Longer words should be better than shorter ones
+        assert!(standard_eff.score > weak_eff.score, "Standard should beat Basic (hard cap applies)");
+        assert!(strong_eff.score > standard_eff.score, "Strong should beat Standard");
+        assert!(maximum_eff.score >= strong_eff.score, "Maximum should be at least as good as Strong");
+        
+        // Verify that Basic EFF words are now in Very Poor category due to hard cap
+        assert!(weak_eff.score <= 25, 
+            "Basic EFF word should be in Very Poor category (≤25) due to hard cap, got: {}", weak_eff.score);
+    }
+
+    #[test]
+    fn test_username_strength_generated_patterns() {
+        // Test patterns that our generator creates (using actual EFF words not in common_words)
+        let word_with_numbers = evaluate_username_security("outcome1234"); // EFF word + 4 digits (11 chars total)
+        let capitalized_word = evaluate_username_security("Outcome");      // Capitalized EFF word (7 chars)
+        let compound_words = evaluate_username_security("outcomeabide");   // Two EFF words (12 chars total)
+        
+        // Generated patterns should score well since they're all longer than 4 characters:
+        // - EFF word + numbers: long enough to avoid hard cap, gets EFF bonus + numbers + generated bonus
+        // - Single EFF word (capitalized): 7 chars, same as lowercase EFF word, good score
+        // - Compound EFF words: very long, gets strong EFF bonus for compound word
+        assert!(word_with_numbers.score >= 60, "EFF word + numbers should be good (long enough), got: {}", word_with_numbers.score);
+        assert!(capitalized_word.score >= 70, "Capitalized EFF word should be very good, got: {}", capitalized_word.score);
+        assert!(compound_words.score >= 75, "Compound EFF words should be excellent, got: {}", compound_words.score);
+    }
+
+    fn mock_app() -> tauri::App<tauri::test::MockRuntime> {
+        tauri::test::mock_builder()
+            .manage(LastGeneratedState::default())
+            .manage(LastRequestState::default())
+            .manage(UsernameCancellation::default())
+            .manage(UsernameDeduplicator::default())
+            .manage(AuditLogState::default())
+            .manage(RateLimits::default())
+            .build(tauri::test::mock_context(tauri::test::noop_assets()))
+            .expect("Failed to build mock app")
+    }
+
+    #[tokio::test]
+    async fn test_last_generated_updated_after_each_generation() {
+        let app = mock_app();
+        assert_eq!(app.state::<LastGeneratedState>().get(), LastGenerated::default());
+
+        let password = generate_password(PasswordGeneratorRequest::default(), app.state(), app.state(), app.state(), app.state())
+            .await
+            .unwrap();
+        let last = app.state::<LastGeneratedState>().get();
+        assert_eq!(last.value.as_deref(), Some(password.as_str()));
+        assert_eq!(last.generator_type.as_deref(), Some("password"));
+
+        let passphrase = generate_passphrase(PassphraseGeneratorRequest::default(), app.state(), app.state(), app.state(), app.state())
+            .await
+            .unwrap();
+        let last = app.state::<LastGeneratedState>().get();
+        assert_eq!(last.value.as_deref(), Some(passphrase.as_str()));
+        assert_eq!(last.generator_type.as_deref(), Some("passphrase"));
+
+        let request = UsernameGeneratorRequest::Word {
+            capitalize: false,
+            number_suffix: generators::username::NumberSuffix::FourDigits,
+            number_position: generators::username::NumberPosition::Suffix,
+            strength: generators::username::UsernameStrength::Standard,
+            format: generators::username::UsernameFormat::Plain,
+        };
+        let username = generate_username(request, None, None, app.state(), app.state(), app.state(), app.state(), app.state()).await.unwrap();
+        let last = app.state::<LastGeneratedState>().get();
+        assert_eq!(last.value.as_deref(), Some(username.as_str()));
+        assert_eq!(last.generator_type.as_deref(), Some("username"));
+    }
+
+    #[test]
+    fn test_tray_generate_and_copy_without_window() {
+        let app = mock_app();
+        assert!(app.get_window("main").is_none());
+        let handle = app.handle();
+
+        let (generator_type, password) = generate_and_copy(&handle, ShortcutAction::CopyPassword).unwrap();
+        assert_eq!(generator_type, "password");
+        assert_eq!(password.len(), PasswordGeneratorRequest::default().length as usize);
+        assert_eq!(app.clipboard_manager().read_text().unwrap().as_deref(), Some(password.as_str()));
+        assert_eq!(app.state::<LastGeneratedState>().get().value.as_deref(), Some(password.as_str()));
+
+        // The last passphrase request made from the UI is reused
+        app.state::<LastRequestState>().record_passphrase(PassphraseGeneratorRequest {
+            num_words: 5,
+            word_separator: "+".to_string(),
+            ..Default::default()
+        });
+        let (generator_type, passphrase) = generate_and_copy(&handle, ShortcutAction::CopyPassphrase).unwrap();
+        assert_eq!(generator_type, "passphrase");
+        assert_eq!(passphrase.split('+').count(), 5);
+        assert_eq!(app.clipboard_manager().read_text().unwrap().as_deref(), Some(passphrase.as_str()));
+        assert_eq!(app.state::<AuditLogState>().entries().len(), 2);
+    }
+
+    #[test]
+    fn test_mask_preview() {
+        assert_eq!(mask_preview("correct-horse-battery"), "co••••ry");
+        assert_eq!(mask_preview("hunter2"), "•••••••");
+        assert_eq!(mask_preview(""), "");
+    }
+
+    #[tokio::test]
+    async fn test_generate_username_deduplicates() {
+        let app = mock_app();
+        // Basic strength without a number suffix draws from a small word list, so repeats are likely
+        let request = UsernameGeneratorRequest::Word {
+            capitalize: false,
+            number_suffix: generators::username::NumberSuffix::None,
+            number_position: generators::username::NumberPosition::Suffix,
+            strength: generators::username::UsernameStrength::Basic,
+            format: generators::username::UsernameFormat::Plain,
+        };
+
+        let mut usernames = HashSet::new();
+        for _ in 0..50 {
+            let username = generate_username(
+                request.clone(),
+                None,
+                Some(true),
+                app.state(),
+                app.state(),
+                app.state(),
+                app.state(),
+                app.state(),
+            )
+            .await
+            .unwrap();
+            assert!(usernames.insert(username.clone()), "{} was generated twice", username);
+        }
+        let deduplicator = app.state::<UsernameDeduplicator>();
+        assert_eq!(get_username_generation_count(app.state()).await.unwrap(), 50);
+
+        clear_username_history(app.state()).await.unwrap();
+        assert_eq!(deduplicator.len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_generate_deduplicated_gives_up() {
+        let deduplicator = UsernameDeduplicator::default();
+        let attempts = std::sync::atomic::AtomicU8::new(0);
+        let generate = || {
+            attempts.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            async { Ok::<_, SerializableError>("quiet_river".to_string()) }
+        };
+
+        assert_eq!(generate_deduplicated(&deduplicator, true, generate).await.unwrap(), "quiet_river");
+        // Without deduplication the repeat is returned as is
+        assert_eq!(generate_deduplicated(&deduplicator, false, generate).await.unwrap(), "quiet_river");
+        attempts.store(0, std::sync::atomic::Ordering::SeqCst);
+
+        let error = generate_deduplicated(&deduplicator, true, generate).await.unwrap_err();
+        assert_eq!(error.code, UsernameErrorCode::DuplicateUsername as u16);
+        assert_eq!(
+            attempts.load(std::sync::atomic::Ordering::SeqCst),
+            MAX_USERNAME_DEDUPLICATION_RETRIES + 1
+        );
+        assert_eq!(deduplicator.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_last_request_recorded_for_shortcuts() {
+        let app = mock_app();
+        assert!(app.state::<LastRequestState>().password().is_none());
+
+        let request = PasswordGeneratorRequest {
+            length: 32,
+            special: true,
+            ..Default::default()
+        };
+        generate_password(request, app.state(), app.state(), app.state(), app.state()).await.unwrap();
+        let recorded = app.state::<LastRequestState>().password().unwrap();
+        assert_eq!(recorded.length, 32);
+        assert!(recorded.special);
+
+        let request = PassphraseGeneratorRequest {
+            num_words: 6,
+            ..Default::default()
+        };
+        generate_passphrase(request, app.state(), app.state(), app.state(), app.state()).await.unwrap();
+        assert_eq!(app.state::<LastRequestState>().passphrase().unwrap().num_words, 6);
+
+        // Failed generations don't replace the last good request
+        let invalid = PasswordGeneratorRequest {
+            length: 2,
+            ..Default::default()
+        };
+        assert!(generate_password(invalid, app.state(), app.state(), app.state(), app.state()).await.is_err());
+        assert_eq!(app.state::<LastRequestState>().password().unwrap().length, 32);
+    }
+
+    #[test]
+    fn test_shortcut_action_serde() {
+        let action: ShortcutAction = serde_json::from_str(r#""CopyPassword""#).unwrap();
+        assert_eq!(action, ShortcutAction::CopyPassword);
+        assert_eq!(ShortcutAction::CopyPassword.default_accelerator(), "CmdOrCtrl+Shift+G");
+        assert_eq!(ShortcutAction::CopyPassphrase.default_accelerator(), "CmdOrCtrl+Shift+P");
+    }
+
+    #[tokio::test]
+    async fn test_last_generated_unchanged_on_error() {
+        let app = mock_app();
+        let password = generate_password(PasswordGeneratorRequest::default(), app.state(), app.state(), app.state(), app.state())
+            .await
+            .unwrap();
+
+        let invalid = PasswordGeneratorRequest {
+            length: 2,
+            ..Default::default()
+        };
+        assert!(generate_password(invalid, app.state(), app.state(), app.state(), app.state()).await.is_err());
+        assert_eq!(app.state::<LastGeneratedState>().get().value, Some(password));
+    }
+
+    /// Mock app with the commands under test registered, saving files to `save_dir`
+    fn ipc_app(save_dir: &Path) -> tauri::App<tauri::test::MockRuntime> {
+        let save_preferences = SavePreferencesState::load(None);
+        save_preferences
+            .update(|preferences| preferences.save_directory = Some(save_dir.to_path_buf()))
+            .unwrap();
+        tauri::test::mock_builder()
+            .manage(LastGeneratedState::default())
+            .manage(LastRequestState::default())
+            .manage(UsernameCancellation::default())
+            .manage(UsernameDeduplicator::default())
+            .manage(AuditLogState::default())
+            .manage(RateLimits::default())
+            .manage(save_preferences)
+            .invoke_handler(tauri::generate_handler![
+                generate_password,
+                generate_passphrase,
+                generate_username,
+                get_generation_audit_log,
+                clear_audit_log,
+                calculate_password_strength,
+                calculate_passphrase_strength,
+                calculate_username_strength,
+                copy_to_clipboard,
+                save_password_to_file,
+            ])
+            .build(tauri::test::mock_context(tauri::test::noop_assets()))
+            .expect("Failed to build mock app")
+    }
+
+    /// Invoke `cmd` through the IPC layer, the same way the frontend does
+    fn invoke(
+        window: &tauri::Window<tauri::test::MockRuntime>,
+        cmd: &str,
+        args: serde_json::Value,
+    ) -> Result<serde_json::Value, serde_json::Value> {
+        tauri::test::get_ipc_response(
+            window,
+            tauri::InvokePayload {
+                cmd: cmd.into(),
+                tauri_module: None,
+                callback: tauri::api::ipc::CallbackFn(0),
+                error: tauri::api::ipc::CallbackFn(1),
+                inner: args,
+                invoke_key: Some(tauri::test::INVOKE_KEY.into()),
+            },
+        )
+    }
+
+    #[test]
+    fn test_generator_commands_over_ipc() {
+        let save_dir = tempfile::tempdir().unwrap();
+        let app = ipc_app(save_dir.path());
+        let window = app.get_window("main").unwrap();
+
+        let password = invoke(
+            &window,
+            "generate_password",
+            serde_json::json!({ "request": PasswordGeneratorRequest::default() }),
+        )
+        .unwrap();
+        assert!(!password.as_str().unwrap().is_empty());
+
+        let passphrase = invoke(
+            &window,
+            "generate_passphrase",
+            serde_json::json!({ "request": PassphraseGeneratorRequest::default() }),
+        )
+        .unwrap();
+        assert!(!passphrase.as_str().unwrap().is_empty());
+
+        let username = invoke(
+            &window,
+            "generate_username",
+            serde_json::json!({
+                "request": { "Word": { "capitalize": true, "number_suffix": "FourDigits", "strength": "Standard" } }
+            }),
+        )
+        .unwrap();
+        assert!(!username.as_str().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_strength_commands_over_ipc() {
+        let save_dir = tempfile::tempdir().unwrap();
+        let app = ipc_app(save_dir.path());
+        let window = app.get_window("main").unwrap();
+
+        for (cmd, args) in [
+            ("calculate_password_strength", serde_json::json!({ "password": "correct horse battery staple" })),
+            (
+                "calculate_passphrase_strength",
+                serde_json::json!({ "passphrase": "correct-horse-battery-staple", "wordSeparator": "-" }),
+            ),
+            ("calculate_username_strength", serde_json::json!({ "username": "QuietRiver4821" })),
+        ] {
+            let response = invoke(&window, cmd, args).unwrap();
+            let score = response["score"].as_u64().unwrap();
+            assert!(score <= 100, "{cmd} returned {score}");
+        }
+    }
+
+    #[test]
+    fn test_clipboard_and_save_commands_over_ipc() {
+        let save_dir = tempfile::tempdir().unwrap();
+        let app = ipc_app(save_dir.path());
+        let window = app.get_window("main").unwrap();
+
+        let copied = invoke(&window, "copy_to_clipboard", serde_json::json!({ "text": "hunter2" })).unwrap();
+        assert_eq!(copied, serde_json::json!(true));
+        assert_eq!(app.clipboard_manager().read_text().unwrap().as_deref(), Some("hunter2"));
+
+        let path = invoke(&window, "save_password_to_file", serde_json::json!({ "password": "hunter2" })).unwrap();
+        let path = PathBuf::from(path.as_str().unwrap());
+        assert!(path.starts_with(save_dir.path()), "{}", path.display());
+        assert!(fs::read_to_string(path).unwrap().contains("hunter2"));
+    }
+
+    #[test]
+    fn test_audit_log_over_ipc() {
+        let save_dir = tempfile::tempdir().unwrap();
+        let app = ipc_app(save_dir.path());
+        let window = app.get_window("main").unwrap();
+
+        let mut generated = Vec::new();
+        for (cmd, request) in [
+            ("generate_password", serde_json::to_value(PasswordGeneratorRequest::default()).unwrap()),
+            ("generate_passphrase", serde_json::to_value(PassphraseGeneratorRequest::default()).unwrap()),
+            (
+                "generate_username",
+                serde_json::json!({ "Word": { "capitalize": true, "number_suffix": "FourDigits", "strength": "Standard" } }),
+            ),
+        ] {
+            let value = invoke(&window, cmd, serde_json::json!({ "request": request })).unwrap();
+            generated.push(value.as_str().unwrap().to_string());
+        }
+
+        let log = invoke(&window, "get_generation_audit_log", serde_json::json!({})).unwrap();
+        let entries: Vec<AuditEntry> = serde_json::from_value(log.clone()).unwrap();
+        let event_types: Vec<&str> = entries.iter().map(|entry| entry.event_type.as_str()).collect();
+        assert_eq!(event_types, ["password", "passphrase", "username"]);
+        assert_eq!(entries[0].generator_config_hash, config_hash(&PasswordGeneratorRequest::default()));
+        assert!(entries.iter().all(|entry| entry.session_id == entries[0].session_id));
+        assert!(entries.iter().all(|entry| chrono::DateTime::parse_from_rfc3339(&entry.timestamp).is_ok()));
+        for value in generated {
+            assert!(!log.to_string().contains(&value), "audit log contains the generated value {value}");
+        }
+
+        invoke(&window, "clear_audit_log", serde_json::json!({})).unwrap();
+        let log = invoke(&window, "get_generation_audit_log", serde_json::json!({})).unwrap();
+        assert_eq!(log, serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_audit_log_keeps_latest_entries() {
+        let audit_log = AuditLogState::default();
+        for length in 0..MAX_AUDIT_ENTRIES + 5 {
+            let request = PasswordGeneratorRequest {
+                length: 8 + length as u8 % 64,
+                ..Default::default()
+            };
+            audit_log.record("password", &request);
+        }
+
+        let entries = audit_log.entries();
+        assert_eq!(entries.len(), MAX_AUDIT_ENTRIES);
+        // The first five entries were dropped
+        let sixth = PasswordGeneratorRequest {
+            length: 8 + 5,
+            ..Default::default()
+        };
+        assert_eq!(entries[0].generator_config_hash, config_hash(&sixth));
+        assert_ne!(AuditLogState::default().session_id, audit_log.session_id);
+    }
+
+    #[test]
+    fn test_sanitize_username_request_rejects_injection() {
+        let request = UsernameGeneratorRequest::Catchall {
+            r#type: AppendType::Random,
+            domain: "example.com; rm -rf /".to_string(),
+            original_domain: None,
+        };
+        assert_eq!(
+            sanitize_username_request(request).unwrap_err(),
+            "Invalid domain: must not contain ';'"
+        );
+
+        let request = UsernameGeneratorRequest::Subaddress {
+            r#type: AppendType::WebsiteName {
+                website: "$(curl evil.example)".to_string(),
+            },
+            email: "user@example.com".to_string(),
+        };
+        assert_eq!(
+            sanitize_username_request(request).unwrap_err(),
+            "Invalid website: must not contain '$'"
+        );
+
+        let request = UsernameGeneratorRequest::Forwarded {
+            service: ForwarderServiceType::ForwardEmail {
+                api_token: "token".to_string(),
+                domain: "example.com`id`".to_string(),
+            },
+            website: None,
+        };
+        assert!(sanitize_username_request(request).is_err());
+    }
+
+    #[test]
+    fn test_sanitize_username_request_strips_nul_bytes() {
+        let request = UsernameGeneratorRequest::Catchall {
+            r#type: AppendType::Random,
+            domain: "exam\0ple.com".to_string(),
+            original_domain: None,
+        };
+        match sanitize_username_request(request).unwrap() {
+            UsernameGeneratorRequest::Catchall { domain, .. } => assert_eq!(domain, "example.com"),
+            other => panic!("unexpected request {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_validate_path() {
+        // Shell metacharacters are valid in directory names
+        validate_path("/home/tom/Tom & Jerry").unwrap();
+        validate_path("/home/tom/$HOME; backups").unwrap();
+        validate_path(&format!("/{}", "a".repeat(MAX_PATH_LENGTH - 1))).unwrap();
+
+        assert!(validate_path("/home/tom/pass\0words").is_err());
+        let error = validate_path(&format!("/{}", "a".repeat(MAX_PATH_LENGTH))).unwrap_err();
+        assert_eq!(error, format!("Invalid path: must be at most {} bytes", MAX_PATH_LENGTH));
+    }
+
+    #[test]
+    fn test_generate_username_rejects_injection_over_ipc() {
+        let save_dir = tempfile::tempdir().unwrap();
+        let app = ipc_app(save_dir.path());
+        let window = app.get_window("main").unwrap();
+
+        let error = invoke(
+            &window,
+            "generate_username",
+            serde_json::json!({ "request": { "Catchall": { "type": "Random", "domain": "; rm -rf /" } } }),
+        )
+        .unwrap_err();
+        assert_eq!(error["code"], GENERIC_ERROR_CODE);
+        assert_eq!(error["message"], "Invalid domain: must not contain ';'");
+    }
+
+    #[test]
+    fn test_generation_rate_limit() {
+        let clock = governor::clock::FakeRelativeClock::default();
+        let limits = RateLimits::with_clock(&clock);
+
+        for _ in 0..GENERATION_RATE_LIMIT {
+            limits.check(GenerationKind::Password).unwrap();
+        }
+        assert_eq!(
+            limits.check(GenerationKind::Password).unwrap_err(),
+            "Rate limit exceeded: please wait before generating more passwords"
+        );
+        // Each generator has its own bucket
+        limits.check(GenerationKind::Passphrase).unwrap();
+        limits.check(GenerationKind::Username).unwrap();
+
+        clock.advance(std::time::Duration::from_secs(1));
+        for _ in 0..GENERATION_RATE_LIMIT {
+            limits.check(GenerationKind::Password).unwrap();
+        }
+        assert!(limits.check(GenerationKind::Password).is_err());
+    }
+
+    #[test]
+    fn test_forwarder_rate_limit() {
+        let clock = governor::clock::FakeRelativeClock::default();
+        let limits = RateLimits::with_clock(&clock);
+
+        for _ in 0..FORWARDER_RATE_LIMIT {
+            limits.check_forwarder("generate_username").unwrap();
+        }
+        let error = limits.check_forwarder("generate_username").unwrap_err();
+        assert_eq!(error.code(), UsernameErrorCode::LocalRateLimitExceeded);
+        limits.check_forwarder("list_addy_domains").unwrap();
+
+        // Not reset after a second, unlike the generators
+        clock.advance(std::time::Duration::from_secs(1));
+        assert!(limits.check_forwarder("generate_username").is_err());
+
+        clock.advance(std::time::Duration::from_secs(60));
+        for _ in 0..FORWARDER_RATE_LIMIT {
+            limits.check_forwarder("generate_username").unwrap();
+        }
+        assert!(limits.check_forwarder("generate_username").is_err());
+    }
+
+    #[test]
+    fn test_rate_limit_over_ipc() {
+        let save_dir = tempfile::tempdir().unwrap();
+        let app = ipc_app(save_dir.path());
+        let window = app.get_window("main").unwrap();
+        let request = serde_json::json!({ "request": PasswordGeneratorRequest::default() });
+
+        // The bucket refills while the calls run, so the limit fires a little after the first 100
+        let error = (0..GENERATION_RATE_LIMIT * 10)
+            .find_map(|_| invoke(&window, "generate_password", request.clone()).err())
+            .expect("rate limit never fired");
+        assert_eq!(
+            error,
+            serde_json::json!("Rate limit exceeded: please wait before generating more passwords")
+        );
+    }
+
+    #[test]
+    fn test_audit_log_hashes_redacted_username_request() {
+        let request = UsernameGeneratorRequest::Forwarded {
+            service: generators::username::ForwarderServiceType::DuckDuckGo {
+                token: "super-secret".to_string(),
+            },
+            website: None,
+        };
+        let audit_log = AuditLogState::default();
+        audit_log.record("username", &request.redact_secrets());
+
+        let entry = &audit_log.entries()[0];
+        assert_ne!(entry.generator_config_hash, config_hash(&request));
+        assert_eq!(entry.generator_config_hash.len(), 64);
+    }
+
+    #[test]
+    fn test_tray_icon_for_generator_types() {
+        let password = tray_icon_for("password");
+        let passphrase = tray_icon_for("passphrase");
+        let username = tray_icon_for("username");
+
+        let bytes = |icon: Option<tauri::Icon>| match icon {
+            Some(tauri::Icon::Raw(bytes)) => bytes,
+            other => panic!("Expected a raw PNG icon, got: {:?}", other),
+        };
+        let (password, passphrase, username) = (bytes(password), bytes(passphrase), bytes(username));
+
+        for icon in [&password, &passphrase, &username] {
+            assert!(icon.starts_with(b"\x89PNG"), "Tray badges should be PNG files");
+        }
+        assert_ne!(password, passphrase);
+        assert_ne!(passphrase, username);
+        assert_ne!(password, username);
+
+        assert!(tray_icon_for("unknown").is_none());
+    }
+
+    #[test]
+    fn test_evaluate_bcrypt_known_hashes() {
+        let hashes = [
+            "$2b$04$a0ThbVHjX0TsJVLfZFOfGOdmSct5DCBVk8kdUkIew8InRrb7yNm.O",
+            "$2b$05$a0ThbVHjX0TsJVLfZFOfGO7Hdf4onnqQEA/ZvMM4aQHTTGEQti/ri",
+            "$2b$06$a0ThbVHjX0TsJVLfZFOfGOpENqvSoonDSE3JpxIBt6ImFpHWAL.vm",
+            "$2b$07$a0ThbVHjX0TsJVLfZFOfGOYMhCpHxTZ7Yaw/JSexkzpR1BawOaPqi",
+            "$2b$08$a0ThbVHjX0TsJVLfZFOfGO31lsrqF0q6WtUxewWsVR5FiYkKdu42W",
+            "$2b$09$a0ThbVHjX0TsJVLfZFOfGO9jUHIdl3YkC/Ahmm0AYoh3nusq2Tjci",
+            "$2b$10$a0ThbVHjX0TsJVLfZFOfGOBaGWEZQaDcL8Dx9XRKIiI9cGZoUA8.u",
+            "$2b$11$a0ThbVHjX0TsJVLfZFOfGO40j47t7p8bG/8uJMThUWOZwhVOtkzEm",
+            "$2b$12$a0ThbVHjX0TsJVLfZFOfGO4FHgw67TWAs5E.tBBfGVqcMLLsfZrvq",
+            "$2b$13$a0ThbVHjX0TsJVLfZFOfGOBv027IKeR.4P9niyUi9Q8m7t8QxHBYy",
+            "$2b$14$a0ThbVHjX0TsJVLfZFOfGOyY7XTEmDsx.uYeIMu7xMz.XCTXbA9E.",
+        ];
+
+        for (hash, expected_cost) in hashes.iter().zip(4u8..) {
+            let result = evaluate_bcrypt_hash(hash, 2024).unwrap();
+            assert_eq!(result.cost, expected_cost);
+
+            let expected_strength = match expected_cost {
+                4..=9 => "Weak",
+                10 | 11 => "Fair",
+                12 | 13 => "Good",
+                _ => "Strong",
+            };
+            assert_eq!(result.strength, expected_strength, "Unexpected strength for cost {}", expected_cost);
+        }
+    }
+
+    #[test]
+    fn test_evaluate_bcrypt_recommendation() {
+        let hash = "$2b$11$a0ThbVHjX0TsJVLfZFOfGO40j47t7p8bG/8uJMThUWOZwhVOtkzEm";
+
+        // Cost 11 met the recommendation in 2020 but not after 2023
+        assert!(evaluate_bcrypt_hash(hash, 2020).unwrap().recommendation.contains("meets"));
+        assert!(evaluate_bcrypt_hash(hash, 2024).unwrap().recommendation.contains("rehash with cost 12"));
+
+        assert_eq!(recommended_bcrypt_cost(2000), 10);
+        assert_eq!(recommended_bcrypt_cost(2030), 12);
+    }
+
+    #[test]
+    fn test_compare_generator_strength() {
+        // Sent by the frontend as a plain request, the type is told apart by its fields
+        let passphrase = PassphraseGeneratorRequest {
+            num_words: 6,
+            word_separator: "-".to_string(),
+            ..Default::default()
+        };
+        let passphrase: GeneratorConfig = serde_json::from_value(serde_json::to_value(passphrase).unwrap()).unwrap();
+        let password = PasswordGeneratorRequest::builder().length(12).build().unwrap();
+        let password: GeneratorConfig = serde_json::from_value(serde_json::to_value(password).unwrap()).unwrap();
+        assert!(matches!(passphrase, GeneratorConfig::Passphrase(_)));
+        assert!(matches!(password, GeneratorConfig::Password(_)));
+
+        let comparison = compare_strength(passphrase.clone(), password.clone()).unwrap();
+        assert_eq!(comparison.winner, "A");
+        assert!(comparison.entropy_a > comparison.entropy_b);
+        assert!((comparison.difference_bits - (comparison.entropy_a - comparison.entropy_b)).abs() < 1e-9);
+        assert_eq!(compare_strength(password, passphrase.clone()).unwrap().winner, "B");
+        assert_eq!(compare_strength(passphrase.clone(), passphrase).unwrap().winner, "Equal");
+
+        let invalid = GeneratorConfig::Passphrase(PassphraseGeneratorRequest { num_words: 1, ..Default::default() });
+        assert!(compare_strength(invalid, GeneratorConfig::Password(PasswordGeneratorRequest::default())).is_err());
+    }
+
+    #[test]
+    fn test_levenshtein_distance() {
+        let distance = |a: &str, b: &str| {
+            levenshtein_distance(&a.chars().collect::<Vec<_>>(), &b.chars().collect::<Vec<_>>())
+        };
+        assert_eq!(distance("", ""), 0);
+        assert_eq!(distance("abc", ""), 3);
+        assert_eq!(distance("kitten", "sitting"), 3);
+        assert_eq!(distance("flaw", "lawn"), 2);
+        assert_eq!(distance("pässword", "password"), 1);
+    }
+
+    #[test]
+    fn test_password_similarity() {
+        let incremented = evaluate_password_similarity("Password2", "Password1", DEFAULT_MAX_PASSWORD_SIMILARITY);
+        assert!(incremented.is_too_similar);
+        assert!((incremented.similarity_score - 8.0 / 9.0).abs() < 1e-9);
+        assert_eq!(
+            incremented.changes,
+            ["differs by only 1 character", "same prefix retained", "same length retained"]
+        );
+
+        let identical = evaluate_password_similarity("hunter2", "hunter2", DEFAULT_MAX_PASSWORD_SIMILARITY);
+        assert!(identical.is_too_similar);
+        assert_eq!(identical.similarity_score, 1.0);
+        assert_eq!(identical.changes, ["identical to the old password"]);
+
+        // 4 of 11 characters changed, below the default threshold but not a stricter one
+        let seasonal = evaluate_password_similarity("Winter2024!", "Summer2024!", DEFAULT_MAX_PASSWORD_SIMILARITY);
+        assert!(!seasonal.is_too_similar);
+        assert_eq!(seasonal.changes, ["same suffix retained", "same length retained"]);
+        assert!(evaluate_password_similarity("Winter2024!", "Summer2024!", 0.5).is_too_similar);
+
+        let unrelated =
+            evaluate_password_similarity("correct horse battery staple", "Tr0ub4dor&3", DEFAULT_MAX_PASSWORD_SIMILARITY);
+        assert!(!unrelated.is_too_similar);
+        assert!(unrelated.similarity_score < 0.2);
+        assert!(unrelated.changes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_compare_passwords_rejects_long_passwords() {
+        let longest = "a".repeat(MAX_COMPARED_PASSWORD_LENGTH);
+        let response = compare_passwords(longest.clone(), longest.clone(), None).await.unwrap();
+        assert_eq!(response.schema_version, SCHEMA_VERSION);
+        assert!(response.data.is_too_similar);
+
+        let too_long = format!("{}a", longest);
+        let error = compare_passwords(too_long.clone(), "hunter2".to_string(), None).await.unwrap_err();
+        assert_eq!(error, format!("Passwords must be at most {} characters", MAX_COMPARED_PASSWORD_LENGTH));
+        assert!(compare_passwords("hunter2".to_string(), too_long, None).await.is_err());
+    }
+
+    #[test]
+    fn test_evaluate_bcrypt_invalid_hashes() {
+        assert!(evaluate_bcrypt_hash("", 2024).is_err());
+        assert!(evaluate_bcrypt_hash("not a hash", 2024).is_err());
+        assert!(evaluate_bcrypt_hash("$1$12$a0ThbVHjX0TsJVLfZFOfGO4FHgw67TWAs5E.tBBfGVqcMLLsfZrvq", 2024).is_err());
+        assert!(evaluate_bcrypt_hash("$2b$03$a0ThbVHjX0TsJVLfZFOfGO4FHgw67TWAs5E.tBBfGVqcMLLsfZrvq", 2024).is_err());
+        assert!(evaluate_bcrypt_hash("$2b$12$tooshort", 2024).is_err());
+    }
+
+    #[test]
+    fn test_mask_ip_address() {
+        assert_eq!(mask_ip_address("203.0.113.42"), "203.0.xxx.xxx");
+        assert_eq!(mask_ip_address("2001:db8::1"), "2001::xxxx:xxxx:xxxx:xxxx");
+        assert_eq!(mask_ip_address("not an ip"), "Not available");
+    }
+
+    #[test]
+    fn test_ip_cache_expires_after_ttl() {
+        let cache = IpCacheState::new(60);
+        let fetched_at = std::time::Instant::now();
+        assert!(cache.get(fetched_at).is_none());
+
+        cache.store(IPResponse::from_ip("203.0.113.7".to_string()), fetched_at);
+        let hit = cache.get(fetched_at + std::time::Duration::from_secs(59)).expect("Fresh entry should be a hit");
+        assert_eq!(hit.ip, "203.0.113.7");
+        assert!(cache.get(fetched_at + std::time::Duration::from_secs(60)).is_none());
+
+        // A shorter TTL applies to the entry already cached, 0 disables caching
+        cache.set_ttl(10);
+        assert!(cache.get(fetched_at + std::time::Duration::from_secs(10)).is_none());
+        cache.set_ttl(0);
+        assert!(cache.get(fetched_at).is_none());
+
+        cache.set_ttl(60);
+        assert!(cache.get(fetched_at).is_some());
+        cache.invalidate();
+        assert!(cache.get(fetched_at).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_system_identity_serializes() {
+        let ip_cache = IpCacheState::new(DEFAULT_IP_CACHE_TTL_SECONDS);
+        let identity = build_system_identity(&ip_cache, None, HwidOutputFormat::Display).await.expect("System identity should always resolve");
+        let json = serde_json::to_value(&identity).expect("System identity should serialize");
+
+        assert_eq!(json["platform"], std::env::consts::OS);
+        assert_eq!(json["cpu_architecture"], std::env::consts::ARCH);
+        assert!(json.get("platform_version").is_some());
+        assert!(json.get("total_memory_mb").is_some());
+
+        if let Some(cores) = identity.cpu_cores {
+            assert!(cores > 0);
+        }
+    }
+
+    #[test]
+    fn test_hardware_id_output_formats_roundtrip() {
+        let display = format_machine_id("test-machine-id");
+        let bytes = from_hwid_display(&display).unwrap();
+
+        assert_eq!(format_hardware_id(&display, HwidOutputFormat::Display).unwrap(), display);
+
+        let hex = format_hardware_id(&display, HwidOutputFormat::Hex).unwrap();
+        assert_eq!(hex.len(), 16);
+        assert_eq!(hex, hex.to_lowercase());
+        assert_eq!(decode_hex(&hex).unwrap(), bytes);
+
+        use base64::Engine;
+        let base64 = format_hardware_id(&display, HwidOutputFormat::Base64).unwrap();
+        assert!(!base64.contains('='));
+        assert_eq!(base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(&base64).unwrap(), bytes);
+
+        assert_eq!(
+            from_hwid_display("HWID-0011-2233-4455-6677").unwrap(),
+            [0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77]
+        );
+        for invalid in ["", "HWID-0011-2233-4455", "HWID-0011-2233-4455-667G", "HWID-FB-0011-2233-4455"] {
+            assert!(from_hwid_display(invalid).is_err(), "{invalid} should be rejected");
+        }
+        assert!(format_hardware_id("HWID-FB-0011-2233-4455", HwidOutputFormat::Hex).is_err());
+    }
+
+    #[test]
+    fn test_combine_machine_ids() {
+        assert_eq!(combine_machine_ids(&[]), None);
+
+        // A single source keeps the ID it had before sources were combined
+        let single = vec!["4c4c4544-0042".to_string()];
+        assert_eq!(combine_machine_ids(&single), Some(format_machine_id("4c4c4544-0042")));
+
+        let pair = vec!["first".to_string(), "second".to_string()];
+        let swapped = vec!["second".to_string(), "first".to_string()];
+        let combined = combine_machine_ids(&pair).unwrap();
+        assert!(from_hwid_display(&combined).is_ok());
+        assert_eq!(combine_machine_ids(&swapped).unwrap(), combined, "Order of sources shouldn't matter");
+        assert_ne!(combined, format_machine_id("first"));
+        assert_ne!(combined, format_machine_id("second"));
+
+        assert_eq!(HwidConfidence::from_source_count(0), HwidConfidence::Fallback);
+        assert_eq!(HwidConfidence::from_source_count(1), HwidConfidence::Low);
+        assert_eq!(HwidConfidence::from_source_count(2), HwidConfidence::Medium);
+        assert_eq!(HwidConfidence::from_source_count(4), HwidConfidence::High);
+    }
+
+    #[test]
+    fn test_parse_platform_machine_ids() {
+        let ioreg = r#"+-o MacBookPro18,1  <class IOPlatformExpertDevice, id 0x100000110, registered, matched, active, busy 0 (0 ms), retain 36>
+    {
+      "IOPlatformSerialNumber" = "C02XXXXXXXXX"
+      "IOPlatformUUID" = "3F2504E0-4F89-11D3-9A0C-0305E82C3301"
+    }"#;
+        assert_eq!(parse_ioreg_platform_uuid(ioreg).as_deref(), Some("3F2504E0-4F89-11D3-9A0C-0305E82C3301"));
+        assert_eq!(parse_ioreg_platform_uuid("no platform device"), None);
+
+        let reg = "\r\nHKEY_LOCAL_MACHINE\\SOFTWARE\\Microsoft\\Cryptography\r\n    MachineGuid    REG_SZ    3f2504e0-4f89-11d3-9a0c-0305e82c3301\r\n\r\n";
+        assert_eq!(parse_reg_machine_guid(reg).as_deref(), Some("3f2504e0-4f89-11d3-9a0c-0305e82c3301"));
+        assert_eq!(parse_reg_machine_guid("ERROR: The system was unable to find the specified registry key or value."), None);
+    }
+
+    #[tokio::test]
+    async fn test_hardware_id_cache_created_and_reused() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let (first_id, first_source, first_confidence) = generate_hardware_id(Some(dir.path())).await.unwrap();
+        assert_ne!(first_source, HwidSource::Cached);
+        assert!(dir.path().join(HWID_CACHE_FILE).exists(), "Cache file should be created");
+
+        let (second_id, second_source, second_confidence) = generate_hardware_id(Some(dir.path())).await.unwrap();
+        assert_eq!(second_source, HwidSource::Cached);
+        assert_eq!(second_id, first_id);
+        assert_eq!(second_confidence, first_confidence);
+    }
+
+    #[tokio::test]
+    async fn test_hardware_id_cache_tampering_triggers_regeneration() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_path = dir.path().join(HWID_CACHE_FILE);
+
+        let (original_id, _, confidence) = generate_hardware_id(Some(dir.path())).await.unwrap();
+
+        // Swap in a different ID while keeping the original HMAC
+        let mut cache: HwidCache = serde_json::from_str(&fs::read_to_string(&cache_path).unwrap()).unwrap();
+        cache.hardware_id = "HWID-0000-0000-0000-0000".to_string();
+        fs::write(&cache_path, serde_json::to_string(&cache).unwrap()).unwrap();
+        assert_eq!(read_cached_hardware_id(dir.path()), None);
+
+        let (regenerated_id, source, _) = generate_hardware_id(Some(dir.path())).await.unwrap();
+        assert_ne!(source, HwidSource::Cached);
+        assert_eq!(regenerated_id, original_id);
+
+        // The cache should have been overwritten with a valid entry
+        assert_eq!(read_cached_hardware_id(dir.path()), Some((original_id.clone(), confidence)));
+
+        // Raising the confidence by hand breaks the HMAC as well
+        let mut cache: HwidCache = serde_json::from_str(&fs::read_to_string(&cache_path).unwrap()).unwrap();
+        cache.confidence = Some(HwidConfidence::High);
+        fs::write(&cache_path, serde_json::to_string(&cache).unwrap()).unwrap();
+        if confidence != HwidConfidence::High {
+            assert_eq!(read_cached_hardware_id(dir.path()), None);
+        }
+
+        // Caches written before the confidence was stored are regenerated
+        fs::write(&cache_path, serde_json::json!({ "hardware_id": original_id, "hmac": "00" }).to_string()).unwrap();
+        assert_eq!(read_cached_hardware_id(dir.path()), None);
+
+        // Garbage contents are also rejected
+        fs::write(&cache_path, "not json").unwrap();
+        assert_eq!(read_cached_hardware_id(dir.path()), None);
+    }
+
+    #[test]
+    fn test_save_preferences_persisted() {
+        let config_dir = tempfile::tempdir().unwrap();
+        let save_dir = tempfile::tempdir().unwrap();
+
+        let state = SavePreferencesState::load(Some(config_dir.path().to_path_buf()));
+        assert_eq!(state.get(), SavePreferences::default());
+
+        state
+            .update(|preferences| preferences.save_directory = Some(save_dir.path().to_path_buf()))
+            .unwrap();
+
+        let reloaded = SavePreferencesState::load(Some(config_dir.path().to_path_buf()));
+        assert_eq!(reloaded.get().save_directory.as_deref(), Some(save_dir.path()));
+
+        // A corrupt preferences file falls back to the defaults
+        fs::write(config_dir.path().join(PREFERENCES_FILE), "{not json").unwrap();
+        assert_eq!(SavePreferences::load(config_dir.path()), SavePreferences::default());
+    }
+
+    #[test]
+    fn test_always_on_top_persisted() {
+        let config_dir = tempfile::tempdir().unwrap();
+        let app = tauri::test::mock_builder()
+            .manage(SavePreferencesState::load(Some(config_dir.path().to_path_buf())))
+            .invoke_handler(tauri::generate_handler![set_always_on_top, get_always_on_top])
+            .build(tauri::test::mock_context(tauri::test::noop_assets()))
+            .expect("Failed to build mock app");
+        let window = app.get_window("main").unwrap();
+
+        assert_eq!(invoke(&window, "get_always_on_top", serde_json::json!({})).unwrap(), false);
+        invoke(&window, "set_always_on_top", serde_json::json!({ "enabled": true })).unwrap();
+        assert_eq!(invoke(&window, "get_always_on_top", serde_json::json!({})).unwrap(), true);
+
+        let reloaded = SavePreferencesState::load(Some(config_dir.path().to_path_buf()));
+        assert!(reloaded.get().always_on_top);
+
+        // Preferences written before the setting existed default to off
+        fs::write(config_dir.path().join(PREFERENCES_FILE), r#"{"max_saved_files":3}"#).unwrap();
+        assert!(!SavePreferences::load(config_dir.path()).always_on_top);
+    }
+
+    #[test]
+    fn test_ensure_save_directory_creates_missing_directory() {
+        let root = tempfile::tempdir().unwrap();
+        let nested = root.path().join("credentials").join("work");
+
+        let dir = ensure_save_directory(&nested).unwrap();
+        assert!(nested.is_dir());
+        assert_eq!(dir, nested.canonicalize().unwrap());
+
+        // Paths pointing at a file are rejected
+        let file = root.path().join("file.txt");
+        fs::write(&file, "x").unwrap();
+        assert!(ensure_save_directory(&file).is_err());
+    }
+
+    #[test]
+    fn test_resolve_save_directory_falls_back_when_inaccessible() {
+        let root = tempfile::tempdir().unwrap();
+        let configured = root.path().join("configured");
+        let default_dir = root.path().join("secgen");
+
+        let (dir, fallback) = resolve_save_directory(Some(&configured), &default_dir).unwrap();
+        assert_eq!(dir, configured.canonicalize().unwrap());
+        assert!(fallback.is_none());
+
+        // Replace the configured directory with a file so it can no longer be used
+        fs::remove_dir(&configured).unwrap();
+        fs::write(&configured, "x").unwrap();
+
+        let (dir, fallback) = resolve_save_directory(Some(&configured), &default_dir).unwrap();
+        assert_eq!(dir, default_dir.canonicalize().unwrap());
+        let fallback = fallback.expect("Should report the fallback");
+        assert_eq!(fallback.requested, configured.to_string_lossy());
+
+        let (dir, fallback) = resolve_save_directory(None, &default_dir).unwrap();
+        assert_eq!(dir, default_dir.canonicalize().unwrap());
+        assert!(fallback.is_none());
+    }
+
+    #[test]
+    fn test_write_password_file() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let file_path = write_password_file(dir.path(), None, "correct-horse").unwrap();
+        assert!(file_path.starts_with(dir.path()));
+        assert!(file_path.to_string_lossy().ends_with("-pw.txt"));
+        assert_eq!(fs::read_to_string(file_path).unwrap(), "correct-horse");
+    }
+
+    #[test]
+    fn test_filename_date_formats() {
+        let date = Utc.with_ymd_and_hms(2024, 3, 7, 9, 5, 0).unwrap();
+        assert_eq!(format_filename_date(&date, ISO8601_FILENAME_DATE_FORMAT).unwrap(), "2024-03-07-09-05");
+        assert_eq!(format_filename_date(&date, "%d-%m-%Y").unwrap(), "07-03-2024");
+        assert_eq!(format_filename_date(&date, "%d.%m.%Y %Hh%M").unwrap(), "07.03.2024 09h05");
+
+        // Separators that are invalid on Windows, macOS or Linux are rejected after formatting
+        for format in ["%d/%m/%Y", "%H:%M", "%Y\\%m", "%F %T", "%Y|%m", "%Y%n", "", "%Q"] {
+            assert!(format_filename_date(&date, format).is_err(), "{format:?} should be rejected");
+            assert!(validate_filename_date_format(format).is_err(), "{format:?} should be rejected");
         }
-        DictionaryUsage::WeakDictionary => {
-            // Non-EFF dictionary words or obvious patterns
-            privacy_score = privacy_score.saturating_sub(15);
-            uniqueness_score = uniqueness_score.saturating_sub(10);
-            feedback.push("Contains easily guessable dictionary pattern".to_string());
+        assert!(validate_filename_date_format(&"%Y".repeat(20)).is_err());
+        assert!(validate_filename_date_format(ISO8601_DATE_PRESET).is_ok());
+        assert!(validate_filename_date_format(LOCALE_DEFAULT_DATE_PRESET).is_ok());
+        assert!(validate_filename_date_format("%A %d %B %Y").is_ok());
+
+        assert_eq!(locale_filename_date_format("en-US"), "%m-%d-%Y-%H-%M");
+        assert_eq!(locale_filename_date_format("de_DE.UTF-8"), "%d-%m-%Y-%H-%M");
+        assert_eq!(locale_filename_date_format("en-GB"), "%d-%m-%Y-%H-%M");
+        assert_eq!(locale_filename_date_format("ja-JP"), ISO8601_FILENAME_DATE_FORMAT);
+        assert_eq!(locale_filename_date_format("C"), ISO8601_FILENAME_DATE_FORMAT);
+        for locale in ["en-US", "fr-FR", "zh-CN", "sv_SE", "en", ""] {
+            let format = locale_filename_date_format(locale);
+            assert!(format_filename_date(&date, format).is_ok(), "{locale}");
         }
-        DictionaryUsage::CompoundWords => {
-            // Multiple dictionary words - depends on context
-            privacy_score = privacy_score.saturating_sub(10);
-            uniqueness_score += 5; // Compound can be more unique
-            feedback.push("Contains compound words - moderate security".to_string());
+
+        // A stored format that has become invalid falls back to the ISO preset
+        let timestamp = filename_timestamp(Some("%H:%M"));
+        assert!(chrono::NaiveDateTime::parse_from_str(&timestamp, ISO8601_FILENAME_DATE_FORMAT).is_ok());
+    }
+
+    #[test]
+    fn test_write_password_file_with_date_format() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let file_path = write_password_file(dir.path(), Some("%d-%m-%Y"), "correct-horse").unwrap();
+        let file_name = file_path.file_name().unwrap().to_string_lossy().to_string();
+        let date = file_name.strip_suffix("-pw.txt").unwrap();
+        assert!(chrono::NaiveDate::parse_from_str(date, "%d-%m-%Y").is_ok(), "{file_name}");
+    }
+
+    #[test]
+    fn test_write_password_file_does_not_overwrite() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let first = write_password_file(dir.path(), None, "first").unwrap();
+        let second = write_password_file(dir.path(), None, "second").unwrap();
+        assert_ne!(first, second);
+        assert_eq!(fs::read_to_string(first).unwrap(), "first");
+        assert_eq!(fs::read_to_string(second).unwrap(), "second");
+    }
+
+    #[test]
+    fn test_rotate_saved_files_keeps_newest() {
+        let dir = tempfile::tempdir().unwrap();
+        let max_files = 3;
+
+        // Unrelated files are never counted or deleted
+        fs::write(dir.path().join("notes.md"), "keep me").unwrap();
+
+        let mut written = Vec::new();
+        for i in 0..max_files + 3 {
+            written.push(write_password_file(dir.path(), None, &format!("password-{}", i)).unwrap());
+            rotate_saved_files(dir.path(), max_files).unwrap();
         }
-        DictionaryUsage::NonDictionary => {
-            // Not a dictionary word - good for uniqueness, but for Basic length still weak
-            if length <= 4 {
-                // Basic non-dictionary usernames are still weak due to short length
-                uniqueness_score += 10; // Reduced from 15 to keep Basic usernames in Poor range
-                feedback.push("Short username - consider adding length for better security".to_string());
-            } else {
-                uniqueness_score += 15;
-            }
+
+        let remaining = collect_saved_files(dir.path()).unwrap();
+        assert_eq!(remaining.len(), max_files as usize);
+        assert!(dir.path().join("notes.md").exists());
+        assert_eq!(written.iter().filter(|path| path.exists()).count(), max_files as usize);
+        for info in &remaining {
+            assert!(info.size_bytes > 0);
+            assert!(chrono::DateTime::parse_from_rfc3339(&info.created_at).is_ok());
         }
     }
-    
-    // Check for common words/brands - but be more lenient for generated usernames
-    if !is_generated {
-        let common_words = [
-            "admin", "administrator", "user", "guest", "test", "demo", "example",
-            "facebook", "google", "apple", "microsoft", "amazon", "twitter", "instagram",
-            "skype", "discord", "telegram", "whatsapp", "youtube", "netflix", "spotify",
-            "password", "login", "email", "mail", "contact", "support", "help",
-            "john", "jane", "mike", "sarah", "alex", "chris", "david", "mary",
-            "2023", "2024", "abc", "qwerty", "asdf"
+
+    #[test]
+    fn test_write_credential_json() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let records = vec![
+            CredentialExportRecord {
+                value: "Xk9#mQ2$vL7@pR4!".to_string(),
+                settings: CredentialSettings::Password(PasswordGeneratorRequest {
+                    length: 16,
+                    special: true,
+                    ..Default::default()
+                }),
+            },
+            CredentialExportRecord {
+                value: "correct horse battery staple".to_string(),
+                settings: CredentialSettings::Passphrase(PassphraseGeneratorRequest {
+                    num_words: 4,
+                    ..Default::default()
+                }),
+            },
+            CredentialExportRecord {
+                value: "QuietRaven47".to_string(),
+                settings: CredentialSettings::Username(UsernameGeneratorRequest::Word {
+                    capitalize: true,
+                    number_suffix: generators::username::NumberSuffix::FourDigits,
+                    number_position: generators::username::NumberPosition::Suffix,
+                    strength: generators::username::UsernameStrength::Standard,
+                    format: generators::username::UsernameFormat::Plain,
+                }),
+            },
         ];
-        
-        let mut contains_common_word = false;
-        
-        for word in &common_words {
-            if username_lower.contains(word) {
-                contains_common_word = true;
-                feedback.push(format!("Contains common word '{}' - reduces privacy", word));
-                break;
-            }
-        }
-        
-        if contains_common_word {
-            privacy_score = privacy_score.saturating_sub(30);
-            uniqueness_score = uniqueness_score.saturating_sub(25);
-        } else {
-            // Apply length-based bonus scaling for consistency
-            if length <= 4 {
-                // Basic usernames get reduced bonus to stay in Poor category
-                privacy_score += 20; // Reduced from 25
-                uniqueness_score += 15; // Reduced from 20
-            } else {
-                privacy_score += 25;
-                uniqueness_score += 20;
-            }
+
+        for (record, kind) in records.into_iter().zip(["password", "passphrase", "username"]) {
+            let expected_value = record.value.clone();
+            let file_path = write_credential_json(dir.path(), None, record).unwrap();
+            assert!(file_path.to_string_lossy().ends_with(&format!("-{}.json", kind)));
+
+            let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(&file_path).unwrap()).unwrap();
+            assert_eq!(json["value"], expected_value.as_str());
+            assert_eq!(json["type"], kind);
+            assert!(chrono::DateTime::parse_from_rfc3339(json["created_at"].as_str().unwrap()).is_ok());
+            let score = json["strength_score"].as_u64().unwrap();
+            assert!(score <= 100);
+            assert!(json["generator_settings"].is_object(), "generator_settings should be populated");
+
+            // The file should round trip back into an export
+            let export: CredentialExport = serde_json::from_value(json).unwrap();
+            assert_eq!(export.settings.kind(), kind);
         }
-    } else {
-        // For generated usernames, give a privacy bonus since they're designed to be secure
-        privacy_score += 25;
-        uniqueness_score += 20;
+
+        // Credential files are listed and rotated with the other saved files
+        assert_eq!(collect_saved_files(dir.path()).unwrap().len(), 3);
     }
-    
-    // Personal information patterns
-    if contains_personal_info_patterns(&username_lower) {
-        feedback.push("May contain personal information patterns".to_string());
-        privacy_score = privacy_score.saturating_sub(25);
-    } else {
-        // Apply length-based bonus scaling
-        if length <= 4 {
-            // Basic usernames get reduced bonus to maintain Poor rating
-            privacy_score += 10; // Reduced from 15
-        } else {
-            privacy_score += 15;
+
+    #[test]
+    fn test_bitwarden_export_format() {
+        let credentials = vec![
+            CredentialRecord {
+                name: "Example".to_string(),
+                username: Some("quiet.river@example.com".to_string()),
+                password: Some("correct-horse-battery".to_string()),
+                uris: vec!["https://example.com/login".to_string()],
+                notes: Some("Generated by SecureGen".to_string()),
+            },
+            CredentialRecord {
+                name: "Password only".to_string(),
+                username: None,
+                password: Some("Tr0ub4dor&3".to_string()),
+                uris: Vec::new(),
+                notes: None,
+            },
+        ];
+        let json: serde_json::Value = serde_json::to_value(BitwardenExport::new(credentials).unwrap()).unwrap();
+
+        let keys = |value: &serde_json::Value| {
+            let mut keys: Vec<String> = value.as_object().unwrap().keys().cloned().collect();
+            keys.sort();
+            keys
+        };
+        assert_eq!(keys(&json), ["encrypted", "folders", "items"]);
+        assert_eq!(json["encrypted"], false);
+        assert_eq!(json["folders"], serde_json::json!([]));
+
+        let items = json["items"].as_array().unwrap();
+        assert_eq!(items.len(), 2);
+        for item in items {
+            assert_eq!(
+                keys(item),
+                [
+                    "collectionIds", "favorite", "folderId", "id", "login", "name", "notes", "organizationId",
+                    "reprompt", "type",
+                ]
+            );
+            assert_eq!(item["type"], 1);
+            assert_eq!(item["reprompt"], 0);
+            assert!(item["folderId"].is_null() && item["organizationId"].is_null() && item["collectionIds"].is_null());
+            assert!(::uuid::Uuid::parse_str(item["id"].as_str().unwrap()).is_ok());
+            assert_eq!(keys(&item["login"]), ["password", "totp", "uris", "username"]);
+            assert!(item["login"]["totp"].is_null());
         }
+        assert_ne!(items[0]["id"], items[1]["id"]);
+
+        assert_eq!(items[0]["name"], "Example");
+        assert_eq!(items[0]["notes"], "Generated by SecureGen");
+        assert_eq!(items[0]["login"]["username"], "quiet.river@example.com");
+        assert_eq!(items[0]["login"]["password"], "correct-horse-battery");
+        assert_eq!(
+            items[0]["login"]["uris"],
+            serde_json::json!([{ "match": null, "uri": "https://example.com/login" }])
+        );
+        assert!(items[1]["login"]["username"].is_null());
+        assert!(items[1]["notes"].is_null());
+        assert_eq!(items[1]["login"]["uris"], serde_json::json!([]));
+
+        let unnamed = CredentialRecord {
+            name: " ".to_string(),
+            username: None,
+            password: None,
+            uris: Vec::new(),
+            notes: None,
+        };
+        assert!(BitwardenExport::new(vec![unnamed]).is_err());
     }
-    
-    // Complexity evaluation (less important for usernames than passwords)
-    let has_numbers = username.chars().any(|c| c.is_numeric());
-    let has_letters = username.chars().any(|c| c.is_alphabetic());
-    let has_special = username.chars().any(|c| !c.is_alphanumeric());
-    
-    if has_letters && has_numbers {
-        uniqueness_score += 10;
-        feedback.push("Good mix of letters and numbers".to_string());
+
+    #[test]
+    fn test_write_bitwarden_export() {
+        let dir = tempfile::tempdir().unwrap();
+        let credential = CredentialRecord {
+            name: "Example".to_string(),
+            username: None,
+            password: Some("correct-horse".to_string()),
+            uris: Vec::new(),
+            notes: None,
+        };
+
+        let file_path = write_bitwarden_export(dir.path(), None, vec![credential]).unwrap();
+        assert!(file_path.to_string_lossy().ends_with("-bitwarden.json"));
+        let export: BitwardenExport = serde_json::from_str(&fs::read_to_string(file_path).unwrap()).unwrap();
+        assert_eq!(export.items[0].login.password.as_deref(), Some("correct-horse"));
     }
-    
-    if has_special {
-        uniqueness_score += 5;
-        feedback.push("Special characters add uniqueness".to_string());
+
+    /// The Title/UserName/Password/URL/Notes strings and creation time of every entry
+    fn parse_keepass_entries(xml: &str) -> Vec<(HashMap<String, String>, String)> {
+        use quick_xml::events::Event;
+
+        let mut reader = quick_xml::Reader::from_str(xml);
+        let mut path: Vec<String> = Vec::new();
+        let mut entries = Vec::new();
+        let (mut key, mut value, mut created) = (String::new(), String::new(), String::new());
+        loop {
+            match reader.read_event().unwrap() {
+                Event::Start(start) => {
+                    let name = String::from_utf8(start.name().as_ref().to_vec()).unwrap();
+                    if name == "Entry" {
+                        entries.push((HashMap::new(), String::new()));
+                    }
+                    path.push(name);
+                }
+                Event::Text(text) => {
+                    let text = text.unescape().unwrap().into_owned();
+                    match path.last().map(String::as_str) {
+                        Some("Key") => key = text,
+                        Some("Value") => value = text,
+                        Some("CreationTime") => created = text,
+                        _ => {}
+                    }
+                }
+                Event::End(_) => match path.pop().as_deref() {
+                    Some("String") if path.last().map(String::as_str) == Some("Entry") => {
+                        let (strings, _) = entries.last_mut().unwrap();
+                        strings.insert(std::mem::take(&mut key), std::mem::take(&mut value));
+                    }
+                    Some("Times") => entries.last_mut().unwrap().1 = std::mem::take(&mut created),
+                    _ => {}
+                },
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        entries
     }
-    
-    // Sequential patterns - be more lenient for generated usernames with intentional sequences
-    if !is_generated && contains_sequential_patterns(&username_lower) {
-        feedback.push("Contains sequential patterns - less secure".to_string());
-        uniqueness_score = uniqueness_score.saturating_sub(15);
+
+    #[test]
+    fn test_keepass_xml_round_trip() {
+        let credentials = vec![
+            CredentialRecord {
+                name: "Example <Work>".to_string(),
+                username: Some("quiet.river@example.com".to_string()),
+                password: Some("a<b&c>\"d'e".to_string()),
+                uris: vec!["https://example.com/login?a=1&b=2".to_string(), "https://example.org".to_string()],
+                notes: Some("Generated by SecureGen\nsecond line".to_string()),
+            },
+            CredentialRecord {
+                name: "Password only".to_string(),
+                username: None,
+                password: Some("Tr0ub4dor&3".to_string()),
+                uris: Vec::new(),
+                notes: None,
+            },
+        ];
+        let xml = build_keepass_xml(&credentials, "Personal & Work").unwrap();
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"utf-8\" standalone=\"yes\"?>"));
+        assert!(xml.contains("<DatabaseName>Personal &amp; Work</DatabaseName>"));
+        assert!(xml.contains("<Name>Personal &amp; Work</Name>"));
+        assert!(xml.contains("<Value ProtectInMemory=\"True\">"));
+        assert!(check_well_formed_xml(&xml).is_ok());
+
+        let entries = parse_keepass_entries(&xml);
+        assert_eq!(entries.len(), 2);
+        for ((strings, created), credential) in entries.iter().zip(&credentials) {
+            assert_eq!(strings["Title"], credential.name);
+            assert_eq!(strings["UserName"], credential.username.clone().unwrap_or_default());
+            assert_eq!(strings["Password"], credential.password.clone().unwrap_or_default());
+            assert_eq!(strings["URL"], credential.uris.first().cloned().unwrap_or_default());
+            assert_eq!(strings["Notes"], credential.notes.clone().unwrap_or_default());
+            assert!(chrono::DateTime::parse_from_rfc3339(created).is_ok(), "bad creation time {}", created);
+        }
+
+        let unnamed = CredentialRecord {
+            name: "".to_string(),
+            username: None,
+            password: None,
+            uris: Vec::new(),
+            notes: None,
+        };
+        assert!(build_keepass_xml(&[unnamed], "Personal").is_err());
+        assert!(build_keepass_xml(&credentials, " ").is_err());
+
+        let mut control = credentials[1].clone();
+        control.password = Some("bell\u{7}".to_string());
+        assert!(build_keepass_xml(&[control], "Personal").is_err());
     }
-    
-    // Boost score for patterns that indicate strong generation
-    if is_generated {
-        privacy_score += 15; // Increased bonus for generated usernames
-        uniqueness_score += 15;
-        feedback.push("Appears to be securely generated".to_string());
+
+    #[test]
+    fn test_check_well_formed_xml() {
+        assert!(check_well_formed_xml("<a><b>text</b></a>").is_ok());
+        assert!(check_well_formed_xml("<a><b>text</a>").is_err());
+        assert!(check_well_formed_xml("<a><b>text</b>").is_err());
     }
-    
-    // Calculate overall score (weighted average)
-    let overall_score = ((privacy_score as f32 * 0.6) + (uniqueness_score as f32 * 0.4)) as u8;
-    let mut clamped_score = overall_score.min(100);
-    
-    // Hard cap for very short usernames - they should never exceed "Very Poor" regardless of other factors
-    // 3-4 character usernames are fundamentally insecure due to small namespace and easy guessing
-    if length <= 4 {
-        clamped_score = clamped_score.min(25);  // Cap at 25 to ensure "Very Poor" rating
-        if !feedback.iter().any(|f| f.contains("too short") || f.contains("Short")) {
-            feedback.insert(0, "Very short username - highly vulnerable to guessing attacks".to_string());
-        }
+
+    #[test]
+    fn test_write_keepass_export() {
+        let dir = tempfile::tempdir().unwrap();
+        let credential = CredentialRecord {
+            name: "Example".to_string(),
+            username: None,
+            password: Some("correct-horse".to_string()),
+            uris: Vec::new(),
+            notes: None,
+        };
+
+        let file_path = write_keepass_export(dir.path(), None, &[credential], "Personal").unwrap();
+        assert!(file_path.to_string_lossy().ends_with("-keepass.xml"));
+        let entries = parse_keepass_entries(&fs::read_to_string(file_path).unwrap());
+        assert_eq!(entries[0].0["Password"], "correct-horse");
     }
-    
-    // Add recommendations based on score
-    if clamped_score < 30 {
-        feedback.push("Consider using a more unique username".to_string());
-        feedback.push("Avoid common words and personal information".to_string());
-    } else if clamped_score < 60 {
-        feedback.push("Username security could be improved".to_string());
-        feedback.push("Consider adding numbers or making it more unique".to_string());
-    } else if clamped_score < 80 {
-        feedback.push("Good username security".to_string());
-    } else {
-        feedback.push("Excellent username security".to_string());
+
+    /// The parsed `export.attributes` and `export.data` of a 1PUX archive
+    fn read_1pux_archive(archive: &[u8]) -> (serde_json::Value, serde_json::Value) {
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(archive)).unwrap();
+        let mut names: Vec<&str> = archive.file_names().collect();
+        names.sort();
+        assert_eq!(names, ["export.attributes", "export.data", "files/"]);
+
+        let mut read_json = |name: &str| -> serde_json::Value {
+            serde_json::from_reader(archive.by_name(name).unwrap()).unwrap()
+        };
+        (read_json("export.attributes"), read_json("export.data"))
     }
-    
-    let security_level = match clamped_score {
-        0..=25 => "Very Poor - High Risk",
-        26..=45 => "Poor - Easily Guessable",
-        46..=65 => "Fair - Some Privacy Concerns",
-        66..=80 => "Good - Reasonably Secure",
-        81..=100 => "Excellent - Highly Secure",
-        _ => "Excellent - Highly Secure", // Fallback for any edge cases
-    };
-    
-    UsernameStrength {
-        score: clamped_score,
-        security_level: security_level.to_string(),
-        feedback,
-        privacy_score,
-        uniqueness_score,
+
+    #[test]
+    fn test_1password_export_format() {
+        let credentials = vec![
+            CredentialRecord {
+                name: "Example".to_string(),
+                username: Some("quiet.river@example.com".to_string()),
+                password: Some("correct-horse-battery".to_string()),
+                uris: vec!["https://example.com/login".to_string()],
+                notes: Some("Generated by SecureGen".to_string()),
+            },
+            CredentialRecord {
+                name: "Password only".to_string(),
+                username: None,
+                password: Some("Tr0ub4dor&3".to_string()),
+                uris: Vec::new(),
+                notes: None,
+            },
+        ];
+        let (attributes, data) = read_1pux_archive(&build_1pux_archive(credentials).unwrap());
+
+        assert_eq!(attributes["version"], ONEPUX_FORMAT_VERSION);
+        assert!(attributes["createdAt"].as_i64().unwrap() > 0);
+
+        let accounts = data["accounts"].as_array().unwrap();
+        assert_eq!(accounts.len(), 1);
+        let vaults = accounts[0]["vaults"].as_array().unwrap();
+        assert_eq!(vaults.len(), 1);
+        assert_eq!(vaults[0]["attrs"]["type"], "P");
+        let items = vaults[0]["items"].as_array().unwrap();
+        assert_eq!(items.len(), 2);
+        for item in items {
+            assert_eq!(item["categoryUuid"], ONEPASSWORD_LOGIN_CATEGORY);
+            assert_eq!(item["state"], "active");
+            assert_eq!(item["createdAt"], attributes["createdAt"]);
+            assert_eq!(item["uuid"].as_str().unwrap().len(), 32);
+        }
+        assert_ne!(items[0]["uuid"], items[1]["uuid"]);
+
+        let login_field = |item: &serde_json::Value, designation: &str| {
+            item["details"]["loginFields"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|field| field["designation"] == designation)
+                .cloned()
+        };
+        let username = login_field(&items[0], "username").unwrap();
+        assert_eq!(username["value"], "quiet.river@example.com");
+        assert_eq!(username["fieldType"], "T");
+        let password = login_field(&items[0], "password").unwrap();
+        assert_eq!(password["value"], "correct-horse-battery");
+        assert_eq!(password["fieldType"], "P");
+        assert_eq!(items[0]["overview"]["title"], "Example");
+        assert_eq!(items[0]["overview"]["url"], "https://example.com/login");
+        assert_eq!(items[0]["details"]["notesPlain"], "Generated by SecureGen");
+
+        assert!(login_field(&items[1], "username").is_none());
+        assert_eq!(login_field(&items[1], "password").unwrap()["value"], "Tr0ub4dor&3");
+        assert_eq!(items[1]["overview"]["urls"], serde_json::json!([]));
+
+        let unnamed = CredentialRecord {
+            name: "".to_string(),
+            username: None,
+            password: None,
+            uris: Vec::new(),
+            notes: None,
+        };
+        assert!(build_1pux_archive(vec![unnamed]).is_err());
     }
-}
 
-#[derive(Debug, PartialEq)]
-enum DictionaryUsage {
-    SecureEFFWord { word_length: usize },
-    WeakDictionary,
-    CompoundWords,
-    NonDictionary,
-}
+    #[test]
+    fn test_write_1password_export() {
+        let dir = tempfile::tempdir().unwrap();
+        let credential = CredentialRecord {
+            name: "Example".to_string(),
+            username: None,
+            password: Some("correct-horse".to_string()),
+            uris: Vec::new(),
+            notes: None,
+        };
 
-fn analyze_dictionary_usage(username: &str) -> DictionaryUsage {
-    use crate::generators::wordlist::EFF_LONG_WORD_LIST;
-    
-    let username_lower = username.to_lowercase();
-    
-    // First check if it's a pure EFF word (these are cryptographically secure)
-    if EFF_LONG_WORD_LIST.contains(&username_lower.as_str()) {
-        return DictionaryUsage::SecureEFFWord { 
-            word_length: username_lower.len() 
+        let file_path = write_1password_export(dir.path(), None, vec![credential]).unwrap();
+        assert!(file_path.to_string_lossy().ends_with("-1password.1pux"));
+        let (_, data) = read_1pux_archive(&fs::read(file_path).unwrap());
+        let item = &data["accounts"][0]["vaults"][0]["items"][0];
+        assert_eq!(item["details"]["loginFields"][0]["value"], "correct-horse");
+    }
+
+    #[test]
+    fn test_credential_export_redacts_forwarder_tokens() {
+        let record = CredentialExportRecord {
+            value: "alias@duck.com".to_string(),
+            settings: CredentialSettings::Username(UsernameGeneratorRequest::Forwarded {
+                service: generators::username::ForwarderServiceType::DuckDuckGo {
+                    token: "super-secret".to_string(),
+                },
+                website: None,
+            }),
         };
+
+        let export = CredentialExport::new(record).unwrap();
+        assert!(!serde_json::to_string(&export).unwrap().contains("super-secret"));
     }
-    
-    // Extract base word by removing numbers from the end
-    let base_word = username_lower.trim_end_matches(|c: char| c.is_numeric());
-    let has_numbers = base_word != username_lower;
-    
-    if has_numbers && base_word.len() >= 3 {
-        // Check if base word is a pure EFF word
-        if EFF_LONG_WORD_LIST.contains(&base_word) {
-            return DictionaryUsage::SecureEFFWord { 
-                word_length: base_word.len() 
-            };
+
+    #[tokio::test]
+    async fn test_stream_passwords_emits_one_event_per_value() {
+        let streams = GenerationStreams::default();
+        let (_, cancelled) = streams.start("main");
+        let mut events = Vec::new();
+
+        let generated = stream_passwords(PasswordGeneratorRequest::default(), 150, cancelled, |event| events.push(event))
+            .await
+            .unwrap();
+
+        assert_eq!(generated, 150);
+        assert_eq!(events.len(), 150);
+        for (i, event) in events.iter().enumerate() {
+            assert_eq!(event.index as usize, i);
+            assert_eq!(event.total, 150);
+            assert_eq!(event.value.len(), PasswordGeneratorRequest::default().length as usize);
         }
-        
-        // Check if base word is compound EFF words
-        if is_compound_eff_word(&base_word) {
-            return DictionaryUsage::SecureEFFWord { 
-                word_length: base_word.len() 
-            };
+    }
+
+    #[tokio::test]
+    async fn test_stream_passwords_cancellation() {
+        let streams = GenerationStreams::default();
+        let (_, cancelled) = streams.start("main");
+        let mut events = 0;
+
+        let generated = stream_passwords(PasswordGeneratorRequest::default(), 100, cancelled, |_| {
+            events += 1;
+            if events == 10 {
+                assert!(streams.cancel("main"));
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(generated, 10);
+        assert_eq!(events, 10);
+        assert!(!streams.cancel("main"), "Cancelled streams should be removed");
+    }
+
+    #[test]
+    fn test_generation_streams_restart_cancels_previous() {
+        let streams = GenerationStreams::default();
+        let (first_id, first) = streams.start("main");
+        let (second_id, second) = streams.start("main");
+
+        assert!(*first.borrow());
+        assert!(!*second.borrow());
+
+        // The first stream finishing must not remove the second stream's channel
+        streams.finish("main", first_id);
+        assert!(streams.cancel("main"));
+        assert!(*second.borrow());
+        streams.finish("main", second_id);
+    }
+
+    fn slow_forwarder_request(server: &wiremock::MockServer) -> UsernameGeneratorRequest {
+        UsernameGeneratorRequest::Forwarded {
+            service: generators::username::ForwarderServiceType::SimpleLogin {
+                api_key: "test-key".to_string(),
+                base_url: server.uri(),
+                note: None,
+                directory: None,
+            },
+            website: None,
         }
     }
-    
-    // Check for compound EFF words (like from our generator)
-    if is_compound_eff_word(&username_lower) {
-        return DictionaryUsage::SecureEFFWord { 
-            word_length: username_lower.len() 
-        };
+
+    async fn start_slow_forwarder() -> wiremock::MockServer {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(
+                ResponseTemplate::new(201)
+                    .set_body_json(serde_json::json!({ "alias": "slow@simplelogin.co" }))
+                    .set_delay(std::time::Duration::from_secs(5)),
+            )
+            .mount(&server)
+            .await;
+        server
     }
-    
-    // Check for compound words (multiple dictionary words)
-    if is_compound_word(&username_lower) {
-        return DictionaryUsage::CompoundWords;
+
+    #[tokio::test]
+    async fn test_username_generation_cancelled_promptly() {
+        let server = start_slow_forwarder().await;
+        let cancellation = std::sync::Arc::new(UsernameCancellation::default());
+        let token = cancellation.token();
+
+        let canceller = cancellation.clone();
+        let cancelled_at = tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+            canceller.cancel();
+            std::time::Instant::now()
+        });
+
+        let client = reqwest::Client::new();
+        let result = generate_username_cancellable(slow_forwarder_request(&server), &client, token).await;
+        let finished_at = std::time::Instant::now();
+        let cancelled_at = cancelled_at.await.unwrap();
+
+        let error = result.unwrap_err();
+        assert_eq!(error.code, generators::username::UsernameErrorCode::Cancelled as u16);
+        assert_eq!(error.message, "Generation cancelled");
+        assert!(
+            finished_at.duration_since(cancelled_at) < std::time::Duration::from_millis(100),
+            "Generation should stop within 100ms of cancellation"
+        );
     }
-    
-    // Check for weak dictionary patterns
-    if is_weak_dictionary_pattern(&username_lower) {
-        return DictionaryUsage::WeakDictionary;
+
+    #[tokio::test]
+    async fn test_username_cancellation_token_reset() {
+        let cancellation = UsernameCancellation::default();
+
+        // Cancelling while nothing is running doesn't affect the next attempt
+        cancellation.cancel();
+        assert!(!cancellation.token().is_cancelled());
+
+        let token = cancellation.token();
+        cancellation.cancel();
+        assert!(token.is_cancelled());
+        cancellation.reset();
+        assert!(!cancellation.token().is_cancelled());
     }
-    
-    DictionaryUsage::NonDictionary
-}
 
-fn is_compound_eff_word(word: &str) -> bool {
-    use crate::generators::wordlist::EFF_LONG_WORD_LIST;
-    
-    // Only check reasonable length words to avoid performance issues
-    if word.len() < 6 || word.len() > 20 {
-        return false;
+    #[tokio::test]
+    async fn test_generate_username_command_cancellation() {
+        let server = start_slow_forwarder().await;
+        let app = mock_app();
+        let handle = app.handle();
+
+        let canceller = app.handle();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+            cancel_username_generation(canceller.state()).await.unwrap();
+        });
+
+        let result = generate_username(slow_forwarder_request(&server), None, None, handle.state(), handle.state(), handle.state(), handle.state(), handle.state()).await;
+        let error = result.unwrap_err();
+        assert_eq!(error.code, generators::username::UsernameErrorCode::Cancelled as u16);
+        assert_eq!(error.message, "Generation cancelled");
+        assert_eq!(app.state::<LastGeneratedState>().get(), LastGenerated::default());
+        assert!(!app.state::<UsernameCancellation>().token().is_cancelled());
     }
-    
-    // Try splitting the word at different positions
-    for i in 3..=(word.len() - 3) {
-        let (first_part, second_part) = word.split_at(i);
-        
-        // Both parts must be at least 3 characters and in the EFF list
-        if first_part.len() >= 3 && second_part.len() >= 3 {
-            if EFF_LONG_WORD_LIST.contains(&first_part) && EFF_LONG_WORD_LIST.contains(&second_part) {
-                return true;
-            }
-        }
+
+    #[tokio::test]
+    async fn test_forwarded_username_times_out() {
+        let server = start_slow_forwarder().await;
+        let app = mock_app();
+        let handle = app.handle();
+
+        let started = std::time::Instant::now();
+        let result =
+            generate_username(slow_forwarder_request(&server), Some(1), None, handle.state(), handle.state(), handle.state(), handle.state(), handle.state())
+                .await;
+        let elapsed = started.elapsed();
+
+        let error = result.unwrap_err();
+        assert_eq!(error.code, UsernameErrorCode::Timeout as u16);
+        assert_eq!(error.message, "Username generation timed out after 1 seconds");
+        assert!(elapsed < std::time::Duration::from_secs(2), "Timed out after {:?}", elapsed);
+        assert_eq!(app.state::<LastGeneratedState>().get(), LastGenerated::default());
     }
-    
-    false
-}
 
-fn is_weak_dictionary_pattern(word: &str) -> bool {
-    // Check for common dictionary word variations that are NOT in EFF list
-    if is_word_variation(word) || is_word_with_common_suffix(word) {
-        return true;
+    #[tokio::test]
+    async fn test_username_timeout_helper() {
+        let server = start_slow_forwarder().await;
+        let client = reqwest::Client::new();
+
+        let started = std::time::Instant::now();
+        let error = generate_username_with_timeout(
+            slow_forwarder_request(&server),
+            &client,
+            CancellationToken::new(),
+            std::time::Duration::from_millis(200),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error.code, UsernameErrorCode::Timeout as u16);
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+
+        // Requests that don't call a forwarder aren't timed out
+        let request = UsernameGeneratorRequest::Catchall {
+            r#type: AppendType::Random,
+            domain: "example.com".to_string(),
+            original_domain: None,
+        };
+        let username = generate_username_with_timeout(request, &client, CancellationToken::new(), std::time::Duration::ZERO)
+            .await
+            .unwrap();
+        assert!(username.ends_with("@example.com"));
     }
-    
-    // Check for common weak patterns
-    let weak_patterns = [
-        "password", "username", "account", "profile", "login",
-        "admin", "user", "guest", "test", "demo", "temp"
-    ];
-    
-    for pattern in &weak_patterns {
-        if word.contains(pattern) {
-            return true;
+
+    #[tokio::test]
+    async fn test_username_timeout_bounds() {
+        let app = mock_app();
+        let handle = app.handle();
+        let request = || UsernameGeneratorRequest::CompoundWord {
+            word_count: 2,
+            strength: generators::username::UsernameStrength::Standard,
+            format: Default::default(),
+        };
+
+        for timeout_seconds in [0, MAX_USERNAME_TIMEOUT_SECONDS + 1] {
+            let error = generate_username(request(), Some(timeout_seconds), None, handle.state(), handle.state(), handle.state(), handle.state(), handle.state())
+                .await
+                .unwrap_err();
+            assert_eq!(error.message, "Timeout must be between 1 and 120 seconds");
         }
+        generate_username(request(), Some(MAX_USERNAME_TIMEOUT_SECONDS), None, handle.state(), handle.state(), handle.state(), handle.state(), handle.state())
+            .await
+            .unwrap();
     }
-    
-    false
-}
 
-fn is_likely_generated_username(username: &str) -> bool {
-    use crate::generators::wordlist::EFF_LONG_WORD_LIST;
-    
-    let username_lower = username.to_lowercase();
-    
-    // Pattern 1: EFF word + numbers (our generator pattern)
-    let base_word = username_lower.trim_end_matches(|c: char| c.is_numeric());
-    if base_word.len() >= 3 && base_word != username_lower {
-        if EFF_LONG_WORD_LIST.contains(&base_word) {
-            let number_part = &username_lower[base_word.len()..];
-            // Check if it's exactly 4 digits (our generator uses 4-digit numbers)
-            if number_part.len() == 4 && number_part.chars().all(|c| c.is_numeric()) {
-                return true;
-            }
-        }
+    fn read_window_state_file(dir: &Path) -> serde_json::Value {
+        serde_json::from_str(&fs::read_to_string(dir.join(WINDOW_STATE_FILE)).unwrap()).unwrap()
     }
-    
-    // Pattern 2: Single EFF word with good length (7+ chars indicates Strong/Maximum generation)
-    if username_lower.len() >= 7 && EFF_LONG_WORD_LIST.contains(&username_lower.as_str()) {
-        return true;
+
+    #[test]
+    fn test_window_state_written_on_move_and_resize() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let initial = WindowState {
+            x: 0,
+            y: 0,
+            width: 1200,
+            height: 900,
+        };
+        let store = WindowStateStore::new(initial, Some(data_dir.path().join("nested")));
+
+        store.record_move(tauri::PhysicalPosition::new(-300, 120));
+        assert_eq!(
+            read_window_state_file(&data_dir.path().join("nested")),
+            serde_json::json!({ "x": -300, "y": 120, "width": 1200, "height": 900 })
+        );
+
+        store.record_resize(tauri::PhysicalSize::new(1400, 1000));
+        // Minimized windows report a 0x0 size
+        store.record_resize(tauri::PhysicalSize::new(0, 0));
+        assert_eq!(
+            read_window_state_file(&data_dir.path().join("nested")),
+            serde_json::json!({ "x": -300, "y": 120, "width": 1400, "height": 1000 })
+        );
     }
-    
-    // Pattern 3: Two EFF words combined (compound generation)
-    if is_compound_eff_word(&username_lower) {
-        return true;
+
+    #[test]
+    fn test_window_state_load() {
+        let data_dir = tempfile::tempdir().unwrap();
+        assert_eq!(WindowState::load(data_dir.path()), None);
+
+        let state = WindowState {
+            x: 1920,
+            y: 40,
+            width: 1200,
+            height: 900,
+        };
+        state.store(data_dir.path()).unwrap();
+        assert_eq!(WindowState::load(data_dir.path()), Some(state));
+
+        fs::write(data_dir.path().join(WINDOW_STATE_FILE), r#"{"x": 10}"#).unwrap();
+        assert_eq!(WindowState::load(data_dir.path()), None);
     }
-    
-    false
-}
 
-fn is_word_variation(word: &str) -> bool {
-    use crate::generators::wordlist::EFF_LONG_WORD_LIST;
-    
-    // Check common variations: plurals, past tense, etc.
-    let variations = [
-        // Remove common suffixes to find root word
-        word.strip_suffix("s"),
-        word.strip_suffix("es"),
-        word.strip_suffix("ed"),
-        word.strip_suffix("ing"),
-        word.strip_suffix("er"),
-        word.strip_suffix("est"),
-        word.strip_suffix("ly"),
-        word.strip_suffix("tion"),
-        word.strip_suffix("sion"),
-        word.strip_suffix("ness"),
-        word.strip_suffix("ment"),
-        word.strip_suffix("able"),
-        word.strip_suffix("ible"),
-    ];
-    
-    for variation in variations.iter().flatten() {
-        if variation.len() >= 3 && EFF_LONG_WORD_LIST.contains(variation) {
-            return true;
-        }
+    #[test]
+    fn test_window_state_on_screen() {
+        let monitors = [
+            (tauri::PhysicalPosition::new(0, 0), tauri::PhysicalSize::new(1920, 1080)),
+            // A second monitor to the left of the primary one
+            (tauri::PhysicalPosition::new(-2560, -200), tauri::PhysicalSize::new(2560, 1440)),
+        ];
+        let at = |x, y| WindowState {
+            x,
+            y,
+            width: 1200,
+            height: 900,
+        };
+
+        assert!(at(100, 100).is_on_screen(&monitors));
+        assert!(at(-1000, -150).is_on_screen(&monitors));
+        assert!(at(1919, 1079).is_on_screen(&monitors));
+        assert!(!at(1920, 100).is_on_screen(&monitors));
+        assert!(!at(-1000, 1300).is_on_screen(&monitors));
+        assert!(!at(100, 100).is_on_screen(&[]));
     }
-    
-    // Check if adding common prefixes creates a dictionary word
-    let prefixes = ["un", "re", "pre", "dis", "mis", "over", "under", "out"];
-    for prefix in &prefixes {
-        if word.starts_with(prefix) {
-            let root = &word[prefix.len()..];
-            if root.len() >= 3 && EFF_LONG_WORD_LIST.contains(&root) {
-                return true;
-            }
-        }
+
+    #[test]
+    fn test_restore_window_state_off_screen() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let app = mock_app();
+        let window = app.get_window("main").unwrap();
+        let saved = WindowState {
+            x: 5000,
+            y: 5000,
+            width: 1200,
+            height: 900,
+        };
+        saved.store(data_dir.path()).unwrap();
+
+        // The mock runtime has no monitors, so every saved position is off screen
+        let restored = restore_window_state(&window, Some(data_dir.path()));
+        assert_ne!(restored, saved);
+        assert_eq!(restored, restore_window_state(&window, None));
     }
-    
-    false
-}
 
-fn is_compound_word(word: &str) -> bool {
-    use crate::generators::wordlist::EFF_LONG_WORD_LIST;
-    
-    // Only check reasonable length words to avoid performance issues
-    if word.len() < 6 || word.len() > 20 {
-        return false;
+    #[test]
+    fn test_deep_link_scheme_configured() {
+        let config: tauri::Config = serde_json::from_str(include_str!("../tauri.conf.json")).unwrap();
+        assert_eq!(deep_link_schemes(&config), [deep_link::SCHEME]);
+        assert!(include_str!("../Info.plist").contains(&format!("<string>{}</string>", deep_link::SCHEME)));
     }
-    
-    // Try splitting the word at different positions
-    for i in 3..=(word.len() - 3) {
-        let (first_part, second_part) = word.split_at(i);
-        
-        // Both parts must be at least 3 characters and in the dictionary
-        if first_part.len() >= 3 && second_part.len() >= 3 {
-            if EFF_LONG_WORD_LIST.contains(&first_part) && EFF_LONG_WORD_LIST.contains(&second_part) {
-                return true;
-            }
+
+    /// Records the span path, from the root down, of every span created
+    #[derive(Clone, Default)]
+    struct SpanRecorder(std::sync::Arc<Mutex<Vec<Vec<&'static str>>>>);
+
+    impl<S> tracing_subscriber::Layer<S> for SpanRecorder
+    where
+        S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    {
+        fn on_new_span(
+            &self,
+            _attrs: &tracing::span::Attributes<'_>,
+            id: &tracing::span::Id,
+            ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            let span = ctx.span(id).unwrap();
+            let path = span.scope().from_root().map(|span| span.name()).collect();
+            self.0.lock().unwrap().push(path);
         }
     }
-    
-    false
-}
 
-fn is_word_with_common_suffix(word: &str) -> bool {
-    use crate::generators::wordlist::EFF_LONG_WORD_LIST;
-    
-    // Check for dictionary words with common numeric or simple suffixes
-    let numeric_suffixes = ["1", "2", "3", "4", "5", "6", "7", "8", "9", "0", 
-                           "12", "123", "01", "02", "03", "99", "00"];
-    
-    for suffix in &numeric_suffixes {
-        if word.ends_with(suffix) {
-            let root = &word[..word.len() - suffix.len()];
-            if root.len() >= 3 && EFF_LONG_WORD_LIST.contains(&root) {
-                return true;
-            }
-        }
+    #[tokio::test]
+    async fn test_generate_username_spans_nested() {
+        use tracing_subscriber::layer::SubscriberExt;
+        use wiremock::{matchers::method, Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({ "alias": "fast@simplelogin.co" })))
+            .mount(&server)
+            .await;
+        let app = mock_app();
+        let handle = app.handle();
+
+        let recorder = SpanRecorder::default();
+        let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(recorder.clone()));
+        let username =
+            generate_username(slow_forwarder_request(&server), None, None, handle.state(), handle.state(), handle.state(), handle.state(), handle.state())
+                .await
+                .unwrap();
+        assert_eq!(username, "fast@simplelogin.co");
+
+        // hyper records spans of its own, for header parsing and the like
+        let mut spans = recorder.0.lock().unwrap().clone();
+        spans.retain(|path| matches!(path.last(), Some(&("generate_username" | "generate" | "http_request"))));
+        assert_eq!(
+            spans,
+            [
+                vec!["generate_username"],
+                vec!["generate_username", "generate_username"],
+                vec!["generate_username", "generate_username", "generate"],
+                vec!["generate_username", "generate_username", "generate", "http_request"],
+            ]
+        );
     }
-    
-    // Check for words with year suffixes (common in usernames)
-    if word.len() >= 7 {
-        let potential_year_suffixes = ["1990", "1991", "1992", "1993", "1994", "1995", 
-                                     "1996", "1997", "1998", "1999", "2000", "2001", 
-                                     "2002", "2003", "2004", "2005", "2006", "2007", 
-                                     "2008", "2009", "2010", "2011", "2012", "2013", 
-                                     "2014", "2015", "2016", "2017", "2018", "2019", 
-                                     "2020", "2021", "2022", "2023", "2024"];
-        
-        for year in &potential_year_suffixes {
-            if word.ends_with(year) {
-                let root = &word[..word.len() - 4];
-                if root.len() >= 3 && EFF_LONG_WORD_LIST.contains(&root) {
-                    return true;
-                }
-            }
+
+    /// The schema version the field lists below were recorded for
+    const RECORDED_SCHEMA_VERSION: u16 = 7;
+
+    fn response_fields<T: JsonSchema>() -> Vec<String> {
+        let mut fields: Vec<String> = schemars::schema_for!(T)
+            .schema
+            .object
+            .map(|object| object.properties.keys().cloned().collect())
+            .unwrap_or_default();
+        fields.sort();
+        fields
+    }
+
+    #[test]
+    fn test_schema_version_matches_response_fields() {
+        // If this fails, a response struct changed shape: bump SCHEMA_VERSION, then update
+        // RECORDED_SCHEMA_VERSION and the field lists to match
+        let recorded: Vec<(&str, Vec<String>, &[&str])> = vec![
+            (
+                "PasswordStrength",
+                response_fields::<PasswordStrength>(),
+                &[
+                    "crack_times_display",
+                    "feedback",
+                    "guesses",
+                    "guesses_log10",
+                    "score",
+                    "weakest_word",
+                    "weakest_word_score",
+                ],
+            ),
+            (
+                "UsernameStrength",
+                response_fields::<UsernameStrength>(),
+                &["feedback", "privacy_score", "score", "security_level", "uniqueness_score"],
+            ),
+            (
+                "BcryptStrengthResult",
+                response_fields::<BcryptStrengthResult>(),
+                &["cost", "recommendation", "strength"],
+            ),
+            (
+                "IPResponse",
+                response_fields::<IPResponse>(),
+                &["country", "geo_lookup_available", "ip", "masked_ip", "region"],
+            ),
+            (
+                "SystemIdentityResponse",
+                response_fields::<SystemIdentityResponse>(),
+                &[
+                    "country",
+                    "cpu_architecture",
+                    "cpu_cores",
+                    "hardware_id",
+                    "hwid_confidence",
+                    "hwid_source",
+                    "ip_address",
+                    "masked_ip",
+                    "platform",
+                    "platform_version",
+                    "region",
+                    "total_memory_mb",
+                    "user_key",
+                ],
+            ),
+            (
+                "DerivedKey",
+                response_fields::<DerivedKey>(),
+                &["algorithm", "key_hex", "parameters", "salt_hex"],
+            ),
+            (
+                "FirefoxRelayQuota",
+                response_fields::<FirefoxRelayQuota>(),
+                &["aliases_remaining", "has_premium"],
+            ),
+            (
+                "DomainVerificationStatus",
+                response_fields::<DomainVerificationStatus>(),
+                &["errors", "mx_records_ok", "txt_records_ok", "verified"],
+            ),
+            (
+                "PassphraseResult",
+                response_fields::<PassphraseResult>(),
+                &["entropy_bits", "passphrase", "word_count", "wordlist_hash", "wordlist_version"],
+            ),
+            (
+                "EntropyEstimate",
+                response_fields::<EntropyEstimate>(),
+                &["crack_time_display", "entropy_bits", "equivalent_password_length", "wordlist_size"],
+            ),
+            (
+                "PasswordResult",
+                response_fields::<PasswordResult>(),
+                &["password", "warnings"],
+            ),
+            (
+                "PasswordSimilarityResult",
+                response_fields::<PasswordSimilarityResult>(),
+                &["changes", "is_too_similar", "similarity_score"],
+            ),
+            (
+                "StrengthComparison",
+                response_fields::<StrengthComparison>(),
+                &["difference_bits", "entropy_a", "entropy_b", "winner"],
+            ),
+        ];
+
+        for (name, actual, expected) in recorded {
+            assert_eq!(
+                actual, expected,
+                "{} fields changed, bump SCHEMA_VERSION and update this test",
+                name
+            );
         }
+        assert_eq!(SCHEMA_VERSION, RECORDED_SCHEMA_VERSION);
     }
-    
-    false
-}
 
-fn contains_personal_info_patterns(username: &str) -> bool {
-    // Check for birth year patterns
-    if username.contains("199") || username.contains("200") || username.contains("201") {
-        return true;
+    #[test]
+    fn test_api_response_flattens_data() {
+        let response = ApiResponse::new(BcryptStrengthResult {
+            cost: 12,
+            strength: "Strong".to_string(),
+            recommendation: "OK".to_string(),
+        });
+
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["schema_version"], SCHEMA_VERSION);
+        assert_eq!(json["cost"], 12);
+        assert_eq!(json["strength"], "Strong");
+
+        // Callers that ignore the wrapper can deserialize the data directly
+        let data: BcryptStrengthResult = serde_json::from_value(json).unwrap();
+        assert_eq!(data.cost, 12);
     }
-    
-    // Check for common age patterns
-    let age_patterns = ["18", "19", "20", "21", "22", "23", "24", "25", "30", "40", "50"];
-    for pattern in &age_patterns {
-        if username.ends_with(pattern) {
-            return true;
-        }
+
+    #[test]
+    fn test_get_api_version() {
+        let app = mock_app();
+        let version = ApiVersion::new(app.package_info().version.to_string());
+
+        assert_eq!(version.schema_version, SCHEMA_VERSION);
+        assert!(!version.app_version.is_empty());
+        assert!(version.rust_version.starts_with("rustc"));
     }
-    
-    // Check for common personal patterns
-    let personal_patterns = ["name", "real", "official", "personal", "my", "the"];
-    for pattern in &personal_patterns {
-        if username.contains(pattern) {
-            return true;
-        }
+
+    #[test]
+    fn test_legacy_config_rejects_oversized_length() {
+        let config = PasswordConfig {
+            length: 300,
+            include_uppercase: true,
+            include_lowercase: true,
+            include_numbers: true,
+            include_symbols: false,
+            exclude_similar: false,
+            exclude_ambiguous: false,
+            custom_exclusions: String::new(),
+        };
+
+        let error = PasswordGeneratorRequest::try_from(config).unwrap_err();
+        assert!(matches!(error, GeneratorError::InvalidInput(_)));
+        assert_eq!(error.to_string(), "Invalid input: Password length 300 is too long");
+    }
+
+    #[test]
+    fn test_legacy_config_keeps_custom_exclusions() {
+        let config = PasswordConfig {
+            length: 16,
+            include_uppercase: true,
+            include_lowercase: true,
+            include_numbers: true,
+            include_symbols: false,
+            exclude_similar: false,
+            exclude_ambiguous: false,
+            custom_exclusions: "xyz".to_string(),
+        };
+
+        let request = PasswordGeneratorRequest::try_from(config).unwrap();
+        assert_eq!(request.custom_exclusions, "xyz");
+    }
+
+    #[test]
+    fn test_list_network_interfaces() {
+        let interfaces = collect_network_interfaces(false).expect("Interface enumeration should succeed");
+
+        assert!(
+            interfaces.iter().any(|i| !i.is_loopback && !i.ip_addresses.is_empty()),
+            "Should find at least one non-loopback interface with an address, got: {:?}",
+            interfaces
+        );
+
+        // Every interface name should only be listed once
+        let mut names: Vec<&str> = interfaces.iter().map(|i| i.name.as_str()).collect();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), interfaces.len());
     }
-    
-    false
-}
 
-fn contains_sequential_patterns(username: &str) -> bool {
-    let sequential_patterns = ["123", "234", "345", "456", "567", "678", "789", "abc", "bcd", "cde"];
-    for pattern in &sequential_patterns {
-        if username.contains(pattern) {
-            return true;
+    #[test]
+    fn test_list_network_interfaces_masked() {
+        let interfaces = collect_network_interfaces(true).expect("Interface enumeration should succeed");
+
+        for ip in interfaces.iter().flat_map(|i| &i.ip_addresses) {
+            assert!(ip.contains("xxx"), "Address should be masked, got: {}", ip);
         }
     }
-    false
-}
-
-fn create_system_tray() -> SystemTray {
-    let show = CustomMenuItem::new("show".to_string(), "Show SecureGen");
-    let hide = CustomMenuItem::new("hide".to_string(), "Hide to Tray");
-    let generate_password = CustomMenuItem::new("generate_password".to_string(), "Generate Password");
-    let generate_passphrase = CustomMenuItem::new("generate_passphrase".to_string(), "Generate Passphrase");
-    let generate_username = CustomMenuItem::new("generate_username".to_string(), "Generate Username");
-    let quit = CustomMenuItem::new("quit".to_string(), "Quit");
-    
-    let tray_menu = SystemTrayMenu::new()
-        .add_item(show)
-        .add_native_item(SystemTrayMenuItem::Separator)
-        .add_item(generate_password)
-        .add_item(generate_passphrase)
-        .add_item(generate_username)
-        .add_native_item(SystemTrayMenuItem::Separator)
-        .add_item(hide)
-        .add_item(quit);
 
-    SystemTray::new().with_menu(tray_menu)
-}
+    /// Answers each command with recorded output, commands without a recording fail like a
+    /// missing binary would
+    struct RecordedCommandRunner(HashMap<&'static str, &'static str>);
 
-fn handle_system_tray_event(app: &tauri::AppHandle, event: SystemTrayEvent) {
-    match event {
-        SystemTrayEvent::LeftClick {
-            position: _,
-            size: _,
-            ..
-        } => {
-            // Left click shows/focuses the window
-            if let Some(window) = app.get_window("main") {
-                if window.is_visible().unwrap_or(false) {
-                    let _ = window.set_focus();
-                } else {
-                    let _ = window.show();
-                    let _ = window.set_focus();
-                }
-            }
-        }
-        SystemTrayEvent::RightClick {
-            position: _,
-            size: _,
-            ..
-        } => {
-            // Right click shows the context menu (handled automatically)
-        }
-        SystemTrayEvent::DoubleClick {
-            position: _,
-            size: _,
-            ..
-        } => {
-            // Double click shows the window
-            if let Some(window) = app.get_window("main") {
-                let _ = window.show();
-                let _ = window.set_focus();
-            }
-        }
-        SystemTrayEvent::MenuItemClick { id, .. } => {
-            match id.as_str() {
-                "show" => {
-                    if let Some(window) = app.get_window("main") {
-                        let _ = window.show();
-                        let _ = window.set_focus();
-                        let _ = window.unminimize();
-                    }
-                }
-                "hide" => {
-                    if let Some(window) = app.get_window("main") {
-                        let _ = window.hide();
-                    }
-                }
-                "generate_password" => {
-                    // Show window and trigger password generation
-                    if let Some(window) = app.get_window("main") {
-                        let _ = window.show();
-                        let _ = window.set_focus();
-                        // Emit an event to the frontend to generate a password
-                        let _ = window.emit("tray-generate-password", ());
-                    }
-                }
-                "generate_passphrase" => {
-                    // Show window and trigger passphrase generation
-                    if let Some(window) = app.get_window("main") {
-                        let _ = window.show();
-                        let _ = window.set_focus();
-                        // Emit an event to the frontend to generate a passphrase
-                        let _ = window.emit("tray-generate-passphrase", ());
-                    }
-                }
-                "generate_username" => {
-                    // Show window and trigger username generation
-                    if let Some(window) = app.get_window("main") {
-                        let _ = window.show();
-                        let _ = window.set_focus();
-                        // Emit an event to the frontend to generate a username
-                        let _ = window.emit("tray-generate-username", ());
-                    }
-                }
-                "quit" => {
-                    app.exit(0);
-                }
-                _ => {}
-            }
+    impl NetworkCommandRunner for RecordedCommandRunner {
+        fn run(&self, args: &[&str]) -> Result<String, String> {
+            self.0
+                .get(args.join(" ").as_str())
+                .map(|output| output.to_string())
+                .ok_or_else(|| format!("{} not found", args[0]))
         }
-        _ => {}
     }
-}
 
-fn main() {
-    let system_tray = create_system_tray();
-    
-    tauri::Builder::default()
-        .system_tray(system_tray)
-        .on_system_tray_event(handle_system_tray_event)
-        .invoke_handler(tauri::generate_handler![
-            generate_password,
-            generate_password_legacy,
-            generate_passphrase,
-            generate_username,
-            calculate_password_strength,
-            calculate_username_strength,
-            copy_to_clipboard,
-            save_password_to_file,
-            get_public_ip_address,
-            get_system_identity
-        ])
-        .setup(|app| {
-            // Handle window close event to hide to tray instead of closing
-            let window = app.get_window("main").unwrap();
-            let app_handle = app.handle();
-            
-            window.on_window_event(move |event| {
-                match event {
-                    tauri::WindowEvent::CloseRequested { api, .. } => {
-                        // Prevent the window from closing and hide it instead
-                        api.prevent_close();
-                        if let Some(window) = app_handle.get_window("main") {
-                            let _ = window.hide();
-                        }
-                    }
-                    _ => {}
-                }
-            });
-            
-            Ok(())
-        })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
-}
+    fn recorded(outputs: &[(&'static str, &'static str)]) -> RecordedCommandRunner {
+        RecordedCommandRunner(outputs.iter().copied().collect())
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    const IPCONFIG_MULTIPLE_ADAPTERS: &str = "
+Windows IP Configuration
+
+
+Ethernet adapter vEthernet (WSL):
+
+   Connection-specific DNS Suffix  . :
+   Link-local IPv6 Address . . . . . : fe80::5d2c:1f0a:8b3e:91c4%23
+   Autoconfiguration IPv4 Address. . : 169.254.12.7
+   Subnet Mask . . . . . . . . . . . : 255.255.0.0
+   Default Gateway . . . . . . . . . :
+
+Wireless LAN adapter Wi-Fi:
+
+   Connection-specific DNS Suffix  . : home
+   Link-local IPv6 Address . . . . . : fe80::a1b2:c3d4:e5f6:1234%12
+   IPv4 Address. . . . . . . . . . . : 192.168.1.42
+   Subnet Mask . . . . . . . . . . . : 255.255.255.0
+   Default Gateway . . . . . . . . . : 192.168.1.1
+
+Ethernet adapter Ethernet 2:
+
+   Connection-specific DNS Suffix  . :
+   IPv4 Address. . . . . . . . . . . : 10.0.0.15
+   Subnet Mask . . . . . . . . . . . : 255.255.255.0
+   Default Gateway . . . . . . . . . :
+";
+
+    const IPCONFIG_IPV6_ONLY: &str = "
+Windows IP Configuration
+
+
+Wireless LAN adapter Wi-Fi:
+
+   Connection-specific DNS Suffix  . :
+   IPv6 Address. . . . . . . . . . . : 2001:db8:85a3::8a2e:370:7334
+   Temporary IPv6 Address. . . . . . : 2001:db8:85a3::1c5e:9f2a:41d0
+   Link-local IPv6 Address . . . . . : fe80::a1b2:c3d4:e5f6:1234%12
+   Default Gateway . . . . . . . . . : fe80::1%12
+";
+
+    const IPCONFIG_DISCONNECTED: &str = "
+Windows IP Configuration
+
+
+Wireless LAN adapter Wi-Fi:
+
+   Media State . . . . . . . . . . . : Media disconnected
+   Connection-specific DNS Suffix  . :
+
+Ethernet adapter Ethernet:
+
+   Media State . . . . . . . . . . . : Media disconnected
+   Connection-specific DNS Suffix  . :
+";
 
     #[test]
-    fn test_username_strength_skype() {
-        let result = evaluate_username_security("Skype");
-        
-        // "Skype" should get a low score due to being a common brand name
-        assert!(result.score < 40, "Skype should have low security score, got: {}", result.score);
-        assert!(result.feedback.len() > 0, "Should have feedback for improvement");
-        
-        // Check that it identifies the brand name issue
-        let has_brand_feedback = result.feedback.iter().any(|f| f.to_lowercase().contains("skype"));
-        assert!(has_brand_feedback, "Should identify Skype as a problematic brand name");
+    fn test_windows_network_ip_skips_link_local_adapters() {
+        let runner = recorded(&[("ipconfig", IPCONFIG_MULTIPLE_ADAPTERS)]);
+        assert_eq!(get_windows_network_ip(&runner), Ok("192.168.1.42".to_string()));
     }
 
     #[test]
-    fn test_username_strength_good_username() {
-        let result = evaluate_username_security("QuietRaven47");
-        
-        // This should get a high score - it's a compound EFF word with numbers (generated pattern)
-        // Actual calculation: Privacy 80, Uniqueness 70 -> (80*0.6 + 70*0.4) = 76
-        assert!(result.score >= 75, "QuietRaven47 should have excellent security score, got: {}", result.score);
+    fn test_windows_network_ip_without_ipv4() {
+        for output in [IPCONFIG_IPV6_ONLY, IPCONFIG_DISCONNECTED] {
+            let runner = recorded(&[("ipconfig", output)]);
+            assert!(get_windows_network_ip(&runner).is_err());
+        }
+        assert!(get_windows_network_ip(&recorded(&[])).is_err());
+    }
+
+    const ROUTE_GET_DEFAULT: &str = "   route to: default
+destination: default
+       mask: default
+    gateway: 192.168.1.1
+  interface: en0
+      flags: <UP,GATEWAY,DONE,STATIC,PRCLONING>
+ recvpipe  sendpipe  ssthresh  rtt,msec    rttvar  hopcount      mtu     expire
+       0         0         0         0         0         0      1500         0
+";
+
+    const IFCONFIG_EN0: &str = "en0: flags=8863<UP,BROADCAST,SMART,RUNNING,SIMPLEX,MULTICAST> mtu 1500
+	options=6463<RXCSUM,TXCSUM,TSO4,TSO6,CHANNEL_IO,PARTIAL_CSUM,ZEROINVERT_CSUM>
+	ether 3c:22:fb:12:34:56
+	inet6 fe80::1c8a:5f3e:a2b4:9d01%en0 prefixlen 64 secured scopeid 0xe
+	inet 192.168.1.42 netmask 0xffffff00 broadcast 192.168.1.255
+	nd6 options=201<PERFORMNUD,DAD>
+	media: autoselect
+	status: active
+";
+
+    const IFCONFIG_EN0_IPV6_ONLY: &str = "en0: flags=8863<UP,BROADCAST,SMART,RUNNING,SIMPLEX,MULTICAST> mtu 1500
+	ether 3c:22:fb:12:34:56
+	inet6 fe80::1c8a:5f3e:a2b4:9d01%en0 prefixlen 64 secured scopeid 0xe
+	inet6 2001:db8:85a3::8a2e:370:7334 prefixlen 64 autoconf secured
+	nd6 options=201<PERFORMNUD,DAD>
+	media: autoselect
+	status: active
+";
+
+    #[test]
+    fn test_macos_network_ip_reads_default_interface() {
+        // Other interfaces are never queried, only the one carrying the default route
+        let runner = recorded(&[
+            ("route get default", ROUTE_GET_DEFAULT),
+            ("ifconfig en0", IFCONFIG_EN0),
+            ("ifconfig en1", "en1: flags=8863<UP> mtu 1500\n\tinet 10.0.0.15 netmask 0xffffff00\n"),
+        ]);
+        assert_eq!(get_macos_network_ip(&runner), Ok("192.168.1.42".to_string()));
     }
 
     #[test]
-    fn test_username_strength_basic_usernames() {
-        // Test that Basic usernames (3-4 characters) are consistently rated as Very Poor due to hard cap
-        let basic_usernames = ["ace", "act", "add", "age", "aid", "aim", "air", "all", "and", "any"];
-        
-        for username in &basic_usernames {
-            let result = evaluate_username_security(username);
-            
-            // Basic usernames should be capped at Very Poor (≤25) regardless of other factors
-            assert!(result.score <= 25, 
-                "Basic username '{}' should be Very Poor (≤25) due to hard cap, got: {} ({})", 
-                username, result.score, result.security_level);
-            
-            // Should have feedback about being very short
-            let has_short_feedback = result.feedback.iter().any(|f| 
-                f.contains("Very short") || f.contains("Short") || f.contains("too short"));
-            assert!(has_short_feedback, 
-                "Basic username '{}' should have feedback about being too short", username);
-        }
+    fn test_macos_network_ip_without_ipv4() {
+        let runner = recorded(&[("route get default", ROUTE_GET_DEFAULT), ("ifconfig en0", IFCONFIG_EN0_IPV6_ONLY)]);
+        assert!(get_macos_network_ip(&runner).is_err());
+
+        // Without an active interface route prints an error on stderr and nothing on stdout
+        let runner = recorded(&[("route get default", ""), ("ifconfig en0", IFCONFIG_EN0)]);
+        assert!(get_macos_network_ip(&runner).is_err());
     }
 
     #[test]
-    fn test_username_strength_eff_words() {
-        // Test single EFF words of different lengths (using actual EFF words not in common_words list)
-        let weak_eff = evaluate_username_security("able"); // 4 chars - Basic strength (hard capped)
-        let standard_eff = evaluate_username_security("abide"); // 5 chars - Standard strength  
-        let strong_eff = evaluate_username_security("outcome"); // 7 chars - Strong strength
-        let maximum_eff = evaluate_username_security("transport"); // 9 chars - Maximum strength
-        
-        // EFF words should get reasonable scores, with longer words scoring higher
-        // Updated expectations with hard cap for basic length:
-        // - 4 char word is capped at 25 (Very Poor due to fundamental length vulnerability)
-        // - 5 char word gets ~55-65 points (better length + EFF bonus)
-        // - 7+ char words get ~75+ points (good length + strong EFF bonus)
-        assert!(weak_eff.score <= 25, "Short EFF word should be capped at Very Poor due to length, got: {}", weak_eff.score);
-        assert!(standard_eff.score >= 50, "Standard EFF word should be good, got: {}", standard_eff.score);
-        assert!(strong_eff.score >= 70, "Strong EFF word should be very good, got: {}", strong_eff.score);
-        assert!(maximum_eff.score >= 75, "Maximum EFF word should be excellent, got: {}", maximum_eff.score);
-        
-        // Longer words should be better than shorter ones
-        assert!(standard_eff.score > weak_eff.score, "Standard should beat Basic (hard cap applies)");
-        assert!(strong_eff.score > standard_eff.score, "Strong should beat Standard");
-        assert!(maximum_eff.score >= strong_eff.score, "Maximum should be at least as good as Strong");
-        
-        // Verify that Basic EFF words are now in Very Poor category due to hard cap
-        assert!(weak_eff.score <= 25, 
-            "Basic EFF word should be in Very Poor category (≤25) due to hard cap, got: {}", weak_eff.score);
+    fn test_linux_network_ip_uses_route_source() {
+        let runner = recorded(&[
+            ("ip route get 8.8.8.8", "8.8.8.8 via 192.168.1.1 dev wlp2s0 src 192.168.1.42 uid 1000 \n    cache \n"),
+            ("hostname -I", "172.17.0.1 192.168.1.42 \n"),
+        ]);
+        assert_eq!(get_linux_network_ip(&runner), Ok("192.168.1.42".to_string()));
     }
 
     #[test]
-    fn test_username_strength_generated_patterns() {
-        // Test patterns that our generator creates (using actual EFF words not in common_words)
-        let word_with_numbers = evaluate_username_security("outcome1234"); // EFF word + 4 digits (11 chars total)
-        let capitalized_word = evaluate_username_security("Outcome");      // Capitalized EFF word (7 chars)
-        let compound_words = evaluate_username_security("outcomeabide");   // Two EFF words (12 chars total)
-        
-        // Generated patterns should score well since they're all longer than 4 characters:
-        // - EFF word + numbers: long enough to avoid hard cap, gets EFF bonus + numbers + generated bonus
-        // - Single EFF word (capitalized): 7 chars, same as lowercase EFF word, good score
-        // - Compound EFF words: very long, gets strong EFF bonus for compound word
-        assert!(word_with_numbers.score >= 60, "EFF word + numbers should be good (long enough), got: {}", word_with_numbers.score);
-        assert!(capitalized_word.score >= 70, "Capitalized EFF word should be very good, got: {}", capitalized_word.score);
-        assert!(compound_words.score >= 75, "Compound EFF words should be excellent, got: {}", compound_words.score);
+    fn test_linux_network_ip_falls_back_to_hostname() {
+        // Multiple adapters, hostname -I lists the addresses in interface order
+        let runner = recorded(&[("ip route get 8.8.8.8", ""), ("hostname -I", "10.0.0.15 172.17.0.1 \n")]);
+        assert_eq!(get_linux_network_ip(&runner), Ok("10.0.0.15".to_string()));
     }
 
+    #[test]
+    fn test_linux_network_ip_without_ipv4() {
+        // IPv6 only, the IPv4 route lookup fails and only IPv6 addresses are assigned
+        let runner = recorded(&[("ip route get 8.8.8.8", ""), ("hostname -I", "2001:db8:85a3::8a2e:370:7334 \n")]);
+        assert!(get_linux_network_ip(&runner).is_err());
 
+        // No active interfaces
+        let runner = recorded(&[("ip route get 8.8.8.8", ""), ("hostname -I", "\n")]);
+        assert!(get_linux_network_ip(&runner).is_err());
+        assert!(get_linux_network_ip(&recorded(&[])).is_err());
+    }
 }
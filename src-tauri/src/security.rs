@@ -0,0 +1,125 @@
+//! Comparisons for secrets that don't leak where two values differ through their timing, and
+//! sanitization of strings received from the frontend.
+//!
+//! `==` returns as soon as a byte differs, so the time it takes reveals how long the matching
+//! prefix is. These helpers always look at every byte of equally long inputs. The length itself is
+//! not hidden, inputs of different lengths compare unequal straight away.
+
+use subtle::ConstantTimeEq;
+
+/// Compare two byte slices, such as MACs, in constant time
+pub fn constant_time_eq_bytes(a: &[u8], b: &[u8]) -> bool {
+    a.ct_eq(b).into()
+}
+
+/// Characters with a special meaning to a shell, rejected in single line inputs
+const SHELL_METACHARACTERS: [char; 5] = ['$', '`', ';', '|', '&'];
+
+/// Clean up a string from the frontend before it reaches the file system, a shell command or an
+/// HTTP request. NUL bytes are removed and the result is cut to `max_len` characters. Single line
+/// inputs, `allow_newlines = false`, are rejected if they contain a line break or a shell
+/// metacharacter.
+pub fn sanitize_string_input(input: &str, max_len: usize, allow_newlines: bool) -> Result<String, String> {
+    let input: String = input.chars().filter(|&c| c != '\0').collect();
+    if !allow_newlines {
+        if input.contains(['\n', '\r']) {
+            return Err("must be a single line".to_string());
+        }
+        if let Some(c) = input.chars().find(|c| SHELL_METACHARACTERS.contains(c)) {
+            return Err(format!("must not contain '{}'", c));
+        }
+    }
+    Ok(input.chars().take(max_len).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_equal_strings() {
+        assert!(constant_time_eq_bytes(b"", b""));
+        assert!(constant_time_eq_bytes(b"api-key-123", b"api-key-123"));
+        assert!(constant_time_eq_bytes("clé-🔑".as_bytes(), "clé-🔑".as_bytes()));
+        assert!(constant_time_eq_bytes(&[0, 1, 2], &[0, 1, 2]));
+    }
+
+    #[test]
+    fn test_unequal_strings() {
+        assert!(!constant_time_eq_bytes(b"api-key-123", b"api-key-124"));
+        assert!(!constant_time_eq_bytes(b"api-key-123", b"api-key-12"));
+        assert!(!constant_time_eq_bytes(b"", b"a"));
+        assert!(!constant_time_eq_bytes(b"API-KEY", b"api-key"));
+        // Same characters, different normalization
+        assert!(!constant_time_eq_bytes("caf\u{e9}".as_bytes(), "cafe\u{301}".as_bytes()));
+        assert!(!constant_time_eq_bytes(&[0, 1, 2], &[0, 1, 3]));
+    }
+
+    #[test]
+    fn test_sanitize_keeps_plain_input() {
+        assert_eq!(sanitize_string_input("example.com", 253, false).unwrap(), "example.com");
+        assert_eq!(sanitize_string_input("bücher.de", 253, false).unwrap(), "bücher.de");
+        assert_eq!(sanitize_string_input("", 253, false).unwrap(), "");
+    }
+
+    #[test]
+    fn test_sanitize_strips_nul_bytes() {
+        assert_eq!(sanitize_string_input("exam\0ple.com\0", 253, false).unwrap(), "example.com");
+    }
+
+    #[test]
+    fn test_sanitize_truncates() {
+        assert_eq!(sanitize_string_input("abcdef", 3, false).unwrap(), "abc");
+        // Counted in characters, so a multi byte character is never split
+        assert_eq!(sanitize_string_input("ééé", 2, false).unwrap(), "éé");
+    }
+
+    #[test]
+    fn test_sanitize_rejects_shell_metacharacters() {
+        for input in ["; rm -rf /", "$(whoami)", "`id`", "a | nc evil 1", "a && b", "${HOME}"] {
+            assert!(sanitize_string_input(input, 253, false).is_err(), "{input:?} was accepted");
+        }
+        assert_eq!(sanitize_string_input("; rm -rf /", 253, false).unwrap_err(), "must not contain ';'");
+    }
+
+    #[test]
+    fn test_sanitize_newlines() {
+        assert!(sanitize_string_input("a\nb", 253, false).is_err());
+        assert!(sanitize_string_input("a\rb", 253, false).is_err());
+        assert_eq!(sanitize_string_input("a\nb; c", 253, true).unwrap(), "a\nb; c");
+    }
+
+    proptest! {
+        #[test]
+        fn test_matches_eq(a in ".*", b in ".*") {
+            prop_assert_eq!(constant_time_eq_bytes(a.as_bytes(), b.as_bytes()), a == b);
+        }
+
+        #[test]
+        fn test_equal_to_itself(a in ".*") {
+            prop_assert!(constant_time_eq_bytes(a.as_bytes(), a.clone().as_bytes()));
+        }
+
+        #[test]
+        fn test_prefix_is_unequal(a in ".*", suffix in ".+") {
+            let longer = format!("{a}{suffix}");
+            prop_assert!(!constant_time_eq_bytes(a.as_bytes(), longer.as_bytes()));
+            prop_assert!(!constant_time_eq_bytes(longer.as_bytes(), a.as_bytes()));
+        }
+
+        #[test]
+        fn test_sanitized_input_is_clean(input in ".*", max_len in 0usize..64) {
+            if let Ok(sanitized) = sanitize_string_input(&input, max_len, false) {
+                prop_assert!(sanitized.chars().count() <= max_len);
+                prop_assert!(!sanitized.contains('\0'));
+                prop_assert!(!sanitized.contains(SHELL_METACHARACTERS));
+            }
+        }
+
+        #[test]
+        fn test_bytes_match_eq(a in proptest::collection::vec(any::<u8>(), 0..64), b in proptest::collection::vec(any::<u8>(), 0..64)) {
+            prop_assert_eq!(constant_time_eq_bytes(&a, &b), a == b);
+        }
+    }
+}
@@ -0,0 +1,239 @@
+//! Minimal STUN client (RFC 5389) for discovering the external address of this machine.
+//!
+//! A Binding Request is sent over UDP and the server answers with the address and port it saw
+//! the request come from, which is the address after NAT rather than the LAN address.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+
+/// Public STUN server queried by [`external_ip`]
+pub const DEFAULT_SERVER: &str = "stun.l.google.com:19302";
+
+const BINDING_REQUEST: u16 = 0x0001;
+const BINDING_SUCCESS_RESPONSE: u16 = 0x0101;
+const MAGIC_COOKIE: u32 = 0x2112_A442;
+const HEADER_LENGTH: usize = 20;
+
+const ATTR_MAPPED_ADDRESS: u16 = 0x0001;
+const ATTR_XOR_MAPPED_ADDRESS: u16 = 0x0020;
+
+const FAMILY_IPV4: u8 = 0x01;
+const FAMILY_IPV6: u8 = 0x02;
+
+/// Ask the STUN server at `server` for the external IP address of this machine
+pub fn external_ip(server: &str, timeout: Duration) -> Result<IpAddr, String> {
+    let server_addr = server
+        .to_socket_addrs()
+        .map_err(|e| format!("Failed to resolve STUN server {}: {}", server, e))?
+        .next()
+        .ok_or_else(|| format!("STUN server {} did not resolve to an address", server))?;
+
+    let local_addr: SocketAddr = if server_addr.is_ipv4() {
+        (Ipv4Addr::UNSPECIFIED, 0).into()
+    } else {
+        (Ipv6Addr::UNSPECIFIED, 0).into()
+    };
+    let socket = UdpSocket::bind(local_addr).map_err(|e| format!("Failed to bind STUN socket: {}", e))?;
+    socket
+        .connect(server_addr)
+        .map_err(|e| format!("Failed to connect to STUN server: {}", e))?;
+
+    let transaction_id: [u8; 12] = rand::random();
+    socket
+        .send(&binding_request(&transaction_id))
+        .map_err(|e| format!("Failed to send STUN request: {}", e))?;
+
+    // Datagrams that aren't the answer to this request are skipped until the deadline
+    let deadline = Instant::now() + timeout;
+    let mut buffer = [0u8; 512];
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err("Timed out waiting for STUN response".to_string());
+        }
+        socket
+            .set_read_timeout(Some(remaining))
+            .map_err(|e| format!("Failed to set STUN timeout: {}", e))?;
+        let len = socket
+            .recv(&mut buffer)
+            .map_err(|e| format!("Failed to receive STUN response: {}", e))?;
+        if let Some(addr) = parse_binding_response(&buffer[..len], &transaction_id) {
+            return Ok(addr.ip());
+        }
+    }
+}
+
+/// Encode a Binding Request without attributes
+fn binding_request(transaction_id: &[u8; 12]) -> [u8; HEADER_LENGTH] {
+    let mut request = [0u8; HEADER_LENGTH];
+    request[0..2].copy_from_slice(&BINDING_REQUEST.to_be_bytes());
+    // Message length stays 0, there are no attributes
+    request[4..8].copy_from_slice(&MAGIC_COOKIE.to_be_bytes());
+    request[8..20].copy_from_slice(transaction_id);
+    request
+}
+
+/// The mapped address of a Binding Success Response to the request with `transaction_id`.
+/// `XOR-MAPPED-ADDRESS` is preferred, `MAPPED-ADDRESS` is accepted from older servers.
+fn parse_binding_response(message: &[u8], transaction_id: &[u8; 12]) -> Option<SocketAddr> {
+    if message.len() < HEADER_LENGTH
+        || u16::from_be_bytes([message[0], message[1]]) != BINDING_SUCCESS_RESPONSE
+        || message[4..8] != MAGIC_COOKIE.to_be_bytes()
+        || message[8..20] != transaction_id[..]
+    {
+        return None;
+    }
+    let length = usize::from(u16::from_be_bytes([message[2], message[3]]));
+    let attributes = message.get(HEADER_LENGTH..HEADER_LENGTH + length)?;
+
+    let mut mapped = None;
+    let mut offset = 0;
+    while offset + 4 <= attributes.len() {
+        let kind = u16::from_be_bytes([attributes[offset], attributes[offset + 1]]);
+        let value_length = usize::from(u16::from_be_bytes([attributes[offset + 2], attributes[offset + 3]]));
+        let value = attributes.get(offset + 4..offset + 4 + value_length)?;
+        match kind {
+            ATTR_XOR_MAPPED_ADDRESS => return parse_address(value, Some(&message[4..20])),
+            ATTR_MAPPED_ADDRESS => mapped = parse_address(value, None),
+            _ => {}
+        }
+        // Attributes are padded to a multiple of 4 bytes
+        offset += 4 + ((value_length + 3) & !3);
+    }
+    mapped
+}
+
+/// Decode a (XOR-)MAPPED-ADDRESS value. `xor_key` is the magic cookie followed by the
+/// transaction id, the port is xored with its first 2 bytes and the address with as many
+/// bytes as it is long.
+fn parse_address(value: &[u8], xor_key: Option<&[u8]>) -> Option<SocketAddr> {
+    let unxor = |bytes: &[u8]| -> Vec<u8> {
+        match xor_key {
+            Some(key) => bytes.iter().zip(key).map(|(byte, key)| byte ^ key).collect(),
+            None => bytes.to_vec(),
+        }
+    };
+
+    let family = *value.get(1)?;
+    let port = unxor(value.get(2..4)?);
+    let port = u16::from_be_bytes([port[0], port[1]]);
+    let ip = match family {
+        FAMILY_IPV4 => {
+            let octets: [u8; 4] = unxor(value.get(4..8)?).try_into().ok()?;
+            IpAddr::from(octets)
+        }
+        FAMILY_IPV6 => {
+            let octets: [u8; 16] = unxor(value.get(4..20)?).try_into().ok()?;
+            IpAddr::from(octets)
+        }
+        _ => return None,
+    };
+    Some(SocketAddr::new(ip, port))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encode a Binding Success Response carrying `addr` as `attribute`
+    fn binding_response(transaction_id: &[u8; 12], attribute: u16, addr: SocketAddr) -> Vec<u8> {
+        let mut key = MAGIC_COOKIE.to_be_bytes().to_vec();
+        key.extend_from_slice(transaction_id);
+        let xor = |bytes: &[u8]| -> Vec<u8> {
+            if attribute == ATTR_XOR_MAPPED_ADDRESS {
+                bytes.iter().zip(&key).map(|(byte, key)| byte ^ key).collect()
+            } else {
+                bytes.to_vec()
+            }
+        };
+
+        let (family, octets) = match addr.ip() {
+            IpAddr::V4(ip) => (FAMILY_IPV4, ip.octets().to_vec()),
+            IpAddr::V6(ip) => (FAMILY_IPV6, ip.octets().to_vec()),
+        };
+        let mut value = vec![0, family];
+        value.extend(xor(&addr.port().to_be_bytes()));
+        value.extend(xor(&octets));
+
+        let mut message = BINDING_SUCCESS_RESPONSE.to_be_bytes().to_vec();
+        message.extend_from_slice(&(4 + value.len() as u16).to_be_bytes());
+        message.extend_from_slice(&MAGIC_COOKIE.to_be_bytes());
+        message.extend_from_slice(transaction_id);
+        message.extend_from_slice(&attribute.to_be_bytes());
+        message.extend_from_slice(&(value.len() as u16).to_be_bytes());
+        message.extend(value);
+        message
+    }
+
+    #[test]
+    fn test_binding_request_header() {
+        let transaction_id = [7u8; 12];
+        let request = binding_request(&transaction_id);
+        assert_eq!(&request[0..4], &[0x00, 0x01, 0x00, 0x00]);
+        assert_eq!(&request[4..8], &[0x21, 0x12, 0xA4, 0x42]);
+        assert_eq!(&request[8..], &transaction_id);
+    }
+
+    #[test]
+    fn test_parse_rfc5769_ipv4_response() {
+        // Sample IPv4 response from RFC 5769 section 2.2, reduced to the XOR-MAPPED-ADDRESS
+        let transaction_id = [0xb7, 0xe7, 0xa7, 0x01, 0xbc, 0x34, 0xd6, 0x86, 0xfa, 0x87, 0xdf, 0xae];
+        let message = [
+            0x01, 0x01, 0x00, 0x0c, 0x21, 0x12, 0xa4, 0x42, 0xb7, 0xe7, 0xa7, 0x01, 0xbc, 0x34, 0xd6, 0x86, 0xfa,
+            0x87, 0xdf, 0xae, 0x00, 0x20, 0x00, 0x08, 0x00, 0x01, 0xa1, 0x47, 0xe1, 0x12, 0xa6, 0x43,
+        ];
+        assert_eq!(
+            parse_binding_response(&message, &transaction_id),
+            Some("192.0.2.1:32853".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_parse_binding_response() {
+        let transaction_id = [1u8; 12];
+        for addr in ["203.0.113.9:40000", "[2001:db8::1]:3478"] {
+            let addr: SocketAddr = addr.parse().unwrap();
+            for attribute in [ATTR_XOR_MAPPED_ADDRESS, ATTR_MAPPED_ADDRESS] {
+                let response = binding_response(&transaction_id, attribute, addr);
+                assert_eq!(parse_binding_response(&response, &transaction_id), Some(addr));
+            }
+        }
+
+        let response = binding_response(&transaction_id, ATTR_XOR_MAPPED_ADDRESS, "203.0.113.9:1".parse().unwrap());
+        assert_eq!(parse_binding_response(&response, &[2u8; 12]), None);
+        assert_eq!(parse_binding_response(&response[..HEADER_LENGTH + 4], &transaction_id), None);
+        assert_eq!(parse_binding_response(&[0u8; 8], &transaction_id), None);
+    }
+
+    #[test]
+    fn test_external_ip_from_mock_server() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let server_addr = server.local_addr().unwrap();
+        let external: SocketAddr = "198.51.100.23:61000".parse().unwrap();
+
+        let handle = std::thread::spawn(move || {
+            let mut buffer = [0u8; 512];
+            let (len, client) = server.recv_from(&mut buffer).unwrap();
+            assert_eq!(len, HEADER_LENGTH);
+            assert_eq!(&buffer[0..2], &BINDING_REQUEST.to_be_bytes());
+            let transaction_id: [u8; 12] = buffer[8..20].try_into().unwrap();
+
+            // A stray datagram is ignored, the client keeps waiting for its answer
+            server.send_to(b"not stun", client).unwrap();
+            let response = binding_response(&transaction_id, ATTR_XOR_MAPPED_ADDRESS, external);
+            server.send_to(&response, client).unwrap();
+        });
+
+        let ip = external_ip(&server_addr.to_string(), Duration::from_secs(5)).unwrap();
+        assert_eq!(ip, external.ip());
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_external_ip_times_out() {
+        // Bound but never answers
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let result = external_ip(&server.local_addr().unwrap().to_string(), Duration::from_millis(100));
+        assert!(result.is_err());
+    }
+}
@@ -36,7 +36,7 @@ export interface UsernameConfig {
   
   // Forwarded variant options
   service?: {
-    type: 'AddyIo' | 'DuckDuckGo' | 'Firefox' | 'Fastmail' | 'ForwardEmail' | 'SimpleLogin';
+    type: 'AddyIo' | 'DuckDuckGo' | 'Firefox' | 'Fastmail' | 'ForwardEmail' | 'SimpleLogin' | 'IronVest';
     api_token?: string;
     domain?: string;
     base_url?: string;